@@ -12,7 +12,7 @@ extern crate regex;
 extern crate typed_arena;
 
 use fallible_iterator::FallibleIterator;
-use gimli::{CompilationUnitHeader, UnitOffset, UnwindSection};
+use gimli::{CompilationUnitHeader, UnwindSection};
 use object::{Object, ObjectSection};
 use regex::bytes::Regex;
 use std::borrow::{Borrow, Cow};
@@ -157,6 +157,9 @@ impl<'input, Endian> Reader for gimli::EndianSlice<'input, Endian> where
 
 type RelocationMap = HashMap<usize, object::Relocation>;
 
+/// A function that returns the name of a register for an architecture.
+type RegisterNameFn = fn(gimli::Register) -> Option<&'static str>;
+
 fn add_relocations(
     relocations: &mut RelocationMap,
     file: &object::File,
@@ -531,6 +534,8 @@ where
     let debug_info = load_section(&arena, file, endian);
     let debug_line = load_section(&arena, file, endian);
     let debug_line_str = load_section(&arena, file, endian);
+    let debug_macinfo = load_section(&arena, file, endian);
+    let debug_macro = load_section(&arena, file, endian);
     let debug_str = load_section(&arena, file, endian);
     let debug_str_offsets = load_section(&arena, file, endian);
     let debug_types = load_section(&arena, file, endian);
@@ -543,18 +548,36 @@ where
     let debug_rnglists = load_section(&arena, file, endian);
     let ranges = gimli::RangeLists::new(debug_ranges, debug_rnglists);
 
+    let debug_aranges = load_section(&arena, file, endian);
     let dwarf = gimli::Dwarf {
         debug_abbrev,
         debug_addr,
+        debug_aranges,
         debug_info,
         debug_line,
         debug_line_str,
+        debug_macinfo,
+        debug_macro,
         debug_str,
         debug_str_offsets,
         debug_str_sup: no_reader.clone().into(),
         debug_types,
         locations,
         ranges,
+        file_type: gimli::DwarfFileType::Main,
+        abbreviations_cache: Default::default(),
+        unit_range_cache: Default::default(),
+    };
+
+    fn register_name_none(_: gimli::Register) -> Option<&'static str> {
+        None
+    }
+    let arch_register_name: RegisterNameFn = match file.machine() {
+        object::Machine::Arm => gimli::Arm::register_name,
+        object::Machine::Arm64 => gimli::AArch64::register_name,
+        object::Machine::X86 => gimli::X86::register_name,
+        object::Machine::X86_64 => gimli::X86_64::register_name,
+        _ => register_name_none,
     };
 
     let out = io::stdout();
@@ -566,15 +589,6 @@ where
             object::Machine::Other => mem::size_of::<usize>() as u8,
         };
 
-        fn register_name_none(_: gimli::Register) -> Option<&'static str> {
-            None
-        }
-        let arch_register_name = match file.machine() {
-            object::Machine::Arm | object::Machine::Arm64 => gimli::Arm::register_name,
-            object::Machine::X86 => gimli::X86::register_name,
-            object::Machine::X86_64 => gimli::X86_64::register_name,
-            _ => register_name_none,
-        };
         let register_name = |register| match arch_register_name(register) {
             Some(name) => Cow::Borrowed(name),
             None => Cow::Owned(format!("{}", register.0)),
@@ -582,11 +596,19 @@ where
 
         let mut eh_frame: gimli::EhFrame<_> = load_section(&arena, file, endian);
         eh_frame.set_address_size(address_size);
+        if file.machine() == object::Machine::Arm64 {
+            eh_frame.set_vendor(gimli::Vendor::AArch64);
+        }
         dump_eh_frame(&mut BufWriter::new(out.lock()), &eh_frame, &register_name)?;
     }
     if flags.info {
-        dump_info(&dwarf, flags)?;
-        dump_types(&mut BufWriter::new(out.lock()), &dwarf, flags)?;
+        dump_info(&dwarf, arch_register_name, flags)?;
+        dump_types(
+            &mut BufWriter::new(out.lock()),
+            &dwarf,
+            arch_register_name,
+            flags,
+        )?;
         writeln!(&mut out.lock())?;
     }
     let w = &mut BufWriter::new(out.lock());
@@ -598,8 +620,7 @@ where
         dump_pubnames(w, debug_pubnames, &dwarf.debug_info)?;
     }
     if flags.aranges {
-        let debug_aranges = &load_section(&arena, file, endian);
-        dump_aranges(w, debug_aranges, &dwarf.debug_info)?;
+        dump_aranges(w, &dwarf.debug_aranges, &dwarf.debug_info)?;
     }
     if flags.pubtypes {
         let debug_pubtypes = &load_section(&arena, file, endian);
@@ -860,6 +881,12 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
                 ArgsSize { size } => {
                     writeln!(w, "                DW_CFA_GNU_args_size ({})", size)?;
                 }
+                WindowSave => {
+                    writeln!(w, "                DW_CFA_GNU_window_save")?;
+                }
+                NegateRaState => {
+                    writeln!(w, "                DW_CFA_AARCH64_negate_ra_state")?;
+                }
                 Nop => {
                     writeln!(w, "                DW_CFA_nop")?;
                 }
@@ -868,7 +895,11 @@ fn dump_cfi_instructions<R: Reader, W: Write>(
     }
 }
 
-fn dump_info<R: Reader>(dwarf: &gimli::Dwarf<R>, flags: &Flags) -> Result<()>
+fn dump_info<R: Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    register_name: RegisterNameFn,
+    flags: &Flags,
+) -> Result<()>
 where
     R::Endian: Send + Sync,
 {
@@ -896,7 +927,7 @@ where
                 }
             };
 
-            let entries_result = dump_entries(buf, unit, dwarf, flags);
+            let entries_result = dump_entries(buf, unit, dwarf, register_name, flags);
             if let Err(err) = entries_result {
                 writeln!(
                     buf,
@@ -922,6 +953,7 @@ where
 fn dump_types<R: Reader, W: Write>(
     w: &mut W,
     dwarf: &gimli::Dwarf<R>,
+    register_name: RegisterNameFn,
     flags: &Flags,
 ) -> Result<()> {
     writeln!(w, "\n.debug_types")?;
@@ -954,7 +986,7 @@ fn dump_types<R: Reader, W: Write>(
                 continue;
             }
         };
-        let entries_result = dump_entries(w, unit, dwarf, flags);
+        let entries_result = dump_entries(w, unit, dwarf, register_name, flags);
         if let Err(err) = entries_result {
             writeln!(
                 w,
@@ -977,6 +1009,7 @@ fn dump_entries<R: Reader, W: Write>(
     w: &mut W,
     unit: gimli::Unit<R>,
     dwarf: &gimli::Dwarf<R>,
+    register_name: RegisterNameFn,
     flags: &Flags,
 ) -> Result<()> {
     let mut spaces_buf = String::new();
@@ -1009,7 +1042,7 @@ fn dump_entries<R: Reader, W: Write>(
             if flags.raw {
                 writeln!(w, "{:?}", attr.raw_value())?;
             } else {
-                match dump_attr_value(w, &attr, &unit, dwarf) {
+                match dump_attr_value(w, &attr, &unit, dwarf, register_name) {
                     Ok(_) => (),
                     Err(ref err) => writeln!(
                         w,
@@ -1028,6 +1061,7 @@ fn dump_attr_value<R: Reader, W: Write>(
     attr: &gimli::Attribute<R>,
     unit: &gimli::Unit<R>,
     dwarf: &gimli::Dwarf<R>,
+    register_name: RegisterNameFn,
 ) -> Result<()> {
     let value = attr.value();
     match value {
@@ -1102,7 +1136,7 @@ fn dump_attr_value<R: Reader, W: Write>(
                 }
                 write!(w, ": ")?;
             }
-            dump_exprloc(w, data, unit)?;
+            dump_exprloc(w, data, unit, register_name)?;
             writeln!(w)?;
         }
         gimli::AttributeValue::Flag(true) => {
@@ -1135,18 +1169,21 @@ fn dump_attr_value<R: Reader, W: Write>(
             writeln!(w, "0x{:08x}", offset)?;
         }
         gimli::AttributeValue::LocationListsRef(offset) => {
-            dump_loc_list(w, offset, unit, dwarf)?;
+            dump_loc_list(w, offset, unit, dwarf, register_name)?;
         }
         gimli::AttributeValue::DebugLocListsBase(base) => {
             writeln!(w, "<.debug_loclists+0x{:08x}>", base.0)?;
         }
         gimli::AttributeValue::DebugLocListsIndex(index) => {
             let offset = dwarf.locations_offset(unit, index)?;
-            dump_loc_list(w, offset, unit, dwarf)?;
+            dump_loc_list(w, offset, unit, dwarf, register_name)?;
         }
         gimli::AttributeValue::DebugMacinfoRef(gimli::DebugMacinfoOffset(offset)) => {
             writeln!(w, "{}", offset)?;
         }
+        gimli::AttributeValue::DebugMacroRef(gimli::DebugMacroOffset(offset)) => {
+            writeln!(w, "<.debug_macro+0x{:08x}>", offset)?;
+        }
         gimli::AttributeValue::RangeListsRef(offset) => {
             dump_range_list(w, offset, unit, dwarf)?;
         }
@@ -1292,202 +1329,9 @@ fn dump_exprloc<R: Reader, W: Write>(
     w: &mut W,
     data: &gimli::Expression<R>,
     unit: &gimli::Unit<R>,
+    register_name: RegisterNameFn,
 ) -> Result<()> {
-    let mut pc = data.0.clone();
-    let mut space = false;
-    while pc.len() != 0 {
-        let mut op_pc = pc.clone();
-        let dwop = gimli::DwOp(op_pc.read_u8()?);
-        match gimli::Operation::parse(&mut pc, &data.0, unit.encoding()) {
-            Ok(op) => {
-                if space {
-                    write!(w, " ")?;
-                } else {
-                    space = true;
-                }
-                dump_op(w, dwop, op, &pc)?;
-            }
-            Err(gimli::Error::InvalidExpression(op)) => {
-                writeln!(w, "WARNING: unsupported operation 0x{:02x}", op.0)?;
-                return Ok(());
-            }
-            Err(gimli::Error::UnsupportedRegister(register)) => {
-                writeln!(w, "WARNING: unsupported register {}", register)?;
-                return Ok(());
-            }
-            otherwise => panic!("Unexpected Operation::parse result: {:?}", otherwise),
-        }
-    }
-    Ok(())
-}
-
-fn dump_op<R: Reader, W: Write>(
-    w: &mut W,
-    dwop: gimli::DwOp,
-    op: gimli::Operation<R, R::Offset>,
-    newpc: &R,
-) -> Result<()> {
-    write!(w, "{}", dwop)?;
-    match op {
-        gimli::Operation::Deref {
-            base_type, size, ..
-        } => {
-            if dwop == gimli::DW_OP_deref_size || dwop == gimli::DW_OP_xderef_size {
-                write!(w, " {}", size)?;
-            }
-            if base_type != UnitOffset(0) {
-                write!(w, " type 0x{:08x}", base_type.0)?;
-            }
-        }
-        gimli::Operation::Pick { index } => {
-            if dwop == gimli::DW_OP_pick {
-                write!(w, " {}", index)?;
-            }
-        }
-        gimli::Operation::PlusConstant { value } => {
-            write!(w, " {}", value as i64)?;
-        }
-        gimli::Operation::Bra { target } => {
-            let offset = newpc.len() as isize - target.len() as isize;
-            write!(w, " {}", offset)?;
-        }
-        gimli::Operation::Skip { target } => {
-            let offset = newpc.len() as isize - target.len() as isize;
-            write!(w, " {}", offset)?;
-        }
-        gimli::Operation::Literal { value } => match dwop {
-            gimli::DW_OP_const1s
-            | gimli::DW_OP_const2s
-            | gimli::DW_OP_const4s
-            | gimli::DW_OP_const8s
-            | gimli::DW_OP_consts => {
-                write!(w, " {}", value as i64)?;
-            }
-            gimli::DW_OP_const1u
-            | gimli::DW_OP_const2u
-            | gimli::DW_OP_const4u
-            | gimli::DW_OP_const8u
-            | gimli::DW_OP_constu => {
-                write!(w, " {}", value)?;
-            }
-            _ => {
-                // These have the value encoded in the operation, eg DW_OP_lit0.
-            }
-        },
-        gimli::Operation::Register { register } => {
-            if dwop == gimli::DW_OP_regx {
-                write!(w, " {}", register.0)?;
-            }
-        }
-        gimli::Operation::RegisterOffset {
-            register,
-            offset,
-            base_type,
-        } => {
-            if dwop >= gimli::DW_OP_breg0 && dwop <= gimli::DW_OP_breg31 {
-                write!(w, "{:+}", offset)?;
-            } else {
-                write!(w, " {}", register.0)?;
-                if offset != 0 {
-                    write!(w, "{:+}", offset)?;
-                }
-                if base_type != UnitOffset(0) {
-                    write!(w, " type 0x{:08x}", base_type.0)?;
-                }
-            }
-        }
-        gimli::Operation::FrameOffset { offset } => {
-            write!(w, " {}", offset)?;
-        }
-        gimli::Operation::Call { offset } => match offset {
-            gimli::DieReference::UnitRef(gimli::UnitOffset(offset)) => {
-                write!(w, " 0x{:08x}", offset)?;
-            }
-            gimli::DieReference::DebugInfoRef(gimli::DebugInfoOffset(offset)) => {
-                write!(w, " 0x{:08x}", offset)?;
-            }
-        },
-        gimli::Operation::Piece {
-            size_in_bits,
-            bit_offset: None,
-        } => {
-            write!(w, " {}", size_in_bits / 8)?;
-        }
-        gimli::Operation::Piece {
-            size_in_bits,
-            bit_offset: Some(bit_offset),
-        } => {
-            write!(w, " 0x{:08x} offset 0x{:08x}", size_in_bits, bit_offset)?;
-        }
-        gimli::Operation::ImplicitValue { data } => {
-            let data = data.to_slice()?;
-            write!(w, " 0x{:08x} contents 0x", data.len())?;
-            for byte in data.iter() {
-                write!(w, "{:02x}", byte)?;
-            }
-        }
-        gimli::Operation::ImplicitPointer { value, byte_offset } => {
-            write!(w, " 0x{:08x} {}", value.0, byte_offset)?;
-        }
-        gimli::Operation::EntryValue { expression } => {
-            write!(w, " 0x{:08x} contents 0x", expression.len())?;
-            for byte in expression.to_slice()?.iter() {
-                write!(w, "{:02x}", byte)?;
-            }
-        }
-        gimli::Operation::ParameterRef { offset } => {
-            write!(w, " 0x{:08x}", offset.0)?;
-        }
-        gimli::Operation::Address { address } => {
-            write!(w, " 0x{:08x}", address)?;
-        }
-        gimli::Operation::AddressIndex { index } => {
-            write!(w, " 0x{:08x}", index.0)?;
-        }
-        gimli::Operation::ConstantIndex { index } => {
-            write!(w, " 0x{:08x}", index.0)?;
-        }
-        gimli::Operation::TypedLiteral { base_type, value } => {
-            write!(w, " type 0x{:08x} contents 0x", base_type.0)?;
-            for byte in value.to_slice()?.iter() {
-                write!(w, "{:02x}", byte)?;
-            }
-        }
-        gimli::Operation::Convert { base_type } => {
-            write!(w, " type 0x{:08x}", base_type.0)?;
-        }
-        gimli::Operation::Reinterpret { base_type } => {
-            write!(w, " type 0x{:08x}", base_type.0)?;
-        }
-        gimli::Operation::Drop
-        | gimli::Operation::Swap
-        | gimli::Operation::Rot
-        | gimli::Operation::Abs
-        | gimli::Operation::And
-        | gimli::Operation::Div
-        | gimli::Operation::Minus
-        | gimli::Operation::Mod
-        | gimli::Operation::Mul
-        | gimli::Operation::Neg
-        | gimli::Operation::Not
-        | gimli::Operation::Or
-        | gimli::Operation::Plus
-        | gimli::Operation::Shl
-        | gimli::Operation::Shr
-        | gimli::Operation::Shra
-        | gimli::Operation::Xor
-        | gimli::Operation::Eq
-        | gimli::Operation::Ge
-        | gimli::Operation::Gt
-        | gimli::Operation::Le
-        | gimli::Operation::Lt
-        | gimli::Operation::Ne
-        | gimli::Operation::Nop
-        | gimli::Operation::PushObjectAddress
-        | gimli::Operation::TLS
-        | gimli::Operation::CallFrameCFA
-        | gimli::Operation::StackValue => {}
-    };
+    write!(w, "{}", data.display(unit.encoding(), Some(register_name)))?;
     Ok(())
 }
 
@@ -1496,6 +1340,7 @@ fn dump_loc_list<R: Reader, W: Write>(
     offset: gimli::LocationListsOffset<R::Offset>,
     unit: &gimli::Unit<R>,
     dwarf: &gimli::Dwarf<R>,
+    register_name: RegisterNameFn,
 ) -> Result<()> {
     let raw_locations = dwarf.locations.raw_locations(offset, unit.encoding())?;
     let raw_locations: Vec<_> = raw_locations.collect()?;
@@ -1514,6 +1359,22 @@ fn dump_loc_list<R: Reader, W: Write>(
     for (i, raw) in raw_locations.iter().enumerate() {
         write!(w, "\t\t\t[{:2}]", i)?;
         match *raw {
+            gimli::RawLocListEntry::AddressOrOffsetPair {
+                begin,
+                end,
+                ref data,
+            } => {
+                let location = locations.next()?.unwrap();
+                write!(
+                    w,
+                    "<address pair \
+                     low-off: 0x{:08x} addr 0x{:08x} \
+                     high-off: 0x{:08x} addr 0x{:08x}>",
+                    begin, location.range.begin, end, location.range.end
+                )?;
+                dump_exprloc(w, data, unit, register_name)?;
+                writeln!(w)?;
+            }
             gimli::RawLocListEntry::BaseAddress { addr } => {
                 writeln!(w, "<new base address 0x{:08x}>", addr)?;
             }
@@ -1536,7 +1397,7 @@ fn dump_loc_list<R: Reader, W: Write>(
                      high-off: [{}]0x{:08x} addr 0x{:08x}>",
                     begin.0, begin_val, location.range.begin, end.0, end_val, location.range.end
                 )?;
-                dump_exprloc(w, data, unit)?;
+                dump_exprloc(w, data, unit, register_name)?;
                 writeln!(w)?;
             }
             gimli::RawLocListEntry::StartxLength {
@@ -1553,7 +1414,7 @@ fn dump_loc_list<R: Reader, W: Write>(
                      high-off: 0x{:08x} addr 0x{:08x}>",
                     begin.0, begin_val, location.range.begin, length, location.range.end
                 )?;
-                dump_exprloc(w, data, unit)?;
+                dump_exprloc(w, data, unit, register_name)?;
                 writeln!(w)?;
             }
             gimli::RawLocListEntry::OffsetPair {
@@ -1569,12 +1430,12 @@ fn dump_loc_list<R: Reader, W: Write>(
                      high-off: 0x{:08x} addr 0x{:08x}>",
                     begin, location.range.begin, end, location.range.end
                 )?;
-                dump_exprloc(w, data, unit)?;
+                dump_exprloc(w, data, unit, register_name)?;
                 writeln!(w)?;
             }
             gimli::RawLocListEntry::DefaultLocation { ref data } => {
                 write!(w, "<default location>")?;
-                dump_exprloc(w, data, unit)?;
+                dump_exprloc(w, data, unit, register_name)?;
                 writeln!(w)?;
             }
             gimli::RawLocListEntry::StartEnd {
@@ -1590,7 +1451,7 @@ fn dump_loc_list<R: Reader, W: Write>(
                      high-off: 0x{:08x} addr 0x{:08x}>",
                     begin, location.range.begin, end, location.range.end
                 )?;
-                dump_exprloc(w, data, unit)?;
+                dump_exprloc(w, data, unit, register_name)?;
                 writeln!(w)?;
             }
             gimli::RawLocListEntry::StartLength {
@@ -1606,7 +1467,7 @@ fn dump_loc_list<R: Reader, W: Write>(
                      high-off: 0x{:08x} addr 0x{:08x}>",
                     begin, location.range.begin, length, location.range.end
                 )?;
-                dump_exprloc(w, data, unit)?;
+                dump_exprloc(w, data, unit, register_name)?;
                 writeln!(w)?;
             }
         };
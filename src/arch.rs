@@ -1,7 +1,10 @@
 use common::Register;
 
+// The `registers!` macro defines a constant for each register, and the lookup
+// functions between register numbers and names. Any names following the
+// display name are aliases that are also accepted by `name_to_register`.
 macro_rules! registers {
-    ($struct_name:ident, { $($name:ident = ($val:expr, $disp:expr)),+ }) => {
+    ($struct_name:ident, { $($name:ident = ($val:expr, $disp:expr $(, $alias:expr)*)),+ }) => {
         #[allow(missing_docs)]
         impl $struct_name {
             $(
@@ -19,11 +22,27 @@ macro_rules! registers {
                     _ => return None,
                 }
             }
+
+            /// Converts a register name into a register number.
+            ///
+            /// The comparison ignores ASCII case, so that both `"rsp"` and
+            /// `"RSP"` are accepted. Common aliases, such as `"sp"` for the
+            /// ARM register `R13`, are also accepted.
+            pub fn name_to_register(name: &str) -> Option<Register> {
+                $(
+                    if name.eq_ignore_ascii_case($disp)
+                        $(|| name.eq_ignore_ascii_case($alias))*
+                    {
+                        return Some(Self::$name);
+                    }
+                )+
+                None
+            }
         }
     };
     // Handle trailing comma
-    ($struct_name:ident, { $($name:ident = ($val:expr, $disp:expr)),+, }) => {
-        registers!($struct_name, { $($name = ($val, $disp)),+ });
+    ($struct_name:ident, { $($name:ident = ($val:expr, $disp:expr $(, $alias:expr)*)),+, }) => {
+        registers!($struct_name, { $($name = ($val, $disp $(, $alias)*)),+ });
     };
 }
 
@@ -33,7 +52,6 @@ macro_rules! registers {
 #[derive(Debug, Clone, Copy)]
 pub struct Arm;
 
-// TODO: add more registers.
 registers!(Arm, {
     R0 = (0, "R0"),
     R1 = (1, "R1"),
@@ -48,9 +66,291 @@ registers!(Arm, {
     R10 = (10, "R10"),
     R11 = (11, "R11"),
     R12 = (12, "R12"),
-    R13 = (13, "R13"),
-    R14 = (14, "R14"),
-    R15 = (15, "R15"),
+    R13 = (13, "R13", "sp"),
+    R14 = (14, "R14", "lr"),
+    R15 = (15, "R15", "pc"),
+
+    // VFP-v2 single precision registers; these are obsolete for new code, which
+    // should use the D registers instead.
+    S0 = (64, "S0"),
+    S1 = (65, "S1"),
+    S2 = (66, "S2"),
+    S3 = (67, "S3"),
+    S4 = (68, "S4"),
+    S5 = (69, "S5"),
+    S6 = (70, "S6"),
+    S7 = (71, "S7"),
+    S8 = (72, "S8"),
+    S9 = (73, "S9"),
+    S10 = (74, "S10"),
+    S11 = (75, "S11"),
+    S12 = (76, "S12"),
+    S13 = (77, "S13"),
+    S14 = (78, "S14"),
+    S15 = (79, "S15"),
+    S16 = (80, "S16"),
+    S17 = (81, "S17"),
+    S18 = (82, "S18"),
+    S19 = (83, "S19"),
+    S20 = (84, "S20"),
+    S21 = (85, "S21"),
+    S22 = (86, "S22"),
+    S23 = (87, "S23"),
+    S24 = (88, "S24"),
+    S25 = (89, "S25"),
+    S26 = (90, "S26"),
+    S27 = (91, "S27"),
+    S28 = (92, "S28"),
+    S29 = (93, "S29"),
+    S30 = (94, "S30"),
+    S31 = (95, "S31"),
+
+    // FPA registers.
+    F0 = (96, "F0"),
+    F1 = (97, "F1"),
+    F2 = (98, "F2"),
+    F3 = (99, "F3"),
+    F4 = (100, "F4"),
+    F5 = (101, "F5"),
+    F6 = (102, "F6"),
+    F7 = (103, "F7"),
+
+    // Intel wireless MMX general purpose and data registers.
+    WCGR0 = (104, "wCGR0", "ACC0"),
+    WCGR1 = (105, "wCGR1", "ACC1"),
+    WCGR2 = (106, "wCGR2", "ACC2"),
+    WCGR3 = (107, "wCGR3", "ACC3"),
+    WCGR4 = (108, "wCGR4", "ACC4"),
+    WCGR5 = (109, "wCGR5", "ACC5"),
+    WCGR6 = (110, "wCGR6", "ACC6"),
+    WCGR7 = (111, "wCGR7", "ACC7"),
+    WR0 = (112, "wR0"),
+    WR1 = (113, "wR1"),
+    WR2 = (114, "wR2"),
+    WR3 = (115, "wR3"),
+    WR4 = (116, "wR4"),
+    WR5 = (117, "wR5"),
+    WR6 = (118, "wR6"),
+    WR7 = (119, "wR7"),
+    WR8 = (120, "wR8"),
+    WR9 = (121, "wR9"),
+    WR10 = (122, "wR10"),
+    WR11 = (123, "wR11"),
+    WR12 = (124, "wR12"),
+    WR13 = (125, "wR13"),
+    WR14 = (126, "wR14"),
+    WR15 = (127, "wR15"),
+
+    SPSR = (128, "SPSR"),
+    SPSR_FIQ = (129, "SPSR_FIQ"),
+    SPSR_IRQ = (130, "SPSR_IRQ"),
+    SPSR_ABT = (131, "SPSR_ABT"),
+    SPSR_UND = (132, "SPSR_UND"),
+    SPSR_SVC = (133, "SPSR_SVC"),
+
+    RA_AUTH_CODE = (143, "RA_AUTH_CODE"),
+
+    R8_USR = (144, "R8_USR"),
+    R9_USR = (145, "R9_USR"),
+    R10_USR = (146, "R10_USR"),
+    R11_USR = (147, "R11_USR"),
+    R12_USR = (148, "R12_USR"),
+    R13_USR = (149, "R13_USR"),
+    R14_USR = (150, "R14_USR"),
+    R8_FIQ = (151, "R8_FIQ"),
+    R9_FIQ = (152, "R9_FIQ"),
+    R10_FIQ = (153, "R10_FIQ"),
+    R11_FIQ = (154, "R11_FIQ"),
+    R12_FIQ = (155, "R12_FIQ"),
+    R13_FIQ = (156, "R13_FIQ"),
+    R14_FIQ = (157, "R14_FIQ"),
+    R13_IRQ = (158, "R13_IRQ"),
+    R14_IRQ = (159, "R14_IRQ"),
+    R13_ABT = (160, "R13_ABT"),
+    R14_ABT = (161, "R14_ABT"),
+    R13_UND = (162, "R13_UND"),
+    R14_UND = (163, "R14_UND"),
+    R13_SVC = (164, "R13_SVC"),
+    R14_SVC = (165, "R14_SVC"),
+
+    WC0 = (192, "wC0"),
+    WC1 = (193, "wC1"),
+    WC2 = (194, "wC2"),
+    WC3 = (195, "wC3"),
+    WC4 = (196, "wC4"),
+    WC5 = (197, "wC5"),
+    WC6 = (198, "wC6"),
+    WC7 = (199, "wC7"),
+
+    // VFP-v3 and Advanced SIMD (NEON) registers. The 128-bit Q registers do not
+    // have their own numbers, and are described as pairs of D registers.
+    D0 = (256, "D0"),
+    D1 = (257, "D1"),
+    D2 = (258, "D2"),
+    D3 = (259, "D3"),
+    D4 = (260, "D4"),
+    D5 = (261, "D5"),
+    D6 = (262, "D6"),
+    D7 = (263, "D7"),
+    D8 = (264, "D8"),
+    D9 = (265, "D9"),
+    D10 = (266, "D10"),
+    D11 = (267, "D11"),
+    D12 = (268, "D12"),
+    D13 = (269, "D13"),
+    D14 = (270, "D14"),
+    D15 = (271, "D15"),
+    D16 = (272, "D16"),
+    D17 = (273, "D17"),
+    D18 = (274, "D18"),
+    D19 = (275, "D19"),
+    D20 = (276, "D20"),
+    D21 = (277, "D21"),
+    D22 = (278, "D22"),
+    D23 = (279, "D23"),
+    D24 = (280, "D24"),
+    D25 = (281, "D25"),
+    D26 = (282, "D26"),
+    D27 = (283, "D27"),
+    D28 = (284, "D28"),
+    D29 = (285, "D29"),
+    D30 = (286, "D30"),
+    D31 = (287, "D31"),
+});
+
+/// ARM 64-bit (AArch64) architecture specific definitions.
+///
+/// See [DWARF for the Arm 64-bit Architecture](https://github.com/ARM-software/abi-aa/blob/main/aadwarf64/aadwarf64.rst).
+#[derive(Debug, Clone, Copy)]
+pub struct AArch64;
+
+registers!(AArch64, {
+    X0 = (0, "X0"),
+    X1 = (1, "X1"),
+    X2 = (2, "X2"),
+    X3 = (3, "X3"),
+    X4 = (4, "X4"),
+    X5 = (5, "X5"),
+    X6 = (6, "X6"),
+    X7 = (7, "X7"),
+    X8 = (8, "X8"),
+    X9 = (9, "X9"),
+    X10 = (10, "X10"),
+    X11 = (11, "X11"),
+    X12 = (12, "X12"),
+    X13 = (13, "X13"),
+    X14 = (14, "X14"),
+    X15 = (15, "X15"),
+    X16 = (16, "X16"),
+    X17 = (17, "X17"),
+    X18 = (18, "X18"),
+    X19 = (19, "X19"),
+    X20 = (20, "X20"),
+    X21 = (21, "X21"),
+    X22 = (22, "X22"),
+    X23 = (23, "X23"),
+    X24 = (24, "X24"),
+    X25 = (25, "X25"),
+    X26 = (26, "X26"),
+    X27 = (27, "X27"),
+    X28 = (28, "X28"),
+    X29 = (29, "X29", "FP"),
+    X30 = (30, "X30", "LR"),
+    SP = (31, "SP"),
+    PC = (32, "PC"),
+    ELR_MODE = (33, "ELR_mode"),
+    RA_SIGN_STATE = (34, "RA_SIGN_STATE"),
+    TPIDRRO_EL0 = (35, "TPIDRRO_EL0"),
+    TPIDR_EL0 = (36, "TPIDR_EL0"),
+    TPIDR_EL1 = (37, "TPIDR_EL1"),
+    TPIDR_EL2 = (38, "TPIDR_EL2"),
+    TPIDR_EL3 = (39, "TPIDR_EL3"),
+
+    // Scalable Vector Extension registers.
+    VG = (46, "VG"),
+    FFR = (47, "FFR"),
+    P0 = (48, "P0"),
+    P1 = (49, "P1"),
+    P2 = (50, "P2"),
+    P3 = (51, "P3"),
+    P4 = (52, "P4"),
+    P5 = (53, "P5"),
+    P6 = (54, "P6"),
+    P7 = (55, "P7"),
+    P8 = (56, "P8"),
+    P9 = (57, "P9"),
+    P10 = (58, "P10"),
+    P11 = (59, "P11"),
+    P12 = (60, "P12"),
+    P13 = (61, "P13"),
+    P14 = (62, "P14"),
+    P15 = (63, "P15"),
+
+    V0 = (64, "V0"),
+    V1 = (65, "V1"),
+    V2 = (66, "V2"),
+    V3 = (67, "V3"),
+    V4 = (68, "V4"),
+    V5 = (69, "V5"),
+    V6 = (70, "V6"),
+    V7 = (71, "V7"),
+    V8 = (72, "V8"),
+    V9 = (73, "V9"),
+    V10 = (74, "V10"),
+    V11 = (75, "V11"),
+    V12 = (76, "V12"),
+    V13 = (77, "V13"),
+    V14 = (78, "V14"),
+    V15 = (79, "V15"),
+    V16 = (80, "V16"),
+    V17 = (81, "V17"),
+    V18 = (82, "V18"),
+    V19 = (83, "V19"),
+    V20 = (84, "V20"),
+    V21 = (85, "V21"),
+    V22 = (86, "V22"),
+    V23 = (87, "V23"),
+    V24 = (88, "V24"),
+    V25 = (89, "V25"),
+    V26 = (90, "V26"),
+    V27 = (91, "V27"),
+    V28 = (92, "V28"),
+    V29 = (93, "V29"),
+    V30 = (94, "V30"),
+    V31 = (95, "V31"),
+
+    Z0 = (96, "Z0"),
+    Z1 = (97, "Z1"),
+    Z2 = (98, "Z2"),
+    Z3 = (99, "Z3"),
+    Z4 = (100, "Z4"),
+    Z5 = (101, "Z5"),
+    Z6 = (102, "Z6"),
+    Z7 = (103, "Z7"),
+    Z8 = (104, "Z8"),
+    Z9 = (105, "Z9"),
+    Z10 = (106, "Z10"),
+    Z11 = (107, "Z11"),
+    Z12 = (108, "Z12"),
+    Z13 = (109, "Z13"),
+    Z14 = (110, "Z14"),
+    Z15 = (111, "Z15"),
+    Z16 = (112, "Z16"),
+    Z17 = (113, "Z17"),
+    Z18 = (114, "Z18"),
+    Z19 = (115, "Z19"),
+    Z20 = (116, "Z20"),
+    Z21 = (117, "Z21"),
+    Z22 = (118, "Z22"),
+    Z23 = (119, "Z23"),
+    Z24 = (120, "Z24"),
+    Z25 = (121, "Z25"),
+    Z26 = (122, "Z26"),
+    Z27 = (123, "Z27"),
+    Z28 = (124, "Z28"),
+    Z29 = (125, "Z29"),
+    Z30 = (126, "Z30"),
+    Z31 = (127, "Z31"),
 });
 
 /// Intel i386 architecture specific definitions.
@@ -222,3 +522,452 @@ registers!(X86_64, {
     K6 = (124, "k6"),
     K7 = (125, "k7"),
 });
+
+/// MIPS architecture specific definitions.
+///
+/// There is no published DWARF register mapping for MIPS, so this follows the
+/// numbering used by GCC and LLVM for both 32-bit and 64-bit targets.
+#[derive(Debug, Clone, Copy)]
+pub struct Mips;
+
+registers!(Mips, {
+    // The temporary and argument registers 8-15 have different names in the o32
+    // and n32/n64 ABIs, so only the ABI independent names are accepted as aliases.
+    R0 = (0, "$0", "$zero"),
+    R1 = (1, "$1", "$at"),
+    R2 = (2, "$2", "$v0"),
+    R3 = (3, "$3", "$v1"),
+    R4 = (4, "$4", "$a0"),
+    R5 = (5, "$5", "$a1"),
+    R6 = (6, "$6", "$a2"),
+    R7 = (7, "$7", "$a3"),
+    R8 = (8, "$8"),
+    R9 = (9, "$9"),
+    R10 = (10, "$10"),
+    R11 = (11, "$11"),
+    R12 = (12, "$12"),
+    R13 = (13, "$13"),
+    R14 = (14, "$14"),
+    R15 = (15, "$15"),
+    R16 = (16, "$16", "$s0"),
+    R17 = (17, "$17", "$s1"),
+    R18 = (18, "$18", "$s2"),
+    R19 = (19, "$19", "$s3"),
+    R20 = (20, "$20", "$s4"),
+    R21 = (21, "$21", "$s5"),
+    R22 = (22, "$22", "$s6"),
+    R23 = (23, "$23", "$s7"),
+    R24 = (24, "$24", "$t8"),
+    R25 = (25, "$25", "$t9"),
+    R26 = (26, "$26", "$k0"),
+    R27 = (27, "$27", "$k1"),
+    R28 = (28, "$28", "$gp"),
+    R29 = (29, "$29", "$sp"),
+    R30 = (30, "$30", "$fp", "$s8"),
+    R31 = (31, "$31", "$ra"),
+
+    F0 = (32, "$f0"),
+    F1 = (33, "$f1"),
+    F2 = (34, "$f2"),
+    F3 = (35, "$f3"),
+    F4 = (36, "$f4"),
+    F5 = (37, "$f5"),
+    F6 = (38, "$f6"),
+    F7 = (39, "$f7"),
+    F8 = (40, "$f8"),
+    F9 = (41, "$f9"),
+    F10 = (42, "$f10"),
+    F11 = (43, "$f11"),
+    F12 = (44, "$f12"),
+    F13 = (45, "$f13"),
+    F14 = (46, "$f14"),
+    F15 = (47, "$f15"),
+    F16 = (48, "$f16"),
+    F17 = (49, "$f17"),
+    F18 = (50, "$f18"),
+    F19 = (51, "$f19"),
+    F20 = (52, "$f20"),
+    F21 = (53, "$f21"),
+    F22 = (54, "$f22"),
+    F23 = (55, "$f23"),
+    F24 = (56, "$f24"),
+    F25 = (57, "$f25"),
+    F26 = (58, "$f26"),
+    F27 = (59, "$f27"),
+    F28 = (60, "$f28"),
+    F29 = (61, "$f29"),
+    F30 = (62, "$f30"),
+    F31 = (63, "$f31"),
+
+    HI = (64, "$hi"),
+    LO = (65, "$lo"),
+});
+
+/// PowerPC 64-bit architecture specific definitions.
+///
+/// See the [64-Bit ELF V2 ABI Specification](https://openpowerfoundation.org/specifications/64bitelfabi/).
+#[derive(Debug, Clone, Copy)]
+pub struct PowerPc64;
+
+registers!(PowerPc64, {
+    R0 = (0, "r0"),
+    R1 = (1, "r1", "sp"),
+    R2 = (2, "r2"),
+    R3 = (3, "r3"),
+    R4 = (4, "r4"),
+    R5 = (5, "r5"),
+    R6 = (6, "r6"),
+    R7 = (7, "r7"),
+    R8 = (8, "r8"),
+    R9 = (9, "r9"),
+    R10 = (10, "r10"),
+    R11 = (11, "r11"),
+    R12 = (12, "r12"),
+    R13 = (13, "r13"),
+    R14 = (14, "r14"),
+    R15 = (15, "r15"),
+    R16 = (16, "r16"),
+    R17 = (17, "r17"),
+    R18 = (18, "r18"),
+    R19 = (19, "r19"),
+    R20 = (20, "r20"),
+    R21 = (21, "r21"),
+    R22 = (22, "r22"),
+    R23 = (23, "r23"),
+    R24 = (24, "r24"),
+    R25 = (25, "r25"),
+    R26 = (26, "r26"),
+    R27 = (27, "r27"),
+    R28 = (28, "r28"),
+    R29 = (29, "r29"),
+    R30 = (30, "r30"),
+    R31 = (31, "r31"),
+
+    F0 = (32, "f0"),
+    F1 = (33, "f1"),
+    F2 = (34, "f2"),
+    F3 = (35, "f3"),
+    F4 = (36, "f4"),
+    F5 = (37, "f5"),
+    F6 = (38, "f6"),
+    F7 = (39, "f7"),
+    F8 = (40, "f8"),
+    F9 = (41, "f9"),
+    F10 = (42, "f10"),
+    F11 = (43, "f11"),
+    F12 = (44, "f12"),
+    F13 = (45, "f13"),
+    F14 = (46, "f14"),
+    F15 = (47, "f15"),
+    F16 = (48, "f16"),
+    F17 = (49, "f17"),
+    F18 = (50, "f18"),
+    F19 = (51, "f19"),
+    F20 = (52, "f20"),
+    F21 = (53, "f21"),
+    F22 = (54, "f22"),
+    F23 = (55, "f23"),
+    F24 = (56, "f24"),
+    F25 = (57, "f25"),
+    F26 = (58, "f26"),
+    F27 = (59, "f27"),
+    F28 = (60, "f28"),
+    F29 = (61, "f29"),
+    F30 = (62, "f30"),
+    F31 = (63, "f31"),
+
+    LR = (65, "lr"),
+    CTR = (66, "ctr"),
+
+    CR0 = (68, "cr0"),
+    CR1 = (69, "cr1"),
+    CR2 = (70, "cr2"),
+    CR3 = (71, "cr3"),
+    CR4 = (72, "cr4"),
+    CR5 = (73, "cr5"),
+    CR6 = (74, "cr6"),
+    CR7 = (75, "cr7"),
+    XER = (76, "xer"),
+
+    VR0 = (77, "vr0", "v0"),
+    VR1 = (78, "vr1", "v1"),
+    VR2 = (79, "vr2", "v2"),
+    VR3 = (80, "vr3", "v3"),
+    VR4 = (81, "vr4", "v4"),
+    VR5 = (82, "vr5", "v5"),
+    VR6 = (83, "vr6", "v6"),
+    VR7 = (84, "vr7", "v7"),
+    VR8 = (85, "vr8", "v8"),
+    VR9 = (86, "vr9", "v9"),
+    VR10 = (87, "vr10", "v10"),
+    VR11 = (88, "vr11", "v11"),
+    VR12 = (89, "vr12", "v12"),
+    VR13 = (90, "vr13", "v13"),
+    VR14 = (91, "vr14", "v14"),
+    VR15 = (92, "vr15", "v15"),
+    VR16 = (93, "vr16", "v16"),
+    VR17 = (94, "vr17", "v17"),
+    VR18 = (95, "vr18", "v18"),
+    VR19 = (96, "vr19", "v19"),
+    VR20 = (97, "vr20", "v20"),
+    VR21 = (98, "vr21", "v21"),
+    VR22 = (99, "vr22", "v22"),
+    VR23 = (100, "vr23", "v23"),
+    VR24 = (101, "vr24", "v24"),
+    VR25 = (102, "vr25", "v25"),
+    VR26 = (103, "vr26", "v26"),
+    VR27 = (104, "vr27", "v27"),
+    VR28 = (105, "vr28", "v28"),
+    VR29 = (106, "vr29", "v29"),
+    VR30 = (107, "vr30", "v30"),
+    VR31 = (108, "vr31", "v31"),
+
+    VSCR = (110, "vscr"),
+
+    // Transactional memory registers.
+    TFHAR = (114, "tfhar"),
+    TFIAR = (115, "tfiar"),
+    TEXASR = (116, "texasr"),
+});
+
+/// RISC-V architecture specific definitions.
+///
+/// The constants use the ABI register names, and the `x` and `f` register
+/// names are accepted as aliases.
+///
+/// See the [RISC-V ELF psABI](https://github.com/riscv-non-isa/riscv-elf-psabi-doc/blob/master/riscv-dwarf.adoc).
+#[derive(Debug, Clone, Copy)]
+pub struct RiscV;
+
+registers!(RiscV, {
+    ZERO = (0, "zero", "x0"),
+    RA = (1, "ra", "x1"),
+    SP = (2, "sp", "x2"),
+    GP = (3, "gp", "x3"),
+    TP = (4, "tp", "x4"),
+    T0 = (5, "t0", "x5"),
+    T1 = (6, "t1", "x6"),
+    T2 = (7, "t2", "x7"),
+    S0 = (8, "s0", "x8", "fp"),
+    S1 = (9, "s1", "x9"),
+    A0 = (10, "a0", "x10"),
+    A1 = (11, "a1", "x11"),
+    A2 = (12, "a2", "x12"),
+    A3 = (13, "a3", "x13"),
+    A4 = (14, "a4", "x14"),
+    A5 = (15, "a5", "x15"),
+    A6 = (16, "a6", "x16"),
+    A7 = (17, "a7", "x17"),
+    S2 = (18, "s2", "x18"),
+    S3 = (19, "s3", "x19"),
+    S4 = (20, "s4", "x20"),
+    S5 = (21, "s5", "x21"),
+    S6 = (22, "s6", "x22"),
+    S7 = (23, "s7", "x23"),
+    S8 = (24, "s8", "x24"),
+    S9 = (25, "s9", "x25"),
+    S10 = (26, "s10", "x26"),
+    S11 = (27, "s11", "x27"),
+    T3 = (28, "t3", "x28"),
+    T4 = (29, "t4", "x29"),
+    T5 = (30, "t5", "x30"),
+    T6 = (31, "t6", "x31"),
+
+    FT0 = (32, "ft0", "f0"),
+    FT1 = (33, "ft1", "f1"),
+    FT2 = (34, "ft2", "f2"),
+    FT3 = (35, "ft3", "f3"),
+    FT4 = (36, "ft4", "f4"),
+    FT5 = (37, "ft5", "f5"),
+    FT6 = (38, "ft6", "f6"),
+    FT7 = (39, "ft7", "f7"),
+    FS0 = (40, "fs0", "f8"),
+    FS1 = (41, "fs1", "f9"),
+    FA0 = (42, "fa0", "f10"),
+    FA1 = (43, "fa1", "f11"),
+    FA2 = (44, "fa2", "f12"),
+    FA3 = (45, "fa3", "f13"),
+    FA4 = (46, "fa4", "f14"),
+    FA5 = (47, "fa5", "f15"),
+    FA6 = (48, "fa6", "f16"),
+    FA7 = (49, "fa7", "f17"),
+    FS2 = (50, "fs2", "f18"),
+    FS3 = (51, "fs3", "f19"),
+    FS4 = (52, "fs4", "f20"),
+    FS5 = (53, "fs5", "f21"),
+    FS6 = (54, "fs6", "f22"),
+    FS7 = (55, "fs7", "f23"),
+    FS8 = (56, "fs8", "f24"),
+    FS9 = (57, "fs9", "f25"),
+    FS10 = (58, "fs10", "f26"),
+    FS11 = (59, "fs11", "f27"),
+    FT8 = (60, "ft8", "f28"),
+    FT9 = (61, "ft9", "f29"),
+    FT10 = (62, "ft10", "f30"),
+    FT11 = (63, "ft11", "f31"),
+
+    V0 = (96, "v0"),
+    V1 = (97, "v1"),
+    V2 = (98, "v2"),
+    V3 = (99, "v3"),
+    V4 = (100, "v4"),
+    V5 = (101, "v5"),
+    V6 = (102, "v6"),
+    V7 = (103, "v7"),
+    V8 = (104, "v8"),
+    V9 = (105, "v9"),
+    V10 = (106, "v10"),
+    V11 = (107, "v11"),
+    V12 = (108, "v12"),
+    V13 = (109, "v13"),
+    V14 = (110, "v14"),
+    V15 = (111, "v15"),
+    V16 = (112, "v16"),
+    V17 = (113, "v17"),
+    V18 = (114, "v18"),
+    V19 = (115, "v19"),
+    V20 = (116, "v20"),
+    V21 = (117, "v21"),
+    V22 = (118, "v22"),
+    V23 = (119, "v23"),
+    V24 = (120, "v24"),
+    V25 = (121, "v25"),
+    V26 = (122, "v26"),
+    V27 = (123, "v27"),
+    V28 = (124, "v28"),
+    V29 = (125, "v29"),
+    V30 = (126, "v30"),
+    V31 = (127, "v31"),
+});
+
+/// IBM z/Architecture (s390x) architecture specific definitions.
+///
+/// See the [ELF Application Binary Interface s390x Supplement](https://github.com/IBM/s390x-abi).
+#[derive(Debug, Clone, Copy)]
+pub struct S390x;
+
+registers!(S390x, {
+    R0 = (0, "r0"),
+    R1 = (1, "r1"),
+    R2 = (2, "r2"),
+    R3 = (3, "r3"),
+    R4 = (4, "r4"),
+    R5 = (5, "r5"),
+    R6 = (6, "r6"),
+    R7 = (7, "r7"),
+    R8 = (8, "r8"),
+    R9 = (9, "r9"),
+    R10 = (10, "r10"),
+    R11 = (11, "r11"),
+    R12 = (12, "r12"),
+    R13 = (13, "r13"),
+    R14 = (14, "r14"),
+    R15 = (15, "r15"),
+
+    // The floating point registers are not numbered in order.
+    F0 = (16, "f0"),
+    F2 = (17, "f2"),
+    F4 = (18, "f4"),
+    F6 = (19, "f6"),
+    F1 = (20, "f1"),
+    F3 = (21, "f3"),
+    F5 = (22, "f5"),
+    F7 = (23, "f7"),
+    F8 = (24, "f8"),
+    F10 = (25, "f10"),
+    F12 = (26, "f12"),
+    F14 = (27, "f14"),
+    F9 = (28, "f9"),
+    F11 = (29, "f11"),
+    F13 = (30, "f13"),
+    F15 = (31, "f15"),
+
+    CR0 = (32, "cr0"),
+    CR1 = (33, "cr1"),
+    CR2 = (34, "cr2"),
+    CR3 = (35, "cr3"),
+    CR4 = (36, "cr4"),
+    CR5 = (37, "cr5"),
+    CR6 = (38, "cr6"),
+    CR7 = (39, "cr7"),
+    CR8 = (40, "cr8"),
+    CR9 = (41, "cr9"),
+    CR10 = (42, "cr10"),
+    CR11 = (43, "cr11"),
+    CR12 = (44, "cr12"),
+    CR13 = (45, "cr13"),
+    CR14 = (46, "cr14"),
+    CR15 = (47, "cr15"),
+
+    A0 = (48, "a0"),
+    A1 = (49, "a1"),
+    A2 = (50, "a2"),
+    A3 = (51, "a3"),
+    A4 = (52, "a4"),
+    A5 = (53, "a5"),
+    A6 = (54, "a6"),
+    A7 = (55, "a7"),
+    A8 = (56, "a8"),
+    A9 = (57, "a9"),
+    A10 = (58, "a10"),
+    A11 = (59, "a11"),
+    A12 = (60, "a12"),
+    A13 = (61, "a13"),
+    A14 = (62, "a14"),
+    A15 = (63, "a15"),
+
+    PSWM = (64, "pswm"),
+    PSWA = (65, "pswa"),
+
+    // The vector registers 0-15 overlap the floating point registers, and use
+    // their numbers.
+    V16 = (68, "v16"),
+    V18 = (69, "v18"),
+    V20 = (70, "v20"),
+    V22 = (71, "v22"),
+    V17 = (72, "v17"),
+    V19 = (73, "v19"),
+    V21 = (74, "v21"),
+    V23 = (75, "v23"),
+    V24 = (76, "v24"),
+    V26 = (77, "v26"),
+    V28 = (78, "v28"),
+    V30 = (79, "v30"),
+    V25 = (80, "v25"),
+    V27 = (81, "v27"),
+    V29 = (82, "v29"),
+    V31 = (83, "v31"),
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_name() {
+        assert_eq!(Arm::register_name(Arm::R13), Some("R13"));
+        assert_eq!(Arm::register_name(Arm::D31), Some("D31"));
+        assert_eq!(AArch64::register_name(Register(34)), Some("RA_SIGN_STATE"));
+        assert_eq!(Mips::register_name(Mips::R29), Some("$29"));
+        assert_eq!(PowerPc64::register_name(Register(65)), Some("lr"));
+        assert_eq!(RiscV::register_name(Register(8)), Some("s0"));
+        assert_eq!(S390x::register_name(Register(17)), Some("f2"));
+        assert_eq!(AArch64::register_name(Register(40)), None);
+    }
+
+    #[test]
+    fn test_name_to_register() {
+        assert_eq!(Arm::name_to_register("sp"), Some(Arm::R13));
+        assert_eq!(Arm::name_to_register("s0"), Some(Arm::S0));
+        assert_eq!(AArch64::name_to_register("lr"), Some(AArch64::X30));
+        assert_eq!(AArch64::name_to_register("vg"), Some(AArch64::VG));
+        assert_eq!(Mips::name_to_register("$sp"), Some(Mips::R29));
+        assert_eq!(Mips::name_to_register("$f12"), Some(Mips::F12));
+        assert_eq!(PowerPc64::name_to_register("v2"), Some(PowerPc64::VR2));
+        assert_eq!(RiscV::name_to_register("x10"), Some(RiscV::A0));
+        assert_eq!(RiscV::name_to_register("fp"), Some(RiscV::S0));
+        assert_eq!(RiscV::name_to_register("f10"), Some(RiscV::FA0));
+        assert_eq!(S390x::name_to_register("v17"), Some(S390x::V17));
+        assert_eq!(X86_64::name_to_register("sp"), None);
+    }
+}
@@ -77,6 +77,20 @@ impl Default for LineEncoding {
     }
 }
 
+/// The set of vendor extensions to use when interpreting DWARF.
+///
+/// Some vendor extensions reuse the same encoding for different purposes, so
+/// the vendor must be known in order to decode them correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Vendor {
+    /// Extensions used by most targets, including the GNU extensions.
+    Default,
+    /// Extensions specific to the AArch64 ABI.
+    AArch64,
+    /// Extensions specific to SPARC.
+    Sparc,
+}
+
 /// A DWARF register number.
 ///
 /// The meaning of this value is ABI dependent. This is generally encoded as
@@ -125,6 +139,10 @@ pub struct DebugLocListsIndex<T = usize>(pub T);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct DebugMacinfoOffset<T = usize>(pub T);
 
+/// An offset into the `.debug_macro` section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DebugMacroOffset<T = usize>(pub T);
+
 /// An offset into either the `.debug_ranges` section or the `.debug_rnglists` section,
 /// depending on the version of the unit the offset was contained in.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -158,6 +176,10 @@ pub struct DebugTypesOffset<T = usize>(pub T);
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct DebugTypeSignature(pub u64);
 
+/// The identifier used to link a skeleton unit with its split compilation unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DwoId(pub u64);
+
 /// An offset into the `.debug_frame` section.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct DebugFrameOffset<T = usize>(pub T);
@@ -188,3 +210,14 @@ pub enum UnitSectionOffset<T = usize> {
     /// An offset into the `.debug_types` section.
     DebugTypesOffset(DebugTypesOffset<T>),
 }
+
+/// Return the hash of a name for the `.debug_names` hash lookup table.
+///
+/// This is the DJB hash of the name after case folding. Only ASCII characters
+/// are folded; other bytes are hashed unchanged.
+pub fn debug_names_hash(name: &[u8]) -> u32 {
+    name.iter().fold(5381u32, |hash, c| {
+        hash.wrapping_mul(33)
+            .wrapping_add(u32::from(c.to_ascii_lowercase()))
+    })
+}
@@ -149,6 +149,10 @@ dw!(DwCfa(u8) {
     DW_CFA_GNU_negative_offset_extended = 0x2f,
 });
 
+/// The AArch64 ABI reuses the encoding of `DW_CFA_GNU_window_save`, so this
+/// can't be part of `DwCfa::static_string`.
+pub const DW_CFA_AARCH64_negate_ra_state: DwCfa = DwCfa(0x2d);
+
 /// The child determination encodings for DIE attributes.
 /// See Section 7.5.3, Table 7.4.
 dw!(DwChildren(u8) {
@@ -953,6 +957,16 @@ dw!(DwLnct(u16) {
     DW_LNCT_hi_user = 0x3fff,
 });
 
+/// The encodings for the entry types in the `.debug_macinfo` section.
+/// See Section 7.22 of the DWARF 4 standard.
+dw!(DwMacinfo(u8) {
+    DW_MACINFO_define = 0x01,
+    DW_MACINFO_undef = 0x02,
+    DW_MACINFO_start_file = 0x03,
+    DW_MACINFO_end_file = 0x04,
+    DW_MACINFO_vendor_ext = 0xff,
+});
+
 /// The encodings for macro information entry types.
 /// See Section 7.23, Table 7.28.
 dw!(DwMacro(u8) {
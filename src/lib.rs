@@ -207,7 +207,6 @@ extern crate stable_deref_trait;
 #[cfg(feature = "std")]
 mod imports {
     pub use std::borrow;
-    pub use std::boxed;
     pub use std::collections;
     pub use std::rc;
     pub use std::string;
@@ -218,7 +217,6 @@ mod imports {
 #[cfg(not(feature = "std"))]
 mod imports {
     pub use alloc::borrow;
-    pub use alloc::boxed;
     pub use alloc::collections;
     pub use alloc::rc;
     pub use alloc::string;
@@ -1,11 +1,16 @@
 //! Functions for parsing DWARF debugging abbreviations.
 
 use collections::btree_map;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
 use vec::Vec;
+use Arc;
 
 use common::DebugAbbrevOffset;
 use constants;
 use endianity::Endianity;
+#[cfg(feature = "std")]
+use read::ReaderOffset;
 use read::{EndianSlice, Error, Reader, Result, Section, UnitHeader};
 
 /// The `DebugAbbrev` struct represents the abbreviations describing
@@ -68,6 +73,100 @@ impl<R: Reader> From<R> for DebugAbbrev<R> {
     }
 }
 
+/// A cache of previously parsed `Abbreviations`, keyed by their offset in
+/// the `.debug_abbrev` section.
+///
+/// Units frequently share the same abbreviations, such as for units produced
+/// by LTO or `dwz`, and this avoids parsing them again for every unit.
+///
+/// The cache is only used if the `std` feature is enabled. It can also be
+/// disabled at runtime to reduce memory usage.
+#[derive(Debug)]
+pub struct AbbreviationsCache {
+    #[cfg(feature = "std")]
+    abbreviations: Mutex<btree_map::BTreeMap<u64, Arc<Abbreviations>>>,
+    enabled: bool,
+}
+
+impl Default for AbbreviationsCache {
+    fn default() -> Self {
+        AbbreviationsCache::new()
+    }
+}
+
+impl AbbreviationsCache {
+    /// Construct a new, empty cache.
+    pub fn new() -> Self {
+        AbbreviationsCache {
+            #[cfg(feature = "std")]
+            abbreviations: Mutex::new(btree_map::BTreeMap::new()),
+            enabled: true,
+        }
+    }
+
+    /// Construct a cache that never stores any abbreviations.
+    pub fn disabled() -> Self {
+        AbbreviationsCache {
+            enabled: false,
+            ..AbbreviationsCache::new()
+        }
+    }
+
+    /// Return true if this cache stores the abbreviations that it parses.
+    ///
+    /// This is always false if the `std` feature is not enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && cfg!(feature = "std")
+    }
+
+    /// Remove all abbreviations from the cache.
+    pub fn clear(&self) {
+        #[cfg(feature = "std")]
+        self.abbreviations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Return the abbreviations at the given offset in the `.debug_abbrev`
+    /// section, parsing them if they are not already in the cache.
+    pub fn get<R: Reader>(
+        &self,
+        debug_abbrev: &DebugAbbrev<R>,
+        offset: DebugAbbrevOffset<R::Offset>,
+    ) -> Result<Arc<Abbreviations>> {
+        if !self.enabled {
+            return debug_abbrev.abbreviations(offset).map(Arc::new);
+        }
+        #[cfg(feature = "std")]
+        {
+            let key = offset.0.into_u64();
+            if let Some(abbreviations) = self
+                .abbreviations
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&key)
+            {
+                return Ok(abbreviations.clone());
+            }
+            // Parse without holding the lock, so that other units can still
+            // use the cache.
+            let abbreviations = Arc::new(debug_abbrev.abbreviations(offset)?);
+            Ok(self
+                .abbreviations
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(key)
+                .or_insert(abbreviations)
+                .clone())
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            debug_abbrev.abbreviations(offset).map(Arc::new)
+        }
+    }
+}
+
 /// A set of type abbreviations.
 ///
 /// Construct an `Abbreviations` instance with the
@@ -629,6 +728,52 @@ pub mod tests {
         assert_eq!(*rest, EndianSlice::new(&expected_rest, LittleEndian));
     }
 
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_abbreviations_cache() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let buf = Section::new()
+            .abbrev(1, constants::DW_TAG_compile_unit, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .abbrev(1, constants::DW_TAG_subprogram, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev_null()
+            .get_contents()
+            .unwrap();
+        let debug_abbrev = DebugAbbrev::new(&buf, LittleEndian);
+        let offset1 = DebugAbbrevOffset(0);
+        let offset2 = DebugAbbrevOffset(8);
+
+        let cache = AbbreviationsCache::new();
+        assert!(cache.is_enabled());
+        let abbrevs1 = cache.get(&debug_abbrev, offset1).unwrap();
+        let abbrevs2 = cache.get(&debug_abbrev, offset2).unwrap();
+        assert_eq!(
+            abbrevs1.get(1).unwrap().tag(),
+            constants::DW_TAG_compile_unit
+        );
+        assert_eq!(abbrevs2.get(1).unwrap().tag(), constants::DW_TAG_subprogram);
+        assert!(Arc::ptr_eq(
+            &abbrevs1,
+            &cache.get(&debug_abbrev, offset1).unwrap()
+        ));
+        assert!(!Arc::ptr_eq(&abbrevs1, &abbrevs2));
+
+        cache.clear();
+        let abbrevs3 = cache.get(&debug_abbrev, offset1).unwrap();
+        assert!(!Arc::ptr_eq(&abbrevs1, &abbrevs3));
+        assert_eq!(abbrevs1.get(1), abbrevs3.get(1));
+
+        let cache = AbbreviationsCache::disabled();
+        assert!(!cache.is_enabled());
+        let abbrevs4 = cache.get(&debug_abbrev, offset1).unwrap();
+        let abbrevs5 = cache.get(&debug_abbrev, offset1).unwrap();
+        assert!(!Arc::ptr_eq(&abbrevs4, &abbrevs5));
+        assert_eq!(abbrevs4.get(1), abbrevs5.get(1));
+    }
+
     #[test]
     fn test_parse_abbreviations_duplicate() {
         let expected_rest = [1, 2, 3, 4];
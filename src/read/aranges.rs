@@ -224,6 +224,12 @@ impl<R: Reader> Section<R> for DebugAranges<R> {
     }
 }
 
+impl<R: Reader + Default> Default for DebugAranges<R> {
+    fn default() -> Self {
+        DebugAranges::from(R::default())
+    }
+}
+
 impl<R: Reader> From<R> for DebugAranges<R> {
     fn from(debug_aranges_section: R) -> Self {
         DebugAranges(DebugLookup::from(debug_aranges_section))
@@ -1,15 +1,15 @@
-use arrayvec::ArrayVec;
-use boxed::Box;
+use arrayvec::{Array, ArrayVec};
 use fallible_iterator::FallibleIterator;
 use std::cell::RefCell;
 use std::cmp::{Ord, Ordering};
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::mem;
 use std::str;
+use vec::Vec;
 
-use common::{DebugFrameOffset, EhFrameOffset, Format, Register};
+use common::{DebugFrameOffset, EhFrameOffset, Encoding, Format, Register, Vendor};
 use constants::{self, DwEhPe};
 use endianity::Endianity;
 use read::{
@@ -36,6 +36,7 @@ pub struct DebugFrame<R: Reader> {
     section: R,
     address_size: u8,
     segment_size: u8,
+    vendor: Vendor,
 }
 
 impl<R: Reader> DebugFrame<R> {
@@ -54,6 +55,13 @@ impl<R: Reader> DebugFrame<R> {
     pub fn set_segment_size(&mut self, segment_size: u8) {
         self.segment_size = segment_size
     }
+
+    /// Set the vendor extensions to use.
+    ///
+    /// This defaults to `Vendor::Default`.
+    pub fn set_vendor(&mut self, vendor: Vendor) {
+        self.vendor = vendor;
+    }
 }
 
 impl<'input, Endian> DebugFrame<EndianSlice<'input, Endian>>
@@ -93,6 +101,7 @@ impl<R: Reader> From<R> for DebugFrame<R> {
             section,
             address_size: mem::size_of::<usize>() as u8,
             segment_size: 0,
+            vendor: Vendor::Default,
         }
     }
 }
@@ -362,6 +371,7 @@ impl<'a, R: Reader + 'a> EhHdrTable<'a, R> {
 pub struct EhFrame<R: Reader> {
     section: R,
     address_size: u8,
+    vendor: Vendor,
 }
 
 impl<R: Reader> EhFrame<R> {
@@ -371,6 +381,13 @@ impl<R: Reader> EhFrame<R> {
     pub fn set_address_size(&mut self, address_size: u8) {
         self.address_size = address_size
     }
+
+    /// Set the vendor extensions to use.
+    ///
+    /// This defaults to `Vendor::Default`.
+    pub fn set_vendor(&mut self, vendor: Vendor) {
+        self.vendor = vendor;
+    }
 }
 
 impl<'input, Endian> EhFrame<EndianSlice<'input, Endian>>
@@ -409,6 +426,7 @@ impl<R: Reader> From<R> for EhFrame<R> {
         EhFrame {
             section,
             address_size: mem::size_of::<usize>() as u8,
+            vendor: Vendor::Default,
         }
     }
 }
@@ -502,6 +520,9 @@ pub trait _UnwindSectionPrivate<R: Reader> {
     /// The segment size to use if `has_address_and_segment_sizes` returns false.
     fn segment_size(&self) -> u8;
 
+    /// The vendor extensions to use when parsing call frame instructions.
+    fn vendor(&self) -> Vendor;
+
     /// What is the encoding used for the return address register in CIEs for
     /// this unwind section?
     fn return_address_register_encoding(version: u8) -> ReturnAddressRegisterEncoding;
@@ -546,6 +567,69 @@ pub trait UnwindSection<R: Reader>: Clone + Debug + _UnwindSectionPrivate<R> {
         }
     }
 
+    /// Parse the `FrameDescriptionEntry` at the given offset.
+    ///
+    /// The `get_cie` callback is used to get the FDE's CIE, so that callers
+    /// can cache parsed CIEs.
+    fn fde_from_offset<F>(
+        &self,
+        bases: &BaseAddresses,
+        offset: Self::Offset,
+        get_cie: F,
+    ) -> Result<FrameDescriptionEntry<Self, R, R::Offset>>
+    where
+        F: FnMut(Self::Offset) -> Result<CommonInformationEntry<Self, R, R::Offset>>,
+    {
+        let input = &mut self.section().clone();
+        input.skip(UnwindOffset::into(offset))?;
+        match parse_cfi_entry(bases, self.clone(), input)? {
+            Some(CieOrFde::Fde(partial)) => partial.parse(get_cie),
+            Some(CieOrFde::Cie(_)) => Err(Error::NotFdePointer),
+            None => Err(Error::NoEntryAtGivenOffset),
+        }
+    }
+
+    /// Find the `FrameDescriptionEntry` that contains the given address.
+    ///
+    /// This searches the entries of the section in order, so each call takes
+    /// time proportional to the size of the section, and returns the first FDE
+    /// that contains the address. When looking up many addresses, use an
+    /// [`EhHdrTable`](./struct.EhHdrTable.html) if there is an `.eh_frame_hdr`
+    /// section, or build an [`FdeIndex`](./struct.FdeIndex.html) otherwise.
+    ///
+    /// Returns `Err(gimli::Error::NoUnwindInfoForAddress)` if there is no
+    /// such FDE.
+    fn fde_for_address(
+        &self,
+        bases: &BaseAddresses,
+        address: u64,
+    ) -> Result<FrameDescriptionEntry<Self, R, R::Offset>> {
+        // FDEs usually follow the CIE that they use, so cache the last CIE.
+        let mut cie: Option<CommonInformationEntry<Self, R, R::Offset>> = None;
+        let mut entries = self.entries(bases);
+        while let Some(entry) = entries.next()? {
+            match entry {
+                CieOrFde::Cie(entry) => cie = Some(entry),
+                CieOrFde::Fde(partial) => {
+                    let fde = partial.parse(|offset| {
+                        if let Some(ref cie) = cie {
+                            if cie.offset() == UnwindOffset::into(offset) {
+                                return Ok(cie.clone());
+                            }
+                        }
+                        let entry = self.cie_from_offset(bases, offset)?;
+                        cie = Some(entry.clone());
+                        Ok(entry)
+                    })?;
+                    if fde.contains(address) {
+                        return Ok(fde);
+                    }
+                }
+            }
+        }
+        Err(Error::NoUnwindInfoForAddress)
+    }
+
     /// Find the frame unwind information for the given address.
     ///
     /// If found, the unwind information is returned along with the reset
@@ -594,39 +678,20 @@ pub trait UnwindSection<R: Reader>: Clone + Debug + _UnwindSectionPrivate<R> {
     /// # }
     /// ```
     #[allow(clippy::type_complexity)]
-    fn unwind_info_for_address<'bases>(
+    fn unwind_info_for_address<'bases, S>(
         &self,
         bases: &'bases BaseAddresses,
-        ctx: UninitializedUnwindContext<Self, R>,
+        ctx: UninitializedUnwindContext<Self, R, S>,
         address: u64,
     ) -> UnwindResult<
-        (UnwindTableRow<R>, UninitializedUnwindContext<Self, R>),
-        UninitializedUnwindContext<Self, R>,
-    > {
-        let mut entries = self.entries(bases);
-        let fde_result = loop {
-            match entries.next() {
-                Err(e) => return Err((e, ctx)),
-                Ok(None) => break Ok(None),
-                Ok(Some(CieOrFde::Cie(_))) => continue,
-                Ok(Some(CieOrFde::Fde(partial))) => {
-                    match partial.parse(|offset| self.cie_from_offset(bases, offset)) {
-                        Err(e) => break Err(e),
-                        Ok(fde) => {
-                            if fde.contains(address) {
-                                break Ok(Some(fde));
-                            } else {
-                                continue;
-                            }
-                        }
-                    }
-                }
-            }
-        };
-
-        let fde = match fde_result {
-            Ok(Some(fde)) => fde,
-            Ok(None) => return Err((Error::NoUnwindInfoForAddress, ctx)),
+        (UnwindTableRow<R, S>, UninitializedUnwindContext<Self, R, S>),
+        UninitializedUnwindContext<Self, R, S>,
+    >
+    where
+        S: UnwindContextStorage<R>,
+    {
+        let fde = match self.fde_for_address(bases, address) {
+            Ok(fde) => fde,
             Err(e) => return Err((e, ctx)),
         };
 
@@ -702,6 +767,10 @@ impl<R: Reader> _UnwindSectionPrivate<R> for DebugFrame<R> {
         self.segment_size
     }
 
+    fn vendor(&self) -> Vendor {
+        self.vendor
+    }
+
     fn return_address_register_encoding(version: u8) -> ReturnAddressRegisterEncoding {
         if version == 1 {
             ReturnAddressRegisterEncoding::U8
@@ -766,6 +835,10 @@ impl<R: Reader> _UnwindSectionPrivate<R> for EhFrame<R> {
         0
     }
 
+    fn vendor(&self) -> Vendor {
+        self.vendor
+    }
+
     fn return_address_register_encoding(_version: u8) -> ReturnAddressRegisterEncoding {
         ReturnAddressRegisterEncoding::Uleb
     }
@@ -775,6 +848,183 @@ impl<R: Reader> UnwindSection<R> for EhFrame<R> {
     type Offset = EhFrameOffset<R::Offset>;
 }
 
+/// An index of the `FrameDescriptionEntry`s in an unwind section, sorted by
+/// address.
+///
+/// This allows fast lookup of the FDE for an address when there is no
+/// `.eh_frame_hdr` section, such as for `.debug_frame`, or for `.eh_frame` in
+/// statically linked binaries and kernels. The index is built once by parsing
+/// every entry in the section, and can then be reused for many lookups. The
+/// parsed CIEs are cached by the index, so they are not parsed again for each
+/// lookup. Use [`Unwinder::set_fde_index`](./struct.Unwinder.html#method.set_fde_index)
+/// to use the index when unwinding.
+///
+/// FDEs are expected not to overlap. If they do, then an address is only
+/// looked up in the FDE with the nearest preceding start address, so an
+/// address within an earlier, longer FDE is not found. This differs from
+/// [`UnwindSection::fde_for_address`](./trait.UnwindSection.html#method.fde_for_address),
+/// which returns the first FDE in the section that contains the address.
+///
+/// ```
+/// use gimli::{BaseAddresses, DebugFrame, FdeIndex, NativeEndian};
+///
+/// # fn foo() -> gimli::Result<()> {
+/// # let read_debug_frame_section = || unimplemented!();
+/// # let addresses: Vec<u64> = unimplemented!();
+/// let debug_frame = DebugFrame::new(read_debug_frame_section(), NativeEndian);
+/// let bases = BaseAddresses::default();
+/// let index = FdeIndex::new(&debug_frame, &bases)?;
+/// for address in addresses {
+///     let fde = index.fde_for_address(&debug_frame, &bases, address)?;
+/// #   let _ = fde;
+/// }
+/// # unreachable!()
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct FdeIndex<Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    // Sorted by start address.
+    fdes: Vec<FdeIndexEntry<R::Offset>>,
+    // Sorted by offset.
+    cies: Vec<CommonInformationEntry<Section, R, R::Offset>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct FdeIndexEntry<T> {
+    start: u64,
+    end: u64,
+    offset: T,
+}
+
+impl<Section, R> FdeIndex<Section, R>
+where
+    R: Reader,
+    Section: UnwindSection<R>,
+{
+    /// Build an index of the FDEs in the given section.
+    ///
+    /// FDEs with an empty address range are not included.
+    pub fn new(section: &Section, bases: &BaseAddresses) -> Result<Self> {
+        let mut index = FdeIndex {
+            fdes: Vec::new(),
+            cies: Vec::new(),
+        };
+        let mut entries = section.entries(bases);
+        while let Some(entry) = entries.next()? {
+            match entry {
+                CieOrFde::Cie(cie) => index.insert_cie(cie),
+                CieOrFde::Fde(partial) => {
+                    let fde = partial.parse(|offset| {
+                        if let Some(cie) = index.cached_cie(UnwindOffset::into(offset)) {
+                            return Ok(cie.clone());
+                        }
+                        let cie = section.cie_from_offset(bases, offset)?;
+                        index.insert_cie(cie.clone());
+                        Ok(cie)
+                    })?;
+                    if fde.len() != 0 {
+                        index.fdes.push(FdeIndexEntry {
+                            start: fde.initial_address(),
+                            end: fde.initial_address().wrapping_add(fde.len()),
+                            offset: fde.offset(),
+                        });
+                    }
+                }
+            }
+        }
+        index.fdes.sort_by_key(|entry| entry.start);
+        Ok(index)
+    }
+
+    /// The number of FDEs in the index.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.fdes.len()
+    }
+
+    /// Return true if the index contains no FDEs.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.fdes.is_empty()
+    }
+
+    /// Return the offset of the FDE that contains the given address.
+    ///
+    /// Only the FDE with the nearest preceding start address is checked, so
+    /// overlapping FDEs may not be found.
+    pub fn lookup(&self, address: u64) -> Option<Section::Offset> {
+        let index = match self
+            .fdes
+            .binary_search_by_key(&address, |entry| entry.start)
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        let entry = &self.fdes[index];
+        if address < entry.end {
+            Some(entry.offset.into())
+        } else {
+            None
+        }
+    }
+
+    /// Find and parse the FDE that contains the given address.
+    ///
+    /// `section` and `bases` must be the same as those used to build the
+    /// index.
+    ///
+    /// Returns `Err(gimli::Error::NoUnwindInfoForAddress)` if there is no
+    /// such FDE.
+    pub fn fde_for_address(
+        &self,
+        section: &Section,
+        bases: &BaseAddresses,
+        address: u64,
+    ) -> Result<FrameDescriptionEntry<Section, R, R::Offset>> {
+        let offset = self.lookup(address).ok_or(Error::NoUnwindInfoForAddress)?;
+        section.fde_from_offset(bases, offset, |offset| {
+            self.cie_from_offset(section, bases, offset)
+        })
+    }
+
+    /// Return the CIE at the given offset, using the cached CIE if possible.
+    pub fn cie_from_offset(
+        &self,
+        section: &Section,
+        bases: &BaseAddresses,
+        offset: Section::Offset,
+    ) -> Result<CommonInformationEntry<Section, R, R::Offset>> {
+        match self.cached_cie(UnwindOffset::into(offset)) {
+            Some(cie) => Ok(cie.clone()),
+            None => section.cie_from_offset(bases, offset),
+        }
+    }
+
+    fn cached_cie(
+        &self,
+        offset: R::Offset,
+    ) -> Option<&CommonInformationEntry<Section, R, R::Offset>> {
+        self.cies
+            .binary_search_by_key(&offset, |cie| cie.offset())
+            .ok()
+            .map(|index| &self.cies[index])
+    }
+
+    fn insert_cie(&mut self, cie: CommonInformationEntry<Section, R, R::Offset>) {
+        if let Err(index) = self
+            .cies
+            .binary_search_by_key(&cie.offset(), |cie| cie.offset())
+        {
+            self.cies.insert(index, cie);
+        }
+    }
+}
+
 /// Optional base addresses for the relative `DW_EH_PE_*` encoded pointers.
 ///
 /// During CIE/FDE parsing, if a relative pointer is encountered for a base
@@ -1089,7 +1339,7 @@ pub struct Augmentation {
     /// > represents the pointer encoding used for the second argument, which is
     /// > the address of a personality routine handler. The size of the
     /// > personality routine pointer is specified by the pointer encoding used.
-    personality: Option<Pointer>,
+    personality: Option<(constants::DwEhPe, Pointer)>,
 
     /// > A 'R' may be present at any position after the first character of the
     /// > string. This character may only be present if 'z' is the first character
@@ -1145,7 +1395,7 @@ impl Augmentation {
                         section.section(),
                         rest,
                     )?;
-                    augmentation.personality = Some(personality);
+                    augmentation.personality = Some((encoding, personality));
                 }
                 b'R' => {
                     let encoding = parse_pointer_encoding(rest)?;
@@ -1264,6 +1514,10 @@ where
     /// in the input.
     initial_instructions: R,
 
+    /// The vendor extensions used when parsing `initial_instructions` and the
+    /// instructions of any FDEs that use this CIE.
+    vendor: Vendor,
+
     phantom: PhantomData<Section>,
 }
 
@@ -1357,6 +1611,7 @@ where
             data_alignment_factor,
             return_address_register,
             initial_instructions: rest,
+            vendor: section.vendor(),
             phantom: PhantomData,
         };
 
@@ -1393,6 +1648,7 @@ where
         CallFrameInstructionIter {
             input: self.initial_instructions.clone(),
             address_size: self.address_size,
+            vendor: self.vendor,
         }
     }
 
@@ -1436,6 +1692,42 @@ where
     pub fn return_address_register(&self) -> Register {
         self.return_address_register
     }
+
+    /// Return the encoding parameters for this CIE.
+    ///
+    /// The version is the CIE version, not the DWARF version.
+    pub fn encoding(&self) -> Encoding {
+        Encoding {
+            format: self.format,
+            version: u16::from(self.version),
+            address_size: self.address_size,
+        }
+    }
+
+    /// Return the encoding and address of the personality routine handler
+    /// for this CIE's FDEs.
+    pub fn personality_with_encoding(&self) -> Option<(constants::DwEhPe, Pointer)> {
+        self.augmentation.as_ref().and_then(|a| a.personality)
+    }
+
+    /// Return the encoding of the LSDA address for this CIE's FDEs.
+    pub fn lsda_encoding(&self) -> Option<constants::DwEhPe> {
+        self.augmentation.as_ref().and_then(|a| a.lsda)
+    }
+
+    /// Return the encoding of the addresses for this CIE's FDEs.
+    pub fn fde_address_encoding(&self) -> Option<constants::DwEhPe> {
+        self.augmentation
+            .as_ref()
+            .and_then(|a| a.fde_address_encoding)
+    }
+
+    /// Return true if this CIE's FDEs are trampolines for signal handlers.
+    pub fn is_signal_trampoline(&self) -> bool {
+        self.augmentation
+            .as_ref()
+            .map_or(false, |a| a.is_signal_trampoline)
+    }
 }
 
 /// A partially parsed `FrameDescriptionEntry`.
@@ -1671,6 +1963,7 @@ where
         CallFrameInstructionIter {
             input: self.instructions.clone(),
             address_size: self.cie.address_size,
+            vendor: self.cie.vendor,
         }
     }
 
@@ -1704,16 +1997,14 @@ where
 
     /// Return true if this FDE's function is a trampoline for a signal handler.
     pub fn is_signal_trampoline(&self) -> bool {
-        self.cie()
-            .augmentation
-            .map_or(false, |a| a.is_signal_trampoline)
+        self.cie().is_signal_trampoline()
     }
 
     /// Return the address of the FDE's function's personality routine
     /// handler. The personality routine does language-specific clean up when
     /// unwinding the stack frames with the intent to not run them again.
     pub fn personality(&self) -> Option<Pointer> {
-        self.cie().augmentation.as_ref().and_then(|a| a.personality)
+        self.cie().personality_with_encoding().map(|(_, p)| p)
     }
 }
 
@@ -1882,11 +2173,20 @@ pub type UnwindResult<T, UnwindContext> = ::std::result::Result<T, (Error, Unwin
 ///          | End |
 ///          +-----+
 /// ```
+///
+/// The rules and rows of the context are stored inline in
+/// [`StoreOnStack`](struct.StoreOnStack.html) by default, which has a fixed
+/// capacity and never allocates, so the context can be kept on the stack or in
+/// a static. Use `Default::default()` to
+/// construct a context with a different
+/// [`UnwindContextStorage`](trait.UnwindContextStorage.html), such as
+/// [`StoreOnHeap`](struct.StoreOnHeap.html).
 #[derive(Clone, Debug)]
-pub struct UninitializedUnwindContext<Section, R>(Box<UnwindContext<Section, R>>)
+pub struct UninitializedUnwindContext<Section, R, S = StoreOnStack>(UnwindContext<Section, R, S>)
 where
     R: Reader,
-    Section: UnwindSection<R>;
+    Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>;
 
 impl<Section, R> UninitializedUnwindContext<Section, R>
 where
@@ -1895,58 +2195,64 @@ where
 {
     /// Construct a new call frame unwinding context.
     pub fn new() -> UninitializedUnwindContext<Section, R> {
-        UninitializedUnwindContext(Box::new(UnwindContext::new()))
+        UninitializedUnwindContext(UnwindContext::new())
     }
 }
 
-impl<Section, R> Default for UninitializedUnwindContext<Section, R>
+impl<Section, R, S> Default for UninitializedUnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     fn default() -> Self {
-        Self::new()
+        UninitializedUnwindContext(UnwindContext::new())
     }
 }
 
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<Section, R> UninitializedUnwindContext<Section, R>
+/// other signal-unsafe operations, depending on the
+/// [storage](trait.UnwindContextStorage.html#signal-safety) that is used.
+impl<Section, R, S> UninitializedUnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     /// Run the CIE's initial instructions, creating an
     /// `InitializedUnwindContext`.
     pub fn initialize(
         mut self,
         cie: &CommonInformationEntry<Section, R, R::Offset>,
-    ) -> UnwindResult<InitializedUnwindContext<Section, R>, Self> {
-        self.0.assert_fully_uninitialized();
+    ) -> UnwindResult<InitializedUnwindContext<Section, R, S>, Self> {
+        match self.0.initialize(cie) {
+            Ok(()) => Ok(InitializedUnwindContext(self.0)),
+            Err(e) => Err((e, self)),
+        }
+    }
 
-        let result = {
-            let mut table = UnwindTable::new_internal(&mut self.0, cie, None);
+    /// Find the row of the FDE's unwind table that contains the given address,
+    /// leaving this context uninitialized again afterwards.
+    pub(crate) fn row_for_address(
+        &mut self,
+        fde: &FrameDescriptionEntry<Section, R, R::Offset>,
+        address: u64,
+    ) -> Result<UnwindTableRow<R, S>> {
+        let result = self.0.initialize(fde.cie()).and_then(|()| {
+            let mut table = UnwindTable::new_internal(&mut self.0, fde.cie(), Some(fde));
             loop {
                 match table.next_row() {
+                    Ok(None) => break Err(Error::NoUnwindInfoForAddress),
+                    Ok(Some(row)) if row.contains(address) => break Ok(row.clone()),
                     Ok(Some(_)) => continue,
-                    Ok(None) => break Ok(()),
                     Err(e) => break Err(e),
                 }
             }
-        };
-
-        match result {
-            Ok(()) => {
-                self.0.save_initial_rules();
-                Ok(InitializedUnwindContext(self.0))
-            }
-            Err(e) => {
-                self.0.reset();
-                Err((e, self))
-            }
-        }
+        });
+        self.0.reset();
+        result
     }
 }
 
@@ -1956,40 +2262,240 @@ where
 /// [`UninitializedUnwindContext`](./struct.UninitializedUnwindContext.html) for
 /// more details.
 #[derive(Clone, Debug)]
-pub struct InitializedUnwindContext<Section, R>(Box<UnwindContext<Section, R>>)
+pub struct InitializedUnwindContext<Section, R, S = StoreOnStack>(UnwindContext<Section, R, S>)
 where
     R: Reader,
-    Section: UnwindSection<R>;
+    Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>;
 
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<Section, R> InitializedUnwindContext<Section, R>
+/// other signal-unsafe operations, depending on the
+/// [storage](trait.UnwindContextStorage.html#signal-safety) that is used.
+impl<Section, R, S> InitializedUnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     /// Reset this context to the uninitialized state.
-    pub fn reset(mut self) -> UninitializedUnwindContext<Section, R> {
+    pub fn reset(mut self) -> UninitializedUnwindContext<Section, R, S> {
         self.0.reset();
         UninitializedUnwindContext(self.0)
     }
 }
 
-const MAX_UNWIND_STACK_DEPTH: usize = 4;
-type UnwindContextStack<R> = ArrayVec<[UnwindTableRow<R>; MAX_UNWIND_STACK_DEPTH]>;
+/// A sequence of items with either a fixed or a growable capacity, which is
+/// used to store the rules and rows of an unwind context.
+///
+/// This is implemented for `Vec`, which grows as needed, and for
+/// [`ArrayStorage`](struct.ArrayStorage.html), which has a fixed capacity and
+/// never allocates.
+pub trait UnwindStorage<T>: Default + Clone + Debug {
+    /// Return the items as a slice.
+    fn as_slice(&self) -> &[T];
+
+    /// Return the items as a mutable slice.
+    fn as_mut_slice(&mut self) -> &mut [T];
+
+    /// Append an item, or return it if the storage is full.
+    fn try_push(&mut self, value: T) -> ::std::result::Result<(), T>;
+
+    /// Remove the last item and return it.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Remove the item at `index` and return it, replacing it with the last
+    /// item.
+    fn swap_remove(&mut self, index: usize) -> T;
+
+    /// Remove all items.
+    fn clear(&mut self);
+}
+
+impl<T: Clone + Debug> UnwindStorage<T> for Vec<T> {
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [T] {
+        self
+    }
+
+    fn try_push(&mut self, value: T) -> ::std::result::Result<(), T> {
+        self.push(value);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        Vec::pop(self)
+    }
+
+    fn swap_remove(&mut self, index: usize) -> T {
+        Vec::swap_remove(self, index)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self)
+    }
+}
+
+/// Storage with a fixed capacity that never allocates.
+///
+/// `A` is an array type such as `[T; 32]`, and its length is the capacity. The
+/// supported lengths are 0 to 32, and 40, 48, 50, 56, 64, 72, 96, 100, 128,
+/// 160, 192, 200, 224, 256, 384, 512, 768, 1024, 2048, 4096, 8192, 16384 and
+/// 32768.
+pub struct ArrayStorage<A: Array>(ArrayVec<A>);
+
+impl<A: Array> Default for ArrayStorage<A> {
+    fn default() -> Self {
+        ArrayStorage(ArrayVec::new())
+    }
+}
+
+impl<A> Clone for ArrayStorage<A>
+where
+    A: Array,
+    A::Item: Clone,
+{
+    fn clone(&self) -> Self {
+        ArrayStorage(self.0.clone())
+    }
+}
+
+impl<A> Debug for ArrayStorage<A>
+where
+    A: Array,
+    A::Item: Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.0.iter()).finish()
+    }
+}
+
+impl<A> PartialEq for ArrayStorage<A>
+where
+    A: Array,
+    A::Item: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0[..] == other.0[..]
+    }
+}
+
+impl<A> Eq for ArrayStorage<A>
+where
+    A: Array,
+    A::Item: Eq,
+{
+}
+
+impl<A> UnwindStorage<A::Item> for ArrayStorage<A>
+where
+    A: Array,
+    A::Item: Clone + Debug,
+{
+    fn as_slice(&self) -> &[A::Item] {
+        &self.0
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [A::Item] {
+        &mut self.0
+    }
+
+    fn try_push(&mut self, value: A::Item) -> ::std::result::Result<(), A::Item> {
+        self.0.try_push(value).map_err(|e| e.element())
+    }
+
+    fn pop(&mut self) -> Option<A::Item> {
+        self.0.pop()
+    }
+
+    fn swap_remove(&mut self, index: usize) -> A::Item {
+        self.0.swap_remove(index)
+    }
+
+    fn clear(&mut self) {
+        self.0.clear()
+    }
+}
+
+/// The storage used by an unwind context for register rules and for the
+/// stack of rows that `DW_CFA_remember_state` pushes onto.
+///
+/// The default, [`StoreOnStack`](struct.StoreOnStack.html), has a fixed
+/// capacity. [`StoreOnHeap`](struct.StoreOnHeap.html) uses `Vec`s, so it has
+/// no limits. Other capacities can be chosen by implementing this trait with
+/// [`ArrayStorage`](struct.ArrayStorage.html). If a fixed capacity is
+/// exceeded, then `Error::TooManyRegisterRules` or `Error::CfiStackFull` is
+/// returned.
+///
+/// # Signal Safety
+///
+/// The methods that are documented as signal safe only avoid allocating if
+/// the storage does not allocate. This is true for `StoreOnStack` and any
+/// other storage built from `ArrayStorage`, so these can be used for
+/// unwinding in a signal handler. `StoreOnHeap` may allocate while evaluating
+/// CFI, so it must not be used there.
+///
+/// ```
+/// use gimli::{ArrayStorage, EhFrame, EndianSlice, NativeEndian, Reader, Register, RegisterRule,
+///             UninitializedUnwindContext, UnwindContextStorage, UnwindTableRow};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// struct LargeStoreOnStack;
+///
+/// impl<R: Reader> UnwindContextStorage<R> for LargeStoreOnStack {
+///     type Rules = ArrayStorage<[(Register, RegisterRule<R>); 64]>;
+///     type Stack = ArrayStorage<[UnwindTableRow<R, Self>; 8]>;
+/// }
+///
+/// let ctx: UninitializedUnwindContext<EhFrame<EndianSlice<NativeEndian>>, _, LargeStoreOnStack> =
+///     Default::default();
+/// # let _ = ctx;
+/// ```
+pub trait UnwindContextStorage<R: Reader>: Sized + Clone + Debug {
+    /// The storage for the register rules of a row.
+    type Rules: UnwindStorage<(Register, RegisterRule<R>)>;
+
+    /// The storage for the stack of rows.
+    type Stack: UnwindStorage<UnwindTableRow<R, Self>>;
+}
+
+/// Unwind context storage with a fixed capacity of 32 register rules and a
+/// `DW_CFA_remember_state` depth of 4.
+///
+/// This is the default storage, and never allocates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoreOnStack;
+
+impl<R: Reader> UnwindContextStorage<R> for StoreOnStack {
+    type Rules = ArrayStorage<[(Register, RegisterRule<R>); 32]>;
+    type Stack = ArrayStorage<[UnwindTableRow<R, StoreOnStack>; 4]>;
+}
+
+/// Unwind context storage that uses `Vec`s, and so has no limit on the number
+/// of register rules or on the depth of `DW_CFA_remember_state`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoreOnHeap;
+
+impl<R: Reader> UnwindContextStorage<R> for StoreOnHeap {
+    type Rules = Vec<(Register, RegisterRule<R>)>;
+    type Stack = Vec<UnwindTableRow<R, StoreOnHeap>>;
+}
 
 #[derive(Clone, Debug, PartialEq, Eq)]
-struct UnwindContext<Section, R>
+struct UnwindContext<Section, R, S = StoreOnStack>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     // Stack of rows. The last row is the row currently being built by the
     // program. There is always at least one row. The vast majority of CFI
     // programs will only ever have one row on the stack.
-    stack: UnwindContextStack<R>,
+    stack: S::Stack,
 
     // If we are evaluating an FDE's instructions, then `is_initialized` will be
     // `true` and `initial_rules` will contain the initial register rules
@@ -1997,7 +2503,7 @@ where
     // `DW_CFA_restore`. Otherwise, when we are currently evaluating a CIE's
     // initial instructions, `is_initialized` will be `false` and
     // `initial_rules` is not to be read from.
-    initial_rules: RegisterRuleMap<R>,
+    initial_rules: RegisterRuleMap<R, S>,
     is_initialized: bool,
 
     phantom: PhantomData<Section>,
@@ -2006,13 +2512,15 @@ where
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<Section, R> UnwindContext<Section, R>
+/// other signal-unsafe operations, depending on the
+/// [storage](trait.UnwindContextStorage.html#signal-safety) that is used.
+impl<Section, R, S> UnwindContext<Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
-    fn new() -> UnwindContext<Section, R> {
+    fn new() -> UnwindContext<Section, R, S> {
         let mut ctx = UnwindContext {
             stack: Default::default(),
             is_initialized: false,
@@ -2034,28 +2542,55 @@ where
         self.assert_fully_uninitialized();
     }
 
+    // Run the CIE's initial instructions. The context is reset if this fails.
+    fn initialize(&mut self, cie: &CommonInformationEntry<Section, R, R::Offset>) -> Result<()> {
+        self.assert_fully_uninitialized();
+
+        let result = {
+            let mut table = UnwindTable::new_internal(self, cie, None);
+            loop {
+                match table.next_row() {
+                    Ok(Some(_)) => continue,
+                    Ok(None) => break Ok(()),
+                    Err(e) => break Err(e),
+                }
+            }
+        };
+
+        match result {
+            Ok(()) => {
+                self.save_initial_rules();
+                Ok(())
+            }
+            Err(e) => {
+                self.reset();
+                Err(e)
+            }
+        }
+    }
+
     // Asserts that we are fully uninitialized, ie not initialized *and* not in
     // the process of initializing.
     #[inline]
     fn assert_fully_uninitialized(&self) {
         assert_eq!(self.is_initialized, false);
-        assert_eq!(self.initial_rules.rules.len(), 0);
-        assert_eq!(self.stack.len(), 1);
-        assert!(self.stack[0].is_default());
+        assert_eq!(self.initial_rules.rules.as_slice().len(), 0);
+        assert_eq!(self.stack.as_slice().len(), 1);
+        assert!(self.stack.as_slice()[0].is_default());
     }
 
-    fn row(&self) -> &UnwindTableRow<R> {
-        self.stack.last().unwrap()
+    fn row(&self) -> &UnwindTableRow<R, S> {
+        self.stack.as_slice().last().unwrap()
     }
 
-    fn row_mut(&mut self) -> &mut UnwindTableRow<R> {
-        self.stack.last_mut().unwrap()
+    fn row_mut(&mut self) -> &mut UnwindTableRow<R, S> {
+        self.stack.as_mut_slice().last_mut().unwrap()
     }
 
     fn save_initial_rules(&mut self) {
         assert_eq!(self.is_initialized, false);
         self.initial_rules
-            .clone_from(&self.stack.last().unwrap().registers);
+            .clone_from(&self.stack.as_slice().last().unwrap().registers);
         self.is_initialized = true;
     }
 
@@ -2099,7 +2634,7 @@ where
     }
 
     fn pop_row(&mut self) {
-        assert!(self.stack.len() > 1);
+        assert!(self.stack.as_slice().len() > 1);
         self.stack.pop();
     }
 }
@@ -2161,16 +2696,17 @@ where
 /// > recording just the differences starting at the beginning address of each
 /// > subroutine in the program.
 #[derive(Debug)]
-pub struct UnwindTable<'cie, 'fde, 'ctx, Section, R>
+pub struct UnwindTable<'cie, 'fde, 'ctx, Section, R, S = StoreOnStack>
 where
     R: 'cie + 'fde + 'ctx + Reader,
     Section: 'cie + 'fde + 'ctx + UnwindSection<R>,
+    S: 'ctx + UnwindContextStorage<R>,
 {
     cie: &'cie CommonInformationEntry<Section, R, R::Offset>,
     next_start_address: u64,
     returned_last_row: bool,
     instructions: CallFrameInstructionIter<R>,
-    ctx: &'ctx mut UnwindContext<Section, R>,
+    ctx: &'ctx mut UnwindContext<Section, R, S>,
     // If this is `None`, then we are executing a CIE's initial_instructions. If
     // this is `Some`, then we are executing an FDE's instructions.
     fde: Option<&'fde FrameDescriptionEntry<Section, R, R::Offset>>,
@@ -2179,18 +2715,20 @@ where
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<'fde, 'ctx, Section, R> UnwindTable<'fde, 'fde, 'ctx, Section, R>
+/// other signal-unsafe operations, depending on the
+/// [storage](trait.UnwindContextStorage.html#signal-safety) that is used.
+impl<'fde, 'ctx, Section, R, S> UnwindTable<'fde, 'fde, 'ctx, Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     /// Construct a new `UnwindTable` for the given
     /// `FrameDescriptionEntry`'s CFI unwinding program.
     pub fn new(
-        ctx: &'ctx mut InitializedUnwindContext<Section, R>,
+        ctx: &'ctx mut InitializedUnwindContext<Section, R, S>,
         fde: &'fde FrameDescriptionEntry<Section, R, R::Offset>,
-    ) -> UnwindTable<'fde, 'fde, 'ctx, Section, R> {
+    ) -> UnwindTable<'fde, 'fde, 'ctx, Section, R, S> {
         assert!(ctx.0.is_initialized);
         Self::new_internal(&mut ctx.0, fde.cie(), Some(fde))
     }
@@ -2199,18 +2737,20 @@ where
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<'cie, 'fde, 'ctx, Section, R> UnwindTable<'cie, 'fde, 'ctx, Section, R>
+/// other signal-unsafe operations, depending on the
+/// [storage](trait.UnwindContextStorage.html#signal-safety) that is used.
+impl<'cie, 'fde, 'ctx, Section, R, S> UnwindTable<'cie, 'fde, 'ctx, Section, R, S>
 where
     R: Reader,
     Section: UnwindSection<R>,
+    S: UnwindContextStorage<R>,
 {
     fn new_internal(
-        ctx: &'ctx mut UnwindContext<Section, R>,
+        ctx: &'ctx mut UnwindContext<Section, R, S>,
         cie: &'cie CommonInformationEntry<Section, R, R::Offset>,
         fde: Option<&'fde FrameDescriptionEntry<Section, R, R::Offset>>,
-    ) -> UnwindTable<'cie, 'fde, 'ctx, Section, R> {
-        assert!(ctx.stack.len() >= 1);
+    ) -> UnwindTable<'cie, 'fde, 'ctx, Section, R, S> {
+        assert!(ctx.stack.as_slice().len() >= 1);
         let next_start_address = fde.map_or(0, |fde| fde.initial_address());
         let instructions = fde.map_or_else(|| cie.instructions(), |fde| fde.instructions());
         UnwindTable {
@@ -2228,8 +2768,8 @@ where
     ///
     /// Unfortunately, this cannot be used with `FallibleIterator` because of
     /// the restricted lifetime of the yielded item.
-    pub fn next_row(&mut self) -> Result<Option<&UnwindTableRow<R>>> {
-        assert!(self.ctx.stack.len() >= 1);
+    pub fn next_row(&mut self) -> Result<Option<&UnwindTableRow<R, S>>> {
+        assert!(self.ctx.stack.as_slice().len() >= 1);
         self.ctx.set_start_address(self.next_start_address);
 
         loop {
@@ -2418,8 +2958,8 @@ where
                 self.ctx.push_row()?;
             }
             RestoreState => {
-                assert!(self.ctx.stack.len() > 0);
-                if self.ctx.stack.len() == 1 {
+                assert!(self.ctx.stack.as_slice().len() > 0);
+                if self.ctx.stack.as_slice().len() == 1 {
                     return Err(Error::PopWithEmptyStack);
                 }
                 // Pop state while preserving current location.
@@ -2434,6 +2974,27 @@ where
                 self.ctx.row_mut().saved_args_size = size;
             }
 
+            // GNU Extension. The out registers are now the caller's in
+            // registers, and the in and local registers were saved on the
+            // stack.
+            WindowSave => {
+                let address_size = u64::from(self.cie.address_size);
+                for i in 8..16 {
+                    let rule = RegisterRule::Register(Register(i + 16));
+                    self.ctx.set_register_rule(Register(i), rule)?;
+                }
+                for i in 16..32 {
+                    let rule = RegisterRule::Offset(((u64::from(i) - 16) * address_size) as i64);
+                    self.ctx.set_register_rule(Register(i), rule)?;
+                }
+            }
+
+            // AArch64 Extension. Toggle whether the return address is signed.
+            NegateRaState => {
+                let row = self.ctx.row_mut();
+                row.return_address_signed = !row.return_address_signed;
+            }
+
             // No operation.
             Nop => {}
         };
@@ -2453,12 +3014,14 @@ where
 // `RegisterRule::Undefined`, we never store a register's rule in this vec if it
 // is undefined and save a little bit more space and do a little fewer
 // comparisons that way.
+//
+// The vec itself is provided by the `UnwindContextStorage`.
 #[derive(Clone, Debug)]
-struct RegisterRuleMap<R: Reader> {
-    rules: ArrayVec<[(Register, RegisterRule<R>); 32]>,
+struct RegisterRuleMap<R: Reader, S: UnwindContextStorage<R> = StoreOnStack> {
+    rules: S::Rules,
 }
 
-impl<R: Reader> Default for RegisterRuleMap<R> {
+impl<R: Reader, S: UnwindContextStorage<R>> Default for RegisterRuleMap<R, S> {
     fn default() -> Self {
         RegisterRuleMap {
             rules: Default::default(),
@@ -2469,14 +3032,16 @@ impl<R: Reader> Default for RegisterRuleMap<R> {
 /// # Signal Safe Methods
 ///
 /// These methods are guaranteed not to allocate, acquire locks, or perform any
-/// other signal-unsafe operations.
-impl<R: Reader> RegisterRuleMap<R> {
+/// other signal-unsafe operations, depending on the
+/// [storage](trait.UnwindContextStorage.html#signal-safety) that is used.
+impl<R: Reader, S: UnwindContextStorage<R>> RegisterRuleMap<R, S> {
     fn is_default(&self) -> bool {
-        self.rules.len() == 0
+        self.rules.as_slice().is_empty()
     }
 
     fn get(&self, register: Register) -> RegisterRule<R> {
         self.rules
+            .as_slice()
             .iter()
             .find(|rule| rule.0 == register)
             .map(|r| {
@@ -2490,6 +3055,7 @@ impl<R: Reader> RegisterRuleMap<R> {
         if !rule.is_defined() {
             let idx = self
                 .rules
+                .as_slice()
                 .iter()
                 .enumerate()
                 .find(|&(_, r)| r.0 == register)
@@ -2500,7 +3066,7 @@ impl<R: Reader> RegisterRuleMap<R> {
             return Ok(());
         }
 
-        for &mut (reg, ref mut old_rule) in &mut self.rules {
+        for &mut (reg, ref mut old_rule) in self.rules.as_mut_slice() {
             debug_assert!(old_rule.is_defined());
             if reg == register {
                 mem::replace(old_rule, rule);
@@ -2518,15 +3084,16 @@ impl<R: Reader> RegisterRuleMap<R> {
     }
 
     fn iter(&self) -> RegisterRuleIter<R> {
-        RegisterRuleIter(self.rules.iter())
+        RegisterRuleIter(self.rules.as_slice().iter())
     }
 }
 
-impl<'a, R> FromIterator<&'a (Register, RegisterRule<R>)> for RegisterRuleMap<R>
+impl<'a, R, S> FromIterator<&'a (Register, RegisterRule<R>)> for RegisterRuleMap<R, S>
 where
     R: 'a + Reader,
+    S: UnwindContextStorage<R>,
 {
-    fn from_iter<T>(iter: T) -> RegisterRuleMap<R>
+    fn from_iter<T>(iter: T) -> RegisterRuleMap<R, S>
     where
         T: IntoIterator<Item = &'a (Register, RegisterRule<R>)>,
     {
@@ -2542,19 +3109,20 @@ where
     }
 }
 
-impl<R> PartialEq for RegisterRuleMap<R>
+impl<R, S> PartialEq for RegisterRuleMap<R, S>
 where
     R: Reader + PartialEq,
+    S: UnwindContextStorage<R>,
 {
     fn eq(&self, rhs: &Self) -> bool {
-        for &(reg, ref rule) in &self.rules {
+        for &(reg, ref rule) in self.rules.as_slice() {
             debug_assert!(rule.is_defined());
             if *rule != rhs.get(reg) {
                 return false;
             }
         }
 
-        for &(reg, ref rhs_rule) in &rhs.rules {
+        for &(reg, ref rhs_rule) in rhs.rules.as_slice() {
             debug_assert!(rhs_rule.is_defined());
             if *rhs_rule != self.get(reg) {
                 return false;
@@ -2565,7 +3133,12 @@ where
     }
 }
 
-impl<R> Eq for RegisterRuleMap<R> where R: Reader + Eq {}
+impl<R, S> Eq for RegisterRuleMap<R, S>
+where
+    R: Reader + Eq,
+    S: UnwindContextStorage<R>,
+{
+}
 
 /// An unordered iterator for register rules.
 #[derive(Debug, Clone)]
@@ -2584,30 +3157,33 @@ impl<'iter, R: Reader> Iterator for RegisterRuleIter<'iter, R> {
 /// A row in the virtual unwind table that describes how to find the values of
 /// the registers in the *previous* frame for a range of PC addresses.
 #[derive(Clone, Debug, PartialEq, Eq)]
-pub struct UnwindTableRow<R: Reader> {
+pub struct UnwindTableRow<R: Reader, S: UnwindContextStorage<R> = StoreOnStack> {
     start_address: u64,
     end_address: u64,
     saved_args_size: u64,
+    return_address_signed: bool,
     cfa: CfaRule<R>,
-    registers: RegisterRuleMap<R>,
+    registers: RegisterRuleMap<R, S>,
 }
 
-impl<R: Reader> Default for UnwindTableRow<R> {
+impl<R: Reader, S: UnwindContextStorage<R>> Default for UnwindTableRow<R, S> {
     fn default() -> Self {
         UnwindTableRow {
             start_address: 0,
             end_address: 0,
             saved_args_size: 0,
+            return_address_signed: false,
             cfa: Default::default(),
             registers: Default::default(),
         }
     }
 }
 
-impl<R: Reader> UnwindTableRow<R> {
+impl<R: Reader, S: UnwindContextStorage<R>> UnwindTableRow<R, S> {
     fn is_default(&self) -> bool {
         self.start_address == 0
             && self.end_address == 0
+            && !self.return_address_signed
             && self.cfa.is_default()
             && self.registers.is_default()
     }
@@ -2642,6 +3218,16 @@ impl<R: Reader> UnwindTableRow<R> {
         self.saved_args_size
     }
 
+    /// Returns `true` if the return address has been signed.
+    ///
+    /// This is the AArch64 `RA_SIGN_STATE` pseudo-register, which is toggled
+    /// by `DW_CFA_AARCH64_negate_ra_state`. If it is set, then the pointer
+    /// authentication code must be removed from the return address before
+    /// it can be used.
+    pub fn is_return_address_signed(&self) -> bool {
+        self.return_address_signed
+    }
+
     /// Get the canonical frame address (CFA) recovery rule for this row.
     pub fn cfa(&self) -> &CfaRule<R> {
         &self.cfa
@@ -2948,6 +3534,9 @@ pub enum CallFrameInstruction<R: Reader> {
         factored_offset: u64,
     },
 
+    /// The `OffsetExtendedSf` instruction represents both
+    /// `DW_CFA_offset_extended_sf` and `DW_CFA_GNU_negative_offset_extended`.
+    ///
     /// > 5. DW_CFA_offset_extended_sf
     /// >
     /// > The DW_CFA_offset_extended_sf instruction takes two operands: an
@@ -3079,6 +3668,31 @@ pub enum CallFrameInstruction<R: Reader> {
         size: u64,
     },
 
+    /// > DW_CFA_GNU_window_save
+    /// >
+    /// > GNU Extension
+    /// >
+    /// > The DW_CFA_GNU_window_save instruction takes no operands. It is used
+    /// > on SPARC to indicate that the register window has been saved: the
+    /// > rules for the in and local registers (16-31) are set to be saved at
+    /// > consecutive address sized offsets from the CFA, and the rules for
+    /// > the out registers (8-15) are set to be the corresponding in registers.
+    ///
+    /// This shares its encoding with `DW_CFA_AARCH64_negate_ra_state`, and is
+    /// only parsed if the section's vendor is `Vendor::Sparc`.
+    WindowSave,
+
+    /// > DW_CFA_AARCH64_negate_ra_state
+    /// >
+    /// > AArch64 Extension
+    /// >
+    /// > The DW_CFA_AARCH64_negate_ra_state operation negates bit 0 of the
+    /// > RA_SIGN_STATE pseudo-register. It does not take any operands.
+    ///
+    /// This shares its encoding with `DW_CFA_GNU_window_save`, and is only
+    /// parsed if the section's vendor is `Vendor::AArch64`.
+    NegateRaState,
+
     // 6.4.2.5 Padding Instruction
     /// > 1. DW_CFA_nop
     /// >
@@ -3091,7 +3705,7 @@ const CFI_INSTRUCTION_HIGH_BITS_MASK: u8 = 0b1100_0000;
 const CFI_INSTRUCTION_LOW_BITS_MASK: u8 = !CFI_INSTRUCTION_HIGH_BITS_MASK;
 
 impl<R: Reader> CallFrameInstruction<R> {
-    fn parse(input: &mut R, address_size: u8) -> Result<CallFrameInstruction<R>> {
+    fn parse(input: &mut R, address_size: u8, vendor: Vendor) -> Result<CallFrameInstruction<R>> {
         let instruction = input.read_u8()?;
         let high_bits = instruction & CFI_INSTRUCTION_HIGH_BITS_MASK;
 
@@ -3275,6 +3889,23 @@ impl<R: Reader> CallFrameInstruction<R> {
                 Ok(CallFrameInstruction::ArgsSize { size })
             }
 
+            constants::DW_CFA_GNU_negative_offset_extended => {
+                let register = input.read_uleb128().and_then(Register::from_u64)?;
+                let offset = input.read_uleb128()?;
+                Ok(CallFrameInstruction::OffsetExtendedSf {
+                    register,
+                    factored_offset: (offset as i64).wrapping_neg(),
+                })
+            }
+
+            constants::DW_CFA_AARCH64_negate_ra_state if vendor == Vendor::AArch64 => {
+                Ok(CallFrameInstruction::NegateRaState)
+            }
+
+            constants::DW_CFA_GNU_window_save if vendor == Vendor::Sparc => {
+                Ok(CallFrameInstruction::WindowSave)
+            }
+
             otherwise => Err(Error::UnknownCallFrameInstruction(otherwise)),
         }
     }
@@ -3288,6 +3919,7 @@ impl<R: Reader> CallFrameInstruction<R> {
 pub struct CallFrameInstructionIter<R: Reader> {
     input: R,
     address_size: u8,
+    vendor: Vendor,
 }
 
 impl<R: Reader> CallFrameInstructionIter<R> {
@@ -3297,7 +3929,7 @@ impl<R: Reader> CallFrameInstructionIter<R> {
             return Ok(None);
         }
 
-        match CallFrameInstruction::parse(&mut self.input, self.address_size) {
+        match CallFrameInstruction::parse(&mut self.input, self.address_size, self.vendor) {
             Ok(instruction) => Ok(Some(instruction)),
             Err(e) => {
                 self.input.empty();
@@ -3597,6 +4229,7 @@ mod tests {
             data_alignment_factor: 2,
             return_address_register: Register(3),
             initial_instructions: EndianSlice::new(&[], LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -3657,6 +4290,7 @@ mod tests {
             data_alignment_factor: 32,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&expected_instrs, LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -3703,6 +4337,7 @@ mod tests {
             data_alignment_factor: 0,
             return_address_register: Register(3),
             initial_instructions: EndianSlice::new(&expected_instrs, LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -3787,6 +4422,7 @@ mod tests {
             data_alignment_factor: 2,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&[], LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -3839,6 +4475,7 @@ mod tests {
             data_alignment_factor: 2,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&[], LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -3891,6 +4528,7 @@ mod tests {
             data_alignment_factor: 2,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&[], LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -3942,6 +4580,7 @@ mod tests {
             data_alignment_factor: 32,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&expected_instrs, BigEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -3977,6 +4616,7 @@ mod tests {
             data_alignment_factor: 32,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&[], BigEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -4041,6 +4681,7 @@ mod tests {
             data_alignment_factor: 2,
             return_address_register: Register(3),
             initial_instructions: EndianSlice::new(&expected_instrs1, BigEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -4056,6 +4697,7 @@ mod tests {
             data_alignment_factor: 2,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&expected_instrs2, BigEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -4163,6 +4805,7 @@ mod tests {
             data_alignment_factor: 8,
             return_address_register: Register(12),
             initial_instructions: EndianSlice::new(&instrs, LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -4195,7 +4838,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::AdvanceLoc {
                 delta: u32::from(expected_delta),
             })
@@ -4215,7 +4858,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Offset {
                 register: Register(expected_reg.into()),
                 factored_offset: expected_offset,
@@ -4234,7 +4877,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Restore {
                 register: Register(expected_reg.into()),
             })
@@ -4251,7 +4894,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Nop)
         );
         assert_eq!(*input, EndianSlice::new(&expected_rest, LittleEndian));
@@ -4268,7 +4911,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::SetLoc {
                 address: expected_addr,
             })
@@ -4287,7 +4930,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::AdvanceLoc {
                 delta: u32::from(expected_delta),
             })
@@ -4306,7 +4949,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::AdvanceLoc {
                 delta: u32::from(expected_delta),
             })
@@ -4325,7 +4968,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::AdvanceLoc {
                 delta: expected_delta,
             })
@@ -4346,7 +4989,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Offset {
                 register: Register(expected_reg),
                 factored_offset: expected_offset,
@@ -4366,7 +5009,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Restore {
                 register: Register(expected_reg),
             })
@@ -4385,7 +5028,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Undefined {
                 register: Register(expected_reg),
             })
@@ -4404,7 +5047,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::SameValue {
                 register: Register(expected_reg),
             })
@@ -4425,7 +5068,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Register {
                 dest_register: Register(expected_dest_reg),
                 src_register: Register(expected_src_reg),
@@ -4443,7 +5086,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::RememberState)
         );
         assert_eq!(*input, EndianSlice::new(&expected_rest, LittleEndian));
@@ -4458,7 +5101,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::RestoreState)
         );
         assert_eq!(*input, EndianSlice::new(&expected_rest, LittleEndian));
@@ -4477,7 +5120,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::DefCfa {
                 register: Register(expected_reg),
                 offset: expected_offset,
@@ -4497,7 +5140,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::DefCfaRegister {
                 register: Register(expected_reg),
             })
@@ -4516,7 +5159,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::DefCfaOffset {
                 offset: expected_offset,
             })
@@ -4546,7 +5189,7 @@ mod tests {
         let input = &mut EndianSlice::new(&contents, LittleEndian);
 
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::DefCfaExpression {
                 expression: Expression(EndianSlice::new(&expected_expr, LittleEndian)),
             })
@@ -4578,7 +5221,7 @@ mod tests {
         let input = &mut EndianSlice::new(&contents, LittleEndian);
 
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::Expression {
                 register: Register(expected_reg),
                 expression: Expression(EndianSlice::new(&expected_expr, LittleEndian)),
@@ -4600,7 +5243,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::OffsetExtendedSf {
                 register: Register(expected_reg),
                 factored_offset: expected_offset,
@@ -4622,7 +5265,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::DefCfaSf {
                 register: Register(expected_reg),
                 factored_offset: expected_offset,
@@ -4642,7 +5285,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::DefCfaOffsetSf {
                 factored_offset: expected_offset,
             })
@@ -4663,7 +5306,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::ValOffset {
                 register: Register(expected_reg),
                 factored_offset: expected_offset,
@@ -4685,7 +5328,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::ValOffsetSf {
                 register: Register(expected_reg),
                 factored_offset: expected_offset,
@@ -4718,7 +5361,7 @@ mod tests {
         let input = &mut EndianSlice::new(&contents, LittleEndian);
 
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Ok(CallFrameInstruction::ValExpression {
                 register: Register(expected_reg),
                 expression: Expression(EndianSlice::new(&expected_expr, LittleEndian)),
@@ -4727,6 +5370,68 @@ mod tests {
         assert_eq!(*input, EndianSlice::new(&expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_parse_cfi_instruction_negative_offset_extended() {
+        let expected_rest = [1, 2, 3, 4];
+        let expected_reg = 7;
+        let expected_offset = 33;
+        let section = Section::with_endian(Endian::Little)
+            .D8(constants::DW_CFA_GNU_negative_offset_extended.0)
+            .uleb(expected_reg.into())
+            .uleb(expected_offset)
+            .append_bytes(&expected_rest);
+        let contents = section.get_contents().unwrap();
+        let input = &mut EndianSlice::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
+            Ok(CallFrameInstruction::OffsetExtendedSf {
+                register: Register(expected_reg),
+                factored_offset: -(expected_offset as i64),
+            })
+        );
+        assert_eq!(*input, EndianSlice::new(&expected_rest, LittleEndian));
+    }
+
+    #[test]
+    fn test_parse_cfi_instruction_window_save() {
+        let expected_rest = [1, 2, 3, 4];
+        let section = Section::with_endian(Endian::Little)
+            .D8(constants::DW_CFA_GNU_window_save.0)
+            .append_bytes(&expected_rest);
+        let contents = section.get_contents().unwrap();
+        let input = &mut EndianSlice::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input, 8, Vendor::Sparc),
+            Ok(CallFrameInstruction::WindowSave)
+        );
+        assert_eq!(*input, EndianSlice::new(&expected_rest, LittleEndian));
+
+        // The encoding is only known to be `DW_CFA_GNU_window_save` if the
+        // vendor is SPARC.
+        let input = &mut EndianSlice::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
+            Err(Error::UnknownCallFrameInstruction(
+                constants::DW_CFA_GNU_window_save
+            ))
+        );
+    }
+
+    #[test]
+    fn test_parse_cfi_instruction_negate_ra_state() {
+        let expected_rest = [1, 2, 3, 4];
+        let section = Section::with_endian(Endian::Little)
+            .D8(constants::DW_CFA_AARCH64_negate_ra_state.0)
+            .append_bytes(&expected_rest);
+        let contents = section.get_contents().unwrap();
+        let input = &mut EndianSlice::new(&contents, LittleEndian);
+        assert_eq!(
+            CallFrameInstruction::parse(input, 8, Vendor::AArch64),
+            Ok(CallFrameInstruction::NegateRaState)
+        );
+        assert_eq!(*input, EndianSlice::new(&expected_rest, LittleEndian));
+    }
+
     #[test]
     fn test_parse_cfi_instruction_unknown_instruction() {
         let expected_rest = [1, 2, 3, 4];
@@ -4737,7 +5442,7 @@ mod tests {
         let contents = section.get_contents().unwrap();
         let input = &mut EndianSlice::new(&contents, LittleEndian);
         assert_eq!(
-            CallFrameInstruction::parse(input, 8),
+            CallFrameInstruction::parse(input, 8, Vendor::Default),
             Err(Error::UnknownCallFrameInstruction(unknown_instr))
         );
     }
@@ -4768,6 +5473,7 @@ mod tests {
         let mut iter = CallFrameInstructionIter {
             input,
             address_size: 8,
+            vendor: Vendor::Default,
         };
 
         assert_eq!(
@@ -4798,6 +5504,7 @@ mod tests {
         let mut iter = CallFrameInstructionIter {
             input,
             address_size: 8,
+            vendor: Vendor::Default,
         };
 
         assert_eq!(iter.next(), Err(Error::UnexpectedEof));
@@ -4844,6 +5551,7 @@ mod tests {
             version: 4,
             address_size: mem::size_of::<usize>() as u8,
             initial_instructions: EndianSlice::new(&[], LittleEndian),
+            vendor: Vendor::Default,
             augmentation: None,
             segment_size: 0,
             data_alignment_factor: 2,
@@ -5264,6 +5972,89 @@ mod tests {
         assert_eval(ctx, expected, cie, None, instructions);
     }
 
+    #[test]
+    fn test_unwind_context_storage() {
+        type R<'a> = EndianSlice<'a, LittleEndian>;
+
+        let mut ctx = UnwindContext::<DebugFrame<R>, R, StoreOnHeap>::new();
+        for i in 0..64 {
+            ctx.set_register_rule(Register(i), RegisterRule::SameValue)
+                .unwrap();
+        }
+        for _ in 0..64 {
+            ctx.push_row().unwrap();
+        }
+        assert_eq!(ctx.row().registers.iter().count(), 64);
+
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        struct SmallStoreOnStack;
+
+        impl<'a> UnwindContextStorage<R<'a>> for SmallStoreOnStack {
+            type Rules = ArrayStorage<[(Register, RegisterRule<R<'a>>); 2]>;
+            type Stack = ArrayStorage<[UnwindTableRow<R<'a>, Self>; 2]>;
+        }
+
+        let mut ctx = UnwindContext::<DebugFrame<R>, R, SmallStoreOnStack>::new();
+        ctx.set_register_rule(Register(0), RegisterRule::SameValue)
+            .unwrap();
+        ctx.set_register_rule(Register(1), RegisterRule::SameValue)
+            .unwrap();
+        assert_eq!(
+            ctx.set_register_rule(Register(2), RegisterRule::SameValue),
+            Err(Error::TooManyRegisterRules)
+        );
+        // Replacing and removing rules doesn't need more capacity.
+        ctx.set_register_rule(Register(1), RegisterRule::Offset(8))
+            .unwrap();
+        ctx.set_register_rule(Register(0), RegisterRule::Undefined)
+            .unwrap();
+        ctx.set_register_rule(Register(2), RegisterRule::SameValue)
+            .unwrap();
+
+        ctx.push_row().unwrap();
+        assert_eq!(ctx.push_row(), Err(Error::CfiStackFull));
+        ctx.pop_row();
+        ctx.push_row().unwrap();
+    }
+
+    #[test]
+    fn test_eval_window_save() {
+        let cie: DebugFrameCie<_, _> = make_test_cie();
+        let ctx = UnwindContext::new();
+        let mut expected = ctx.clone();
+        for i in 8..16 {
+            expected
+                .set_register_rule(Register(i), RegisterRule::Register(Register(i + 16)))
+                .unwrap();
+        }
+        for i in 16..32 {
+            let offset = i64::from(i - 16) * i64::from(cie.address_size);
+            expected
+                .set_register_rule(Register(i), RegisterRule::Offset(offset))
+                .unwrap();
+        }
+        let instructions = [(Ok(false), CallFrameInstruction::WindowSave)];
+        assert_eval(ctx, expected, cie, None, instructions);
+    }
+
+    #[test]
+    fn test_eval_negate_ra_state() {
+        let cie: DebugFrameCie<_, _> = make_test_cie();
+        let ctx = UnwindContext::new();
+        let mut expected = ctx.clone();
+        expected.row_mut().return_address_signed = true;
+        let instructions = [(Ok(false), CallFrameInstruction::NegateRaState)];
+        assert_eval(ctx, expected.clone(), cie.clone(), None, instructions);
+
+        let instructions = [
+            (Ok(false), CallFrameInstruction::NegateRaState),
+            (Ok(false), CallFrameInstruction::RememberState),
+            (Ok(false), CallFrameInstruction::NegateRaState),
+            (Ok(false), CallFrameInstruction::RestoreState),
+        ];
+        assert_eval(UnwindContext::new(), expected, cie, None, instructions);
+    }
+
     #[test]
     fn test_eval_nop() {
         let cie: DebugFrameCie<_, _> = make_test_cie();
@@ -5302,6 +6093,7 @@ mod tests {
             data_alignment_factor: 1,
             return_address_register: Register(3),
             initial_instructions: EndianSlice::new(&initial_instructions, LittleEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -5363,6 +6155,7 @@ mod tests {
                 start_address: 0,
                 end_address: 1,
                 saved_args_size: 0,
+                return_address_signed: false,
                 cfa: CfaRule::RegisterAndOffset {
                     register: Register(4),
                     offset: -12,
@@ -5383,6 +6176,7 @@ mod tests {
                 start_address: 1,
                 end_address: 33,
                 saved_args_size: 0,
+                return_address_signed: false,
                 cfa: CfaRule::RegisterAndOffset {
                     register: Register(4),
                     offset: -12,
@@ -5403,6 +6197,7 @@ mod tests {
                 start_address: 33,
                 end_address: 97,
                 saved_args_size: 0,
+                return_address_signed: false,
                 cfa: CfaRule::RegisterAndOffset {
                     register: Register(4),
                     offset: -12,
@@ -5423,6 +6218,7 @@ mod tests {
                 start_address: 97,
                 end_address: 100,
                 saved_args_size: 0,
+                return_address_signed: false,
                 cfa: CfaRule::RegisterAndOffset {
                     register: Register(4),
                     offset: -12,
@@ -5478,6 +6274,7 @@ mod tests {
             data_alignment_factor: 1,
             return_address_register: Register(3),
             initial_instructions: EndianSlice::new(&instrs1, BigEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -5493,6 +6290,7 @@ mod tests {
             data_alignment_factor: 1,
             return_address_register: Register(1),
             initial_instructions: EndianSlice::new(&instrs2, BigEndian),
+            vendor: Vendor::Default,
             phantom: PhantomData,
         };
 
@@ -5553,6 +6351,7 @@ mod tests {
                 start_address: fde1.initial_address() + 100,
                 end_address: fde1.initial_address() + fde1.len(),
                 saved_args_size: 0,
+                return_address_signed: false,
                 cfa: CfaRule::RegisterAndOffset {
                     register: Register(4),
                     offset: -12,
@@ -5574,6 +6373,137 @@ mod tests {
         assert_eq!(result.unwrap_err().0, Error::NoUnwindInfoForAddress);
     }
 
+    #[test]
+    fn test_fde_index() {
+        let instrs: Vec<_> = (0..4).map(|_| constants::DW_CFA_nop.0).collect();
+
+        let mut cie1 = DebugFrameCie {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            version: 4,
+            augmentation: None,
+            address_size: 8,
+            segment_size: 0,
+            code_alignment_factor: 1,
+            data_alignment_factor: 1,
+            return_address_register: Register(3),
+            initial_instructions: EndianSlice::new(&instrs, LittleEndian),
+            vendor: Vendor::Default,
+            phantom: PhantomData,
+        };
+        let mut cie2 = DebugFrameCie {
+            return_address_register: Register(1),
+            ..cie1.clone()
+        };
+
+        let cie1_location = Label::new();
+        let cie2_location = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            .mark(&cie1_location)
+            .cie(None, &mut cie1)
+            .mark(&cie2_location)
+            .cie(None, &mut cie2);
+
+        let fde = |cie: &DebugFrameCie<_>, initial_address, address_range| DebugFrameFde {
+            offset: 0,
+            length: 0,
+            format: Format::Dwarf32,
+            cie: cie.clone(),
+            initial_segment: 0,
+            initial_address,
+            address_range,
+            augmentation: None,
+            instructions: EndianSlice::new(&instrs, LittleEndian),
+        };
+        // The FDEs are deliberately not sorted by address.
+        let mut fde1 = fde(&cie1, 0x3000, 0x100);
+        let mut fde2 = fde(&cie2, 0x1000, 0x100);
+        let mut fde3 = fde(&cie1, 0x2000, 0x80);
+        let mut fde4 = fde(&cie2, 0x4000, 0);
+        // This overlaps with the end of `fde1`.
+        let mut fde5 = fde(&cie2, 0x3040, 0x10);
+        let section = section
+            .fde(&cie1_location, &mut fde1)
+            .fde(&cie2_location, &mut fde2)
+            .fde(&cie1_location, &mut fde3)
+            .fde(&cie2_location, &mut fde4)
+            .fde(&cie2_location, &mut fde5);
+        section.start().set_const(0);
+
+        let contents = section.get_contents().unwrap();
+        let debug_frame = DebugFrame::new(&contents, LittleEndian);
+        let bases = BaseAddresses::default();
+
+        let index = FdeIndex::new(&debug_frame, &bases).unwrap();
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.cies.len(), 2);
+
+        for &(address, ref expected) in &[
+            (0xfff, None),
+            (0x1000, Some(&fde2)),
+            (0x10ff, Some(&fde2)),
+            (0x1100, None),
+            (0x2000, Some(&fde3)),
+            (0x207f, Some(&fde3)),
+            (0x2080, None),
+            (0x303f, Some(&fde1)),
+            (0x3100, None),
+            (0x4000, None),
+        ] {
+            assert_eq!(
+                index.lookup(address),
+                expected.map(|fde| DebugFrameOffset(fde.offset))
+            );
+            match *expected {
+                Some(expected) => {
+                    assert_eq!(
+                        index.fde_for_address(&debug_frame, &bases, address),
+                        Ok(expected.clone())
+                    );
+                    assert_eq!(
+                        debug_frame.fde_for_address(&bases, address),
+                        Ok(expected.clone())
+                    );
+                }
+                None => {
+                    assert_eq!(
+                        index.fde_for_address(&debug_frame, &bases, address),
+                        Err(Error::NoUnwindInfoForAddress)
+                    );
+                    assert_eq!(
+                        debug_frame.fde_for_address(&bases, address),
+                        Err(Error::NoUnwindInfoForAddress)
+                    );
+                }
+            }
+        }
+
+        // Overlapping FDEs are resolved by the nearest preceding start address
+        // in the index, but by the first matching entry in the section.
+        assert_eq!(
+            index.fde_for_address(&debug_frame, &bases, 0x3048),
+            Ok(fde5.clone())
+        );
+        assert_eq!(
+            debug_frame.fde_for_address(&bases, 0x3048),
+            Ok(fde1.clone())
+        );
+        assert_eq!(
+            index.fde_for_address(&debug_frame, &bases, 0x3080),
+            Err(Error::NoUnwindInfoForAddress)
+        );
+        assert_eq!(
+            debug_frame.fde_for_address(&bases, 0x3080),
+            Ok(fde1.clone())
+        );
+
+        assert_eq!(
+            debug_frame.fde_from_offset(&bases, DebugFrameOffset(cie1.offset), |_| unreachable!()),
+            Err(Error::NotFdePointer)
+        );
+    }
+
     #[test]
     fn test_eh_frame_hdr_unknown_version() {
         let bases = BaseAddresses::default();
@@ -6059,7 +6989,7 @@ mod tests {
         let aug_str = &mut EndianSlice::new(b"zP", LittleEndian);
 
         let mut augmentation = Augmentation::default();
-        augmentation.personality = Some(Pointer::Direct(0xf00d_f00d));
+        augmentation.personality = Some((constants::DW_EH_PE_udata8, Pointer::Direct(0xf00d_f00d)));
 
         assert_eq!(
             Augmentation::parse(aug_str, &bases, address_size, &section, input),
@@ -6145,7 +7075,7 @@ mod tests {
 
         let augmentation = Augmentation {
             lsda: Some(constants::DW_EH_PE_uleb128),
-            personality: Some(Pointer::Direct(0x1bad_f00d)),
+            personality: Some((constants::DW_EH_PE_udata8, Pointer::Direct(0x1bad_f00d))),
             fde_address_encoding: Some(constants::DW_EH_PE_uleb128),
             is_signal_trampoline: true,
         };
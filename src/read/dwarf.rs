@@ -1,18 +1,42 @@
+use std::result;
+#[cfg(feature = "std")]
+use std::sync::{PoisonError, RwLock};
+use vec::Vec;
+use Arc;
+
 use common::{
     DebugAddrBase, DebugAddrIndex, DebugInfoOffset, DebugLineStrOffset, DebugLocListsBase,
-    DebugLocListsIndex, DebugRngListsBase, DebugRngListsIndex, DebugStrOffset, DebugStrOffsetsBase,
-    DebugStrOffsetsIndex, DebugTypesOffset, Encoding, LocationListsOffset, RangeListsOffset,
+    DebugLocListsIndex, DebugMacinfoOffset, DebugMacroOffset, DebugRngListsBase,
+    DebugRngListsIndex, DebugStrOffset, DebugStrOffsetsBase, DebugStrOffsetsIndex,
+    DebugTypesOffset, DwoId, Encoding, Format, LocationListsOffset, RangeListsOffset,
     UnitSectionOffset,
 };
 use constants;
 use read::{
-    Abbreviations, AttributeValue, CompilationUnitHeader, CompilationUnitHeadersIter, DebugAbbrev,
-    DebugAddr, DebugInfo, DebugLine, DebugLineStr, DebugStr, DebugStrOffsets, DebugTypes,
-    EntriesCursor, EntriesTree, Error, IncompleteLineProgram, LocListIter, LocationLists,
-    RangeLists, Reader, ReaderOffset, Result, RngListIter, TypeUnitHeader, TypeUnitHeadersIter,
-    UnitHeader, UnitOffset,
+    Abbreviations, AbbreviationsCache, AttributeValue, CompilationUnitHeader,
+    CompilationUnitHeadersIter, DebugAbbrev, DebugAddr, DebugAranges, DebugInfo, DebugLine,
+    DebugLineStr, DebugMacinfo, DebugMacro, DebugStr, DebugStrOffsets, DebugTypes,
+    DebuggingInformationEntry, EntriesCursor, EntriesTree, Error, IncompleteLineProgram, LineTable,
+    LocListIter, LocationLists, MacroIter, MacroString, Range, RangeLists, Reader, ReaderOffset,
+    Result, RngListIter, TypeUnitHeader, TypeUnitHeadersIter, UnitHeader, UnitIndex, UnitOffset,
+    UnitType,
 };
 
+/// The type of a DWARF file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DwarfFileType {
+    /// A normal executable or object file.
+    Main,
+    /// A split DWARF `.dwo` file.
+    Dwo,
+}
+
+impl Default for DwarfFileType {
+    fn default() -> Self {
+        DwarfFileType::Main
+    }
+}
+
 /// All of the commonly used DWARF sections, and other common information.
 #[derive(Debug, Default)]
 pub struct Dwarf<R: Reader> {
@@ -22,6 +46,9 @@ pub struct Dwarf<R: Reader> {
     /// The `.debug_addr` section.
     pub debug_addr: DebugAddr<R>,
 
+    /// The `.debug_aranges` section.
+    pub debug_aranges: DebugAranges<R>,
+
     /// The `.debug_info` section.
     pub debug_info: DebugInfo<R>,
 
@@ -31,6 +58,12 @@ pub struct Dwarf<R: Reader> {
     /// The `.debug_line_str` section.
     pub debug_line_str: DebugLineStr<R>,
 
+    /// The `.debug_macinfo` section.
+    pub debug_macinfo: DebugMacinfo<R>,
+
+    /// The `.debug_macro` section.
+    pub debug_macro: DebugMacro<R>,
+
     /// The `.debug_str` section.
     pub debug_str: DebugStr<R>,
 
@@ -48,6 +81,20 @@ pub struct Dwarf<R: Reader> {
 
     /// The range lists in the `.debug_ranges` and `.debug_rnglists` sections.
     pub ranges: RangeLists<R>,
+
+    /// The type of this file.
+    pub file_type: DwarfFileType,
+
+    /// The cache of parsed abbreviations for units in this file.
+    ///
+    /// This should normally be initialized with `Default::default()`, or
+    /// with `AbbreviationsCache::disabled()` to reduce memory usage.
+    pub abbreviations_cache: AbbreviationsCache,
+
+    /// The cache of the unit address ranges used by `find_unit`.
+    ///
+    /// This should normally be initialized with `Default::default()`.
+    pub unit_range_cache: UnitRangeCache,
 }
 
 impl<R: Reader> Dwarf<R> {
@@ -70,21 +117,196 @@ impl<R: Reader> Dwarf<R> {
         self.debug_types.units()
     }
 
+    /// Load the split DWARF (`.dwo`) file for a skeleton unit.
+    ///
+    /// `section` is called with the `DW_AT_dwo_name` (or `DW_AT_GNU_dwo_name`)
+    /// of the skeleton unit and the name of a section in the `.dwo` file, such
+    /// as `".debug_info.dwo"`, and must return the data for that section.
+    /// An empty reader should be returned for sections that are not present.
+    ///
+    /// The `.debug_addr` and `.debug_ranges` sections are not present in `.dwo`
+    /// files, so they are shared with this `Dwarf`.
+    ///
+    /// Use `split_unit` on the returned `Dwarf` to find the split unit
+    /// for the skeleton unit.
+    ///
+    /// Returns `None` if the unit does not have a `.dwo` file name.
+    pub fn load_dwo<F, E>(
+        &self,
+        unit: &Unit<R>,
+        mut section: F,
+    ) -> result::Result<Option<Dwarf<R>>, E>
+    where
+        F: FnMut(&R, &'static str) -> result::Result<R, E>,
+    {
+        let dwo_name = match unit.dwo_name {
+            Some(ref dwo_name) => dwo_name,
+            None => return Ok(None),
+        };
+        self.load_dwo_sections(|name| section(dwo_name, name))
+            .map(Some)
+    }
+
+    /// Load the split DWARF sections for a unit in a DWARF package (`.dwp`) file.
+    ///
+    /// `index` is the parsed `.debug_cu_index` or `.debug_tu_index` section of
+    /// the package file, and `signature` is either the DWO id of a skeleton unit
+    /// or the signature of a type unit.
+    ///
+    /// `section` is called with the name of a section in the package file, such
+    /// as `".debug_info.dwo"`, and must return the data for that section.
+    /// An empty reader should be returned for sections that are not present.
+    /// The sections of the returned `Dwarf` are limited to the contributions of
+    /// the unit, except for `.debug_str.dwo` which is shared by all units.
+    ///
+    /// The `.debug_addr` and `.debug_ranges` sections are shared with `parent`.
+    ///
+    /// Returns `None` if the signature is not in the index.
+    pub fn from_dwp<F, E>(
+        parent: &Dwarf<R>,
+        index: &UnitIndex<R>,
+        signature: u64,
+        mut section: F,
+    ) -> result::Result<Option<Dwarf<R>>, E>
+    where
+        F: FnMut(&'static str) -> result::Result<R, E>,
+        E: From<Error>,
+    {
+        let row = match index.find(signature) {
+            Some(row) => row,
+            None => return Ok(None),
+        };
+        let contributions = index.sections(row)?;
+        parent
+            .load_dwo_sections(|name| {
+                let mut data = section(name)?;
+                let contribution = contributions
+                    .clone()
+                    .find(|contribution| contribution.section.dwo_name() == name);
+                if let Some(contribution) = contribution {
+                    data.skip(R::Offset::from_u32(contribution.offset))?;
+                    data.truncate(R::Offset::from_u32(contribution.size))?;
+                }
+                Ok(data)
+            })
+            .map(Some)
+    }
+
+    fn load_dwo_sections<F, E>(&self, mut section: F) -> result::Result<Dwarf<R>, E>
+    where
+        F: FnMut(&'static str) -> result::Result<R, E>,
+    {
+        let debug_abbrev = section(".debug_abbrev.dwo")?;
+        // Split DWARF files never contain a `.debug_aranges` section.
+        let mut debug_aranges = debug_abbrev.clone();
+        debug_aranges.empty();
+        let debug_info = section(".debug_info.dwo")?.into();
+        let debug_line = section(".debug_line.dwo")?.into();
+        let debug_macinfo = section(".debug_macinfo.dwo")?.into();
+        let debug_macro = section(".debug_macro.dwo")?.into();
+        let debug_str = section(".debug_str.dwo")?.into();
+        let debug_str_offsets = section(".debug_str_offsets.dwo")?.into();
+        let debug_types = section(".debug_types.dwo")?.into();
+        let debug_loc = section(".debug_loc.dwo")?.into();
+        let debug_loclists = section(".debug_loclists.dwo")?.into();
+        let debug_rnglists = section(".debug_rnglists.dwo")?.into();
+        Ok(Dwarf {
+            debug_abbrev: debug_abbrev.into(),
+            debug_addr: self.debug_addr.clone(),
+            debug_aranges: debug_aranges.into(),
+            debug_info,
+            debug_line,
+            debug_line_str: self.debug_line_str.clone(),
+            debug_macinfo,
+            debug_macro,
+            debug_str,
+            debug_str_offsets,
+            debug_str_sup: self.debug_str_sup.clone(),
+            debug_types,
+            locations: LocationLists::new(debug_loc, debug_loclists),
+            ranges: RangeLists::new(self.ranges.debug_ranges().clone(), debug_rnglists),
+            file_type: DwarfFileType::Dwo,
+            abbreviations_cache: if self.abbreviations_cache.is_enabled() {
+                AbbreviationsCache::new()
+            } else {
+                AbbreviationsCache::disabled()
+            },
+            unit_range_cache: Default::default(),
+        })
+    }
+
+    /// Find the split unit in this `.dwo` file that matches the given skeleton unit.
+    ///
+    /// The skeleton and split units are matched using their DWO ids.
+    ///
+    /// The returned unit is a merged view of the two units: `entries()` walks
+    /// the entries of the split unit, and the unit uses the `DW_AT_low_pc` and
+    /// `DW_AT_addr_base` of the skeleton unit (and `DW_AT_GNU_ranges_base` for
+    /// the GNU extension), so its entries can be used with the methods of this
+    /// `Dwarf` in the same way as any other unit. The `name`, `comp_dir` and
+    /// `line_program` of the skeleton unit are used if the split unit does not
+    /// have them. Note that a line program from the skeleton unit refers to
+    /// the line and string sections of the skeleton's file, which are only
+    /// shared with this `Dwarf` for `DW_FORM_line_strp` and inline strings.
+    ///
+    /// Returns `None` if the skeleton unit does not have a DWO id, or if there
+    /// is no matching split unit.
+    pub fn split_unit(&self, skeleton: &Unit<R>) -> Result<Option<Unit<R>>> {
+        let dwo_id = match skeleton.dwo_id {
+            Some(dwo_id) => dwo_id,
+            None => return Ok(None),
+        };
+        let mut units = self.units();
+        while let Some(header) = units.next()? {
+            match header.unit_type() {
+                UnitType::SplitCompilation(id) if id != dwo_id => continue,
+                _ => {}
+            }
+            let mut unit = Unit::new(self, header)?;
+            if unit.dwo_id != Some(dwo_id) {
+                continue;
+            }
+            unit.low_pc = skeleton.low_pc;
+            unit.addr_base = skeleton.addr_base;
+            if unit.header.version() < 5 {
+                unit.rnglists_base = skeleton.rnglists_base;
+            }
+            if unit.name.is_none() {
+                unit.name = skeleton.name.clone();
+            }
+            if unit.comp_dir.is_none() {
+                unit.comp_dir = skeleton.comp_dir.clone();
+            }
+            if unit.line_program.is_none() {
+                unit.line_program = skeleton.line_program.clone();
+            }
+            return Ok(Some(unit));
+        }
+        Ok(None)
+    }
+
     /// Parse the abbreviations for a compilation unit.
-    // TODO: provide caching of abbreviations
+    ///
+    /// The abbreviations are shared with other units using `abbreviations_cache`.
     #[inline]
     pub fn abbreviations(
         &self,
         unit: &CompilationUnitHeader<R, R::Offset>,
-    ) -> Result<Abbreviations> {
-        unit.abbreviations(&self.debug_abbrev)
+    ) -> Result<Arc<Abbreviations>> {
+        self.abbreviations_cache
+            .get(&self.debug_abbrev, unit.debug_abbrev_offset())
     }
 
     /// Parse the abbreviations for a type unit.
-    // TODO: provide caching of abbreviations
+    ///
+    /// The abbreviations are shared with other units using `abbreviations_cache`.
     #[inline]
-    pub fn type_abbreviations(&self, unit: &TypeUnitHeader<R, R::Offset>) -> Result<Abbreviations> {
-        unit.abbreviations(&self.debug_abbrev)
+    pub fn type_abbreviations(
+        &self,
+        unit: &TypeUnitHeader<R, R::Offset>,
+    ) -> Result<Arc<Abbreviations>> {
+        self.abbreviations_cache
+            .get(&self.debug_abbrev, unit.debug_abbrev_offset())
     }
 
     /// Return the string offset at the given index.
@@ -142,6 +364,33 @@ impl<R: Reader> Dwarf<R> {
         }
     }
 
+    /// Return the string for a string operand of a macro entry.
+    ///
+    /// `unit` must be the unit that the macro information belongs to.
+    pub fn macro_string(&self, unit: &Unit<R>, string: MacroString<R>) -> Result<R> {
+        let attr = match string {
+            MacroString::Direct(string) => AttributeValue::String(string),
+            MacroString::StringPointer(offset) => AttributeValue::DebugStrRef(offset),
+            MacroString::IndirectStringPointer(index) => {
+                AttributeValue::DebugStrOffsetsIndex(index)
+            }
+            MacroString::Supplementary(offset) => AttributeValue::DebugStrRefSup(offset),
+        };
+        self.attr_string(unit, attr)
+    }
+
+    /// Iterate over the macro information entries at the given
+    /// `.debug_macinfo` offset.
+    pub fn macinfo(&self, offset: DebugMacinfoOffset<R::Offset>) -> Result<MacroIter<R>> {
+        self.debug_macinfo.get_macinfo(offset)
+    }
+
+    /// Iterate over the entries of the macro unit at the given
+    /// `.debug_macro` offset.
+    pub fn macros(&self, offset: DebugMacroOffset<R::Offset>) -> Result<MacroIter<R>> {
+        self.debug_macro.get_macros(offset)
+    }
+
     /// Return the address at the given index.
     pub fn address(&self, unit: &Unit<R>, index: DebugAddrIndex<R::Offset>) -> Result<u64> {
         self.debug_addr
@@ -188,6 +437,13 @@ impl<R: Reader> Dwarf<R> {
         attr: AttributeValue<R, R::Offset>,
     ) -> Result<Option<RangeListsOffset<R::Offset>>> {
         match attr {
+            AttributeValue::RangeListsRef(offset)
+                if self.file_type == DwarfFileType::Dwo && unit.header.version() < 5 =>
+            {
+                // The GNU extension uses `DW_AT_GNU_ranges_base` from the skeleton
+                // unit as the base for all range list offsets in the split unit.
+                Ok(Some(RangeListsOffset(offset.0 + unit.rnglists_base.0)))
+            }
             AttributeValue::RangeListsRef(offset) => Ok(Some(offset)),
             AttributeValue::DebugRngListsIndex(index) => self.ranges_offset(unit, index).map(Some),
             _ => Ok(None),
@@ -281,6 +537,272 @@ impl<R: Reader> Dwarf<R> {
             None => Ok(None),
         }
     }
+
+    /// Call `f` for each of the address ranges of an entry.
+    ///
+    /// The ranges are given by either the `DW_AT_low_pc` and `DW_AT_high_pc`
+    /// attributes, or the `DW_AT_ranges` attribute. Empty ranges are skipped.
+    pub fn die_ranges<F>(
+        &self,
+        unit: &Unit<R>,
+        entry: &DebuggingInformationEntry<R, R::Offset>,
+        mut f: F,
+    ) -> Result<()>
+    where
+        F: FnMut(Range),
+    {
+        let mut low_pc = None;
+        let mut high_pc = None;
+        let mut size = None;
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_low_pc => match attr.value() {
+                    AttributeValue::Addr(address) => low_pc = Some(address),
+                    AttributeValue::DebugAddrIndex(index) => {
+                        low_pc = Some(self.address(unit, index)?)
+                    }
+                    _ => {}
+                },
+                constants::DW_AT_high_pc => match attr.value() {
+                    AttributeValue::Addr(address) => high_pc = Some(address),
+                    AttributeValue::DebugAddrIndex(index) => {
+                        high_pc = Some(self.address(unit, index)?)
+                    }
+                    value => size = value.udata_value(),
+                },
+                constants::DW_AT_ranges => {
+                    if let Some(mut ranges) = self.attr_ranges(unit, attr.value())? {
+                        while let Some(range) = ranges.next()? {
+                            if range.begin < range.end {
+                                f(range);
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(begin) = low_pc {
+            let end = match (high_pc, size) {
+                (Some(end), _) => end,
+                (None, Some(size)) => begin.wrapping_add(size),
+                (None, None) => return Ok(()),
+            };
+            if begin < end {
+                f(Range { begin, end });
+            }
+        }
+        Ok(())
+    }
+
+    /// Find the header of the compilation unit containing the given address.
+    ///
+    /// This uses a `UnitRangeIndex`, which is built on the first call and
+    /// cached in `unit_range_cache`. See `UnitRangeIndex` for details of how
+    /// the unit is found.
+    ///
+    /// Only the unit header is parsed for each lookup. Use `Unit::new` to
+    /// parse the abbreviations and line program of the unit.
+    pub fn find_unit(&self, address: u64) -> Result<Option<CompilationUnitHeader<R, R::Offset>>> {
+        match self.unit_range_cache.find_unit_offset(self, address)? {
+            Some(offset) => self.debug_info.header_from_offset(offset).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Build a `LineTable` for the line number program of each unit.
+    ///
+    /// Units without a line number program are skipped. The tables can be
+    /// searched together with `LineTable::find_all_line_ranges`, such as for
+    /// setting a breakpoint on a line in any unit. Building the tables runs
+    /// every line number program, so they should be reused for multiple
+    /// lookups.
+    pub fn line_tables(&self) -> Result<Vec<LineTable>> {
+        let mut tables = Vec::new();
+        let mut headers = self.units();
+        while let Some(header) = headers.next()? {
+            let mut unit = Unit::new(self, header)?;
+            if let Some(program) = unit.line_program.take() {
+                tables.push(LineTable::new(self, &unit, program)?);
+            }
+        }
+        Ok(tables)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct UnitRange<T> {
+    begin: u64,
+    end: u64,
+    unit: DebugInfoOffset<T>,
+}
+
+/// An index of the address ranges of the compilation units in a `Dwarf`.
+///
+/// The ranges of a unit are taken from `.debug_aranges` if the unit has an
+/// entry there, and otherwise from the attributes of the root entry of the
+/// unit. Where the ranges of units overlap, the addresses belong to the unit
+/// with the lowest range.
+///
+/// Building the index parses the root entry of every unit that is not
+/// listed in `.debug_aranges`, so it should be built once and reused for
+/// multiple lookups. `Dwarf::find_unit` does this automatically.
+#[derive(Debug, Clone)]
+pub struct UnitRangeIndex<T = usize> {
+    ranges: Vec<UnitRange<T>>,
+}
+
+impl<T: ReaderOffset> UnitRangeIndex<T> {
+    /// Build the index for the units in `dwarf`.
+    ///
+    /// `dwarf.debug_aranges` may be empty, in which case the ranges of all
+    /// units are taken from their root entries.
+    pub fn new<R>(dwarf: &Dwarf<R>) -> Result<Self>
+    where
+        R: Reader<Offset = T>,
+    {
+        Self::build(dwarf, |offset| offset)
+    }
+
+    fn build<R, F>(dwarf: &Dwarf<R>, convert: F) -> Result<Self>
+    where
+        R: Reader,
+        F: Fn(DebugInfoOffset<R::Offset>) -> DebugInfoOffset<T>,
+    {
+        let mut ranges = Vec::new();
+        let mut arange_units = Vec::new();
+        let mut aranges = dwarf.debug_aranges.items();
+        while let Some(arange) = aranges.next()? {
+            let unit = arange.debug_info_offset();
+            arange_units.push(unit);
+            let begin = arange.address();
+            let end = begin.wrapping_add(arange.length());
+            if begin < end {
+                ranges.push(UnitRange {
+                    begin,
+                    end,
+                    unit: convert(unit),
+                });
+            }
+        }
+        arange_units.sort();
+        arange_units.dedup();
+
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            let offset = header.offset();
+            if arange_units.binary_search(&offset).is_ok() {
+                continue;
+            }
+            let unit = Unit::new(dwarf, header)?;
+            let mut entries = unit.entries();
+            entries.next_dfs()?;
+            let root = entries.current().ok_or(Error::MissingUnitDie)?;
+            dwarf.die_ranges(&unit, root, |range| {
+                ranges.push(UnitRange {
+                    begin: range.begin,
+                    end: range.end,
+                    unit: convert(offset),
+                })
+            })?;
+        }
+
+        ranges.sort_by_key(|range| (range.begin, range.end));
+        let mut result: Vec<UnitRange<T>> = Vec::with_capacity(ranges.len());
+        for mut range in ranges {
+            if let Some(last) = result.last_mut() {
+                // Overlapping addresses belong to the earliest range.
+                if range.end <= last.end {
+                    continue;
+                }
+                if range.begin < last.end {
+                    range.begin = last.end;
+                }
+                if range.begin == last.end && range.unit == last.unit {
+                    last.end = range.end;
+                    continue;
+                }
+            }
+            result.push(range);
+        }
+        Ok(UnitRangeIndex { ranges: result })
+    }
+
+    /// Find the offset of the compilation unit containing the given address.
+    pub fn find_unit_offset(&self, address: u64) -> Option<DebugInfoOffset<T>> {
+        let index = match self
+            .ranges
+            .binary_search_by_key(&address, |range| range.begin)
+        {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(index) => index - 1,
+        };
+        let range = &self.ranges[index];
+        if address < range.end {
+            Some(range.unit)
+        } else {
+            None
+        }
+    }
+}
+
+/// A `UnitRangeIndex` that is built when it is first used by
+/// `Dwarf::find_unit`.
+///
+/// The index is only cached if the `std` feature is enabled. Without it, the
+/// index is rebuilt for every lookup, so build a `UnitRangeIndex` once and
+/// use it directly instead.
+#[derive(Debug, Default)]
+pub struct UnitRangeCache {
+    #[cfg(feature = "std")]
+    index: RwLock<Option<UnitRangeIndex<u64>>>,
+}
+
+impl UnitRangeCache {
+    fn find_unit_offset<R: Reader>(
+        &self,
+        dwarf: &Dwarf<R>,
+        address: u64,
+    ) -> Result<Option<DebugInfoOffset<R::Offset>>> {
+        let offset = self.find(dwarf, address)?;
+        match offset {
+            Some(offset) => Ok(Some(DebugInfoOffset(R::Offset::from_u64(offset.0)?))),
+            None => Ok(None),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn find<R: Reader>(
+        &self,
+        dwarf: &Dwarf<R>,
+        address: u64,
+    ) -> Result<Option<DebugInfoOffset<u64>>> {
+        if let Some(ref index) = *self.index.read().unwrap_or_else(PoisonError::into_inner) {
+            return Ok(index.find_unit_offset(address));
+        }
+        let mut index = self.index.write().unwrap_or_else(PoisonError::into_inner);
+        if index.is_none() {
+            *index = Some(Self::build(dwarf)?);
+        }
+        Ok(index
+            .as_ref()
+            .and_then(|index| index.find_unit_offset(address)))
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn find<R: Reader>(
+        &self,
+        dwarf: &Dwarf<R>,
+        address: u64,
+    ) -> Result<Option<DebugInfoOffset<u64>>> {
+        Ok(Self::build(dwarf)?.find_unit_offset(address))
+    }
+
+    fn build<R: Reader>(dwarf: &Dwarf<R>) -> Result<UnitRangeIndex<u64>> {
+        UnitRangeIndex::build(dwarf, |offset| DebugInfoOffset(offset.0.into_u64()))
+    }
 }
 
 /// All of the commonly used information for a unit in the `.debug_info` or `.debug_types`
@@ -294,7 +816,7 @@ pub struct Unit<R: Reader> {
     pub header: UnitHeader<R, R::Offset>,
 
     /// The parsed abbreviations for the unit.
-    pub abbreviations: Abbreviations,
+    pub abbreviations: Arc<Abbreviations>,
 
     /// The `DW_AT_name` attribute of the unit.
     pub name: Option<R>,
@@ -317,6 +839,12 @@ pub struct Unit<R: Reader> {
     /// The `DW_AT_rnglists_base` attribute of the unit. Defaults to 0.
     pub rnglists_base: DebugRngListsBase<R::Offset>,
 
+    /// The DWO id of a skeleton unit or split compilation unit.
+    pub dwo_id: Option<DwoId>,
+
+    /// The `DW_AT_dwo_name` attribute of a skeleton unit.
+    pub dwo_name: Option<R>,
+
     /// The line number program of the unit.
     pub line_program: Option<IncompleteLineProgram<R, R::Offset>>,
 }
@@ -342,12 +870,32 @@ impl<R: Reader> Unit<R> {
         )
     }
 
-    fn new_internal(
+    pub(crate) fn new_internal(
         dwarf: &Dwarf<R>,
         offset: UnitSectionOffset<R::Offset>,
         header: UnitHeader<R, R::Offset>,
     ) -> Result<Self> {
-        let abbreviations = header.abbreviations(&dwarf.debug_abbrev)?;
+        let abbreviations = dwarf
+            .abbreviations_cache
+            .get(&dwarf.debug_abbrev, header.debug_abbrev_offset())?;
+        let dwo_id = match header.unit_type() {
+            UnitType::Skeleton(dwo_id) | UnitType::SplitCompilation(dwo_id) => Some(dwo_id),
+            _ => None,
+        };
+        // Split units in DWARF 5 `.dwo` files have no `DW_AT_str_offsets_base`,
+        // `DW_AT_loclists_base` or `DW_AT_rnglists_base`, and instead use the
+        // entries following the header of the corresponding `.dwo` section.
+        // The headers of the location and range lists sections also contain
+        // an offset entry count.
+        let (str_offsets_base, lists_base) =
+            if dwarf.file_type == DwarfFileType::Dwo && header.version() >= 5 {
+                match header.format() {
+                    Format::Dwarf32 => (R::Offset::from_u8(8), R::Offset::from_u8(12)),
+                    Format::Dwarf64 => (R::Offset::from_u8(16), R::Offset::from_u8(20)),
+                }
+            } else {
+                (R::Offset::from_u8(0), R::Offset::from_u8(0))
+            };
         let mut unit = Unit {
             offset,
             header,
@@ -356,14 +904,18 @@ impl<R: Reader> Unit<R> {
             comp_dir: None,
             low_pc: 0,
             // Defaults to 0 for GNU extensions.
-            str_offsets_base: DebugStrOffsetsBase(R::Offset::from_u8(0)),
+            str_offsets_base: DebugStrOffsetsBase(str_offsets_base),
             addr_base: DebugAddrBase(R::Offset::from_u8(0)),
-            loclists_base: DebugLocListsBase(R::Offset::from_u8(0)),
-            rnglists_base: DebugRngListsBase(R::Offset::from_u8(0)),
+            loclists_base: DebugLocListsBase(lists_base),
+            rnglists_base: DebugRngListsBase(lists_base),
+            dwo_id,
+            dwo_name: None,
             line_program: None,
         };
         let mut name = None;
+        let mut dwo_name = None;
         let mut comp_dir = None;
+        let mut low_pc = None;
         let mut line_program_offset = None;
 
         {
@@ -380,9 +932,7 @@ impl<R: Reader> Unit<R> {
                         comp_dir = Some(attr.value());
                     }
                     constants::DW_AT_low_pc => {
-                        if let AttributeValue::Addr(address) = attr.value() {
-                            unit.low_pc = address;
-                        }
+                        low_pc = Some(attr.value());
                     }
                     constants::DW_AT_stmt_list => {
                         if let AttributeValue::DebugLineRef(offset) = attr.value() {
@@ -394,7 +944,13 @@ impl<R: Reader> Unit<R> {
                             unit.str_offsets_base = base;
                         }
                     }
-                    constants::DW_AT_addr_base => {
+                    constants::DW_AT_dwo_name | constants::DW_AT_GNU_dwo_name => {
+                        dwo_name = Some(attr.value());
+                    }
+                    constants::DW_AT_GNU_dwo_id => {
+                        unit.dwo_id = attr.udata_value().map(DwoId);
+                    }
+                    constants::DW_AT_addr_base | constants::DW_AT_GNU_addr_base => {
                         if let AttributeValue::DebugAddrBase(base) = attr.value() {
                             unit.addr_base = base;
                         }
@@ -404,7 +960,7 @@ impl<R: Reader> Unit<R> {
                             unit.loclists_base = base;
                         }
                     }
-                    constants::DW_AT_rnglists_base => {
+                    constants::DW_AT_rnglists_base | constants::DW_AT_GNU_ranges_base => {
                         if let AttributeValue::DebugRngListsBase(base) = attr.value() {
                             unit.rnglists_base = base;
                         }
@@ -414,6 +970,17 @@ impl<R: Reader> Unit<R> {
             }
         }
 
+        unit.low_pc = match low_pc {
+            Some(AttributeValue::Addr(address)) => address,
+            // The `DW_AT_addr_base` of a split unit is in its skeleton unit, so
+            // `split_unit` sets `low_pc` from the skeleton instead.
+            Some(AttributeValue::DebugAddrIndex(index))
+                if dwarf.file_type != DwarfFileType::Dwo =>
+            {
+                dwarf.address(&unit, index)?
+            }
+            _ => 0,
+        };
         unit.name = match name {
             Some(val) => Some(dwarf.attr_string(&unit, val)?),
             None => None,
@@ -422,6 +989,10 @@ impl<R: Reader> Unit<R> {
             Some(val) => Some(dwarf.attr_string(&unit, val)?),
             None => None,
         };
+        unit.dwo_name = match dwo_name {
+            Some(val) => Some(dwarf.attr_string(&unit, val)?),
+            None => None,
+        };
         unit.line_program = match line_program_offset {
             Some(offset) => Some(dwarf.debug_line.program(
                 offset,
@@ -511,9 +1082,15 @@ impl<T: ReaderOffset> UnitOffset<T> {
 
 #[cfg(test)]
 mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
     use super::*;
-    use read::EndianSlice;
-    use Endianity;
+    use fallible_iterator::FallibleIterator;
+    use read::{DebugCuIndex, DebugRanges, DebugRngLists, EndianSlice};
+    use test_util::GimliSectionMethods;
+    use vec::Vec;
+    use {Endianity, LittleEndian};
 
     /// Ensure that `Dwarf<R>` is covariant wrt R.
     #[test]
@@ -524,4 +1101,520 @@ mod tests {
             x
         }
     }
+
+    // Append a unit with the given header fields and entries.
+    fn unit(
+        section: Section,
+        version: u16,
+        unit_type: u8,
+        dwo_id: u64,
+        entries: Section,
+    ) -> Section {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let section = section.L32(&length).mark(&start).L16(version);
+        let section = if version >= 5 {
+            section.D8(unit_type).D8(8).L32(0).L64(dwo_id)
+        } else {
+            section.L32(0).D8(8)
+        };
+        let section = section.append_section(entries).mark(&end);
+        length.set_const((&end - &start) as u64);
+        section
+    }
+
+    fn load<'a>(
+        sections: &[(&'static str, &'a [u8])],
+        name: &str,
+    ) -> EndianSlice<'a, LittleEndian> {
+        let data = sections
+            .iter()
+            .find(|&&(section, _)| section == name)
+            .map(|&(_, data)| data)
+            .unwrap_or(&[]);
+        EndianSlice::new(data, LittleEndian)
+    }
+
+    // Return the `.debug_abbrev`, `.debug_info` and `.debug_addr` sections
+    // for a skeleton unit.
+    fn skeleton_sections(
+        dwo_id: u64,
+        low_pc_form: constants::DwForm,
+    ) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev = Section::with_endian(Endian::Little)
+            .D8(1).uleb(constants::DW_TAG_skeleton_unit.0.into()).D8(0)
+            .uleb(constants::DW_AT_dwo_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_comp_dir.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_low_pc.0.into()).uleb(low_pc_form.0.into())
+            .uleb(constants::DW_AT_addr_base.0.into()).uleb(constants::DW_FORM_sec_offset.0.into())
+            .D8(0).D8(0)
+            .D8(0);
+        let entries = Section::with_endian(Endian::Little)
+            .D8(1)
+            .append_bytes(b"foo.dwo\0")
+            .append_bytes(b"/src\0");
+        // The address is at index 0 of the `.debug_addr` entries.
+        let entries = match low_pc_form {
+            constants::DW_FORM_addr => entries.L64(0x1000),
+            constants::DW_FORM_addrx1 => entries.D8(0),
+            _ => unreachable!(),
+        };
+        let entries = entries.L32(8);
+        let info = unit(
+            Section::with_endian(Endian::Little),
+            5,
+            constants::DW_UT_skeleton.0,
+            dwo_id,
+            entries,
+        );
+        let addr = Section::with_endian(Endian::Little)
+            .L32(20)
+            .L16(5)
+            .D8(8)
+            .D8(0)
+            .L64(0x1000)
+            .L64(0x2000);
+        (
+            abbrev.get_contents().unwrap(),
+            info.get_contents().unwrap(),
+            addr.get_contents().unwrap(),
+        )
+    }
+
+    // Return the `.debug_abbrev.dwo`, `.debug_info.dwo`, `.debug_str.dwo`
+    // and `.debug_str_offsets.dwo` sections for a split unit.
+    fn split_sections(
+        dwo_id: u64,
+        name: &[u8],
+        str_base: u32,
+    ) -> (Vec<u8>, Vec<u8>, Vec<u8>, Vec<u8>) {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev = Section::with_endian(Endian::Little)
+            .D8(1).uleb(constants::DW_TAG_compile_unit.0.into()).D8(1)
+            .uleb(constants::DW_AT_name.0.into()).uleb(constants::DW_FORM_strx1.0.into())
+            .D8(0).D8(0)
+            .D8(2).uleb(constants::DW_TAG_subprogram.0.into()).D8(0)
+            .uleb(constants::DW_AT_name.0.into()).uleb(constants::DW_FORM_strx1.0.into())
+            .uleb(constants::DW_AT_low_pc.0.into()).uleb(constants::DW_FORM_addrx1.0.into())
+            .D8(0).D8(0)
+            .D8(0);
+        let entries = Section::with_endian(Endian::Little)
+            .D8(1)
+            .D8(0)
+            .D8(2)
+            .D8(1)
+            .D8(1)
+            .D8(0);
+        let info = unit(
+            Section::with_endian(Endian::Little),
+            5,
+            constants::DW_UT_split_compile.0,
+            dwo_id,
+            entries,
+        );
+        let str = Section::with_endian(Endian::Little)
+            .append_bytes(name)
+            .D8(0)
+            .append_bytes(b"main\0");
+        let str_offsets = Section::with_endian(Endian::Little)
+            .L32(12)
+            .L16(5)
+            .L16(0)
+            .L32(str_base)
+            .L32(str_base + name.len() as u32 + 1);
+        (
+            abbrev.get_contents().unwrap(),
+            info.get_contents().unwrap(),
+            str.get_contents().unwrap(),
+            str_offsets.get_contents().unwrap(),
+        )
+    }
+
+    // Check the entries of a split unit created by `split_sections`.
+    fn check_split_unit<'a>(
+        dwo: &Dwarf<EndianSlice<'a, LittleEndian>>,
+        split: &Unit<EndianSlice<'a, LittleEndian>>,
+    ) {
+        assert_eq!(split.low_pc, 0x1000);
+        assert_eq!(split.addr_base, DebugAddrBase(8));
+        assert_eq!(split.str_offsets_base, DebugStrOffsetsBase(8));
+
+        let mut entries = split.entries();
+        entries.next_dfs().unwrap();
+        entries.next_dfs().unwrap();
+        let entry = entries.current().unwrap();
+        assert_eq!(entry.tag(), constants::DW_TAG_subprogram);
+        let name = entry.attr_value(constants::DW_AT_name).unwrap().unwrap();
+        assert_eq!(dwo.attr_string(split, name).unwrap().slice(), b"main");
+        match entry.attr_value(constants::DW_AT_low_pc).unwrap() {
+            Some(AttributeValue::DebugAddrIndex(index)) => {
+                assert_eq!(dwo.address(split, index), Ok(0x2000));
+            }
+            otherwise => panic!("Unexpected low_pc: {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn test_split_unit() {
+        let dwo_id = 0x0102_0304_0506_0708;
+        let (abbrev, info, addr) = skeleton_sections(dwo_id, constants::DW_FORM_addr);
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_addr: EndianSlice::new(&addr, LittleEndian).into(),
+            debug_info: DebugInfo::new(&info, LittleEndian),
+            ..Default::default()
+        };
+        let mut skeleton = Unit::new(&dwarf, dwarf.units().next().unwrap().unwrap()).unwrap();
+        assert_eq!(skeleton.dwo_id, Some(DwoId(dwo_id)));
+        assert_eq!(skeleton.dwo_name.map(|r| r.slice()), Some(&b"foo.dwo"[..]));
+
+        let (dwo_abbrev, dwo_info, dwo_str, dwo_str_offsets) = split_sections(dwo_id, b"foo.c", 0);
+        let dwo_sections = [
+            (".debug_abbrev.dwo", &dwo_abbrev[..]),
+            (".debug_info.dwo", &dwo_info[..]),
+            (".debug_str.dwo", &dwo_str[..]),
+            (".debug_str_offsets.dwo", &dwo_str_offsets[..]),
+        ];
+        let dwo = dwarf
+            .load_dwo(&skeleton, |dwo_name, name| {
+                assert_eq!(dwo_name.slice(), b"foo.dwo");
+                Ok::<_, ()>(load(&dwo_sections, name))
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(dwo.file_type, DwarfFileType::Dwo);
+
+        let split = dwo.split_unit(&skeleton).unwrap().unwrap();
+        assert_eq!(split.name.map(|r| r.slice()), Some(&b"foo.c"[..]));
+        // The split unit has no `DW_AT_comp_dir`, so the skeleton's is used.
+        assert_eq!(split.comp_dir.map(|r| r.slice()), Some(&b"/src"[..]));
+        check_split_unit(&dwo, &split);
+
+        skeleton.dwo_id = Some(DwoId(0));
+        assert!(dwo.split_unit(&skeleton).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_split_unit_lists() {
+        let dwo_id = 0x0102_0304_0506_0708;
+        // DWARF 5 skeleton units normally use an index for `DW_AT_low_pc`.
+        let (abbrev, info, addr) = skeleton_sections(dwo_id, constants::DW_FORM_addrx1);
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_addr: EndianSlice::new(&addr, LittleEndian).into(),
+            debug_info: DebugInfo::new(&info, LittleEndian),
+            ..Default::default()
+        };
+        let skeleton = Unit::new(&dwarf, dwarf.units().next().unwrap().unwrap()).unwrap();
+        assert_eq!(skeleton.low_pc, 0x1000);
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let dwo_abbrev = Section::with_endian(Endian::Little)
+            .D8(1).uleb(constants::DW_TAG_compile_unit.0.into()).D8(0)
+            .uleb(constants::DW_AT_ranges.0.into()).uleb(constants::DW_FORM_rnglistx.0.into())
+            .uleb(constants::DW_AT_location.0.into()).uleb(constants::DW_FORM_loclistx.0.into())
+            .D8(0).D8(0)
+            .D8(0);
+        let dwo_entries = Section::with_endian(Endian::Little).D8(1).uleb(1).uleb(1);
+        let dwo_info = unit(
+            Section::with_endian(Endian::Little),
+            5,
+            constants::DW_UT_split_compile.0,
+            dwo_id,
+            dwo_entries,
+        );
+
+        // Each section has two lists, and the offsets are relative to the
+        // end of the header.
+        let dwo_rnglists = Section::with_endian(Endian::Little)
+            .L32(0)
+            .L16(5)
+            .D8(8)
+            .D8(0)
+            .L32(2)
+            .L32(8)
+            .L32(9)
+            .D8(constants::DW_RLE_end_of_list.0)
+            .D8(constants::DW_RLE_offset_pair.0)
+            .uleb(0x10)
+            .uleb(0x20)
+            .D8(constants::DW_RLE_end_of_list.0);
+        let dwo_loclists = Section::with_endian(Endian::Little)
+            .L32(0)
+            .L16(5)
+            .D8(8)
+            .D8(0)
+            .L32(2)
+            .L32(8)
+            .L32(9)
+            .D8(constants::DW_LLE_end_of_list.0)
+            .D8(constants::DW_LLE_offset_pair.0)
+            .uleb(0)
+            .uleb(8)
+            .uleb(1)
+            .D8(constants::DW_OP_reg0.0)
+            .D8(constants::DW_LLE_end_of_list.0);
+
+        let dwo_abbrev = dwo_abbrev.get_contents().unwrap();
+        let dwo_info = dwo_info.get_contents().unwrap();
+        let dwo_rnglists = dwo_rnglists.get_contents().unwrap();
+        let dwo_loclists = dwo_loclists.get_contents().unwrap();
+        let dwo_sections = [
+            (".debug_abbrev.dwo", &dwo_abbrev[..]),
+            (".debug_info.dwo", &dwo_info[..]),
+            (".debug_rnglists.dwo", &dwo_rnglists[..]),
+            (".debug_loclists.dwo", &dwo_loclists[..]),
+        ];
+        let dwo = dwarf
+            .load_dwo(&skeleton, |_, name| Ok::<_, ()>(load(&dwo_sections, name)))
+            .unwrap()
+            .unwrap();
+
+        let split = dwo.split_unit(&skeleton).unwrap().unwrap();
+        assert_eq!(split.rnglists_base, DebugRngListsBase(12));
+        assert_eq!(split.loclists_base, DebugLocListsBase(12));
+
+        let mut entries = split.entries();
+        entries.next_dfs().unwrap();
+        let entry = entries.current().unwrap();
+
+        let ranges = entry.attr_value(constants::DW_AT_ranges).unwrap().unwrap();
+        let mut ranges = dwo.attr_ranges(&split, ranges).unwrap().unwrap();
+        assert_eq!(
+            ranges.next(),
+            Ok(Some(Range {
+                begin: 0x1010,
+                end: 0x1020,
+            }))
+        );
+        assert_eq!(ranges.next(), Ok(None));
+
+        let location = entry
+            .attr_value(constants::DW_AT_location)
+            .unwrap()
+            .unwrap();
+        let mut locations = dwo.attr_locations(&split, location).unwrap().unwrap();
+        let location = locations.next().unwrap().unwrap();
+        assert_eq!(
+            location.range,
+            Range {
+                begin: 0x1000,
+                end: 0x1008,
+            }
+        );
+        assert_eq!(location.data.0.slice(), &[constants::DW_OP_reg0.0][..]);
+        assert_eq!(locations.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_split_unit_gnu() {
+        let dwo_id = 0x0102_0304_0506_0708;
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev = Section::with_endian(Endian::Little)
+            .D8(1).uleb(constants::DW_TAG_compile_unit.0.into()).D8(0)
+            .uleb(constants::DW_AT_GNU_dwo_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_GNU_dwo_id.0.into()).uleb(constants::DW_FORM_data8.0.into())
+            .uleb(constants::DW_AT_GNU_addr_base.0.into()).uleb(constants::DW_FORM_sec_offset.0.into())
+            .uleb(constants::DW_AT_GNU_ranges_base.0.into()).uleb(constants::DW_FORM_sec_offset.0.into())
+            .D8(0).D8(0)
+            .D8(0);
+        let entries = Section::with_endian(Endian::Little)
+            .D8(1)
+            .append_bytes(b"foo.dwo\0")
+            .L64(dwo_id)
+            .L32(0x10)
+            .L32(0x20);
+        let info = unit(Section::with_endian(Endian::Little), 4, 0, 0, entries);
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let dwo_abbrev = Section::with_endian(Endian::Little)
+            .D8(1).uleb(constants::DW_TAG_compile_unit.0.into()).D8(0)
+            .uleb(constants::DW_AT_GNU_dwo_id.0.into()).uleb(constants::DW_FORM_data8.0.into())
+            .uleb(constants::DW_AT_ranges.0.into()).uleb(constants::DW_FORM_sec_offset.0.into())
+            .D8(0).D8(0)
+            .D8(0);
+        let dwo_entries = Section::with_endian(Endian::Little)
+            .D8(1)
+            .L64(dwo_id)
+            .L32(0x30);
+        let dwo_info = unit(Section::with_endian(Endian::Little), 4, 0, 0, dwo_entries);
+
+        let abbrev = abbrev.get_contents().unwrap();
+        let info = info.get_contents().unwrap();
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_info: DebugInfo::new(&info, LittleEndian),
+            ranges: RangeLists::new(
+                DebugRanges::new(&[], LittleEndian),
+                DebugRngLists::new(&[], LittleEndian),
+            ),
+            ..Default::default()
+        };
+        let skeleton = Unit::new(&dwarf, dwarf.units().next().unwrap().unwrap()).unwrap();
+        assert_eq!(skeleton.dwo_id, Some(DwoId(dwo_id)));
+        assert_eq!(skeleton.dwo_name.map(|r| r.slice()), Some(&b"foo.dwo"[..]));
+
+        let dwo_abbrev = dwo_abbrev.get_contents().unwrap();
+        let dwo_info = dwo_info.get_contents().unwrap();
+        let dwo_sections = [
+            (".debug_abbrev.dwo", &dwo_abbrev[..]),
+            (".debug_info.dwo", &dwo_info[..]),
+        ];
+        let dwo = dwarf
+            .load_dwo(&skeleton, |_, name| Ok::<_, ()>(load(&dwo_sections, name)))
+            .unwrap()
+            .unwrap();
+
+        let split = dwo.split_unit(&skeleton).unwrap().unwrap();
+        assert_eq!(split.addr_base, DebugAddrBase(0x10));
+        assert_eq!(split.rnglists_base, DebugRngListsBase(0x20));
+        assert_eq!(split.str_offsets_base, DebugStrOffsetsBase(0));
+
+        let mut entries = split.entries();
+        entries.next_dfs().unwrap();
+        let entry = entries.current().unwrap();
+        let ranges = entry.attr_value(constants::DW_AT_ranges).unwrap().unwrap();
+        assert_eq!(
+            dwo.attr_ranges_offset(&split, ranges),
+            Ok(Some(RangeListsOffset(0x50)))
+        );
+    }
+
+    #[test]
+    fn test_from_dwp() {
+        let dwo_id1 = 0x0102_0304_0506_0708;
+        let dwo_id2 = 0x1112_1314_1516_1715;
+        let (abbrev, info, addr) = skeleton_sections(dwo_id2, constants::DW_FORM_addr);
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_addr: EndianSlice::new(&addr, LittleEndian).into(),
+            debug_info: DebugInfo::new(&info, LittleEndian),
+            ..Default::default()
+        };
+        let skeleton = Unit::new(&dwarf, dwarf.units().next().unwrap().unwrap()).unwrap();
+
+        let (abbrev1, info1, str1, str_offsets1) = split_sections(dwo_id1, b"foo.c", 0);
+        let (abbrev2, info2, str2, str_offsets2) =
+            split_sections(dwo_id2, b"bar.c", str1.len() as u32);
+        let dwp_abbrev = [&abbrev1[..], &abbrev2[..]].concat();
+        let dwp_info = [&info1[..], &info2[..]].concat();
+        let dwp_str = [&str1[..], &str2[..]].concat();
+        let dwp_str_offsets = [&str_offsets1[..], &str_offsets2[..]].concat();
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let cu_index = Section::with_endian(Endian::Little)
+            // Header.
+            .L16(5).L16(0).L32(3).L32(2).L32(4)
+            // Slots.
+            .L64(dwo_id1).L64(dwo_id2).L64(0).L64(0)
+            .L32(1).L32(2).L32(0).L32(0)
+            // Sections.
+            .L32(1).L32(3).L32(6)
+            // Offsets.
+            .L32(0).L32(0).L32(0)
+            .L32(info1.len() as u32).L32(abbrev1.len() as u32).L32(str_offsets1.len() as u32)
+            // Sizes.
+            .L32(info1.len() as u32).L32(abbrev1.len() as u32).L32(str_offsets1.len() as u32)
+            .L32(info2.len() as u32).L32(abbrev2.len() as u32).L32(str_offsets2.len() as u32);
+        let cu_index = cu_index.get_contents().unwrap();
+        let index = DebugCuIndex::new(&cu_index, LittleEndian).index().unwrap();
+
+        let dwp_sections = [
+            (".debug_abbrev.dwo", &dwp_abbrev[..]),
+            (".debug_info.dwo", &dwp_info[..]),
+            (".debug_str.dwo", &dwp_str[..]),
+            (".debug_str_offsets.dwo", &dwp_str_offsets[..]),
+        ];
+        let dwo = Dwarf::from_dwp(&dwarf, &index, dwo_id2, |name| {
+            Ok::<_, Error>(load(&dwp_sections, name))
+        })
+        .unwrap()
+        .unwrap();
+        assert_eq!(dwo.file_type, DwarfFileType::Dwo);
+
+        let split = dwo.split_unit(&skeleton).unwrap().unwrap();
+        assert_eq!(split.name.map(|r| r.slice()), Some(&b"bar.c"[..]));
+        check_split_unit(&dwo, &split);
+
+        assert!(Dwarf::from_dwp(&dwarf, &index, 0, |name| {
+            Ok::<_, Error>(load(&dwp_sections, name))
+        })
+        .unwrap()
+        .is_none());
+    }
+
+    #[test]
+    fn test_find_unit() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev = Section::with_endian(Endian::Little)
+            .D8(1).uleb(constants::DW_TAG_compile_unit.0.into()).D8(0)
+            .uleb(constants::DW_AT_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_low_pc.0.into()).uleb(constants::DW_FORM_addr.0.into())
+            .uleb(constants::DW_AT_high_pc.0.into()).uleb(constants::DW_FORM_data8.0.into())
+            .D8(0).D8(0)
+            .D8(0);
+        let abbrev = abbrev.get_contents().unwrap();
+        let mut info = Section::with_endian(Endian::Little);
+        for &(name, low_pc, size) in &[
+            (b"a\0", 0x1000, 0x100),
+            (b"b\0", 0x2000, 0x100),
+            (b"c\0", 0x1080, 0x100),
+            (b"d\0", 0x1180, 0x80),
+        ] {
+            let entries = Section::with_endian(Endian::Little)
+                .D8(1)
+                .append_bytes(name)
+                .L64(low_pc)
+                .L64(size);
+            info = unit(info, 4, 0, 0, entries);
+        }
+        let info = info.get_contents().unwrap();
+        let debug_info = DebugInfo::new(&info, LittleEndian);
+        let offsets = debug_info
+            .units()
+            .map(|header| header.offset())
+            .collect::<Vec<_>>()
+            .unwrap();
+
+        // Unit "b" has an entry in `.debug_aranges`, which takes precedence
+        // over its `DW_AT_low_pc`.
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let aranges = Section::with_endian(Endian::Little)
+            .L32(44).L16(2).L32(offsets[1].0 as u32).D8(8).D8(0).L32(0)
+            .L64(0x3000).L64(0x100)
+            .L64(0).L64(0);
+        let aranges = aranges.get_contents().unwrap();
+
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_aranges: DebugAranges::new(&aranges, LittleEndian),
+            debug_info,
+            ..Default::default()
+        };
+
+        let find = |address| {
+            dwarf.find_unit(address).unwrap().map(|header| {
+                let unit = Unit::new(&dwarf, header).unwrap();
+                unit.name.unwrap().slice()
+            })
+        };
+        assert_eq!(find(0xfff), None);
+        assert_eq!(find(0x1000), Some(&b"a"[..]));
+        assert_eq!(find(0x10ff), Some(&b"a"[..]));
+        assert_eq!(find(0x1100), Some(&b"c"[..]));
+        assert_eq!(find(0x117f), Some(&b"c"[..]));
+        assert_eq!(find(0x1180), Some(&b"d"[..]));
+        assert_eq!(find(0x11ff), Some(&b"d"[..]));
+        assert_eq!(find(0x1200), None);
+        assert_eq!(find(0x2000), None);
+        assert_eq!(find(0x3000), Some(&b"b"[..]));
+        assert_eq!(find(0x30ff), Some(&b"b"[..]));
+        assert_eq!(find(0x3100), None);
+
+        let index = UnitRangeIndex::new(&dwarf).unwrap();
+        assert_eq!(index.find_unit_offset(0x1180), Some(offsets[3]));
+    }
 }
@@ -0,0 +1,522 @@
+use std::slice;
+
+use arrayvec::ArrayVec;
+
+use endianity::Endianity;
+use read::{EndianSlice, Error, Reader, ReaderOffset, Result, Section};
+
+/// The data in the `.debug_cu_index` section of a `.dwp` file.
+///
+/// This section contains the compilation unit index.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DebugCuIndex<R: Reader> {
+    section: R,
+}
+
+impl<'input, Endian> DebugCuIndex<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugCuIndex` instance from the data in the `.debug_cu_index`
+    /// section.
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(section, endian))
+    }
+}
+
+impl<R: Reader> DebugCuIndex<R> {
+    /// Parse the index header.
+    pub fn index(self) -> Result<UnitIndex<R>> {
+        UnitIndex::parse(self.section)
+    }
+}
+
+impl<R: Reader> Section<R> for DebugCuIndex<R> {
+    fn section_name() -> &'static str {
+        ".debug_cu_index"
+    }
+}
+
+impl<R: Reader> From<R> for DebugCuIndex<R> {
+    fn from(section: R) -> Self {
+        DebugCuIndex { section }
+    }
+}
+
+/// The data in the `.debug_tu_index` section of a `.dwp` file.
+///
+/// This section contains the type unit index.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DebugTuIndex<R: Reader> {
+    section: R,
+}
+
+impl<'input, Endian> DebugTuIndex<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugTuIndex` instance from the data in the `.debug_tu_index`
+    /// section.
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(section, endian))
+    }
+}
+
+impl<R: Reader> DebugTuIndex<R> {
+    /// Parse the index header.
+    pub fn index(self) -> Result<UnitIndex<R>> {
+        UnitIndex::parse(self.section)
+    }
+}
+
+impl<R: Reader> Section<R> for DebugTuIndex<R> {
+    fn section_name() -> &'static str {
+        ".debug_tu_index"
+    }
+}
+
+impl<R: Reader> From<R> for DebugTuIndex<R> {
+    fn from(section: R) -> Self {
+        DebugTuIndex { section }
+    }
+}
+
+const SECTION_COUNT_MAX: u8 = 8;
+
+/// The partially parsed index from a `DebugCuIndex` or `DebugTuIndex`.
+#[derive(Debug, Clone)]
+pub struct UnitIndex<R: Reader> {
+    version: u16,
+    section_count: u32,
+    unit_count: u32,
+    slot_count: u32,
+    hash_ids: R,
+    hash_rows: R,
+    // Only `section_count` values are valid.
+    sections: ArrayVec<[IndexSectionId; SECTION_COUNT_MAX as usize]>,
+    offsets: R,
+    sizes: R,
+}
+
+impl<R: Reader> UnitIndex<R> {
+    fn parse(mut input: R) -> Result<UnitIndex<R>> {
+        // The GNU extension to DWARF 4 uses a 32-bit version, but DWARF 5
+        // uses a 16-bit version followed by 16 bits of padding.
+        let version = if input.clone().read_u16()? == 5 {
+            input.skip(R::Offset::from_u8(4))?;
+            5
+        } else {
+            match input.read_u32()? {
+                2 => 2,
+                version => return Err(Error::UnknownVersion(u64::from(version))),
+            }
+        };
+
+        let section_count = input.read_u32()?;
+        let unit_count = input.read_u32()?;
+        let slot_count = input.read_u32()?;
+        if slot_count != 0 && (slot_count & (slot_count - 1) != 0 || slot_count <= unit_count) {
+            return Err(Error::InvalidIndexSlotCount);
+        }
+        if section_count > u32::from(SECTION_COUNT_MAX) {
+            return Err(Error::InvalidIndexSectionCount);
+        }
+
+        let hash_ids = input.split(R::Offset::from_u64(u64::from(slot_count) * 8)?)?;
+        let hash_rows = input.split(R::Offset::from_u64(u64::from(slot_count) * 4)?)?;
+
+        let mut sections = ArrayVec::new();
+        for _ in 0..section_count {
+            let section = input.read_u32()?;
+            let section = match (version, section) {
+                (2, 1) | (5, 1) => IndexSectionId::DebugInfo,
+                (2, 2) => IndexSectionId::DebugTypes,
+                (2, 3) | (5, 3) => IndexSectionId::DebugAbbrev,
+                (2, 4) | (5, 4) => IndexSectionId::DebugLine,
+                (2, 5) => IndexSectionId::DebugLoc,
+                (5, 5) => IndexSectionId::DebugLocLists,
+                (2, 6) | (5, 6) => IndexSectionId::DebugStrOffsets,
+                (2, 7) => IndexSectionId::DebugMacinfo,
+                (2, 8) | (5, 7) => IndexSectionId::DebugMacro,
+                (5, 8) => IndexSectionId::DebugRngLists,
+                _ => return Err(Error::UnknownIndexSection),
+            };
+            sections.push(section);
+        }
+
+        let size = R::Offset::from_u64(u64::from(unit_count) * u64::from(section_count) * 4)?;
+        let offsets = input.split(size)?;
+        let sizes = input.split(size)?;
+
+        Ok(UnitIndex {
+            version,
+            section_count,
+            unit_count,
+            slot_count,
+            hash_ids,
+            hash_rows,
+            sections,
+            offsets,
+            sizes,
+        })
+    }
+
+    /// Find `id` in the index hash table, and return the row index.
+    ///
+    /// `id` may be a compilation unit ID if this index is from `.debug_cu_index`,
+    /// or a type signature if this index is from `.debug_tu_index`.
+    pub fn find(&self, id: u64) -> Option<u32> {
+        if self.slot_count == 0 {
+            return None;
+        }
+        let mask = u64::from(self.slot_count - 1);
+        let mut hash1 = id & mask;
+        let hash2 = ((id >> 32) & mask) | 1;
+        for _ in 0..self.slot_count {
+            // The length of these arrays was validated in `UnitIndex::parse`.
+            let mut hash_ids = self.hash_ids.clone();
+            hash_ids.skip(R::Offset::from_u64(hash1 * 8).ok()?).ok()?;
+            let hash_id = hash_ids.read_u64().ok()?;
+            let mut hash_rows = self.hash_rows.clone();
+            hash_rows.skip(R::Offset::from_u64(hash1 * 4).ok()?).ok()?;
+            let row = hash_rows.read_u32().ok()?;
+            if row == 0 {
+                return None;
+            }
+            if hash_id == id {
+                return Some(row);
+            }
+            hash1 = (hash1 + hash2) & mask;
+        }
+        None
+    }
+
+    /// Return the section offsets and sizes for the given row index.
+    pub fn sections(&self, row: u32) -> Result<UnitIndexSectionIterator<R>> {
+        if row == 0 || row > self.unit_count {
+            return Err(Error::InvalidIndexRow);
+        }
+        let row = row - 1;
+        let skip = R::Offset::from_u64(u64::from(row) * u64::from(self.section_count) * 4)?;
+        let mut offsets = self.offsets.clone();
+        offsets.skip(skip)?;
+        let mut sizes = self.sizes.clone();
+        sizes.skip(skip)?;
+        Ok(UnitIndexSectionIterator {
+            sections: self.sections[..].iter(),
+            offsets,
+            sizes,
+        })
+    }
+
+    /// Return the version.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Return the number of sections.
+    pub fn section_count(&self) -> u32 {
+        self.section_count
+    }
+
+    /// Return the number of units.
+    pub fn unit_count(&self) -> u32 {
+        self.unit_count
+    }
+
+    /// Return the number of slots.
+    pub fn slot_count(&self) -> u32 {
+        self.slot_count
+    }
+}
+
+/// An iterator over the section offsets and sizes for a row in a `UnitIndex`.
+#[derive(Debug, Clone)]
+pub struct UnitIndexSectionIterator<'index, R: Reader> {
+    sections: slice::Iter<'index, IndexSectionId>,
+    offsets: R,
+    sizes: R,
+}
+
+impl<'index, R: Reader> Iterator for UnitIndexSectionIterator<'index, R> {
+    type Item = UnitIndexSection;
+
+    fn next(&mut self) -> Option<UnitIndexSection> {
+        let section = *self.sections.next()?;
+        // The length of these arrays was validated in `UnitIndex::parse`.
+        let offset = self.offsets.read_u32().ok()?;
+        let size = self.sizes.read_u32().ok()?;
+        Some(UnitIndexSection {
+            section,
+            offset,
+            size,
+        })
+    }
+}
+
+/// Information about a unit's contribution to a section in a `.dwp` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnitIndexSection {
+    /// The section kind.
+    pub section: IndexSectionId,
+    /// The base offset of the unit's contribution to the section.
+    pub offset: u32,
+    /// The size of the unit's contribution to the section.
+    pub size: u32,
+}
+
+/// Section kinds which are permitted in a `.dwp` index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexSectionId {
+    /// The `.debug_abbrev.dwo` section.
+    DebugAbbrev,
+    /// The `.debug_info.dwo` section.
+    DebugInfo,
+    /// The `.debug_line.dwo` section.
+    DebugLine,
+    /// The `.debug_loc.dwo` section.
+    DebugLoc,
+    /// The `.debug_loclists.dwo` section.
+    DebugLocLists,
+    /// The `.debug_macinfo.dwo` section.
+    DebugMacinfo,
+    /// The `.debug_macro.dwo` section.
+    DebugMacro,
+    /// The `.debug_rnglists.dwo` section.
+    DebugRngLists,
+    /// The `.debug_str_offsets.dwo` section.
+    DebugStrOffsets,
+    /// The `.debug_types.dwo` section.
+    DebugTypes,
+}
+
+impl IndexSectionId {
+    /// Returns the ELF section name for this kind, when found in a `.dwo` or `.dwp` file.
+    pub fn dwo_name(self) -> &'static str {
+        match self {
+            IndexSectionId::DebugAbbrev => ".debug_abbrev.dwo",
+            IndexSectionId::DebugInfo => ".debug_info.dwo",
+            IndexSectionId::DebugLine => ".debug_line.dwo",
+            IndexSectionId::DebugLoc => ".debug_loc.dwo",
+            IndexSectionId::DebugLocLists => ".debug_loclists.dwo",
+            IndexSectionId::DebugMacinfo => ".debug_macinfo.dwo",
+            IndexSectionId::DebugMacro => ".debug_macro.dwo",
+            IndexSectionId::DebugRngLists => ".debug_rnglists.dwo",
+            IndexSectionId::DebugStrOffsets => ".debug_str_offsets.dwo",
+            IndexSectionId::DebugTypes => ".debug_types.dwo",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Section};
+    use super::*;
+    use {BigEndian, LittleEndian};
+
+    #[test]
+    fn test_empty() {
+        let buf = EndianSlice::new(&[], BigEndian);
+        let index = UnitIndex::parse(buf);
+        assert_eq!(index.err(), Some(Error::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_version_2() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            // Header.
+            .D32(2).D32(0).D32(0).D32(0);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf).unwrap();
+        assert_eq!(index.version, 2);
+        assert_eq!(index.find(0), None);
+    }
+
+    #[test]
+    fn test_version_5() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            // Header.
+            .D16(5).D16(0).D32(0).D32(0).D32(0);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf).unwrap();
+        assert_eq!(index.version, 5);
+    }
+
+    #[test]
+    fn test_version_5_little_endian() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Little)
+            // Header.
+            .D16(5).D16(0).D32(0).D32(0).D32(0);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, LittleEndian);
+        let index = UnitIndex::parse(buf).unwrap();
+        assert_eq!(index.version, 5);
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            .D32(3).D32(0).D32(0).D32(0);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf);
+        assert_eq!(index.err(), Some(Error::UnknownVersion(3)));
+    }
+
+    #[test]
+    fn test_invalid_slot_count() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            // Header.
+            .D32(2).D32(0).D32(1).D32(3);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf);
+        assert_eq!(index.err(), Some(Error::InvalidIndexSlotCount));
+    }
+
+    #[test]
+    fn test_version_2_sections() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            // Header.
+            .D32(2).D32(8).D32(1).D32(2)
+            // Slots.
+            .D64(0).D64(0)
+            .D32(0).D32(0)
+            // Sections.
+            .D32(1).D32(2).D32(3).D32(4).D32(5).D32(6).D32(7).D32(8)
+            // Offsets.
+            .D32(11).D32(12).D32(13).D32(14).D32(15).D32(16).D32(17).D32(18)
+            // Sizes.
+            .D32(21).D32(22).D32(23).D32(24).D32(25).D32(26).D32(27).D32(28);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf).unwrap();
+        assert_eq!(index.section_count(), 8);
+        assert_eq!(
+            &index.sections[..],
+            &[
+                IndexSectionId::DebugInfo,
+                IndexSectionId::DebugTypes,
+                IndexSectionId::DebugAbbrev,
+                IndexSectionId::DebugLine,
+                IndexSectionId::DebugLoc,
+                IndexSectionId::DebugStrOffsets,
+                IndexSectionId::DebugMacinfo,
+                IndexSectionId::DebugMacro,
+            ]
+        );
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let expect = [
+            UnitIndexSection { section: IndexSectionId::DebugInfo, offset: 11, size: 21 },
+            UnitIndexSection { section: IndexSectionId::DebugTypes, offset: 12, size: 22 },
+            UnitIndexSection { section: IndexSectionId::DebugAbbrev, offset: 13, size: 23 },
+            UnitIndexSection { section: IndexSectionId::DebugLine, offset: 14, size: 24 },
+            UnitIndexSection { section: IndexSectionId::DebugLoc, offset: 15, size: 25 },
+            UnitIndexSection { section: IndexSectionId::DebugStrOffsets, offset: 16, size: 26 },
+            UnitIndexSection { section: IndexSectionId::DebugMacinfo, offset: 17, size: 27 },
+            UnitIndexSection { section: IndexSectionId::DebugMacro, offset: 18, size: 28 },
+        ];
+        let mut sections = index.sections(1).unwrap();
+        for section in &expect {
+            assert_eq!(*section, sections.next().unwrap());
+        }
+        assert!(sections.next().is_none());
+        assert_eq!(index.sections(0).err(), Some(Error::InvalidIndexRow));
+        assert_eq!(index.sections(2).err(), Some(Error::InvalidIndexRow));
+    }
+
+    #[test]
+    fn test_version_5_sections() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            // Header.
+            .D16(5).D16(0).D32(7).D32(1).D32(2)
+            // Slots.
+            .D64(0).D64(0)
+            .D32(0).D32(0)
+            // Sections.
+            .D32(1).D32(3).D32(4).D32(5).D32(6).D32(7).D32(8)
+            // Offsets.
+            .D32(11).D32(12).D32(13).D32(14).D32(15).D32(16).D32(17)
+            // Sizes.
+            .D32(21).D32(22).D32(23).D32(24).D32(25).D32(26).D32(27);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf).unwrap();
+        assert_eq!(index.section_count(), 7);
+        assert_eq!(
+            &index.sections[..],
+            &[
+                IndexSectionId::DebugInfo,
+                IndexSectionId::DebugAbbrev,
+                IndexSectionId::DebugLine,
+                IndexSectionId::DebugLocLists,
+                IndexSectionId::DebugStrOffsets,
+                IndexSectionId::DebugMacro,
+                IndexSectionId::DebugRngLists,
+            ]
+        );
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let expect = [
+            UnitIndexSection { section: IndexSectionId::DebugInfo, offset: 11, size: 21 },
+            UnitIndexSection { section: IndexSectionId::DebugAbbrev, offset: 12, size: 22 },
+            UnitIndexSection { section: IndexSectionId::DebugLine, offset: 13, size: 23 },
+            UnitIndexSection { section: IndexSectionId::DebugLocLists, offset: 14, size: 24 },
+            UnitIndexSection { section: IndexSectionId::DebugStrOffsets, offset: 15, size: 25 },
+            UnitIndexSection { section: IndexSectionId::DebugMacro, offset: 16, size: 26 },
+            UnitIndexSection { section: IndexSectionId::DebugRngLists, offset: 17, size: 27 },
+        ];
+        let mut sections = index.sections(1).unwrap();
+        for section in &expect {
+            assert_eq!(*section, sections.next().unwrap());
+        }
+        assert!(sections.next().is_none());
+    }
+
+    #[test]
+    fn test_unknown_section() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            // Header.
+            .D16(5).D16(0).D32(1).D32(0).D32(0)
+            // Sections.
+            .D32(2);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf);
+        assert_eq!(index.err(), Some(Error::UnknownIndexSection));
+    }
+
+    #[test]
+    fn test_hash() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Big)
+            // Header.
+            .D32(2).D32(0).D32(3).D32(4)
+            // Slots.
+            .D64(0xffff_fff2_ffff_fff1)
+            .D64(0xffff_fff0_ffff_fff1)
+            .D64(0xffff_fff1_ffff_fff1)
+            .D64(0)
+            .D32(3).D32(1).D32(2).D32(0);
+        let buf = section.get_contents().unwrap();
+        let buf = EndianSlice::new(&buf, BigEndian);
+        let index = UnitIndex::parse(buf).unwrap();
+        assert_eq!(index.unit_count(), 3);
+        assert_eq!(index.slot_count(), 4);
+        assert_eq!(index.find(0xffff_fff0_ffff_fff1), Some(1));
+        assert_eq!(index.find(0xffff_fff1_ffff_fff1), Some(2));
+        assert_eq!(index.find(0xffff_fff2_ffff_fff1), Some(3));
+        assert_eq!(index.find(0xffff_fff3_ffff_fff1), None);
+    }
+}
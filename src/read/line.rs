@@ -1,5 +1,7 @@
+use std::cmp;
 use std::fmt;
 use std::result;
+use std::slice;
 use vec::Vec;
 
 use common::{
@@ -8,7 +10,9 @@ use common::{
 };
 use constants;
 use endianity::Endianity;
-use read::{AttributeValue, EndianSlice, Error, Reader, ReaderOffset, Result, Section};
+use read::{
+    AttributeValue, Dwarf, EndianSlice, Error, Range, Reader, ReaderOffset, Result, Section, Unit,
+};
 
 /// The `DebugLine` struct contains the source location to instruction mapping
 /// found in the `.debug_line` section.
@@ -1857,17 +1861,535 @@ fn parse_attribute<R: Reader>(
     })
 }
 
+/// An owned table of the rows of a line number program, which can be searched
+/// by address.
+///
+/// The rows of every sequence are materialized when the table is built, and
+/// the file names are resolved to full paths using the include directories of
+/// the line number program header and the compilation directory of the unit.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LineTable {
+    files: Vec<Option<Vec<u8>>>,
+    sequences: Vec<LineTableSequence>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct LineTableSequence {
+    start: u64,
+    end: u64,
+    /// The rows of the sequence, ordered by address.
+    ///
+    /// Multiple rows may have the same address. The last of these is the
+    /// one that applies to the address, but the others are kept for
+    /// `LineTable::find_line_ranges`.
+    rows: Vec<LineTableRow>,
+}
+
+impl LineTableSequence {
+    /// Return the index of the last row with an address at or before the
+    /// given address.
+    fn last_row(&self, address: u64) -> Option<usize> {
+        let index = self
+            .rows
+            .binary_search_by(|row| {
+                if row.address <= address {
+                    cmp::Ordering::Less
+                } else {
+                    cmp::Ordering::Greater
+                }
+            })
+            .unwrap_err();
+        index.checked_sub(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LineTableRow {
+    address: u64,
+    file: u64,
+    line: u64,
+    column: u64,
+    is_stmt: bool,
+    prologue_end: bool,
+}
+
+/// A source location that was found in a `LineTable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineLocation<'table> {
+    /// The full path of the source file, or `None` if the file index of the
+    /// row is invalid.
+    pub file: Option<&'table [u8]>,
+
+    /// The line number, or `None` if unknown.
+    pub line: Option<u64>,
+
+    /// The column number, or `None` if unknown or the location is the
+    /// left edge of the line.
+    pub column: Option<u64>,
+}
+
+impl LineTable {
+    /// Build a `LineTable` by running the line number program of a unit.
+    ///
+    /// `program` is normally the `line_program` of `unit`.
+    pub fn new<R: Reader>(
+        dwarf: &Dwarf<R>,
+        unit: &Unit<R>,
+        program: IncompleteLineProgram<R, R::Offset>,
+    ) -> Result<LineTable> {
+        let (program, sequences) = program.sequences()?;
+
+        let mut table_sequences = Vec::with_capacity(sequences.len());
+        for sequence in &sequences {
+            let mut rows: Vec<LineTableRow> = Vec::new();
+            let mut line_rows = program.resume_from(sequence);
+            while let Some((_, row)) = line_rows.next_row()? {
+                if row.end_sequence() {
+                    break;
+                }
+                let row = LineTableRow {
+                    address: row.address(),
+                    file: row.file_index(),
+                    line: row.line().unwrap_or(0),
+                    column: match row.column() {
+                        ColumnType::LeftEdge => 0,
+                        ColumnType::Column(column) => column,
+                    },
+                    is_stmt: row.is_stmt(),
+                    prologue_end: row.prologue_end(),
+                };
+                rows.push(row);
+            }
+            if !rows.is_empty() {
+                table_sequences.push(LineTableSequence {
+                    start: sequence.start,
+                    end: sequence.end,
+                    rows,
+                });
+            }
+        }
+        table_sequences.sort_by_key(|sequence| sequence.start);
+
+        let header = program.header();
+        let mut count = header.file_names().len() as u64;
+        if header.version() <= 4 {
+            // Include the compilation unit file at index 0.
+            count += 1;
+        }
+        let mut files = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            files.push(match header.file(index) {
+                Some(file) => Some(file_path(dwarf, unit, header, file)?),
+                None => None,
+            });
+        }
+
+        Ok(LineTable {
+            files,
+            sequences: table_sequences,
+        })
+    }
+
+    /// Return the full path of the file with the given index in the
+    /// line number program header.
+    pub fn file(&self, index: u64) -> Option<&[u8]> {
+        self.files
+            .get(index as usize)
+            .and_then(|path| path.as_ref())
+            .map(|path| &path[..])
+    }
+
+    /// Find the source location for the given address.
+    ///
+    /// Returns `None` if no sequence contains the address.
+    pub fn find_location(&self, address: u64) -> Option<LineLocation> {
+        let sequence = match self.find_sequence(address) {
+            Some(index) => &self.sequences[index],
+            None => return None,
+        };
+        if address >= sequence.end {
+            return None;
+        }
+        sequence
+            .last_row(address)
+            .map(|index| self.location(&sequence.rows[index]))
+    }
+
+    /// Iterate over the source locations for the addresses in the range
+    /// `start..end`.
+    ///
+    /// Each item is the address and length of a range of addresses with the
+    /// same location. The first and last ranges may extend outside of
+    /// `start..end`.
+    pub fn find_location_range(&self, start: u64, end: u64) -> LineLocationRangeIter {
+        let sequence = self.find_sequence(start).unwrap_or(0);
+        let row = match self.sequences.get(sequence) {
+            Some(sequence) => sequence.last_row(start).unwrap_or(0),
+            None => 0,
+        };
+        LineLocationRangeIter {
+            table: self,
+            sequence,
+            row,
+            start,
+            end,
+        }
+    }
+
+    /// Find the address ranges for a line in a source file, such as for
+    /// setting a breakpoint.
+    ///
+    /// `file` matches the trailing components of the full path of a source
+    /// file, so `"b.c"` and `"src/b.c"` both match `"/home/a/src/b.c"`.
+    ///
+    /// If the line has no code then the ranges for the next line that has
+    /// code are returned instead. Rows that are recommended breakpoint
+    /// locations (`is_stmt`) are preferred over other rows for the line.
+    /// Within each run of contiguous addresses for the line, rows that are the
+    /// end of a function prologue are also preferred over other rows in that
+    /// run. Separate runs, such as the line in other functions or inlined
+    /// copies of the line, each have a range returned.
+    pub fn find_line_ranges(&self, file: &[u8], line: u64) -> Vec<Range> {
+        LineTable::find_all_line_ranges(slice::from_ref(self), file, line)
+    }
+
+    /// Find the address ranges for a line in a source file in any of the
+    /// given tables.
+    ///
+    /// This is the same as `find_line_ranges`, except that the next line
+    /// that has code is searched for in all of the tables. The tables are
+    /// normally built once for all units using `Dwarf::line_tables`, and then
+    /// reused for multiple lookups.
+    pub fn find_all_line_ranges(tables: &[LineTable], file: &[u8], line: u64) -> Vec<Range> {
+        let files: Vec<_> = tables
+            .iter()
+            .map(|table| table.matching_files(file))
+            .collect();
+
+        let mut ranges = Vec::new();
+        for (table, files) in tables.iter().zip(files.iter()) {
+            ranges.extend(select_line_ranges(&table.line_matches(files, line)));
+        }
+        if ranges.is_empty() {
+            let next = tables
+                .iter()
+                .zip(files.iter())
+                .filter_map(|(table, files)| table.next_line(files, line))
+                .min();
+            if let Some(next) = next {
+                for (table, files) in tables.iter().zip(files.iter()) {
+                    ranges.extend(select_line_ranges(&table.line_matches(files, next)));
+                }
+            }
+        }
+        merge_ranges(ranges)
+    }
+
+    /// Return whether each file in the table matches the given path suffix.
+    fn matching_files(&self, file: &[u8]) -> Vec<bool> {
+        self.files
+            .iter()
+            .map(|path| match *path {
+                Some(ref path) => path_ends_with(path, file),
+                None => false,
+            })
+            .collect()
+    }
+
+    /// Return the rows for the given line in any of the matching files.
+    ///
+    /// The matches are ordered by sequence, and then by address. The range of
+    /// a row that is followed by other rows at the same address extends to
+    /// the next address.
+    fn line_matches(&self, files: &[bool], line: u64) -> Vec<LineMatch> {
+        let mut matches = Vec::new();
+        for sequence in &self.sequences {
+            for (i, row) in sequence.rows.iter().enumerate() {
+                if row.line != line || !files.get(row.file as usize).cloned().unwrap_or(false) {
+                    continue;
+                }
+                let end = sequence.rows[i + 1..]
+                    .iter()
+                    .map(|next| next.address)
+                    .find(|&address| address != row.address)
+                    .unwrap_or(sequence.end);
+                matches.push(LineMatch {
+                    range: Range {
+                        begin: row.address,
+                        end,
+                    },
+                    is_stmt: row.is_stmt,
+                    prologue_end: row.prologue_end,
+                });
+            }
+        }
+        matches
+    }
+
+    /// Return the first line after the given line that has code in any of
+    /// the matching files.
+    fn next_line(&self, files: &[bool], line: u64) -> Option<u64> {
+        self.sequences
+            .iter()
+            .flat_map(|sequence| sequence.rows.iter())
+            .filter(|row| row.line > line && files.get(row.file as usize).cloned().unwrap_or(false))
+            .map(|row| row.line)
+            .min()
+    }
+
+    /// Return the index of the last sequence that starts at or before the
+    /// address.
+    fn find_sequence(&self, address: u64) -> Option<usize> {
+        match self
+            .sequences
+            .binary_search_by_key(&address, |sequence| sequence.start)
+        {
+            Ok(index) => Some(index),
+            Err(0) => None,
+            Err(index) => Some(index - 1),
+        }
+    }
+
+    fn location(&self, row: &LineTableRow) -> LineLocation {
+        LineLocation {
+            file: self.file(row.file),
+            line: if row.line != 0 { Some(row.line) } else { None },
+            column: if row.column != 0 {
+                Some(row.column)
+            } else {
+                None
+            },
+        }
+    }
+}
+
+/// A row in a `LineTable` that matches a source line.
+#[derive(Debug, Clone, Copy)]
+struct LineMatch {
+    range: Range,
+    is_stmt: bool,
+    prologue_end: bool,
+}
+
+/// An iterator over the source locations for a range of addresses in a
+/// `LineTable`.
+///
+/// This is returned by `LineTable::find_location_range`.
+#[derive(Debug, Clone)]
+pub struct LineLocationRangeIter<'table> {
+    table: &'table LineTable,
+    sequence: usize,
+    row: usize,
+    start: u64,
+    end: u64,
+}
+
+impl<'table> Iterator for LineLocationRangeIter<'table> {
+    type Item = (u64, u64, LineLocation<'table>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(sequence) = self.table.sequences.get(self.sequence) {
+            if sequence.start >= self.end {
+                break;
+            }
+            let row = match sequence.rows.get(self.row) {
+                Some(row) => row,
+                None => {
+                    self.sequence += 1;
+                    self.row = 0;
+                    continue;
+                }
+            };
+            self.row += 1;
+            let row_end = match sequence.rows.get(self.row) {
+                Some(next) => next.address,
+                None => sequence.end,
+            };
+            if row.address >= self.end {
+                break;
+            }
+            // Skip rows that are replaced by a later row at the same address.
+            if row_end <= self.start || row_end == row.address {
+                continue;
+            }
+            return Some((row.address, row_end - row.address, self.table.location(row)));
+        }
+        self.sequence = self.table.sequences.len();
+        None
+    }
+}
+
+/// Return the full path of a file entry in a line number program header.
+fn file_path<R: Reader>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    header: &LineProgramHeader<R, R::Offset>,
+    file: &FileEntry<R, R::Offset>,
+) -> Result<Vec<u8>> {
+    let mut path = Vec::new();
+    if let Some(ref comp_dir) = unit.comp_dir {
+        path_push(&mut path, &comp_dir.to_slice()?);
+    }
+    // For DWARF 4 and earlier, directory 0 is the compilation directory, which
+    // has already been added.
+    if header.version() >= 5 || file.directory_index() != 0 {
+        if let Some(directory) = file.directory(header) {
+            let directory = dwarf.attr_string(unit, directory)?;
+            path_push(&mut path, &directory.to_slice()?);
+        }
+    }
+    let path_name = dwarf.attr_string(unit, file.path_name())?;
+    path_push(&mut path, &path_name.to_slice()?);
+    Ok(path_normalize(&path))
+}
+
+/// Append `p` to `path`, replacing `path` if `p` is absolute.
+fn path_push(path: &mut Vec<u8>, p: &[u8]) {
+    if path_is_absolute(p) {
+        path.clear();
+    } else if !path.is_empty() {
+        let separator = if path_is_windows(path) { b'\\' } else { b'/' };
+        if path.last() != Some(&separator) {
+            path.push(separator);
+        }
+    }
+    path.extend_from_slice(p);
+}
+
+fn path_is_absolute(path: &[u8]) -> bool {
+    path.first() == Some(&b'/') || path.first() == Some(&b'\\') || path_is_windows(path)
+}
+
+/// Return true if the path begins with a Windows drive letter.
+fn path_is_windows(path: &[u8]) -> bool {
+    path.len() > 2
+        && path[0].is_ascii_alphabetic()
+        && path[1] == b':'
+        && (path[2] == b'\\' || path[2] == b'/')
+}
+
+/// Split a path into its components, removing empty and `.` components,
+/// and resolving `..` components where possible.
+///
+/// The root or drive letter of an absolute path is not included.
+fn path_components(path: &[u8]) -> Vec<&[u8]> {
+    let (root, path) = if path_is_windows(path) {
+        (true, &path[3..])
+    } else {
+        (path_is_absolute(path), path)
+    };
+    let mut components: Vec<&[u8]> = Vec::new();
+    for component in path.split(|&c| c == b'/' || c == b'\\') {
+        match component {
+            b"" | b"." => {}
+            b".." => match components.last() {
+                Some(&last) if last != b".." => {
+                    components.pop();
+                }
+                // `..` at the root is the root.
+                None if root => {}
+                _ => components.push(component),
+            },
+            _ => components.push(component),
+        }
+    }
+    components
+}
+
+/// Remove redundant separators and `.` and `..` components from a path.
+fn path_normalize(path: &[u8]) -> Vec<u8> {
+    let (mut normalized, separator) = if path_is_windows(path) {
+        (path[..3].to_vec(), path[2])
+    } else if path_is_absolute(path) {
+        (path[..1].to_vec(), path[0])
+    } else {
+        (Vec::new(), b'/')
+    };
+    for (i, component) in path_components(path).into_iter().enumerate() {
+        if i != 0 {
+            normalized.push(separator);
+        }
+        normalized.extend_from_slice(component);
+    }
+    normalized
+}
+
+/// Return true if the trailing components of `path` are the components of
+/// `suffix`.
+fn path_ends_with(path: &[u8], suffix: &[u8]) -> bool {
+    let path = path_components(path);
+    let suffix = path_components(suffix);
+    !suffix.is_empty() && path.ends_with(&suffix)
+}
+
+/// Select the best breakpoint locations from the rows of a `LineTable` that
+/// match a line.
+///
+/// Rows that are recommended breakpoint locations (`is_stmt`) are preferred.
+/// The matches are then split into runs of contiguous addresses, and within
+/// each run the rows that are the end of a function prologue are preferred.
+/// The prologue preference can't be applied to a whole sequence, because
+/// compilers normally emit a single sequence for all of the functions in a
+/// section, and the line may occur in more than one of them.
+fn select_line_ranges(matches: &[LineMatch]) -> Vec<Range> {
+    let is_stmt = matches.iter().any(|m| m.is_stmt);
+    let mut ranges = Vec::with_capacity(matches.len());
+    let mut start = 0;
+    while start < matches.len() {
+        let mut end = start + 1;
+        let mut run_end = matches[start].range.end;
+        while end < matches.len() && matches[end].range.begin <= run_end {
+            run_end = cmp::max(run_end, matches[end].range.end);
+            end += 1;
+        }
+        let run = &matches[start..end];
+        let prologue_end = run
+            .iter()
+            .any(|m| m.prologue_end && (m.is_stmt || !is_stmt));
+        ranges.extend(
+            run.iter()
+                .filter(|m| (m.is_stmt || !is_stmt) && (m.prologue_end || !prologue_end))
+                .map(|m| m.range),
+        );
+        start = end;
+    }
+    ranges
+}
+
+/// Sort the ranges and merge adjacent or overlapping ranges.
+fn merge_ranges(mut ranges: Vec<Range>) -> Vec<Range> {
+    ranges.sort_by_key(|range| (range.begin, range.end));
+    let mut result: Vec<Range> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        if let Some(last) = result.last_mut() {
+            if range.begin <= last.end {
+                if range.end > last.end {
+                    last.end = range.end;
+                }
+                continue;
+            }
+        }
+        result.push(range);
+    }
+    result
+}
+
 #[cfg(test)]
 mod tests {
     extern crate test_assembler;
 
     use self::test_assembler::{Endian, Label, LabelMaker, Section};
     use super::*;
+    use common::{
+        DebugAbbrevOffset, DebugAddrBase, DebugInfoOffset, DebugLocListsBase, DebugRngListsBase,
+        DebugStrOffsetsBase, UnitSectionOffset,
+    };
     use constants;
     use endianity::LittleEndian;
-    use read::{EndianSlice, Error};
+    use read::{Abbreviations, EndianSlice, Error, UnitHeader, UnitType};
     use std::u8;
     use test_util::GimliSectionMethods;
+    use Arc;
 
     #[test]
     fn test_parse_debug_line_32_ok() {
@@ -2978,4 +3500,406 @@ mod tests {
             assert_eq!(header.file(0), Some(&expected_file_names[0]));
         }
     }
+
+    // Return a unit with the given name and compilation directory, for
+    // building a `LineTable`.
+    fn line_table_unit<'a>(
+        comp_dir: EndianSlice<'a, LittleEndian>,
+        comp_name: EndianSlice<'a, LittleEndian>,
+    ) -> Unit<EndianSlice<'a, LittleEndian>> {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        Unit {
+            offset: UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0)),
+            header: UnitHeader::new(
+                encoding,
+                0,
+                UnitType::Compilation,
+                DebugAbbrevOffset(0),
+                EndianSlice::new(&[], LittleEndian),
+            ),
+            abbreviations: Arc::new(Abbreviations::default()),
+            name: Some(comp_name),
+            comp_dir: Some(comp_dir),
+            low_pc: 0,
+            str_offsets_base: DebugStrOffsetsBase(0),
+            addr_base: DebugAddrBase(0),
+            loclists_base: DebugLocListsBase(0),
+            rnglists_base: DebugRngListsBase(0),
+            dwo_id: None,
+            dwo_name: None,
+            line_program: None,
+        }
+    }
+
+    #[test]
+    fn test_line_table() {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let header_length = Label::new();
+        let header_start = Label::new();
+        let header_end = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Little)
+            .L32(&length).mark(&start).L16(4).L32(&header_length).mark(&header_start)
+            // Minimum instruction length, maximum operations, default is_stmt.
+            .D8(1).D8(1).D8(1)
+            // Line base, line range, opcode base.
+            .D8(-5i8 as u8).D8(14).D8(13)
+            .append_bytes(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+            .append_bytes(b"inc\0").append_bytes(b"/abs\0").D8(0)
+            .append_bytes(b"a.c\0").uleb(0).uleb(0).uleb(0)
+            .append_bytes(b"b.h\0").uleb(1).uleb(0).uleb(0)
+            .append_bytes(b"c.h\0").uleb(2).uleb(0).uleb(0)
+            .D8(0)
+            .mark(&header_end)
+            // First sequence.
+            .D8(0).uleb(9).D8(constants::DW_LNE_set_address.0).L64(0x2000)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_set_file.0).uleb(2)
+            .D8(constants::DW_LNS_advance_line.0).sleb(4)
+            .D8(constants::DW_LNS_set_column.0).uleb(2)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(8)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_line.0).sleb(1)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(8)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_line.0).sleb(1)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(0x10)
+            .D8(0).uleb(1).D8(constants::DW_LNE_end_sequence.0)
+            // Second sequence, at a lower address.
+            .D8(0).uleb(9).D8(constants::DW_LNE_set_address.0).L64(0x1000)
+            .D8(constants::DW_LNS_set_file.0).uleb(3)
+            .D8(constants::DW_LNS_advance_line.0).sleb(2)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(0).uleb(1).D8(constants::DW_LNE_end_sequence.0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        header_length.set_const((&header_end - &header_start) as u64);
+        let section = section.get_contents().unwrap();
+
+        let comp_dir = EndianSlice::new(b"/src", LittleEndian);
+        let comp_name = EndianSlice::new(b"a.c", LittleEndian);
+        let debug_line = DebugLine::new(&section, LittleEndian);
+        let program = debug_line
+            .program(DebugLineOffset(0), 8, Some(comp_dir), Some(comp_name))
+            .unwrap();
+
+        let dwarf = Dwarf::default();
+        let unit = line_table_unit(comp_dir, comp_name);
+        let table = LineTable::new(&dwarf, &unit, program).unwrap();
+
+        assert_eq!(table.file(0), Some(&b"/src/a.c"[..]));
+        assert_eq!(table.file(1), Some(&b"/src/a.c"[..]));
+        assert_eq!(table.file(2), Some(&b"/src/inc/b.h"[..]));
+        assert_eq!(table.file(3), Some(&b"/abs/c.h"[..]));
+        assert_eq!(table.file(4), None);
+
+        let a = LineLocation {
+            file: Some(&b"/src/a.c"[..]),
+            line: Some(1),
+            column: None,
+        };
+        let b = LineLocation {
+            file: Some(&b"/src/inc/b.h"[..]),
+            line: Some(5),
+            column: Some(2),
+        };
+        let b7 = LineLocation { line: Some(7), ..b };
+        let c = LineLocation {
+            file: Some(&b"/abs/c.h"[..]),
+            line: Some(3),
+            column: None,
+        };
+        assert_eq!(table.find_location(0xfff), None);
+        assert_eq!(table.find_location(0x1000), Some(c));
+        assert_eq!(table.find_location(0x1003), Some(c));
+        assert_eq!(table.find_location(0x1004), None);
+        assert_eq!(table.find_location(0x2000), Some(a));
+        assert_eq!(table.find_location(0x2007), Some(a));
+        assert_eq!(table.find_location(0x2008), Some(b));
+        assert_eq!(table.find_location(0x2010), Some(b7));
+        assert_eq!(table.find_location(0x201f), Some(b7));
+        assert_eq!(table.find_location(0x2020), None);
+
+        assert_eq!(
+            table
+                .find_location_range(0x1002, 0x2009)
+                .collect::<Vec<_>>(),
+            vec![(0x1000, 4, c), (0x2000, 8, a), (0x2008, 8, b)]
+        );
+        assert_eq!(
+            table
+                .find_location_range(0x2010, 0x3000)
+                .collect::<Vec<_>>(),
+            vec![(0x2010, 0x10, b7)]
+        );
+        assert_eq!(table.find_location_range(0x1004, 0x2000).next(), None);
+        assert_eq!(table.find_location_range(0, 0x1000).next(), None);
+    }
+
+    #[test]
+    fn test_line_table_find_line_ranges() {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let header_length = Label::new();
+        let header_start = Label::new();
+        let header_end = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Little)
+            .L32(&length).mark(&start).L16(4).L32(&header_length).mark(&header_start)
+            // Minimum instruction length, maximum operations, default is_stmt.
+            .D8(1).D8(1).D8(1)
+            // Line base, line range, opcode base.
+            .D8(-5i8 as u8).D8(14).D8(13)
+            .append_bytes(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+            .append_bytes(b"src\0").D8(0)
+            .append_bytes(b"./src/../src/a.c\0").uleb(0).uleb(0).uleb(0)
+            .append_bytes(b"b.h\0").uleb(1).uleb(0).uleb(0)
+            .D8(0)
+            .mark(&header_end)
+            // 0x1000: a.c:10
+            .D8(0).uleb(9).D8(constants::DW_LNE_set_address.0).L64(0x1000)
+            .D8(constants::DW_LNS_advance_line.0).sleb(9)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1004: a.c:11, prologue end
+            .D8(constants::DW_LNS_set_prologue_end.0)
+            .D8(constants::DW_LNS_advance_line.0).sleb(1)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1008: a.c:12
+            .D8(constants::DW_LNS_advance_line.0).sleb(1)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x100c: a.c:11, not a statement
+            .D8(constants::DW_LNS_negate_stmt.0)
+            .D8(constants::DW_LNS_advance_line.0).sleb(-1)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1010: a.c:15
+            .D8(constants::DW_LNS_negate_stmt.0)
+            .D8(constants::DW_LNS_advance_line.0).sleb(4)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1014: a.c:11
+            .D8(constants::DW_LNS_advance_line.0).sleb(-4)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1018: b.h:1
+            .D8(constants::DW_LNS_set_file.0).uleb(2)
+            .D8(constants::DW_LNS_advance_line.0).sleb(-10)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(0).uleb(1).D8(constants::DW_LNE_end_sequence.0)
+            // 0x2000: a.c:11, in another sequence
+            .D8(0).uleb(9).D8(constants::DW_LNE_set_address.0).L64(0x2000)
+            .D8(constants::DW_LNS_advance_line.0).sleb(10)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(0).uleb(1).D8(constants::DW_LNE_end_sequence.0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        header_length.set_const((&header_end - &header_start) as u64);
+        let section = section.get_contents().unwrap();
+
+        let comp_dir = EndianSlice::new(b"/home/x", LittleEndian);
+        let comp_name = EndianSlice::new(b"src/a.c", LittleEndian);
+        let debug_line = DebugLine::new(&section, LittleEndian);
+        let program = debug_line
+            .program(DebugLineOffset(0), 8, Some(comp_dir), Some(comp_name))
+            .unwrap();
+        let dwarf = Dwarf::default();
+        let unit = line_table_unit(comp_dir, comp_name);
+        let table = LineTable::new(&dwarf, &unit, program).unwrap();
+
+        assert_eq!(table.file(1), Some(&b"/home/x/src/a.c"[..]));
+        assert_eq!(table.file(2), Some(&b"/home/x/src/b.h"[..]));
+
+        let range = |begin, end| vec![Range { begin, end }];
+        assert_eq!(table.find_line_ranges(b"a.c", 10), range(0x1000, 0x1004));
+        assert_eq!(
+            table.find_line_ranges(b"src/a.c", 10),
+            range(0x1000, 0x1004)
+        );
+        assert_eq!(
+            table.find_line_ranges(b"/home/x/src/a.c", 10),
+            range(0x1000, 0x1004)
+        );
+        assert_eq!(table.find_line_ranges(b"rc/a.c", 10), Vec::new());
+        // The prologue end only affects the run of addresses that contains it,
+        // and the row that is not a statement is never used.
+        assert_eq!(
+            table.find_line_ranges(b"a.c", 11),
+            vec![
+                Range {
+                    begin: 0x1004,
+                    end: 0x1008,
+                },
+                Range {
+                    begin: 0x1014,
+                    end: 0x1018,
+                },
+                Range {
+                    begin: 0x2000,
+                    end: 0x2004,
+                },
+            ]
+        );
+        assert_eq!(table.find_line_ranges(b"a.c", 12), range(0x1008, 0x100c));
+        assert_eq!(table.find_line_ranges(b"a.c", 13), range(0x1010, 0x1014));
+        assert_eq!(table.find_line_ranges(b"a.c", 1), range(0x1000, 0x1004));
+        assert_eq!(table.find_line_ranges(b"a.c", 16), Vec::new());
+        assert_eq!(table.find_line_ranges(b"b.h", 1), range(0x1018, 0x101c));
+
+        let files = table.matching_files(b"a.c");
+        let mut matches = table.line_matches(&files, 11);
+        matches.retain(|m| !m.prologue_end && m.range.begin < 0x2000);
+        assert_eq!(select_line_ranges(&matches), range(0x1014, 0x1018));
+        let mut matches = table.line_matches(&files, 11);
+        matches.retain(|m| !m.is_stmt);
+        assert_eq!(select_line_ranges(&matches), range(0x100c, 0x1010));
+
+        // Ranges from multiple tables are merged.
+        let tables = [table.clone(), table];
+        assert_eq!(
+            LineTable::find_all_line_ranges(&tables, b"a.c", 12),
+            range(0x1008, 0x100c)
+        );
+        assert_eq!(
+            LineTable::find_all_line_ranges(&tables, b"a.c", 13),
+            range(0x1010, 0x1014)
+        );
+        assert_eq!(
+            LineTable::find_all_line_ranges(&tables[..0], b"a.c", 13),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    fn test_line_table_find_line_ranges_runs() {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let header_length = Label::new();
+        let header_start = Label::new();
+        let header_end = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Little)
+            .L32(&length).mark(&start).L16(4).L32(&header_length).mark(&header_start)
+            // Minimum instruction length, maximum operations, default is_stmt.
+            .D8(1).D8(1).D8(1)
+            // Line base, line range, opcode base.
+            .D8(-5i8 as u8).D8(14).D8(13)
+            .append_bytes(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+            .D8(0)
+            .append_bytes(b"a.c\0").uleb(0).uleb(0).uleb(0)
+            .D8(0)
+            .mark(&header_end)
+            // 0x1000: a.c:10
+            .D8(0).uleb(9).D8(constants::DW_LNE_set_address.0).L64(0x1000)
+            .D8(constants::DW_LNS_advance_line.0).sleb(9)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1004: a.c:10, prologue end
+            .D8(constants::DW_LNS_set_prologue_end.0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1008: a.c:20, replaced by a.c:22 at the same address
+            .D8(constants::DW_LNS_advance_line.0).sleb(10)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_line.0).sleb(2)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x100c: a.c:21
+            .D8(constants::DW_LNS_advance_line.0).sleb(-1)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            // 0x1010: a.c:10, a second copy in the same sequence
+            .D8(constants::DW_LNS_advance_line.0).sleb(-11)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(4)
+            .D8(0).uleb(1).D8(constants::DW_LNE_end_sequence.0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        header_length.set_const((&header_end - &header_start) as u64);
+        let section = section.get_contents().unwrap();
+
+        let comp_dir = EndianSlice::new(b"/home/x", LittleEndian);
+        let comp_name = EndianSlice::new(b"a.c", LittleEndian);
+        let debug_line = DebugLine::new(&section, LittleEndian);
+        let program = debug_line
+            .program(DebugLineOffset(0), 8, Some(comp_dir), Some(comp_name))
+            .unwrap();
+        let dwarf = Dwarf::default();
+        let unit = line_table_unit(comp_dir, comp_name);
+        let table = LineTable::new(&dwarf, &unit, program).unwrap();
+
+        let range = |begin, end| vec![Range { begin, end }];
+        // The prologue end in the first copy doesn't affect the second copy.
+        assert_eq!(
+            table.find_line_ranges(b"a.c", 10),
+            vec![
+                Range {
+                    begin: 0x1004,
+                    end: 0x1008,
+                },
+                Range {
+                    begin: 0x1010,
+                    end: 0x1014,
+                },
+            ]
+        );
+        // A row that is replaced by a later row at the same address is still
+        // found for its line.
+        assert_eq!(table.find_line_ranges(b"a.c", 20), range(0x1008, 0x100c));
+        assert_eq!(table.find_line_ranges(b"a.c", 22), range(0x1008, 0x100c));
+        assert_eq!(table.find_line_ranges(b"a.c", 21), range(0x100c, 0x1010));
+
+        let location = |line| LineLocation {
+            file: Some(&b"/home/x/a.c"[..]),
+            line: Some(line),
+            column: None,
+        };
+        assert_eq!(table.find_location(0x1008), Some(location(22)));
+        assert_eq!(
+            table
+                .find_location_range(0x1004, 0x1010)
+                .collect::<Vec<_>>(),
+            vec![
+                (0x1004, 4, location(10)),
+                (0x1008, 4, location(22)),
+                (0x100c, 4, location(21)),
+            ]
+        );
+        assert_eq!(
+            table
+                .find_location_range(0x1008, 0x100c)
+                .collect::<Vec<_>>(),
+            vec![(0x1008, 4, location(22))]
+        );
+    }
+
+    #[test]
+    fn test_path_normalize() {
+        for &(path, expect) in &[
+            (&b"/a/./b/../c"[..], &b"/a/c"[..]),
+            (b"/a//b/", b"/a/b"),
+            (b"/../a", b"/a"),
+            (b"a/../../b", b"../b"),
+            (b"./a", b"a"),
+            (b"C:\\a\\..\\b", b"C:\\b"),
+            (b"C:/a/./b", b"C:/a/b"),
+            (b"\\a\\b", b"\\a\\b"),
+        ] {
+            assert_eq!(path_normalize(path), expect);
+        }
+    }
 }
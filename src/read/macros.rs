@@ -0,0 +1,696 @@
+use fallible_iterator::FallibleIterator;
+
+use common::{
+    DebugLineOffset, DebugMacinfoOffset, DebugMacroOffset, DebugStrOffset, DebugStrOffsetsIndex,
+    Format,
+};
+use constants;
+use endianity::Endianity;
+use read::{EndianSlice, Error, Reader, ReaderOffset, Result, Section};
+
+/// The `DebugMacinfo` struct represents the macro information
+/// found in the `.debug_macinfo` section.
+///
+/// This section is used by DWARF versions 2 to 4.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DebugMacinfo<R: Reader> {
+    section: R,
+}
+
+impl<'input, Endian> DebugMacinfo<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugMacinfo` instance from the data in the `.debug_macinfo`
+    /// section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_macinfo` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugMacinfo, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_macinfo_section_somehow = || &buf;
+    /// let debug_macinfo = DebugMacinfo::new(read_debug_macinfo_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(section, endian))
+    }
+}
+
+impl<R: Reader> DebugMacinfo<R> {
+    /// Iterate over the macro information entries at the given offset.
+    ///
+    /// The offset is usually the value of the `DW_AT_macro_info` attribute
+    /// of a compilation unit.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn get_macinfo(&self, offset: DebugMacinfoOffset<R::Offset>) -> Result<MacroIter<R>> {
+        let mut input = self.section.clone();
+        input.skip(offset.0)?;
+        let mut opcode_operands = input.clone();
+        opcode_operands.empty();
+        Ok(MacroIter {
+            input,
+            format: Format::Dwarf32,
+            is_macro: false,
+            opcode_operands,
+        })
+    }
+}
+
+impl<R: Reader> Section<R> for DebugMacinfo<R> {
+    fn section_name() -> &'static str {
+        ".debug_macinfo"
+    }
+}
+
+impl<R: Reader> From<R> for DebugMacinfo<R> {
+    fn from(section: R) -> Self {
+        DebugMacinfo { section }
+    }
+}
+
+/// The `DebugMacro` struct represents the macro information
+/// found in the `.debug_macro` section.
+///
+/// This section is used by DWARF version 5, and also as a GNU extension
+/// to DWARF version 4.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DebugMacro<R: Reader> {
+    section: R,
+}
+
+impl<'input, Endian> DebugMacro<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugMacro` instance from the data in the `.debug_macro`
+    /// section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_macro` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugMacro, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_macro_section_somehow = || &buf;
+    /// let debug_macro = DebugMacro::new(read_debug_macro_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(section, endian))
+    }
+}
+
+impl<R: Reader> DebugMacro<R> {
+    /// Parse the header of the macro unit at the given offset.
+    ///
+    /// The offset is usually the value of the `DW_AT_macros` attribute
+    /// of a compilation unit, or the operand of an import entry.
+    pub fn header(&self, offset: DebugMacroOffset<R::Offset>) -> Result<MacroUnitHeader<R>> {
+        let mut input = self.section.clone();
+        input.skip(offset.0)?;
+        MacroUnitHeader::parse(&mut input)
+    }
+
+    /// Iterate over the entries of the macro unit at the given offset.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn get_macros(&self, offset: DebugMacroOffset<R::Offset>) -> Result<MacroIter<R>> {
+        self.header(offset).map(|header| header.entries())
+    }
+}
+
+impl<R: Reader> Section<R> for DebugMacro<R> {
+    fn section_name() -> &'static str {
+        ".debug_macro"
+    }
+}
+
+impl<R: Reader> From<R> for DebugMacro<R> {
+    fn from(section: R) -> Self {
+        DebugMacro { section }
+    }
+}
+
+/// The header of a macro unit in the `.debug_macro` section.
+#[derive(Debug, Clone)]
+pub struct MacroUnitHeader<R: Reader> {
+    version: u16,
+    format: Format,
+    debug_line_offset: Option<DebugLineOffset<R::Offset>>,
+    opcode_operands: R,
+    entries: R,
+}
+
+impl<R: Reader> MacroUnitHeader<R> {
+    fn parse(input: &mut R) -> Result<Self> {
+        let version = input.read_u16()?;
+        if version != 4 && version != 5 {
+            return Err(Error::UnknownVersion(u64::from(version)));
+        }
+
+        let flags = input.read_u8()?;
+        let format = if flags & 0x1 != 0 {
+            Format::Dwarf64
+        } else {
+            Format::Dwarf32
+        };
+        let debug_line_offset = if flags & 0x2 != 0 {
+            Some(DebugLineOffset(input.read_offset(format)?))
+        } else {
+            None
+        };
+
+        let mut opcode_operands = input.clone();
+        if flags & 0x4 != 0 {
+            let count = input.read_u8()?;
+            for _ in 0..count {
+                input.read_u8()?;
+                let operand_count = input.read_uleb128().and_then(R::Offset::from_u64)?;
+                input.skip(operand_count)?;
+            }
+        }
+        let len = input.offset_from(&opcode_operands);
+        opcode_operands.truncate(len)?;
+        if flags & 0x4 != 0 {
+            // Skip the opcode count.
+            opcode_operands.read_u8()?;
+        }
+
+        Ok(MacroUnitHeader {
+            version,
+            format,
+            debug_line_offset,
+            opcode_operands,
+            entries: input.clone(),
+        })
+    }
+
+    /// Return the version of this macro unit.
+    #[inline]
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Return the format of the section offsets in this macro unit.
+    #[inline]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Return the offset of the line number program that is used for the
+    /// file indices in this macro unit, if any.
+    #[inline]
+    pub fn debug_line_offset(&self) -> Option<DebugLineOffset<R::Offset>> {
+        self.debug_line_offset
+    }
+
+    /// Iterate over the entries of this macro unit.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn entries(&self) -> MacroIter<R> {
+        MacroIter {
+            input: self.entries.clone(),
+            format: self.format,
+            is_macro: true,
+            opcode_operands: self.opcode_operands.clone(),
+        }
+    }
+}
+
+/// An iterator over the entries in the `.debug_macinfo` or `.debug_macro` sections.
+#[derive(Debug, Clone)]
+pub struct MacroIter<R: Reader> {
+    input: R,
+    format: Format,
+    is_macro: bool,
+    opcode_operands: R,
+}
+
+impl<R: Reader> MacroIter<R> {
+    /// Advance the iterator to the next entry.
+    ///
+    /// Entries with vendor extension opcodes that are described by the opcode
+    /// operands table of a `.debug_macro` unit are skipped.
+    pub fn next(&mut self) -> Result<Option<MacroEntry<R>>> {
+        loop {
+            if self.input.is_empty() {
+                return Ok(None);
+            }
+
+            match self.parse_entry() {
+                Ok(Some(entry)) => return Ok(Some(entry)),
+                // Either the terminator, or a skipped entry.
+                Ok(None) => {}
+                Err(e) => {
+                    self.input.empty();
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    fn parse_entry(&mut self) -> Result<Option<MacroEntry<R>>> {
+        let opcode = self.input.read_u8()?;
+        if opcode == 0 {
+            self.input.empty();
+            return Ok(None);
+        }
+        if self.is_macro {
+            self.parse_macro(constants::DwMacro(opcode))
+        } else {
+            self.parse_macinfo(constants::DwMacinfo(opcode)).map(Some)
+        }
+    }
+
+    fn parse_macinfo(&mut self, opcode: constants::DwMacinfo) -> Result<MacroEntry<R>> {
+        let input = &mut self.input;
+        let entry = match opcode {
+            constants::DW_MACINFO_define => MacroEntry::Define {
+                line: input.read_uleb128()?,
+                text: MacroString::Direct(input.read_null_terminated_slice()?),
+            },
+            constants::DW_MACINFO_undef => MacroEntry::Undef {
+                line: input.read_uleb128()?,
+                name: MacroString::Direct(input.read_null_terminated_slice()?),
+            },
+            constants::DW_MACINFO_start_file => MacroEntry::StartFile {
+                line: input.read_uleb128()?,
+                file: input.read_uleb128()?,
+            },
+            constants::DW_MACINFO_end_file => MacroEntry::EndFile,
+            constants::DW_MACINFO_vendor_ext => MacroEntry::VendorExt {
+                numeric: input.read_uleb128()?,
+                string: input.read_null_terminated_slice()?,
+            },
+            otherwise => return Err(Error::UnknownMacinfoType(otherwise)),
+        };
+        Ok(entry)
+    }
+
+    fn parse_macro(&mut self, opcode: constants::DwMacro) -> Result<Option<MacroEntry<R>>> {
+        let format = self.format;
+        let input = &mut self.input;
+        let entry = match opcode {
+            constants::DW_MACRO_define => MacroEntry::Define {
+                line: input.read_uleb128()?,
+                text: MacroString::Direct(input.read_null_terminated_slice()?),
+            },
+            constants::DW_MACRO_undef => MacroEntry::Undef {
+                line: input.read_uleb128()?,
+                name: MacroString::Direct(input.read_null_terminated_slice()?),
+            },
+            constants::DW_MACRO_start_file => MacroEntry::StartFile {
+                line: input.read_uleb128()?,
+                file: input.read_uleb128()?,
+            },
+            constants::DW_MACRO_end_file => MacroEntry::EndFile,
+            constants::DW_MACRO_define_strp => MacroEntry::Define {
+                line: input.read_uleb128()?,
+                text: MacroString::StringPointer(DebugStrOffset(input.read_offset(format)?)),
+            },
+            constants::DW_MACRO_undef_strp => MacroEntry::Undef {
+                line: input.read_uleb128()?,
+                name: MacroString::StringPointer(DebugStrOffset(input.read_offset(format)?)),
+            },
+            constants::DW_MACRO_import => MacroEntry::Import {
+                offset: DebugMacroOffset(input.read_offset(format)?),
+            },
+            constants::DW_MACRO_define_sup => MacroEntry::Define {
+                line: input.read_uleb128()?,
+                text: MacroString::Supplementary(DebugStrOffset(input.read_offset(format)?)),
+            },
+            constants::DW_MACRO_undef_sup => MacroEntry::Undef {
+                line: input.read_uleb128()?,
+                name: MacroString::Supplementary(DebugStrOffset(input.read_offset(format)?)),
+            },
+            constants::DW_MACRO_import_sup => MacroEntry::ImportSup {
+                offset: DebugMacroOffset(input.read_offset(format)?),
+            },
+            constants::DW_MACRO_define_strx => MacroEntry::Define {
+                line: input.read_uleb128()?,
+                text: MacroString::IndirectStringPointer(DebugStrOffsetsIndex(
+                    input.read_uleb128().and_then(R::Offset::from_u64)?,
+                )),
+            },
+            constants::DW_MACRO_undef_strx => MacroEntry::Undef {
+                line: input.read_uleb128()?,
+                name: MacroString::IndirectStringPointer(DebugStrOffsetsIndex(
+                    input.read_uleb128().and_then(R::Offset::from_u64)?,
+                )),
+            },
+            otherwise => {
+                skip_operands(input, format, &self.opcode_operands, otherwise)?;
+                return Ok(None);
+            }
+        };
+        Ok(Some(entry))
+    }
+}
+
+impl<R: Reader> FallibleIterator for MacroIter<R> {
+    type Item = MacroEntry<R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        MacroIter::next(self)
+    }
+}
+
+/// Skip the operands of an opcode that is described by the opcode operands table.
+fn skip_operands<R: Reader>(
+    input: &mut R,
+    format: Format,
+    opcode_operands: &R,
+    opcode: constants::DwMacro,
+) -> Result<()> {
+    let mut table = opcode_operands.clone();
+    while !table.is_empty() {
+        let table_opcode = constants::DwMacro(table.read_u8()?);
+        let count = table.read_uleb128()?;
+        if table_opcode != opcode {
+            table.skip(R::Offset::from_u64(count)?)?;
+            continue;
+        }
+        for _ in 0..count {
+            let form = constants::DwForm(table.read_u8()?.into());
+            skip_form(input, format, form)?;
+        }
+        return Ok(());
+    }
+    Err(Error::UnknownMacroOpcode(opcode))
+}
+
+/// Skip a value of one of the forms that may be used in the opcode operands table.
+fn skip_form<R: Reader>(input: &mut R, format: Format, form: constants::DwForm) -> Result<()> {
+    let len = match form {
+        constants::DW_FORM_block => input.read_uleb128().and_then(R::Offset::from_u64)?,
+        constants::DW_FORM_block1 => R::Offset::from_u8(input.read_u8()?),
+        constants::DW_FORM_block2 => R::Offset::from_u16(input.read_u16()?),
+        constants::DW_FORM_block4 => R::Offset::from_u32(input.read_u32()?),
+        constants::DW_FORM_data1 | constants::DW_FORM_flag | constants::DW_FORM_strx1 => {
+            R::Offset::from_u8(1)
+        }
+        constants::DW_FORM_data2 | constants::DW_FORM_strx2 => R::Offset::from_u8(2),
+        constants::DW_FORM_strx3 => R::Offset::from_u8(3),
+        constants::DW_FORM_data4 | constants::DW_FORM_strx4 => R::Offset::from_u8(4),
+        constants::DW_FORM_data8 => R::Offset::from_u8(8),
+        constants::DW_FORM_data16 => R::Offset::from_u8(16),
+        constants::DW_FORM_strp | constants::DW_FORM_line_strp | constants::DW_FORM_sec_offset => {
+            R::Offset::from_u8(format.word_size())
+        }
+        constants::DW_FORM_sdata => {
+            input.read_sleb128()?;
+            return Ok(());
+        }
+        constants::DW_FORM_udata | constants::DW_FORM_strx => {
+            input.read_uleb128()?;
+            return Ok(());
+        }
+        constants::DW_FORM_string => {
+            input.read_null_terminated_slice()?;
+            return Ok(());
+        }
+        _ => return Err(Error::UnknownForm),
+    };
+    input.skip(len)
+}
+
+/// An entry in the `.debug_macinfo` or `.debug_macro` sections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroEntry<R: Reader> {
+    /// A macro definition.
+    Define {
+        /// The line number of the definition.
+        line: u64,
+        /// The name of the macro, followed by its optional parameters and
+        /// definition.
+        text: MacroString<R>,
+    },
+
+    /// The removal of a macro definition.
+    Undef {
+        /// The line number of the removal.
+        line: u64,
+        /// The name of the macro.
+        name: MacroString<R>,
+    },
+
+    /// The start of a new source file inclusion.
+    StartFile {
+        /// The line number of the inclusion directive, or 0 for the
+        /// primary source file.
+        line: u64,
+        /// The index of the file in the line number program.
+        file: u64,
+    },
+
+    /// The end of the current source file inclusion.
+    EndFile,
+
+    /// The inclusion of the entries of another macro unit in the
+    /// `.debug_macro` section.
+    Import {
+        /// The offset of the macro unit.
+        offset: DebugMacroOffset<R::Offset>,
+    },
+
+    /// The inclusion of the entries of a macro unit in the `.debug_macro`
+    /// section of the supplementary object file.
+    ImportSup {
+        /// The offset of the macro unit.
+        offset: DebugMacroOffset<R::Offset>,
+    },
+
+    /// A vendor extension in the `.debug_macinfo` section.
+    VendorExt {
+        /// A vendor defined constant.
+        numeric: u64,
+        /// A vendor defined string.
+        string: R,
+    },
+}
+
+/// A string operand of a macro entry.
+///
+/// Use `Dwarf::macro_string` to obtain the string data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroString<R: Reader> {
+    /// The string is stored inline in the entry.
+    Direct(R),
+
+    /// An offset into the `.debug_str` section.
+    StringPointer(DebugStrOffset<R::Offset>),
+
+    /// An index into the `.debug_str_offsets` section of the unit.
+    IndirectStringPointer(DebugStrOffsetsIndex<R::Offset>),
+
+    /// An offset into the `.debug_str` section of the supplementary object file.
+    Supplementary(DebugStrOffset<R::Offset>),
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Section};
+    use super::*;
+    use test_util::GimliSectionMethods;
+    use LittleEndian;
+
+    #[test]
+    fn test_macinfo() {
+        let section = Section::with_endian(Endian::Little)
+            // Some padding before the entries.
+            .D8(0xaa)
+            .D8(constants::DW_MACINFO_start_file.0)
+            .uleb(0)
+            .uleb(1)
+            .D8(constants::DW_MACINFO_define.0)
+            .uleb(1)
+            .append_bytes(b"FOO 1\0")
+            .D8(constants::DW_MACINFO_vendor_ext.0)
+            .uleb(2)
+            .append_bytes(b"vendor\0")
+            .D8(constants::DW_MACINFO_undef.0)
+            .uleb(3)
+            .append_bytes(b"FOO\0")
+            .D8(constants::DW_MACINFO_end_file.0)
+            .D8(0)
+            // Entries after the terminator must not be read.
+            .D8(constants::DW_MACINFO_end_file.0);
+        let buf = section.get_contents().unwrap();
+        let debug_macinfo = DebugMacinfo::new(&buf, LittleEndian);
+        let mut entries = debug_macinfo.get_macinfo(DebugMacinfoOffset(1)).unwrap();
+
+        let slice = |s: &'static [u8]| EndianSlice::new(s, LittleEndian);
+        assert_eq!(
+            entries.next(),
+            Ok(Some(MacroEntry::StartFile { line: 0, file: 1 }))
+        );
+        assert_eq!(
+            entries.next(),
+            Ok(Some(MacroEntry::Define {
+                line: 1,
+                text: MacroString::Direct(slice(b"FOO 1")),
+            }))
+        );
+        assert_eq!(
+            entries.next(),
+            Ok(Some(MacroEntry::VendorExt {
+                numeric: 2,
+                string: slice(b"vendor"),
+            }))
+        );
+        assert_eq!(
+            entries.next(),
+            Ok(Some(MacroEntry::Undef {
+                line: 3,
+                name: MacroString::Direct(slice(b"FOO")),
+            }))
+        );
+        assert_eq!(entries.next(), Ok(Some(MacroEntry::EndFile)));
+        assert_eq!(entries.next(), Ok(None));
+        assert_eq!(entries.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_macinfo_unknown_type() {
+        let buf = [0x10, 0x00];
+        let debug_macinfo = DebugMacinfo::new(&buf, LittleEndian);
+        let mut entries = debug_macinfo.get_macinfo(DebugMacinfoOffset(0)).unwrap();
+        assert_eq!(
+            entries.next(),
+            Err(Error::UnknownMacinfoType(constants::DwMacinfo(0x10)))
+        );
+        assert_eq!(entries.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_macro() {
+        for &format in &[Format::Dwarf32, Format::Dwarf64] {
+            test_macro_format(format);
+        }
+    }
+
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn test_macro_format(format: Format) {
+        let (flags, size) = match format {
+            Format::Dwarf32 => (0x6, 4),
+            Format::Dwarf64 => (0x7, 8),
+        };
+        let section = Section::with_endian(Endian::Little)
+            // Some padding before the unit.
+            .D8(0xaa)
+            .D16(5)
+            .D8(flags)
+            .word(size, 0x1234)
+            // The opcode operands table.
+            .D8(2)
+            .D8(0xe0).uleb(2).D8(constants::DW_FORM_udata.0 as u8).D8(constants::DW_FORM_string.0 as u8)
+            .D8(0xe1).uleb(1).D8(constants::DW_FORM_block1.0 as u8)
+            // The entries.
+            .D8(constants::DW_MACRO_start_file.0).uleb(0).uleb(1)
+            .D8(constants::DW_MACRO_define.0).uleb(1).append_bytes(b"FOO 1\0")
+            .D8(0xe1).D8(3).append_bytes(&[1, 2, 3])
+            .D8(constants::DW_MACRO_define_strp.0).uleb(2).word(size, 0x10)
+            .D8(0xe0).uleb(0x100).append_bytes(b"skip\0")
+            .D8(constants::DW_MACRO_undef_strp.0).uleb(3).word(size, 0x20)
+            .D8(constants::DW_MACRO_define_strx.0).uleb(4).uleb(5)
+            .D8(constants::DW_MACRO_undef_strx.0).uleb(5).uleb(6)
+            .D8(constants::DW_MACRO_define_sup.0).uleb(6).word(size, 0x30)
+            .D8(constants::DW_MACRO_undef_sup.0).uleb(7).word(size, 0x40)
+            .D8(constants::DW_MACRO_import.0).word(size, 0x50)
+            .D8(constants::DW_MACRO_import_sup.0).word(size, 0x60)
+            .D8(constants::DW_MACRO_undef.0).uleb(8).append_bytes(b"FOO\0")
+            .D8(constants::DW_MACRO_end_file.0)
+            .D8(0);
+        let buf = section.get_contents().unwrap();
+        let debug_macro = DebugMacro::new(&buf, LittleEndian);
+        let offset = DebugMacroOffset(1);
+
+        let header = debug_macro.header(offset).unwrap();
+        assert_eq!(header.version(), 5);
+        assert_eq!(header.format(), format);
+        assert_eq!(header.debug_line_offset(), Some(DebugLineOffset(0x1234)));
+
+        let slice = |s: &'static [u8]| EndianSlice::new(s, LittleEndian);
+        let expect = [
+            MacroEntry::StartFile { line: 0, file: 1 },
+            MacroEntry::Define { line: 1, text: MacroString::Direct(slice(b"FOO 1")) },
+            MacroEntry::Define {
+                line: 2,
+                text: MacroString::StringPointer(DebugStrOffset(0x10)),
+            },
+            MacroEntry::Undef {
+                line: 3,
+                name: MacroString::StringPointer(DebugStrOffset(0x20)),
+            },
+            MacroEntry::Define {
+                line: 4,
+                text: MacroString::IndirectStringPointer(DebugStrOffsetsIndex(5)),
+            },
+            MacroEntry::Undef {
+                line: 5,
+                name: MacroString::IndirectStringPointer(DebugStrOffsetsIndex(6)),
+            },
+            MacroEntry::Define {
+                line: 6,
+                text: MacroString::Supplementary(DebugStrOffset(0x30)),
+            },
+            MacroEntry::Undef {
+                line: 7,
+                name: MacroString::Supplementary(DebugStrOffset(0x40)),
+            },
+            MacroEntry::Import { offset: DebugMacroOffset(0x50) },
+            MacroEntry::ImportSup { offset: DebugMacroOffset(0x60) },
+            MacroEntry::Undef { line: 8, name: MacroString::Direct(slice(b"FOO")) },
+            MacroEntry::EndFile,
+        ];
+        let mut entries = debug_macro.get_macros(offset).unwrap();
+        for expect in expect.iter() {
+            assert_eq!(entries.next(), Ok(Some(expect.clone())));
+        }
+        assert_eq!(entries.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_macro_header() {
+        let buf = [0x04, 0x00, 0x00, constants::DW_MACRO_end_file.0, 0x00];
+        let debug_macro = DebugMacro::new(&buf, LittleEndian);
+        let header = debug_macro.header(DebugMacroOffset(0)).unwrap();
+        assert_eq!(header.version(), 4);
+        assert_eq!(header.format(), Format::Dwarf32);
+        assert_eq!(header.debug_line_offset(), None);
+        let mut entries = header.entries();
+        assert_eq!(entries.next(), Ok(Some(MacroEntry::EndFile)));
+        assert_eq!(entries.next(), Ok(None));
+
+        let buf = [0x03, 0x00, 0x00];
+        let debug_macro = DebugMacro::new(&buf, LittleEndian);
+        assert_eq!(
+            debug_macro.header(DebugMacroOffset(0)).map(|_| ()),
+            Err(Error::UnknownVersion(3))
+        );
+    }
+
+    #[test]
+    fn test_macro_unknown_opcode() {
+        let buf = [0x05, 0x00, 0x00, 0xe0, 0x00];
+        let debug_macro = DebugMacro::new(&buf, LittleEndian);
+        let mut entries = debug_macro.get_macros(DebugMacroOffset(0)).unwrap();
+        assert_eq!(
+            entries.next(),
+            Err(Error::UnknownMacroOpcode(constants::DwMacro(0xe0)))
+        );
+        assert_eq!(entries.next(), Ok(None));
+    }
+}
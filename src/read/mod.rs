@@ -32,6 +32,9 @@ pub use self::abbrev::*;
 mod aranges;
 pub use self::aranges::*;
 
+mod index;
+pub use self::index::*;
+
 mod line;
 pub use self::line::*;
 
@@ -40,9 +43,18 @@ pub use self::loclists::*;
 
 mod lookup;
 
+mod macros;
+pub use self::macros::*;
+
+mod names;
+pub use self::names::*;
+
 mod op;
 pub use self::op::*;
 
+mod op_text;
+pub use self::op_text::*;
+
 mod pubnames;
 pub use self::pubnames::*;
 
@@ -55,9 +67,18 @@ pub use self::rnglists::*;
 mod str;
 pub use self::str::*;
 
+mod symbolize;
+pub use self::symbolize::*;
+
+mod types;
+pub use self::types::*;
+
 mod unit;
 pub use self::unit::*;
 
+mod unwind;
+pub use self::unwind::*;
+
 mod value;
 pub use self::value::*;
 
@@ -150,6 +171,9 @@ pub enum Error {
     /// An unrecognized operation was found while parsing a DWARF
     /// expression.
     InvalidExpression(constants::DwOp),
+    /// The textual form of a DWARF expression could not be parsed. The
+    /// value is the byte position in the text at which parsing failed.
+    InvalidExpressionText(usize),
     /// The expression had a piece followed by an expression
     /// terminator without a piece.
     InvalidPiece,
@@ -217,6 +241,46 @@ pub enum Error {
     MissingFileEntryFormatPath,
     /// Expected an attribute value to be a string form.
     ExpectedStringAttributeValue,
+    /// The `.dwp` index has an invalid number of slots.
+    InvalidIndexSlotCount,
+    /// The `.dwp` index has too many sections.
+    InvalidIndexSectionCount,
+    /// The `.dwp` index section id is unknown.
+    UnknownIndexSection,
+    /// The `.dwp` index row is invalid.
+    InvalidIndexRow,
+    /// An unknown entry type in the `.debug_macinfo` section.
+    UnknownMacinfoType(constants::DwMacinfo),
+    /// An unknown opcode in the `.debug_macro` section.
+    UnknownMacroOpcode(constants::DwMacro),
+    /// A `DW_FORM_ref_sig8` reference to a type unit that could not be found.
+    MissingTypeUnit,
+    /// An `EvaluationContext` did not provide the memory at the given address.
+    UnavailableMemory(u64),
+    /// An `EvaluationContext` did not provide the value of the given register.
+    UnavailableRegister(Register),
+    /// An `EvaluationContext` did not provide the frame base.
+    UnavailableFrameBase,
+    /// An `EvaluationContext` did not provide the TLS value for the given offset.
+    UnavailableTls(u64),
+    /// An `EvaluationContext` did not provide the CFA.
+    UnavailableCallFrameCfa,
+    /// An `EvaluationContext` did not provide the location of a DIE.
+    UnavailableAtLocation,
+    /// An `EvaluationContext` did not provide an entry value.
+    UnavailableEntryValue,
+    /// An `EvaluationContext` did not provide the value of a parameter.
+    UnavailableParameterRef,
+    /// An `EvaluationContext` did not relocate the given address.
+    UnavailableRelocatedAddress(u64),
+    /// An `EvaluationContext` did not provide an address from the `.debug_addr` section.
+    UnavailableIndexedAddress,
+    /// An `EvaluationContext` did not provide the value type of a base type DIE.
+    UnavailableBaseType,
+    /// A piece of a value has no register or memory location that can be written.
+    UnwritablePiece,
+    /// A CFI expression did not evaluate to a single address or value.
+    UnsupportedUnwindExpression,
 }
 
 impl fmt::Display for Error {
@@ -295,6 +359,7 @@ impl Error {
             Error::NotEnoughStackItems => "Not enough items on stack when evaluating expression",
             Error::TooManyIterations => "Too many iterations to evaluate DWARF expression",
             Error::InvalidExpression(_) => "Invalid opcode in DWARF expression",
+            Error::InvalidExpressionText(_) => "Invalid text for DWARF expression",
             Error::InvalidPiece => {
                 "DWARF expression has piece followed by non-piece expression at end"
             }
@@ -357,6 +422,48 @@ impl Error {
             Error::ExpectedStringAttributeValue => {
                 "Expected an attribute value to be a string form."
             }
+            Error::InvalidIndexSlotCount => "The `.dwp` index has an invalid number of slots.",
+            Error::InvalidIndexSectionCount => "The `.dwp` index has too many sections.",
+            Error::UnknownIndexSection => "The `.dwp` index section id is unknown.",
+            Error::InvalidIndexRow => "The `.dwp` index row is invalid.",
+            Error::UnknownMacinfoType(_) => {
+                "An unknown entry type in the `.debug_macinfo` section."
+            }
+            Error::UnknownMacroOpcode(_) => "An unknown opcode in the `.debug_macro` section.",
+            Error::MissingTypeUnit => {
+                "A `DW_FORM_ref_sig8` reference to a type unit that could not be found."
+            }
+            Error::UnavailableMemory(_) => "The memory required by an expression is unavailable.",
+            Error::UnavailableRegister(_) => {
+                "The register required by an expression is unavailable."
+            }
+            Error::UnavailableFrameBase => {
+                "The frame base required by an expression is unavailable."
+            }
+            Error::UnavailableTls(_) => "The TLS value required by an expression is unavailable.",
+            Error::UnavailableCallFrameCfa => "The CFA required by an expression is unavailable.",
+            Error::UnavailableAtLocation => {
+                "The DIE location required by an expression is unavailable."
+            }
+            Error::UnavailableEntryValue => {
+                "The entry value required by an expression is unavailable."
+            }
+            Error::UnavailableParameterRef => {
+                "The parameter value required by an expression is unavailable."
+            }
+            Error::UnavailableRelocatedAddress(_) => {
+                "The relocated address required by an expression is unavailable."
+            }
+            Error::UnavailableIndexedAddress => {
+                "The indexed address required by an expression is unavailable."
+            }
+            Error::UnavailableBaseType => "The base type required by an expression is unavailable.",
+            Error::UnwritablePiece => {
+                "A piece of a value has no register or memory location that can be written."
+            }
+            Error::UnsupportedUnwindExpression => {
+                "A CFI expression did not evaluate to a single address or value."
+            }
         }
     }
 }
@@ -0,0 +1,905 @@
+use fallible_iterator::FallibleIterator;
+use vec::Vec;
+
+use common::{debug_names_hash, DebugInfoOffset, DebugStrOffset, DebugTypeSignature, Format};
+use constants;
+use endianity::Endianity;
+use read::{
+    AttributeValue, DebugStr, EndianSlice, Error, Reader, ReaderOffset, Result, Section, UnitOffset,
+};
+
+/// The `DebugNames` struct represents the DWARF 5 name index information
+/// found in the `.debug_names` section.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DebugNames<R: Reader> {
+    section: R,
+}
+
+impl<'input, Endian> DebugNames<EndianSlice<'input, Endian>>
+where
+    Endian: Endianity,
+{
+    /// Construct a new `DebugNames` instance from the data in the `.debug_names`
+    /// section.
+    ///
+    /// It is the caller's responsibility to read the `.debug_names` section and
+    /// present it as a `&[u8]` slice. That means using some ELF loader on
+    /// Linux, a Mach-O loader on OSX, etc.
+    ///
+    /// ```
+    /// use gimli::{DebugNames, LittleEndian};
+    ///
+    /// # let buf = [];
+    /// # let read_debug_names_section_somehow = || &buf;
+    /// let debug_names = DebugNames::new(read_debug_names_section_somehow(), LittleEndian);
+    /// ```
+    pub fn new(section: &'input [u8], endian: Endian) -> Self {
+        Self::from(EndianSlice::new(section, endian))
+    }
+}
+
+impl<R: Reader> DebugNames<R> {
+    /// Iterate the name indexes in the `.debug_names` section.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn name_indexes(&self) -> NameIndexIter<R> {
+        NameIndexIter {
+            input: self.section.clone(),
+        }
+    }
+}
+
+impl<R: Reader> Section<R> for DebugNames<R> {
+    fn section_name() -> &'static str {
+        ".debug_names"
+    }
+}
+
+impl<R: Reader> From<R> for DebugNames<R> {
+    fn from(section: R) -> Self {
+        DebugNames { section }
+    }
+}
+
+/// An iterator over the name indexes in the `.debug_names` section.
+#[derive(Debug, Clone)]
+pub struct NameIndexIter<R: Reader> {
+    input: R,
+}
+
+impl<R: Reader> NameIndexIter<R> {
+    /// Advance the iterator to the next name index.
+    pub fn next(&mut self) -> Result<Option<NameIndex<R>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        match NameIndex::parse(&mut self.input) {
+            Ok(index) => Ok(Some(index)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<R: Reader> FallibleIterator for NameIndexIter<R> {
+    type Item = NameIndex<R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        NameIndexIter::next(self)
+    }
+}
+
+/// A type unit referenced by a name index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameIndexTypeUnit<T = usize> {
+    /// A type unit in the `.debug_info` section of this file.
+    Local(DebugInfoOffset<T>),
+    /// A type unit in a separate file, such as a `.dwo` file.
+    Foreign(DebugTypeSignature),
+}
+
+/// A single name index from the `.debug_names` section.
+///
+/// A name index contains the names of the entries in one or more units,
+/// along with a hash table for looking up these names.
+#[derive(Debug, Clone)]
+pub struct NameIndex<R: Reader> {
+    format: Format,
+    version: u16,
+    comp_unit_count: u32,
+    local_type_unit_count: u32,
+    foreign_type_unit_count: u32,
+    bucket_count: u32,
+    name_count: u32,
+    augmentation_string: R,
+    comp_units: R,
+    local_type_units: R,
+    foreign_type_units: R,
+    buckets: R,
+    hashes: R,
+    string_offsets: R,
+    entry_offsets: R,
+    abbreviations: Vec<NameAbbreviation>,
+    entry_pool: R,
+}
+
+impl<R: Reader> NameIndex<R> {
+    fn parse(input: &mut R) -> Result<NameIndex<R>> {
+        let (length, format) = input.read_initial_length()?;
+        let mut rest = input.split(length)?;
+
+        let version = rest.read_u16()?;
+        if version != 5 {
+            return Err(Error::UnknownVersion(u64::from(version)));
+        }
+        let _padding = rest.read_u16()?;
+        let comp_unit_count = rest.read_u32()?;
+        let local_type_unit_count = rest.read_u32()?;
+        let foreign_type_unit_count = rest.read_u32()?;
+        let bucket_count = rest.read_u32()?;
+        let name_count = rest.read_u32()?;
+        let abbrev_table_size = rest.read_u32()?;
+        let augmentation_string_size = rest.read_u32()?;
+        let augmentation_string = rest.split(R::Offset::from_u32(augmentation_string_size))?;
+
+        let offset_size = u64::from(format.word_size());
+        let mut split = |count: u32, size: u64| {
+            let len = R::Offset::from_u64(u64::from(count) * size)?;
+            rest.split(len)
+        };
+        let comp_units = split(comp_unit_count, offset_size)?;
+        let local_type_units = split(local_type_unit_count, offset_size)?;
+        let foreign_type_units = split(foreign_type_unit_count, 8)?;
+        let buckets = split(bucket_count, 4)?;
+        // The hashes are omitted if there is no hash lookup table.
+        let hashes = split(if bucket_count == 0 { 0 } else { name_count }, 4)?;
+        let string_offsets = split(name_count, offset_size)?;
+        let entry_offsets = split(name_count, offset_size)?;
+        let mut abbrev_table = split(abbrev_table_size, 1)?;
+
+        let mut abbreviations = Vec::new();
+        while let Some(abbrev) = NameAbbreviation::parse(&mut abbrev_table)? {
+            if abbreviations
+                .iter()
+                .any(|a: &NameAbbreviation| a.code == abbrev.code)
+            {
+                return Err(Error::DuplicateAbbreviationCode);
+            }
+            abbreviations.push(abbrev);
+        }
+
+        Ok(NameIndex {
+            format,
+            version,
+            comp_unit_count,
+            local_type_unit_count,
+            foreign_type_unit_count,
+            bucket_count,
+            name_count,
+            augmentation_string,
+            comp_units,
+            local_type_units,
+            foreign_type_units,
+            buckets,
+            hashes,
+            string_offsets,
+            entry_offsets,
+            abbreviations,
+            entry_pool: rest,
+        })
+    }
+
+    /// Return the DWARF format of this name index.
+    #[inline]
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Return the version of this name index.
+    #[inline]
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Return the number of compilation units in this name index.
+    #[inline]
+    pub fn comp_unit_count(&self) -> u32 {
+        self.comp_unit_count
+    }
+
+    /// Return the number of local type units in this name index.
+    #[inline]
+    pub fn local_type_unit_count(&self) -> u32 {
+        self.local_type_unit_count
+    }
+
+    /// Return the number of foreign type units in this name index.
+    #[inline]
+    pub fn foreign_type_unit_count(&self) -> u32 {
+        self.foreign_type_unit_count
+    }
+
+    /// Return the number of buckets in the hash lookup table.
+    ///
+    /// This is 0 if the name index does not have a hash lookup table.
+    #[inline]
+    pub fn bucket_count(&self) -> u32 {
+        self.bucket_count
+    }
+
+    /// Return the number of names in this name index.
+    #[inline]
+    pub fn name_count(&self) -> u32 {
+        self.name_count
+    }
+
+    /// Return the augmentation string of this name index, including any padding.
+    #[inline]
+    pub fn augmentation_string(&self) -> &R {
+        &self.augmentation_string
+    }
+
+    /// Return the offset of the compilation unit at the given index in the
+    /// compilation unit list.
+    pub fn compile_unit(&self, index: u32) -> Result<DebugInfoOffset<R::Offset>> {
+        if index >= self.comp_unit_count {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let mut input = self.comp_units.clone();
+        input.skip(R::Offset::from_u64(
+            u64::from(index) * u64::from(self.format.word_size()),
+        )?)?;
+        input.read_offset(self.format).map(DebugInfoOffset)
+    }
+
+    /// Return the type unit at the given index.
+    ///
+    /// The index is the value of a `DW_IDX_type_unit` attribute. The local type
+    /// units are numbered first, followed by the foreign type units.
+    pub fn type_unit(&self, index: u32) -> Result<NameIndexTypeUnit<R::Offset>> {
+        if index < self.local_type_unit_count {
+            let mut input = self.local_type_units.clone();
+            input.skip(R::Offset::from_u64(
+                u64::from(index) * u64::from(self.format.word_size()),
+            )?)?;
+            input
+                .read_offset(self.format)
+                .map(|offset| NameIndexTypeUnit::Local(DebugInfoOffset(offset)))
+        } else {
+            let index = index - self.local_type_unit_count;
+            if index >= self.foreign_type_unit_count {
+                return Err(Error::OffsetOutOfBounds);
+            }
+            let mut input = self.foreign_type_units.clone();
+            input.skip(R::Offset::from_u64(u64::from(index) * 8)?)?;
+            input
+                .read_u64()
+                .map(|signature| NameIndexTypeUnit::Foreign(DebugTypeSignature(signature)))
+        }
+    }
+
+    /// Return the offset of the compilation unit containing the given entry.
+    ///
+    /// This uses the `DW_IDX_compile_unit` attribute of the entry if present.
+    /// Otherwise, if the entry is not in a type unit and this name index
+    /// only has a single compilation unit, then that unit is used.
+    ///
+    /// Returns `None` if the entry is not in a compilation unit.
+    pub fn entry_compile_unit(
+        &self,
+        entry: &NameEntry<R>,
+    ) -> Result<Option<DebugInfoOffset<R::Offset>>> {
+        if let Some(index) = entry.compile_unit() {
+            if index > u64::from(u32::max_value()) {
+                return Err(Error::OffsetOutOfBounds);
+            }
+            return self.compile_unit(index as u32).map(Some);
+        }
+        if entry.type_unit().is_none() && self.comp_unit_count == 1 {
+            return self.compile_unit(0).map(Some);
+        }
+        Ok(None)
+    }
+
+    /// Return the name at the given index in the name table.
+    pub fn name(&self, index: u32) -> Result<Name<R>> {
+        if index >= self.name_count {
+            return Err(Error::OffsetOutOfBounds);
+        }
+        let skip = R::Offset::from_u64(u64::from(index) * u64::from(self.format.word_size()))?;
+        let mut string_offsets = self.string_offsets.clone();
+        string_offsets.skip(skip)?;
+        let string_offset = string_offsets.read_offset(self.format)?;
+        let mut entry_offsets = self.entry_offsets.clone();
+        entry_offsets.skip(skip)?;
+        let entry_offset = entry_offsets.read_offset(self.format)?;
+        Ok(Name {
+            index: self,
+            string_offset: DebugStrOffset(string_offset),
+            entry_offset,
+        })
+    }
+
+    /// Iterate the names in the name table.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn names(&self) -> NameIter<R> {
+        NameIter {
+            index: self,
+            next: 0,
+        }
+    }
+
+    /// Find the given name using the hash lookup table.
+    ///
+    /// The name strings are read from `debug_str`. If this name index does not
+    /// have a hash lookup table, then all of the names are searched.
+    ///
+    /// Returns `None` if the name is not in this name index.
+    pub fn lookup(&self, name: &[u8], debug_str: &DebugStr<R>) -> Result<Option<Name<R>>> {
+        if self.bucket_count == 0 {
+            let mut names = self.names();
+            while let Some(entry) = names.next()? {
+                if entry.matches(name, debug_str)? {
+                    return Ok(Some(entry));
+                }
+            }
+            return Ok(None);
+        }
+
+        let hash = debug_names_hash(name);
+        let bucket = hash % self.bucket_count;
+        let mut buckets = self.buckets.clone();
+        buckets.skip(R::Offset::from_u64(u64::from(bucket) * 4)?)?;
+        // Indices in the bucket table start at 1, and 0 is an empty bucket.
+        let mut index = buckets.read_u32()?;
+        if index == 0 {
+            return Ok(None);
+        }
+        let mut hashes = self.hashes.clone();
+        hashes.skip(R::Offset::from_u64(u64::from(index - 1) * 4)?)?;
+        while index <= self.name_count {
+            let name_hash = hashes.read_u32()?;
+            if name_hash % self.bucket_count != bucket {
+                break;
+            }
+            if name_hash == hash {
+                let entry = self.name(index - 1)?;
+                if entry.matches(name, debug_str)? {
+                    return Ok(Some(entry));
+                }
+            }
+            index += 1;
+        }
+        Ok(None)
+    }
+
+    fn abbreviation(&self, code: u64) -> Option<&NameAbbreviation> {
+        // Abbreviation codes are usually sequential.
+        if code > 0 && code <= self.abbreviations.len() as u64 {
+            let abbrev = &self.abbreviations[code as usize - 1];
+            if abbrev.code == code {
+                return Some(abbrev);
+            }
+        }
+        self.abbreviations.iter().find(|abbrev| abbrev.code == code)
+    }
+}
+
+/// An iterator over the names in a name index.
+#[derive(Debug, Clone)]
+pub struct NameIter<'index, R: Reader + 'index> {
+    index: &'index NameIndex<R>,
+    next: u32,
+}
+
+impl<'index, R: Reader> NameIter<'index, R> {
+    /// Advance the iterator to the next name.
+    pub fn next(&mut self) -> Result<Option<Name<'index, R>>> {
+        if self.next >= self.index.name_count {
+            return Ok(None);
+        }
+        let name = self.index.name(self.next)?;
+        self.next += 1;
+        Ok(Some(name))
+    }
+}
+
+impl<'index, R: Reader> FallibleIterator for NameIter<'index, R> {
+    type Item = Name<'index, R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        NameIter::next(self)
+    }
+}
+
+/// A name in the name table of a name index.
+#[derive(Debug, Clone)]
+pub struct Name<'index, R: Reader + 'index> {
+    index: &'index NameIndex<R>,
+    string_offset: DebugStrOffset<R::Offset>,
+    entry_offset: R::Offset,
+}
+
+impl<'index, R: Reader> Name<'index, R> {
+    /// Return the offset of the name string in the `.debug_str` section.
+    #[inline]
+    pub fn string_offset(&self) -> DebugStrOffset<R::Offset> {
+        self.string_offset
+    }
+
+    /// Iterate the entries for this name.
+    ///
+    /// Can be [used with
+    /// `FallibleIterator`](./index.html#using-with-fallibleiterator).
+    pub fn entries(&self) -> Result<NameEntryIter<'index, R>> {
+        let mut input = self.index.entry_pool.clone();
+        input.skip(self.entry_offset)?;
+        Ok(NameEntryIter {
+            index: self.index,
+            input,
+        })
+    }
+
+    fn matches(&self, name: &[u8], debug_str: &DebugStr<R>) -> Result<bool> {
+        let string = debug_str.get_str(self.string_offset)?;
+        Ok(&*string.to_slice()? == name)
+    }
+}
+
+/// An iterator over the entries for a name in a name index.
+#[derive(Debug, Clone)]
+pub struct NameEntryIter<'index, R: Reader + 'index> {
+    index: &'index NameIndex<R>,
+    input: R,
+}
+
+impl<'index, R: Reader> NameEntryIter<'index, R> {
+    /// Advance the iterator to the next entry.
+    pub fn next(&mut self) -> Result<Option<NameEntry<R>>> {
+        if self.input.is_empty() {
+            return Ok(None);
+        }
+        match self.parse_entry() {
+            Ok(None) => {
+                self.input.empty();
+                Ok(None)
+            }
+            Ok(Some(entry)) => Ok(Some(entry)),
+            Err(e) => {
+                self.input.empty();
+                Err(e)
+            }
+        }
+    }
+
+    fn parse_entry(&mut self) -> Result<Option<NameEntry<R>>> {
+        let offset = self.input.offset_from(&self.index.entry_pool);
+        let code = self.input.read_uleb128()?;
+        if code == 0 {
+            return Ok(None);
+        }
+        let abbrev = self
+            .index
+            .abbreviation(code)
+            .ok_or(Error::UnknownAbbreviation)?;
+        let mut attrs = Vec::with_capacity(abbrev.attributes.len());
+        for &(name, form) in &abbrev.attributes {
+            let value = parse_value(&mut self.input, form, self.index.format)?;
+            attrs.push(NameEntryAttribute { name, value });
+        }
+        Ok(Some(NameEntry {
+            offset,
+            tag: abbrev.tag,
+            attrs,
+        }))
+    }
+}
+
+impl<'index, R: Reader> FallibleIterator for NameEntryIter<'index, R> {
+    type Item = NameEntry<R>;
+    type Error = Error;
+
+    fn next(&mut self) -> ::std::result::Result<Option<Self::Item>, Self::Error> {
+        NameEntryIter::next(self)
+    }
+}
+
+/// An entry in the entry pool of a name index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameEntry<R: Reader> {
+    offset: R::Offset,
+    tag: constants::DwTag,
+    attrs: Vec<NameEntryAttribute<R>>,
+}
+
+impl<R: Reader> NameEntry<R> {
+    /// Return the offset of this entry within the entry pool.
+    ///
+    /// This may be used to match the `DW_IDX_parent` attribute of other entries.
+    #[inline]
+    pub fn offset(&self) -> R::Offset {
+        self.offset
+    }
+
+    /// Return the tag of the debugging information entry.
+    #[inline]
+    pub fn tag(&self) -> constants::DwTag {
+        self.tag
+    }
+
+    /// Return the attributes of this entry.
+    #[inline]
+    pub fn attrs(&self) -> &[NameEntryAttribute<R>] {
+        &self.attrs
+    }
+
+    /// Find the first attribute in this entry which has the given name,
+    /// and return its value. Returns `None` if no attribute is found.
+    pub fn attr_value(&self, name: constants::DwIdx) -> Option<AttributeValue<R, R::Offset>> {
+        self.attrs
+            .iter()
+            .find(|attr| attr.name == name)
+            .map(|attr| attr.value.clone())
+    }
+
+    /// Return the value of the `DW_IDX_compile_unit` attribute.
+    ///
+    /// This is an index into the compilation unit list of the name index.
+    pub fn compile_unit(&self) -> Option<u64> {
+        self.attr_value(constants::DW_IDX_compile_unit)
+            .and_then(|value| value.udata_value())
+    }
+
+    /// Return the value of the `DW_IDX_type_unit` attribute.
+    ///
+    /// This is an index into the type unit lists of the name index.
+    pub fn type_unit(&self) -> Option<u64> {
+        self.attr_value(constants::DW_IDX_type_unit)
+            .and_then(|value| value.udata_value())
+    }
+
+    /// Return the value of the `DW_IDX_die_offset` attribute.
+    pub fn die_offset(&self) -> Option<UnitOffset<R::Offset>> {
+        match self.attr_value(constants::DW_IDX_die_offset)? {
+            AttributeValue::UnitRef(offset) => Some(offset),
+            value => value
+                .udata_value()
+                .and_then(|offset| R::Offset::from_u64(offset).ok())
+                .map(UnitOffset),
+        }
+    }
+
+    /// Return the value of the `DW_IDX_type_hash` attribute.
+    pub fn type_hash(&self) -> Option<u64> {
+        self.attr_value(constants::DW_IDX_type_hash)
+            .and_then(|value| value.udata_value())
+    }
+}
+
+/// An attribute of an entry in a name index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameEntryAttribute<R: Reader> {
+    name: constants::DwIdx,
+    value: AttributeValue<R, R::Offset>,
+}
+
+impl<R: Reader> NameEntryAttribute<R> {
+    /// Return the name of this attribute.
+    #[inline]
+    pub fn name(&self) -> constants::DwIdx {
+        self.name
+    }
+
+    /// Return the value of this attribute.
+    #[inline]
+    pub fn value(&self) -> AttributeValue<R, R::Offset> {
+        self.value.clone()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NameAbbreviation {
+    code: u64,
+    tag: constants::DwTag,
+    attributes: Vec<(constants::DwIdx, constants::DwForm)>,
+}
+
+impl NameAbbreviation {
+    fn parse<R: Reader>(input: &mut R) -> Result<Option<NameAbbreviation>> {
+        let code = input.read_uleb128()?;
+        if code == 0 {
+            return Ok(None);
+        }
+        let tag = input.read_uleb128()?;
+        if tag == 0 {
+            return Err(Error::AbbreviationTagZero);
+        }
+        let mut attributes = Vec::new();
+        loop {
+            let name = input.read_uleb128()?;
+            let form = input.read_uleb128()?;
+            if name == 0 {
+                if form == 0 {
+                    break;
+                }
+                return Err(Error::ExpectedZero);
+            }
+            if form == 0 {
+                return Err(Error::AttributeFormZero);
+            }
+            let name = if name > u64::from(u16::max_value()) {
+                constants::DwIdx(u16::max_value())
+            } else {
+                constants::DwIdx(name as u16)
+            };
+            attributes.push((name, constants::DwForm(form)));
+        }
+        Ok(Some(NameAbbreviation {
+            code,
+            tag: constants::DwTag(tag),
+            attributes,
+        }))
+    }
+}
+
+fn parse_value<R: Reader>(
+    input: &mut R,
+    form: constants::DwForm,
+    format: Format,
+) -> Result<AttributeValue<R, R::Offset>> {
+    Ok(match form {
+        constants::DW_FORM_data1 => AttributeValue::Data1(input.read_u8()?),
+        constants::DW_FORM_data2 => AttributeValue::Data2(input.read_u16()?),
+        constants::DW_FORM_data4 => AttributeValue::Data4(input.read_u32()?),
+        constants::DW_FORM_data8 => AttributeValue::Data8(input.read_u64()?),
+        constants::DW_FORM_udata => AttributeValue::Udata(input.read_uleb128()?),
+        constants::DW_FORM_sdata => AttributeValue::Sdata(input.read_sleb128()?),
+        constants::DW_FORM_flag => AttributeValue::Flag(input.read_u8()? != 0),
+        constants::DW_FORM_flag_present => AttributeValue::Flag(true),
+        constants::DW_FORM_ref1 => {
+            AttributeValue::UnitRef(UnitOffset(R::Offset::from_u8(input.read_u8()?)))
+        }
+        constants::DW_FORM_ref2 => {
+            AttributeValue::UnitRef(UnitOffset(R::Offset::from_u16(input.read_u16()?)))
+        }
+        constants::DW_FORM_ref4 => {
+            AttributeValue::UnitRef(UnitOffset(R::Offset::from_u32(input.read_u32()?)))
+        }
+        constants::DW_FORM_ref8 => {
+            AttributeValue::UnitRef(UnitOffset(R::Offset::from_u64(input.read_u64()?)?))
+        }
+        constants::DW_FORM_ref_udata => {
+            AttributeValue::UnitRef(UnitOffset(R::Offset::from_u64(input.read_uleb128()?)?))
+        }
+        constants::DW_FORM_sec_offset => AttributeValue::SecOffset(input.read_offset(format)?),
+        _ => return Err(Error::UnknownForm),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
+    use super::*;
+    use test_util::GimliSectionMethods;
+    use LittleEndian;
+
+    #[test]
+    fn test_debug_names_hash() {
+        assert_eq!(debug_names_hash(b""), 5381);
+        assert_eq!(debug_names_hash(b"a"), 5381 * 33 + 0x61);
+        assert_eq!(debug_names_hash(b"A"), debug_names_hash(b"a"));
+        assert_eq!(debug_names_hash(b"main"), debug_names_hash(b"MAIN"));
+    }
+
+    // The names in the test name index, and the `.debug_info` offset of their DIE.
+    const NAMES: [(&str, u32); 4] = [("main", 0x10), ("foo", 0x20), ("Bar", 0x30), ("baz", 0x40)];
+
+    fn compile_unit(die_offset: u32) -> u8 {
+        if die_offset < 0x20 {
+            0
+        } else {
+            1
+        }
+    }
+
+    // Return the `.debug_names` and `.debug_str` sections for a name index
+    // containing `NAMES`, split between two compilation units.
+    fn sections(format: Format, bucket_count: u32) -> (Vec<u8>, Vec<u8>) {
+        let mut debug_str = Section::with_endian(Endian::Little);
+        let mut names = Vec::new();
+        for &(name, die_offset) in NAMES.iter() {
+            let offset = debug_str.size();
+            debug_str = debug_str.append_bytes(name.as_bytes()).D8(0);
+            let hash = debug_names_hash(name.as_bytes());
+            names.push((hash, offset, die_offset));
+        }
+        // Names must be sorted by bucket.
+        if bucket_count != 0 {
+            names.sort_by_key(|&(hash, _, _)| hash % bucket_count);
+        }
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let size = format.word_size();
+        let mut section = Section::with_endian(Endian::Little)
+            .initial_length(format, &length, &start)
+            .L16(5)
+            .L16(0)
+            // comp_unit_count, local_type_unit_count, foreign_type_unit_count
+            .L32(2)
+            .L32(1)
+            .L32(1)
+            .L32(bucket_count)
+            .L32(names.len() as u32)
+            // abbrev_table_size
+            .L32(9)
+            // augmentation_string_size
+            .L32(4)
+            .append_bytes(b"ABC\0")
+            .word(size, 0x100)
+            .word(size, 0x200)
+            .word(size, 0x300)
+            .L64(0x0102_0304_0506_0708);
+
+        let mut buckets = vec![0; bucket_count as usize];
+        for (i, &(hash, _, _)) in names.iter().enumerate().rev() {
+            if bucket_count != 0 {
+                buckets[(hash % bucket_count) as usize] = i as u32 + 1;
+            }
+        }
+        for bucket in buckets {
+            section = section.L32(bucket);
+        }
+        if bucket_count != 0 {
+            for &(hash, _, _) in &names {
+                section = section.L32(hash);
+            }
+        }
+        for &(_, offset, _) in &names {
+            section = section.word(size, offset);
+        }
+        // Each entry in the entry pool is 7 bytes, including the terminator.
+        for i in 0..names.len() {
+            section = section.word(size, 7 * i as u64);
+        }
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = section
+            .D8(1).D8(constants::DW_TAG_subprogram.0 as u8)
+            .D8(constants::DW_IDX_compile_unit.0 as u8).D8(constants::DW_FORM_data1.0 as u8)
+            .D8(constants::DW_IDX_die_offset.0 as u8).D8(constants::DW_FORM_ref4.0 as u8)
+            .D8(0).D8(0)
+            .D8(0);
+        let mut section = section;
+        for &(_, _, die_offset) in &names {
+            section = section
+                .D8(1)
+                .D8(compile_unit(die_offset))
+                .L32(die_offset)
+                .D8(0);
+        }
+        let section = section.mark(&end);
+        length.set_const((&end - &start) as u64);
+
+        (
+            section.get_contents().unwrap(),
+            debug_str.get_contents().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_name_index() {
+        for &format in &[Format::Dwarf32, Format::Dwarf64] {
+            for &bucket_count in &[0, 1, 3] {
+                let (section, debug_str) = sections(format, bucket_count);
+                let debug_names = DebugNames::new(&section, LittleEndian);
+                let debug_str = DebugStr::new(&debug_str, LittleEndian);
+
+                let mut indexes = debug_names.name_indexes();
+                let index = indexes.next().unwrap().unwrap();
+                assert!(indexes.next().unwrap().is_none());
+
+                assert_eq!(index.format(), format);
+                assert_eq!(index.version(), 5);
+                assert_eq!(index.comp_unit_count(), 2);
+                assert_eq!(index.bucket_count(), bucket_count);
+                assert_eq!(index.name_count(), 4);
+                assert_eq!(index.augmentation_string().slice(), b"ABC\0");
+                assert_eq!(index.compile_unit(0), Ok(DebugInfoOffset(0x100)));
+                assert_eq!(index.compile_unit(1), Ok(DebugInfoOffset(0x200)));
+                assert_eq!(index.compile_unit(2), Err(Error::OffsetOutOfBounds));
+                assert_eq!(
+                    index.type_unit(0),
+                    Ok(NameIndexTypeUnit::Local(DebugInfoOffset(0x300)))
+                );
+                assert_eq!(
+                    index.type_unit(1),
+                    Ok(NameIndexTypeUnit::Foreign(DebugTypeSignature(
+                        0x0102_0304_0506_0708
+                    )))
+                );
+                assert_eq!(index.type_unit(2), Err(Error::OffsetOutOfBounds));
+
+                assert_eq!(index.names().count().unwrap(), 4);
+
+                for &(name, die_offset) in NAMES.iter() {
+                    let found = index.lookup(name.as_bytes(), &debug_str).unwrap().unwrap();
+                    assert_eq!(
+                        debug_str.get_str(found.string_offset()).unwrap().slice(),
+                        name.as_bytes()
+                    );
+                    let mut entries = found.entries().unwrap();
+                    let entry = entries.next().unwrap().unwrap();
+                    assert!(entries.next().unwrap().is_none());
+                    assert_eq!(entry.tag(), constants::DW_TAG_subprogram);
+                    assert_eq!(entry.attrs().len(), 2);
+                    assert_eq!(entry.attrs()[0].name(), constants::DW_IDX_compile_unit);
+                    assert_eq!(
+                        entry.attrs()[0].value(),
+                        AttributeValue::Data1(compile_unit(die_offset))
+                    );
+                    assert_eq!(entry.die_offset(), Some(UnitOffset(die_offset as usize)));
+                    let unit = 0x100 * (usize::from(compile_unit(die_offset)) + 1);
+                    assert_eq!(
+                        index.entry_compile_unit(&entry),
+                        Ok(Some(DebugInfoOffset(unit)))
+                    );
+                }
+
+                assert!(index.lookup(b"missing", &debug_str).unwrap().is_none());
+                // Names are case sensitive, even though the hash is not.
+                assert!(index.lookup(b"bar", &debug_str).unwrap().is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn test_unknown_version() {
+        let section = Section::with_endian(Endian::Little)
+            .L32(4)
+            .L16(4)
+            .L16(0)
+            .get_contents()
+            .unwrap();
+        let debug_names = DebugNames::new(&section, LittleEndian);
+        let mut indexes = debug_names.name_indexes();
+        assert_eq!(indexes.next().err(), Some(Error::UnknownVersion(4)));
+        assert!(indexes.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unknown_abbreviation() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let section = Section::with_endian(Endian::Little)
+            .L32(46)
+            .L16(5).L16(0)
+            .L32(1).L32(0).L32(0).L32(0).L32(1)
+            // abbrev_table_size, augmentation_string_size
+            .L32(1).L32(0)
+            .L32(0)
+            // Name table.
+            .L32(0).L32(0)
+            // Abbreviation table.
+            .D8(0)
+            // Entry pool.
+            .D8(1)
+            .get_contents()
+            .unwrap();
+        let debug_names = DebugNames::new(&section, LittleEndian);
+        let index = debug_names.name_indexes().next().unwrap().unwrap();
+        let name = index.name(0).unwrap();
+        let mut entries = name.entries().unwrap();
+        assert_eq!(entries.next(), Err(Error::UnknownAbbreviation));
+        assert_eq!(entries.next(), Ok(None));
+    }
+}
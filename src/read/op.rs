@@ -1,10 +1,11 @@
 //! Functions for parsing and evaluating DWARF expressions.
 
-use std::mem;
+use std::{cmp, mem};
 use vec::Vec;
 
 use common::{DebugAddrIndex, DebugInfoOffset, Encoding, Register};
 use constants;
+use endianity::Endianity;
 use read::{Error, Reader, ReaderOffset, Result, UnitOffset, Value, ValueType};
 
 /// A reference to a DIE, either relative to the current CU or
@@ -334,6 +335,349 @@ where
     pub location: Location<R, Offset>,
 }
 
+/// Provides access to the registers and memory of a target, for reading and
+/// writing the value described by a list of `Piece`s.
+///
+/// Every method has a default implementation that declines.
+pub trait PieceContext {
+    /// Replace the contents of `value` with the contents of `register`, in
+    /// the byte order of the target.
+    ///
+    /// Return false if the register is unavailable.
+    fn read_register(&mut self, _register: Register, _value: &mut Vec<u8>) -> bool {
+        false
+    }
+
+    /// Fill `buf` with the memory at `address`.
+    ///
+    /// Return false if the memory is unavailable.
+    fn read_memory(&mut self, _address: u64, _buf: &mut [u8]) -> bool {
+        false
+    }
+
+    /// Set the contents of `register` to `value`, in the byte order of the
+    /// target.
+    ///
+    /// `value` always has the same size as the contents returned by
+    /// `read_register`. Return false if the register cannot be written.
+    fn write_register(&mut self, _register: Register, _value: &[u8]) -> bool {
+        false
+    }
+
+    /// Write `data` to the memory at `address`.
+    ///
+    /// Return false if the memory cannot be written.
+    fn write_memory(&mut self, _address: u64, _data: &[u8]) -> bool {
+        false
+    }
+}
+
+/// The bytes of a value that is described by a list of `Piece`s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterializedValue {
+    /// The bytes of the value, in the byte order of the target.
+    ///
+    /// Bits that are unavailable are 0.
+    pub bytes: Vec<u8>,
+
+    /// A mask of the bits of `bytes` that are unavailable.
+    ///
+    /// A bit is unavailable if its piece is `Location::Empty` or
+    /// `Location::ImplicitPointer`, if its register or memory could not be
+    /// read, or if it is not described by any piece.
+    pub unavailable: Vec<u8>,
+}
+
+impl MaterializedValue {
+    /// Assemble the bytes of a value of `byte_size` bytes from the pieces
+    /// returned by `Evaluation::result`.
+    ///
+    /// The pieces are concatenated in the order given. A piece without a
+    /// size extends to the end of the value, or to the end of its register
+    /// or value. Bits of pieces that extend past `byte_size` are ignored.
+    ///
+    /// `endian` is the byte order of the target, which is also used for the
+    /// bit numbering of pieces in memory.
+    pub fn read<R, E, C>(
+        pieces: &[Piece<R, R::Offset>],
+        byte_size: usize,
+        endian: E,
+        context: &mut C,
+    ) -> Result<MaterializedValue>
+    where
+        R: Reader,
+        E: Endianity,
+        C: PieceContext + ?Sized,
+    {
+        let big_endian = endian.is_big_endian();
+        let mut bytes = vec![0; byte_size];
+        let mut unavailable = vec![0xff; byte_size];
+        let total = byte_size as u64 * 8;
+        let mut position = 0;
+        let mut register = Vec::new();
+        let mut memory = Vec::new();
+        for piece in pieces {
+            if position >= total {
+                break;
+            }
+            let remaining = total - position;
+            let offset = piece.bit_offset.unwrap_or(0);
+            let size = match piece.location {
+                Location::Register { register: reg } => {
+                    register.clear();
+                    let available = context.read_register(reg, &mut register);
+                    let size = piece_size(piece, register.len() as u64 * 8, remaining);
+                    if available {
+                        for i in 0..size {
+                            let index = source_bit(offset, size, i, big_endian);
+                            if let Some(bit) = value_bit(&register, index, big_endian) {
+                                store_bit(
+                                    &mut bytes,
+                                    &mut unavailable,
+                                    position + i,
+                                    big_endian,
+                                    bit,
+                                );
+                            }
+                        }
+                    }
+                    size
+                }
+                Location::Value { value } => {
+                    let (bits, bit_size) = value_bits(value);
+                    let size = piece_size(piece, bit_size, remaining);
+                    for i in 0..size {
+                        let index = source_bit(offset, size, i, big_endian);
+                        if index < bit_size {
+                            let bit = (bits >> index) & 1 != 0;
+                            store_bit(&mut bytes, &mut unavailable, position + i, big_endian, bit);
+                        }
+                    }
+                    size
+                }
+                Location::Address { address } => {
+                    let size = piece_size(piece, remaining, remaining);
+                    memory.clear();
+                    memory.resize(bit_span(offset, size), 0);
+                    let address = address.wrapping_add(offset / 8);
+                    if context.read_memory(address, &mut memory) {
+                        for i in 0..size {
+                            let bit = memory_bit(&memory, offset % 8 + i, big_endian);
+                            store_bit(&mut bytes, &mut unavailable, position + i, big_endian, bit);
+                        }
+                    }
+                    size
+                }
+                Location::Bytes { ref value } => {
+                    let value = value.to_slice()?;
+                    let size = piece_size(piece, value.len() as u64 * 8, remaining);
+                    for i in 0..size {
+                        let index = offset + i;
+                        if index / 8 < value.len() as u64 {
+                            let bit = memory_bit(&value, index, big_endian);
+                            store_bit(&mut bytes, &mut unavailable, position + i, big_endian, bit);
+                        }
+                    }
+                    size
+                }
+                Location::Empty | Location::ImplicitPointer { .. } => {
+                    piece_size(piece, remaining, remaining)
+                }
+            };
+            position += size;
+        }
+        Ok(MaterializedValue { bytes, unavailable })
+    }
+
+    /// Return true if all of the bits of the value are available.
+    pub fn is_available(&self) -> bool {
+        self.unavailable.iter().all(|&mask| mask == 0)
+    }
+
+    /// Write the bytes of this value to the locations described by the
+    /// pieces returned by `Evaluation::result`.
+    ///
+    /// The pieces are laid out in the same way as for `read`. Registers,
+    /// and memory that is not a whole number of bytes, are read before
+    /// being modified so that the bits outside of the pieces are preserved.
+    ///
+    /// Returns an error if a piece does not have a register or memory
+    /// location, or if the register or memory cannot be accessed. Pieces
+    /// before the failing piece will have already been written.
+    pub fn write<R, E, C>(
+        &self,
+        pieces: &[Piece<R, R::Offset>],
+        endian: E,
+        context: &mut C,
+    ) -> Result<()>
+    where
+        R: Reader,
+        E: Endianity,
+        C: PieceContext + ?Sized,
+    {
+        let big_endian = endian.is_big_endian();
+        let total = self.bytes.len() as u64 * 8;
+        let mut position = 0;
+        let mut register = Vec::new();
+        let mut memory = Vec::new();
+        for piece in pieces {
+            if position >= total {
+                break;
+            }
+            let remaining = total - position;
+            let offset = piece.bit_offset.unwrap_or(0);
+            let size = match piece.location {
+                Location::Register { register: reg } => {
+                    register.clear();
+                    if !context.read_register(reg, &mut register) {
+                        return Err(Error::UnavailableRegister(reg));
+                    }
+                    let size = piece_size(piece, register.len() as u64 * 8, remaining);
+                    for i in 0..size {
+                        let bit = memory_bit(&self.bytes, position + i, big_endian);
+                        let index = source_bit(offset, size, i, big_endian);
+                        set_value_bit(&mut register, index, big_endian, bit);
+                    }
+                    if !context.write_register(reg, &register) {
+                        return Err(Error::UnavailableRegister(reg));
+                    }
+                    size
+                }
+                Location::Address { address } => {
+                    let size = piece_size(piece, remaining, remaining);
+                    memory.clear();
+                    memory.resize(bit_span(offset, size), 0);
+                    let address = address.wrapping_add(offset / 8);
+                    if (offset % 8 != 0 || size % 8 != 0)
+                        && !context.read_memory(address, &mut memory)
+                    {
+                        return Err(Error::UnavailableMemory(address));
+                    }
+                    for i in 0..size {
+                        let bit = memory_bit(&self.bytes, position + i, big_endian);
+                        set_memory_bit(&mut memory, offset % 8 + i, big_endian, bit);
+                    }
+                    if !context.write_memory(address, &memory) {
+                        return Err(Error::UnavailableMemory(address));
+                    }
+                    size
+                }
+                Location::Empty
+                | Location::Value { .. }
+                | Location::Bytes { .. }
+                | Location::ImplicitPointer { .. } => return Err(Error::UnwritablePiece),
+            };
+            position += size;
+        }
+        Ok(())
+    }
+}
+
+/// Return the size in bits of a piece, given the default size of its
+/// location and the number of bits remaining in the value.
+fn piece_size<R: Reader>(piece: &Piece<R, R::Offset>, default: u64, remaining: u64) -> u64 {
+    let size = match piece.size_in_bits {
+        Some(size) => size,
+        None => default.saturating_sub(piece.bit_offset.unwrap_or(0)),
+    };
+    cmp::min(size, remaining)
+}
+
+/// Return the number of bytes that contain `size` bits starting at bit `offset`.
+fn bit_span(offset: u64, size: u64) -> usize {
+    let bits = offset % 8 + size;
+    let bytes = bits / 8;
+    if bits > bytes * 8 {
+        (bytes + 1) as usize
+    } else {
+        bytes as usize
+    }
+}
+
+/// Return the bit number, counting from the least significant bit, within
+/// a register or value for the bit at `index` within a piece.
+///
+/// The pieces of a value are stored in memory order, so the most significant
+/// bits are first for big endian targets.
+fn source_bit(offset: u64, size: u64, index: u64, big_endian: bool) -> u64 {
+    if big_endian {
+        offset + size - 1 - index
+    } else {
+        offset + index
+    }
+}
+
+/// Return the bits and bit size of a `Value`.
+fn value_bits(value: Value) -> (u64, u64) {
+    match value {
+        Value::Generic(value) => (value, 64),
+        Value::I8(value) => (u64::from(value as u8), 8),
+        Value::U8(value) => (u64::from(value), 8),
+        Value::I16(value) => (u64::from(value as u16), 16),
+        Value::U16(value) => (u64::from(value), 16),
+        Value::I32(value) => (u64::from(value as u32), 32),
+        Value::U32(value) => (u64::from(value), 32),
+        Value::I64(value) => (value as u64, 64),
+        Value::U64(value) => (value, 64),
+        Value::F32(value) => (u64::from(value.to_bits()), 32),
+        Value::F64(value) => (value.to_bits(), 64),
+    }
+}
+
+/// Return the byte index and shift for the bit at `index` in memory order.
+fn memory_bit_position(index: u64, big_endian: bool) -> (usize, u64) {
+    let shift = if big_endian { 7 - index % 8 } else { index % 8 };
+    ((index / 8) as usize, shift)
+}
+
+fn memory_bit(bytes: &[u8], index: u64, big_endian: bool) -> bool {
+    let (byte, shift) = memory_bit_position(index, big_endian);
+    (bytes[byte] >> shift) & 1 != 0
+}
+
+fn set_memory_bit(bytes: &mut [u8], index: u64, big_endian: bool, bit: bool) {
+    let (byte, shift) = memory_bit_position(index, big_endian);
+    if bit {
+        bytes[byte] |= 1 << shift;
+    } else {
+        bytes[byte] &= !(1 << shift);
+    }
+}
+
+fn store_bit(bytes: &mut [u8], unavailable: &mut [u8], index: u64, big_endian: bool, bit: bool) {
+    set_memory_bit(bytes, index, big_endian, bit);
+    set_memory_bit(unavailable, index, big_endian, false);
+}
+
+/// Return the byte index and shift for the bit at `index`, counting from the
+/// least significant bit, within the contents of a register.
+///
+/// The register contents are in the byte order of the target, so the byte
+/// index depends on the endianity, but the bit numbering does not.
+fn value_bit_position(len: usize, index: u64, big_endian: bool) -> Option<(usize, u64)> {
+    let byte = (index / 8) as usize;
+    if byte >= len {
+        return None;
+    }
+    let byte = if big_endian { len - 1 - byte } else { byte };
+    Some((byte, index % 8))
+}
+
+fn value_bit(bytes: &[u8], index: u64, big_endian: bool) -> Option<bool> {
+    value_bit_position(bytes.len(), index, big_endian)
+        .map(|(byte, shift)| (bytes[byte] >> shift) & 1 != 0)
+}
+
+fn set_value_bit(bytes: &mut [u8], index: u64, big_endian: bool, bit: bool) {
+    if let Some((byte, shift)) = value_bit_position(bytes.len(), index, big_endian) {
+        if bit {
+            bytes[byte] |= 1 << shift;
+        } else {
+            bytes[byte] &= !(1 << shift);
+        }
+    }
+}
+
 // A helper function to handle branch offsets.
 fn compute_pc<R: Reader>(pc: &R, bytecode: &R, offset: i16) -> Result<R> {
     let pc_offset = pc.offset_from(bytecode);
@@ -889,6 +1233,98 @@ impl<R: Reader> Expression<R> {
     }
 }
 
+/// Provides the values that are required to evaluate a DWARF expression.
+///
+/// This is used by [`Evaluation::evaluate_with`](struct.Evaluation.html#method.evaluate_with)
+/// to drive an evaluation to completion. Each method corresponds to an
+/// `EvaluationResult::Requires*` variant, and may return `None` to decline
+/// to provide the value, in which case the evaluation fails with the
+/// corresponding `Error::Unavailable*` error.
+///
+/// Every method has a default implementation that declines, except for
+/// `relocated_address`, which returns the address unchanged.
+pub trait EvaluationContext<R: Reader> {
+    /// Return the value of the given `size` at `address`.
+    ///
+    /// `base_type` is the DIE of the base type of the value, or 0 for the
+    /// generic type.
+    fn memory(
+        &mut self,
+        _address: u64,
+        _size: u8,
+        _space: Option<u64>,
+        _base_type: UnitOffset<R::Offset>,
+    ) -> Option<Value> {
+        None
+    }
+
+    /// Return the value of the given register.
+    ///
+    /// `base_type` is the DIE of the base type of the value, or 0 for the
+    /// generic type.
+    fn register(
+        &mut self,
+        _register: Register,
+        _base_type: UnitOffset<R::Offset>,
+    ) -> Option<Value> {
+        None
+    }
+
+    /// Return the frame base of the current function.
+    fn frame_base(&mut self) -> Option<u64> {
+        None
+    }
+
+    /// Return the address of the TLS value at the given offset.
+    fn tls(&mut self, _offset: u64) -> Option<u64> {
+        None
+    }
+
+    /// Return the CFA of the current frame.
+    fn call_frame_cfa(&mut self) -> Option<u64> {
+        None
+    }
+
+    /// Return the bytecode of the `DW_AT_location` of the given DIE.
+    ///
+    /// Return an empty `R` if the DIE has no location.
+    fn at_location(&mut self, _die: DieReference<R::Offset>) -> Option<R> {
+        None
+    }
+
+    /// Return the value that the given expression had on entry to the
+    /// current function.
+    fn entry_value(&mut self, _expression: Expression<R>) -> Option<Value> {
+        None
+    }
+
+    /// Return the value of the parameter at the given offset in the caller
+    /// of the current function.
+    fn parameter_ref(&mut self, _parameter: UnitOffset<R::Offset>) -> Option<u64> {
+        None
+    }
+
+    /// Return the relocated value of `address`.
+    fn relocated_address(&mut self, address: u64) -> Option<u64> {
+        Some(address)
+    }
+
+    /// Return the address at the given index in the `.debug_addr` section,
+    /// relocating it if `relocate` is true.
+    fn indexed_address(
+        &mut self,
+        _index: DebugAddrIndex<R::Offset>,
+        _relocate: bool,
+    ) -> Option<u64> {
+        None
+    }
+
+    /// Return the `ValueType` of the base type DIE at the given offset.
+    fn base_type(&mut self, _base_type: UnitOffset<R::Offset>) -> Option<ValueType> {
+        None
+    }
+}
+
 /// A DWARF expression evaluator.
 ///
 /// # Usage
@@ -907,6 +1343,10 @@ impl<R: Reader> Expression<R> {
 /// be driven synchronously (as shown below) or by some asynchronous mechanism
 /// such as futures.
 ///
+/// When the data can be produced synchronously, the consumer can instead
+/// implement `EvaluationContext` and call `evaluate_with()`, which resumes the
+/// computation until it is complete.
+///
 /// # Examples
 /// ```rust,no_run
 /// use gimli::{EndianSlice, Evaluation, EvaluationResult, Format, LittleEndian, Value};
@@ -1690,6 +2130,95 @@ impl<R: Reader> Evaluation<R> {
         self.evaluate_internal()
     }
 
+    /// Evaluate a DWARF expression to completion, using `context` to provide
+    /// any values that are required.
+    ///
+    /// This is an alternative to calling `evaluate` and the `resume_with`
+    /// methods, and should only ever be called once. When this returns
+    /// `Ok`, the caller can call `result()`.
+    pub fn evaluate_with<C>(&mut self, context: &mut C) -> Result<()>
+    where
+        C: EvaluationContext<R> + ?Sized,
+    {
+        let mut result = self.evaluate()?;
+        loop {
+            result = match result {
+                EvaluationResult::Complete => return Ok(()),
+                EvaluationResult::RequiresMemory {
+                    address,
+                    size,
+                    space,
+                    base_type,
+                } => match context.memory(address, size, space, base_type) {
+                    Some(value) => self.resume_with_memory(value)?,
+                    None => return Err(self.unavailable(Error::UnavailableMemory(address))),
+                },
+                EvaluationResult::RequiresRegister {
+                    register,
+                    base_type,
+                } => match context.register(register, base_type) {
+                    Some(value) => self.resume_with_register(value)?,
+                    None => return Err(self.unavailable(Error::UnavailableRegister(register))),
+                },
+                EvaluationResult::RequiresFrameBase => match context.frame_base() {
+                    Some(frame_base) => self.resume_with_frame_base(frame_base)?,
+                    None => return Err(self.unavailable(Error::UnavailableFrameBase)),
+                },
+                EvaluationResult::RequiresTls(offset) => match context.tls(offset) {
+                    Some(value) => self.resume_with_tls(value)?,
+                    None => return Err(self.unavailable(Error::UnavailableTls(offset))),
+                },
+                EvaluationResult::RequiresCallFrameCfa => match context.call_frame_cfa() {
+                    Some(cfa) => self.resume_with_call_frame_cfa(cfa)?,
+                    None => return Err(self.unavailable(Error::UnavailableCallFrameCfa)),
+                },
+                EvaluationResult::RequiresAtLocation(die) => match context.at_location(die) {
+                    Some(bytes) => self.resume_with_at_location(bytes)?,
+                    None => return Err(self.unavailable(Error::UnavailableAtLocation)),
+                },
+                EvaluationResult::RequiresEntryValue(expression) => {
+                    match context.entry_value(expression) {
+                        Some(value) => self.resume_with_entry_value(value)?,
+                        None => return Err(self.unavailable(Error::UnavailableEntryValue)),
+                    }
+                }
+                EvaluationResult::RequiresParameterRef(parameter) => {
+                    match context.parameter_ref(parameter) {
+                        Some(value) => self.resume_with_parameter_ref(value)?,
+                        None => return Err(self.unavailable(Error::UnavailableParameterRef)),
+                    }
+                }
+                EvaluationResult::RequiresRelocatedAddress(address) => {
+                    match context.relocated_address(address) {
+                        Some(address) => self.resume_with_relocated_address(address)?,
+                        None => {
+                            return Err(
+                                self.unavailable(Error::UnavailableRelocatedAddress(address))
+                            )
+                        }
+                    }
+                }
+                EvaluationResult::RequiresIndexedAddress { index, relocate } => {
+                    match context.indexed_address(index, relocate) {
+                        Some(address) => self.resume_with_indexed_address(address)?,
+                        None => return Err(self.unavailable(Error::UnavailableIndexedAddress)),
+                    }
+                }
+                EvaluationResult::RequiresBaseType(base_type) => {
+                    match context.base_type(base_type) {
+                        Some(value_type) => self.resume_with_base_type(value_type)?,
+                        None => return Err(self.unavailable(Error::UnavailableBaseType)),
+                    }
+                }
+            };
+        }
+    }
+
+    fn unavailable(&mut self, error: Error) -> Error {
+        self.state = EvaluationState::Error(error);
+        error
+    }
+
     fn end_of_expression(&mut self) -> bool {
         while self.pc.is_empty() {
             match self.expression_stack.pop() {
@@ -1790,7 +2319,7 @@ mod tests {
     use super::*;
     use common::Format;
     use constants;
-    use endianity::LittleEndian;
+    use endianity::{BigEndian, LittleEndian};
     use leb128;
     use read::{EndianSlice, Error, Result, UnitOffset};
     use std::usize;
@@ -3925,4 +4454,297 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_eval_with_context() {
+        use self::AssemblerEntry::*;
+        use constants::*;
+
+        struct Context;
+
+        impl<'a> EvaluationContext<EndianSlice<'a, LittleEndian>> for Context {
+            fn memory(
+                &mut self,
+                address: u64,
+                _size: u8,
+                _space: Option<u64>,
+                _base_type: UnitOffset,
+            ) -> Option<Value> {
+                if address == 0x108 {
+                    Some(Value::Generic(0x20))
+                } else {
+                    None
+                }
+            }
+
+            fn register(&mut self, register: Register, _base_type: UnitOffset) -> Option<Value> {
+                if register == Register(5) {
+                    Some(Value::Generic(0x100))
+                } else {
+                    None
+                }
+            }
+
+            fn call_frame_cfa(&mut self) -> Option<u64> {
+                Some(0x300)
+            }
+
+            fn base_type(&mut self, base_type: UnitOffset) -> Option<ValueType> {
+                if base_type == UnitOffset(1) {
+                    Some(ValueType::U16)
+                } else {
+                    None
+                }
+            }
+        }
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let program = [
+            Op(DW_OP_breg5), Sleb(8),
+            Op(DW_OP_deref),
+            Op(DW_OP_call_frame_cfa),
+            Op(DW_OP_plus),
+            Op(DW_OP_addr), U32(0x1000),
+            Op(DW_OP_plus),
+            Op(DW_OP_stack_value),
+        ];
+        let bytes = assemble(&program);
+        let bytes = EndianSlice::new(&bytes, LittleEndian);
+        let mut eval = Evaluation::new(bytes, encoding4());
+        assert_eq!(eval.evaluate_with(&mut Context), Ok(()));
+        assert_eq!(
+            eval.result(),
+            vec![Piece {
+                size_in_bits: None,
+                bit_offset: None,
+                location: Location::Value {
+                    value: Value::Generic(0x1320),
+                },
+            }]
+        );
+
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let program = [
+            Op(DW_OP_const_type), Uleb(1), U8(2), U16(0x1234),
+            Op(DW_OP_stack_value),
+        ];
+        let bytes = assemble(&program);
+        let bytes = EndianSlice::new(&bytes, LittleEndian);
+        let mut eval = Evaluation::new(bytes, encoding4());
+        assert_eq!(eval.evaluate_with(&mut Context), Ok(()));
+        assert_eq!(
+            eval.result()[0].location,
+            Location::Value {
+                value: Value::U16(0x1234),
+            }
+        );
+
+        let tests = [
+            (
+                &[Op(DW_OP_breg4), Sleb(0)][..],
+                Error::UnavailableRegister(Register(4)),
+            ),
+            (
+                &[Op(DW_OP_addr), U32(0x200), Op(DW_OP_deref)][..],
+                Error::UnavailableMemory(0x200),
+            ),
+            (&[Op(DW_OP_fbreg), Sleb(0)][..], Error::UnavailableFrameBase),
+            (
+                &[Op(DW_OP_const_type), Uleb(2), U8(2), U16(0x1234)][..],
+                Error::UnavailableBaseType,
+            ),
+        ];
+        for &(program, error) in &tests {
+            let bytes = assemble(program);
+            let bytes = EndianSlice::new(&bytes, LittleEndian);
+            let mut eval = Evaluation::new(bytes, encoding4());
+            assert_eq!(eval.evaluate_with(&mut Context), Err(error));
+            assert_eq!(eval.evaluate(), Err(error));
+        }
+    }
+
+    struct PieceTestContext {
+        registers: Vec<(Register, Vec<u8>)>,
+        memory: Vec<(u64, Vec<u8>)>,
+    }
+
+    impl PieceContext for PieceTestContext {
+        fn read_register(&mut self, register: Register, value: &mut Vec<u8>) -> bool {
+            match self.registers.iter().find(|r| r.0 == register) {
+                Some(r) => {
+                    value.extend_from_slice(&r.1);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn read_memory(&mut self, address: u64, buf: &mut [u8]) -> bool {
+            match self.memory.iter().find(|m| m.0 == address) {
+                Some(m) if m.1.len() == buf.len() => {
+                    buf.copy_from_slice(&m.1);
+                    true
+                }
+                _ => false,
+            }
+        }
+
+        fn write_register(&mut self, register: Register, value: &[u8]) -> bool {
+            match self.registers.iter_mut().find(|r| r.0 == register) {
+                Some(r) => {
+                    r.1 = value.to_vec();
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn write_memory(&mut self, address: u64, data: &[u8]) -> bool {
+            match self.memory.iter_mut().find(|m| m.0 == address) {
+                Some(m) => {
+                    m.1 = data.to_vec();
+                    true
+                }
+                None => false,
+            }
+        }
+    }
+
+    #[test]
+    fn test_materialized_value_little_endian() {
+        let piece = |size_in_bits, bit_offset, location| Piece::<EndianSlice<LittleEndian>> {
+            size_in_bits,
+            bit_offset,
+            location,
+        };
+        let pieces = [
+            piece(
+                Some(16),
+                None,
+                Location::Register {
+                    register: Register(1),
+                },
+            ),
+            piece(Some(8), None, Location::Address { address: 0x1000 }),
+            piece(Some(8), None, Location::Empty),
+            piece(
+                Some(8),
+                Some(8),
+                Location::Value {
+                    value: Value::U16(0xbeef),
+                },
+            ),
+            piece(
+                Some(4),
+                Some(4),
+                Location::Register {
+                    register: Register(2),
+                },
+            ),
+            piece(Some(4), Some(2), Location::Address { address: 0x2000 }),
+        ];
+        let mut context = PieceTestContext {
+            registers: vec![
+                (Register(1), vec![0x11, 0x22, 0x33, 0x44]),
+                (Register(2), vec![0xf5]),
+            ],
+            memory: vec![(0x1000, vec![0xaa]), (0x2000, vec![0xe9])],
+        };
+        let value = MaterializedValue::read(&pieces, 7, LittleEndian, &mut context).unwrap();
+        assert_eq!(value.bytes, [0x11, 0x22, 0xaa, 0x00, 0xbe, 0xaf, 0x00]);
+        assert_eq!(value.unavailable, [0, 0, 0, 0xff, 0, 0, 0xff]);
+        assert!(!value.is_available());
+        assert_eq!(
+            value.write(&pieces, LittleEndian, &mut context),
+            Err(Error::UnwritablePiece)
+        );
+
+        let pieces = [
+            pieces[0].clone(),
+            pieces[1].clone(),
+            pieces[4].clone(),
+            pieces[5].clone(),
+        ];
+        let mut value = MaterializedValue::read(&pieces, 4, LittleEndian, &mut context).unwrap();
+        assert_eq!(value.bytes, [0x11, 0x22, 0xaa, 0xaf]);
+        assert!(value.is_available());
+
+        value.bytes = vec![0x55, 0x66, 0x77, 0x9c];
+        assert_eq!(value.write(&pieces, LittleEndian, &mut context), Ok(()));
+        assert_eq!(context.registers[0].1, [0x55, 0x66, 0x33, 0x44]);
+        assert_eq!(context.registers[1].1, [0xc5]);
+        assert_eq!(context.memory[0].1, [0x77]);
+        assert_eq!(context.memory[1].1, [0xe5]);
+        assert_eq!(
+            MaterializedValue::read(&pieces, 4, LittleEndian, &mut context),
+            Ok(value)
+        );
+
+        // Unreadable locations are unavailable, and cannot be written.
+        let pieces = [piece(None, None, Location::Address { address: 0x3000 })];
+        let value = MaterializedValue::read(&pieces, 2, LittleEndian, &mut context).unwrap();
+        assert_eq!(value.unavailable, [0xff, 0xff]);
+        assert_eq!(
+            value.write(&pieces, LittleEndian, &mut context),
+            Err(Error::UnavailableMemory(0x3000))
+        );
+    }
+
+    #[test]
+    fn test_materialized_value_big_endian() {
+        let piece = |size_in_bits, bit_offset, location| Piece::<EndianSlice<BigEndian>> {
+            size_in_bits,
+            bit_offset,
+            location,
+        };
+        let mut context = PieceTestContext {
+            registers: vec![(
+                Register(1),
+                vec![0xff, 0xff, 0xff, 0xff, 0x12, 0x34, 0x56, 0x78],
+            )],
+            memory: vec![(0x1000, vec![0xab, 0xcd])],
+        };
+
+        let pieces = [piece(
+            None,
+            None,
+            Location::Register {
+                register: Register(1),
+            },
+        )];
+        let mut value = MaterializedValue::read(&pieces, 4, BigEndian, &mut context).unwrap();
+        assert_eq!(value.bytes, [0x12, 0x34, 0x56, 0x78]);
+        assert!(value.is_available());
+
+        value.bytes = vec![0x87, 0x65, 0x43, 0x21];
+        assert_eq!(value.write(&pieces, BigEndian, &mut context), Ok(()));
+        assert_eq!(
+            context.registers[0].1,
+            [0xff, 0xff, 0xff, 0xff, 0x87, 0x65, 0x43, 0x21]
+        );
+
+        let pieces = [piece(
+            None,
+            None,
+            Location::Value {
+                value: Value::U32(0x1234_5678),
+            },
+        )];
+        let value = MaterializedValue::read(&pieces, 4, BigEndian, &mut context).unwrap();
+        assert_eq!(value.bytes, [0x12, 0x34, 0x56, 0x78]);
+
+        let pieces = [
+            piece(Some(8), Some(4), Location::Address { address: 0x1000 }),
+            piece(
+                Some(8),
+                None,
+                Location::Value {
+                    value: Value::U8(0x5a),
+                },
+            ),
+        ];
+        let value = MaterializedValue::read(&pieces, 2, BigEndian, &mut context).unwrap();
+        assert_eq!(value.bytes, [0xbc, 0x5a]);
+        assert!(value.is_available());
+    }
 }
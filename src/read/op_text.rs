@@ -0,0 +1,858 @@
+use std::fmt::{self, Write};
+use vec::Vec;
+
+use common::{Encoding, Register};
+use constants;
+use endianity::Endianity;
+use read::{DieReference, Error, Expression, Operation, Reader, ReaderOffset, Result};
+
+impl<R: Reader> Expression<R> {
+    /// Return a formatter for the textual form of this expression.
+    ///
+    /// The text is compatible with the output of `llvm-dwarfdump`, for example
+    /// `DW_OP_breg7 RSP+8, DW_OP_deref`. If `register_name` is given, then it
+    /// is used to print the names of registers, such as
+    /// [`X86_64::register_name`](struct.X86_64.html#method.register_name).
+    ///
+    /// The text can be converted back into bytecode with
+    /// [`assemble_expression`](fn.assemble_expression.html).
+    ///
+    /// ```
+    /// use gimli::{Encoding, EndianSlice, Expression, Format, LittleEndian, X86_64};
+    ///
+    /// let encoding = Encoding {
+    ///     format: Format::Dwarf32,
+    ///     version: 4,
+    ///     address_size: 8,
+    /// };
+    /// let bytecode = [0x77, 0x08, 0x06];
+    /// let expression = Expression(EndianSlice::new(&bytecode, LittleEndian));
+    /// let text = format!("{}", expression.display(encoding, Some(X86_64::register_name)));
+    /// assert_eq!(text, "DW_OP_breg7 RSP+8, DW_OP_deref");
+    /// ```
+    pub fn display(
+        &self,
+        encoding: Encoding,
+        register_name: Option<fn(Register) -> Option<&'static str>>,
+    ) -> ExpressionDisplay<R> {
+        ExpressionDisplay {
+            expression: self,
+            encoding,
+            register_name,
+        }
+    }
+}
+
+/// A formatter for the textual form of a DWARF expression.
+///
+/// This is created by [`Expression::display`](struct.Expression.html#method.display).
+/// Operations that cannot be decoded are printed as `<decoding error>`, and
+/// the remainder of the expression is skipped.
+#[derive(Debug, Clone)]
+pub struct ExpressionDisplay<'a, R: Reader + 'a> {
+    expression: &'a Expression<R>,
+    encoding: Encoding,
+    register_name: Option<fn(Register) -> Option<&'static str>>,
+}
+
+impl<'a, R: Reader> ExpressionDisplay<'a, R> {
+    fn register_name(&self, register: Register) -> Option<&'static str> {
+        self.register_name
+            .and_then(|register_name| register_name(register))
+    }
+
+    fn fmt_register(&self, f: &mut fmt::Formatter, register: Register) -> fmt::Result {
+        match self.register_name(register) {
+            Some(name) => {
+                // Register names are printed in upper case, to match llvm-dwarfdump.
+                for c in name.chars() {
+                    f.write_char(c.to_ascii_uppercase())?;
+                }
+                Ok(())
+            }
+            None => write!(f, "0x{:x}", register.0),
+        }
+    }
+
+    fn fmt_bytes(f: &mut fmt::Formatter, mut bytes: R) -> fmt::Result {
+        while let Ok(byte) = bytes.read_u8() {
+            write!(f, " 0x{:02x}", byte)?;
+        }
+        Ok(())
+    }
+
+    fn fmt_operation(
+        &self,
+        f: &mut fmt::Formatter,
+        opcode: constants::DwOp,
+        operation: Operation<R, R::Offset>,
+        pc: &R,
+    ) -> fmt::Result {
+        write!(f, "{}", opcode)?;
+        match operation {
+            Operation::Deref {
+                base_type, size, ..
+            } => match opcode {
+                constants::DW_OP_deref_size | constants::DW_OP_xderef_size => {
+                    write!(f, " 0x{:x}", size)?;
+                }
+                constants::DW_OP_deref_type
+                | constants::DW_OP_xderef_type
+                | constants::DW_OP_GNU_deref_type => {
+                    write!(f, " 0x{:x} 0x{:x}", size, base_type.0.into_u64())?;
+                }
+                _ => {}
+            },
+            Operation::Pick { index } if opcode == constants::DW_OP_pick => {
+                write!(f, " 0x{:x}", index)?;
+            }
+            Operation::PlusConstant { value } => {
+                write!(f, " 0x{:x}", value)?;
+            }
+            Operation::Bra { target } | Operation::Skip { target } => {
+                let offset = pc.len().into_u64() as i64 - target.len().into_u64() as i64;
+                write!(f, " {:+}", offset)?;
+            }
+            Operation::Literal { value } => match opcode {
+                constants::DW_OP_const1s
+                | constants::DW_OP_const2s
+                | constants::DW_OP_const4s
+                | constants::DW_OP_const8s
+                | constants::DW_OP_consts => {
+                    write!(f, " {:+}", value as i64)?;
+                }
+                constants::DW_OP_const1u
+                | constants::DW_OP_const2u
+                | constants::DW_OP_const4u
+                | constants::DW_OP_const8u
+                | constants::DW_OP_constu => {
+                    write!(f, " 0x{:x}", value)?;
+                }
+                _ => {
+                    // The value is encoded in the opcode, eg DW_OP_lit0.
+                }
+            },
+            Operation::Register { register }
+                if opcode == constants::DW_OP_regx || self.register_name(register).is_some() =>
+            {
+                f.write_str(" ")?;
+                self.fmt_register(f, register)?;
+            }
+            Operation::RegisterOffset {
+                register,
+                offset,
+                base_type,
+            } => match opcode {
+                constants::DW_OP_regval_type | constants::DW_OP_GNU_regval_type => {
+                    f.write_str(" ")?;
+                    self.fmt_register(f, register)?;
+                    write!(f, " 0x{:x}", base_type.0.into_u64())?;
+                }
+                _ => {
+                    if self.register_name(register).is_some() {
+                        f.write_str(" ")?;
+                        self.fmt_register(f, register)?;
+                    } else if opcode == constants::DW_OP_bregx {
+                        write!(f, " 0x{:x} ", register.0)?;
+                    } else {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{:+}", offset)?;
+                }
+            },
+            Operation::FrameOffset { offset } => {
+                write!(f, " {:+}", offset)?;
+            }
+            Operation::Call { offset } => match offset {
+                DieReference::UnitRef(offset) => {
+                    write!(f, " 0x{:x}", offset.0.into_u64())?;
+                }
+                DieReference::DebugInfoRef(offset) => {
+                    write!(f, " 0x{:x}", offset.0.into_u64())?;
+                }
+            },
+            Operation::Piece {
+                size_in_bits,
+                bit_offset: None,
+            } => {
+                write!(f, " 0x{:x}", size_in_bits / 8)?;
+            }
+            Operation::Piece {
+                size_in_bits,
+                bit_offset: Some(bit_offset),
+            } => {
+                write!(f, " 0x{:x} 0x{:x}", size_in_bits, bit_offset)?;
+            }
+            Operation::ImplicitValue { data } => {
+                write!(f, " 0x{:x}", data.len().into_u64())?;
+                Self::fmt_bytes(f, data)?;
+            }
+            Operation::ImplicitPointer { value, byte_offset } => {
+                write!(f, " 0x{:x} {:+}", value.0.into_u64(), byte_offset)?;
+            }
+            Operation::EntryValue { expression } => {
+                let expression = Expression(expression);
+                let display = expression.display(self.encoding, self.register_name);
+                write!(f, "({})", display)?;
+            }
+            Operation::ParameterRef { offset } => {
+                write!(f, " 0x{:x}", offset.0.into_u64())?;
+            }
+            Operation::Address { address } => {
+                write!(f, " 0x{:x}", address)?;
+            }
+            Operation::AddressIndex { index } | Operation::ConstantIndex { index } => {
+                write!(f, " 0x{:x}", index.0.into_u64())?;
+            }
+            Operation::TypedLiteral { base_type, value } => {
+                write!(
+                    f,
+                    " 0x{:x} 0x{:x}",
+                    base_type.0.into_u64(),
+                    value.len().into_u64()
+                )?;
+                Self::fmt_bytes(f, value)?;
+            }
+            Operation::Convert { base_type } | Operation::Reinterpret { base_type } => {
+                write!(f, " 0x{:x}", base_type.0.into_u64())?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<'a, R: Reader> fmt::Display for ExpressionDisplay<'a, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytecode = &self.expression.0;
+        let mut pc = bytecode.clone();
+        let mut first = true;
+        while !pc.is_empty() {
+            if !first {
+                f.write_str(", ")?;
+            }
+            first = false;
+
+            let opcode = match pc.clone().read_u8() {
+                Ok(opcode) => constants::DwOp(opcode),
+                Err(_) => return f.write_str("<decoding error>"),
+            };
+            match Operation::parse(&mut pc, bytecode, self.encoding) {
+                Ok(operation) => self.fmt_operation(f, opcode, operation, &pc)?,
+                Err(_) => return f.write_str("<decoding error>"),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Convert the textual form of a DWARF expression into its bytecode.
+///
+/// The text uses the syntax that is produced by
+/// [`Expression::display`](struct.Expression.html#method.display): a comma
+/// separated list of operations, each of which is the name of the opcode
+/// followed by its operands. Unsigned operands may be given in hexadecimal
+/// (with a `0x` prefix) or decimal, and signed operands may have a sign.
+/// Registers may be given by number, or by name if `name_to_register` is
+/// given, such as
+/// [`X86_64::name_to_register`](struct.X86_64.html#method.name_to_register).
+///
+/// Multi-byte operands are written using the given `encoding` and `endian`.
+///
+/// Returns `Error::InvalidExpressionText` with the position of the error
+/// if the text cannot be parsed.
+///
+/// ```
+/// use gimli::{assemble_expression, Encoding, Format, LittleEndian, X86_64};
+///
+/// let encoding = Encoding {
+///     format: Format::Dwarf32,
+///     version: 4,
+///     address_size: 8,
+/// };
+/// let bytecode = assemble_expression(
+///     "DW_OP_breg7 RSP+8, DW_OP_deref",
+///     encoding,
+///     LittleEndian,
+///     Some(X86_64::name_to_register),
+/// ).unwrap();
+/// assert_eq!(bytecode, [0x77, 0x08, 0x06]);
+/// ```
+pub fn assemble_expression<Endian: Endianity>(
+    text: &str,
+    encoding: Encoding,
+    endian: Endian,
+    name_to_register: Option<fn(&str) -> Option<Register>>,
+) -> Result<Vec<u8>> {
+    let mut parser = ExpressionParser {
+        text,
+        pos: 0,
+        token_start: 0,
+        encoding,
+        endian,
+        name_to_register,
+    };
+    let mut bytecode = Vec::new();
+    parser.parse_operations(&mut bytecode, false)?;
+    Ok(bytecode)
+}
+
+struct ExpressionParser<'a, Endian: Endianity> {
+    text: &'a str,
+    pos: usize,
+    token_start: usize,
+    encoding: Encoding,
+    endian: Endian,
+    name_to_register: Option<fn(&str) -> Option<Register>>,
+}
+
+impl<'a, Endian: Endianity> ExpressionParser<'a, Endian> {
+    fn error(&self) -> Error {
+        Error::InvalidExpressionText(self.pos)
+    }
+
+    fn token_error(&self) -> Error {
+        Error::InvalidExpressionText(self.token_start)
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Return true if there are no more operands for the current operation.
+    fn at_operation_end(&mut self) -> bool {
+        match self.peek() {
+            Some(c) => ",()".contains(c),
+            None => true,
+        }
+    }
+
+    fn token(&mut self) -> Result<&'a str> {
+        if self.at_operation_end() {
+            return Err(self.error());
+        }
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| c.is_whitespace() || c == ',' || c == '(' || c == ')')
+            .unwrap_or(rest.len());
+        self.token_start = self.pos;
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn unsigned(&mut self) -> Result<u64> {
+        let token = self.token()?;
+        parse_unsigned(token).ok_or_else(|| self.token_error())
+    }
+
+    fn signed(&mut self) -> Result<i64> {
+        let token = self.token()?;
+        parse_signed(token).ok_or_else(|| self.token_error())
+    }
+
+    fn parse_register(&self, token: &str) -> Option<Register> {
+        if token.starts_with(|c: char| c.is_ascii_digit()) {
+            let register = parse_unsigned(token)?;
+            if register > 0xffff {
+                return None;
+            }
+            Some(Register(register as u16))
+        } else {
+            self.name_to_register.and_then(|f| f(token))
+        }
+    }
+
+    fn register(&mut self) -> Result<Register> {
+        let token = self.token()?;
+        self.parse_register(token).ok_or_else(|| self.token_error())
+    }
+
+    /// Parse a register and offset, such as `RSP+8`, `0x7 +8`, or `+8`.
+    ///
+    /// The register may only be omitted if it is `fixed` by the opcode.
+    fn register_offset(&mut self, fixed: Option<Register>) -> Result<(Register, i64)> {
+        let token = self.token()?;
+        let signs = &['+', '-'][..];
+        let split = if token.starts_with(signs) {
+            0
+        } else {
+            token.find(signs).unwrap_or(token.len())
+        };
+        let (name, offset) = token.split_at(split);
+        let register = if name.is_empty() {
+            fixed.ok_or_else(|| self.token_error())?
+        } else {
+            let register = self
+                .parse_register(name)
+                .ok_or_else(|| self.token_error())?;
+            if fixed.is_some() && fixed != Some(register) {
+                return Err(self.token_error());
+            }
+            register
+        };
+        let offset = if offset.is_empty() {
+            self.signed()?
+        } else {
+            parse_signed(offset).ok_or_else(|| self.token_error())?
+        };
+        Ok((register, offset))
+    }
+
+    fn write_fixed(&self, out: &mut Vec<u8>, value: u64, size: u8) -> Result<()> {
+        let mut buf = [0; 8];
+        match size {
+            1 => buf[0] = value as u8,
+            2 => self.endian.write_u16(&mut buf[..2], value as u16),
+            4 => self.endian.write_u32(&mut buf[..4], value as u32),
+            8 => self.endian.write_u64(&mut buf, value),
+            _ => return Err(self.token_error()),
+        }
+        out.extend_from_slice(&buf[..size as usize]);
+        Ok(())
+    }
+
+    fn fixed_unsigned(&mut self, out: &mut Vec<u8>, size: u8) -> Result<()> {
+        let value = self.unsigned()?;
+        if size < 8 && value >> (size * 8) != 0 {
+            return Err(self.token_error());
+        }
+        self.write_fixed(out, value, size)
+    }
+
+    fn fixed_signed(&mut self, out: &mut Vec<u8>, size: u8) -> Result<()> {
+        let value = self.signed()?;
+        if size < 8 {
+            let shift = 64 - size * 8;
+            if (value << shift) >> shift != value {
+                return Err(self.token_error());
+            }
+        }
+        self.write_fixed(out, value as u64, size)
+    }
+
+    fn uleb128(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        let value = self.unsigned()?;
+        write_uleb128(out, value);
+        Ok(())
+    }
+
+    fn sleb128(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        let value = self.signed()?;
+        write_sleb128(out, value);
+        Ok(())
+    }
+
+    fn parse_operations(&mut self, out: &mut Vec<u8>, nested: bool) -> Result<()> {
+        match self.peek() {
+            None => return Ok(()),
+            Some(')') if nested => return Ok(()),
+            _ => {}
+        }
+        loop {
+            self.parse_operation(out)?;
+            if !self.eat(',') {
+                break;
+            }
+        }
+        match self.peek() {
+            None if !nested => Ok(()),
+            Some(')') if nested => Ok(()),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn parse_operation(&mut self, out: &mut Vec<u8>) -> Result<()> {
+        let name = self.token()?;
+        let opcode = (0..=255)
+            .map(constants::DwOp)
+            .find(|opcode| opcode.static_string() == Some(name))
+            .ok_or_else(|| self.token_error())?;
+        out.push(opcode.0);
+        match opcode {
+            constants::DW_OP_addr => {
+                let address_size = self.encoding.address_size;
+                self.fixed_unsigned(out, address_size)?;
+            }
+            constants::DW_OP_const1u
+            | constants::DW_OP_pick
+            | constants::DW_OP_deref_size
+            | constants::DW_OP_xderef_size => {
+                self.fixed_unsigned(out, 1)?;
+            }
+            constants::DW_OP_const1s => self.fixed_signed(out, 1)?,
+            constants::DW_OP_const2u | constants::DW_OP_call2 => self.fixed_unsigned(out, 2)?,
+            constants::DW_OP_const2s | constants::DW_OP_bra | constants::DW_OP_skip => {
+                self.fixed_signed(out, 2)?;
+            }
+            constants::DW_OP_const4u
+            | constants::DW_OP_call4
+            | constants::DW_OP_GNU_parameter_ref => {
+                self.fixed_unsigned(out, 4)?;
+            }
+            constants::DW_OP_const4s => self.fixed_signed(out, 4)?,
+            constants::DW_OP_const8u => self.fixed_unsigned(out, 8)?,
+            constants::DW_OP_const8s => self.fixed_signed(out, 8)?,
+            constants::DW_OP_constu
+            | constants::DW_OP_plus_uconst
+            | constants::DW_OP_piece
+            | constants::DW_OP_addrx
+            | constants::DW_OP_constx
+            | constants::DW_OP_convert
+            | constants::DW_OP_GNU_convert
+            | constants::DW_OP_reinterpret
+            | constants::DW_OP_GNU_reinterpret => {
+                self.uleb128(out)?;
+            }
+            constants::DW_OP_consts | constants::DW_OP_fbreg => self.sleb128(out)?,
+            constants::DW_OP_bit_piece => {
+                self.uleb128(out)?;
+                self.uleb128(out)?;
+            }
+            constants::DW_OP_call_ref => {
+                let word_size = self.encoding.format.word_size();
+                self.fixed_unsigned(out, word_size)?;
+            }
+            constants::DW_OP_implicit_pointer | constants::DW_OP_GNU_implicit_pointer => {
+                let word_size = self.encoding.format.word_size();
+                self.fixed_unsigned(out, word_size)?;
+                self.sleb128(out)?;
+            }
+            constants::DW_OP_deref_type
+            | constants::DW_OP_xderef_type
+            | constants::DW_OP_GNU_deref_type => {
+                self.fixed_unsigned(out, 1)?;
+                self.uleb128(out)?;
+            }
+            constants::DW_OP_implicit_value => {
+                let len = self.unsigned()?;
+                write_uleb128(out, len);
+                for _ in 0..len {
+                    self.fixed_unsigned(out, 1)?;
+                }
+            }
+            constants::DW_OP_const_type | constants::DW_OP_GNU_const_type => {
+                self.uleb128(out)?;
+                let len = self.unsigned()?;
+                if len > 0xff {
+                    return Err(self.token_error());
+                }
+                out.push(len as u8);
+                for _ in 0..len {
+                    self.fixed_unsigned(out, 1)?;
+                }
+            }
+            constants::DW_OP_entry_value | constants::DW_OP_GNU_entry_value => {
+                if !self.eat('(') {
+                    return Err(self.error());
+                }
+                let mut expression = Vec::new();
+                self.parse_operations(&mut expression, true)?;
+                if !self.eat(')') {
+                    return Err(self.error());
+                }
+                write_uleb128(out, expression.len() as u64);
+                out.extend_from_slice(&expression);
+            }
+            constants::DW_OP_regx => {
+                let register = self.register()?;
+                write_uleb128(out, register.0.into());
+            }
+            constants::DW_OP_bregx => {
+                let (register, offset) = self.register_offset(None)?;
+                write_uleb128(out, register.0.into());
+                write_sleb128(out, offset);
+            }
+            constants::DW_OP_regval_type | constants::DW_OP_GNU_regval_type => {
+                let register = self.register()?;
+                write_uleb128(out, register.0.into());
+                self.uleb128(out)?;
+            }
+            _ if opcode >= constants::DW_OP_reg0 && opcode <= constants::DW_OP_reg31 => {
+                // The register name is optional.
+                if !self.at_operation_end() {
+                    let fixed = Register((opcode.0 - constants::DW_OP_reg0.0).into());
+                    if self.register()? != fixed {
+                        return Err(self.token_error());
+                    }
+                }
+            }
+            _ if opcode >= constants::DW_OP_breg0 && opcode <= constants::DW_OP_breg31 => {
+                let fixed = Register((opcode.0 - constants::DW_OP_breg0.0).into());
+                let (_, offset) = self.register_offset(Some(fixed))?;
+                write_sleb128(out, offset);
+            }
+            _ if opcode >= constants::DW_OP_lit0 && opcode <= constants::DW_OP_lit31 => {}
+            constants::DW_OP_deref
+            | constants::DW_OP_dup
+            | constants::DW_OP_drop
+            | constants::DW_OP_over
+            | constants::DW_OP_swap
+            | constants::DW_OP_rot
+            | constants::DW_OP_xderef
+            | constants::DW_OP_abs
+            | constants::DW_OP_and
+            | constants::DW_OP_div
+            | constants::DW_OP_minus
+            | constants::DW_OP_mod
+            | constants::DW_OP_mul
+            | constants::DW_OP_neg
+            | constants::DW_OP_not
+            | constants::DW_OP_or
+            | constants::DW_OP_plus
+            | constants::DW_OP_shl
+            | constants::DW_OP_shr
+            | constants::DW_OP_shra
+            | constants::DW_OP_xor
+            | constants::DW_OP_eq
+            | constants::DW_OP_ge
+            | constants::DW_OP_gt
+            | constants::DW_OP_le
+            | constants::DW_OP_lt
+            | constants::DW_OP_ne
+            | constants::DW_OP_nop
+            | constants::DW_OP_push_object_address
+            | constants::DW_OP_form_tls_address
+            | constants::DW_OP_GNU_push_tls_address
+            | constants::DW_OP_call_frame_cfa
+            | constants::DW_OP_stack_value => {}
+            _ => return Err(self.token_error()),
+        }
+        Ok(())
+    }
+}
+
+fn parse_unsigned(token: &str) -> Option<u64> {
+    match token.get(..2) {
+        Some("0x") | Some("0X") => u64::from_str_radix(&token[2..], 16).ok(),
+        _ => token.parse().ok(),
+    }
+}
+
+fn parse_signed(token: &str) -> Option<i64> {
+    let negative = token.starts_with('-');
+    let magnitude = if negative || token.starts_with('+') {
+        parse_unsigned(token.split_at(1).1)?
+    } else {
+        parse_unsigned(token)?
+    };
+    if negative && magnitude <= 1 << 63 {
+        Some((magnitude as i64).wrapping_neg())
+    } else if !negative && magnitude < 1 << 63 {
+        Some(magnitude as i64)
+    } else {
+        None
+    }
+}
+
+fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arch::{Arm, X86_64};
+    use common::Format;
+    use endianity::{BigEndian, LittleEndian};
+    use read::EndianSlice;
+    use std::string::{String, ToString};
+
+    fn encoding() -> Encoding {
+        Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        }
+    }
+
+    fn disassemble(
+        bytecode: &[u8],
+        register_name: Option<fn(Register) -> Option<&'static str>>,
+    ) -> String {
+        let expression = Expression(EndianSlice::new(bytecode, LittleEndian));
+        expression.display(encoding(), register_name).to_string()
+    }
+
+    #[test]
+    fn test_expression_text_round_trip() {
+        let tests: &[(&[u8], &str)] = &[
+            (&[0x77, 0x08, 0x06], "DW_OP_breg7 RSP+8, DW_OP_deref"),
+            (&[0x50], "DW_OP_reg0 RAX"),
+            (&[0x91, 0x6c], "DW_OP_fbreg -20"),
+            (
+                &[0x03, 0x00, 0x10, 0, 0, 0, 0, 0, 0, 0x9f],
+                "DW_OP_addr 0x1000, DW_OP_stack_value",
+            ),
+            (&[0x30, 0x31, 0x22], "DW_OP_lit0, DW_OP_lit1, DW_OP_plus"),
+            (
+                &[0x08, 0xff, 0x09, 0x80],
+                "DW_OP_const1u 0xff, DW_OP_const1s -128",
+            ),
+            (&[0x10, 0xe5, 0x8e, 0x26], "DW_OP_constu 0x98765"),
+            (&[0x11, 0x7f], "DW_OP_consts -1"),
+            (
+                &[0x23, 0x10, 0x94, 0x04],
+                "DW_OP_plus_uconst 0x10, DW_OP_deref_size 0x4",
+            ),
+            (
+                &[0x2f, 0x02, 0x00, 0x96, 0x96],
+                "DW_OP_skip +2, DW_OP_nop, DW_OP_nop",
+            ),
+            (&[0x92, 0x07, 0x00], "DW_OP_bregx RSP+0"),
+            (&[0x90, 0x10], "DW_OP_regx RA"),
+            (
+                &[0x93, 0x04, 0x9d, 0x20, 0x00],
+                "DW_OP_piece 0x4, DW_OP_bit_piece 0x20 0x0",
+            ),
+            (
+                &[0x9e, 0x02, 0x01, 0x02],
+                "DW_OP_implicit_value 0x2 0x01 0x02",
+            ),
+            (
+                &[0xa3, 0x01, 0x55, 0x9f],
+                "DW_OP_entry_value(DW_OP_reg5 RDI), DW_OP_stack_value",
+            ),
+            (
+                &[0xa4, 0x2a, 0x04, 0x01, 0x00, 0x00, 0x00],
+                "DW_OP_const_type 0x2a 0x4 0x01 0x00 0x00 0x00",
+            ),
+            (&[0xa5, 0x03, 0x2a], "DW_OP_regval_type RBX 0x2a"),
+            (
+                &[0xa6, 0x08, 0x2a, 0xa8, 0x30],
+                "DW_OP_deref_type 0x8 0x2a, DW_OP_convert 0x30",
+            ),
+            (&[0xe0], "DW_OP_GNU_push_tls_address"),
+        ];
+        for &(bytecode, text) in tests {
+            assert_eq!(disassemble(bytecode, Some(X86_64::register_name)), text);
+            assert_eq!(
+                assemble_expression(
+                    text,
+                    encoding(),
+                    LittleEndian,
+                    Some(X86_64::name_to_register)
+                ),
+                Ok(bytecode.to_vec())
+            );
+        }
+    }
+
+    #[test]
+    fn test_expression_text_registers() {
+        let bytecode = [0x77, 0x08, 0x55, 0x92, 0x07, 0x7c];
+        let text = "DW_OP_breg7 +8, DW_OP_reg5, DW_OP_bregx 0x7 -4";
+        assert_eq!(disassemble(&bytecode, None), text);
+        assert_eq!(
+            assemble_expression(text, encoding(), LittleEndian, None),
+            Ok(bytecode.to_vec())
+        );
+
+        // Register names are case insensitive, and the offset may be separate.
+        assert_eq!(
+            assemble_expression(
+                "DW_OP_breg7 rsp +8, DW_OP_reg5 rdi, DW_OP_bregx rsp-4",
+                encoding(),
+                LittleEndian,
+                Some(X86_64::name_to_register)
+            ),
+            Ok(bytecode.to_vec())
+        );
+
+        assert_eq!(
+            disassemble(&[0x7d, 0x04], Some(Arm::register_name)),
+            "DW_OP_breg13 R13+4"
+        );
+
+        // The register name must match the register of the opcode.
+        assert_eq!(
+            assemble_expression(
+                "DW_OP_breg7 RBP+8",
+                encoding(),
+                LittleEndian,
+                Some(X86_64::name_to_register)
+            ),
+            Err(Error::InvalidExpressionText(12))
+        );
+        // Register names require a lookup function.
+        assert_eq!(
+            assemble_expression("DW_OP_regx RSP", encoding(), LittleEndian, None),
+            Err(Error::InvalidExpressionText(11))
+        );
+    }
+
+    #[test]
+    fn test_expression_text_big_endian() {
+        let bytecode = [0x0a, 0x12, 0x34, 0x0c, 0x12, 0x34, 0x56, 0x78];
+        let text = "DW_OP_const2u 0x1234, DW_OP_const4u 0x12345678";
+        let expression = Expression(EndianSlice::new(&bytecode, BigEndian));
+        assert_eq!(expression.display(encoding(), None).to_string(), text);
+        assert_eq!(
+            assemble_expression(text, encoding(), BigEndian, None),
+            Ok(bytecode.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_expression_text_errors() {
+        assert_eq!(
+            disassemble(&[0x30, 0xff], None),
+            "DW_OP_lit0, <decoding error>"
+        );
+        assert_eq!(disassemble(&[0x08], None), "<decoding error>");
+
+        let tests = [
+            ("DW_OP_foo", 0),
+            ("DW_OP_lit0 DW_OP_lit1", 11),
+            ("DW_OP_lit0,", 11),
+            ("DW_OP_const1u 0x100", 14),
+            ("DW_OP_const1s 128", 14),
+            ("DW_OP_fbreg", 11),
+            ("DW_OP_fbreg x", 12),
+            ("DW_OP_entry_value(DW_OP_reg5", 28),
+            ("DW_OP_lo_user", 0),
+        ];
+        for &(text, position) in &tests {
+            assert_eq!(
+                assemble_expression(text, encoding(), LittleEndian, None),
+                Err(Error::InvalidExpressionText(position)),
+                "{}",
+                text
+            );
+        }
+        assert_eq!(
+            assemble_expression("", encoding(), LittleEndian, None),
+            Ok(Vec::new())
+        );
+    }
+}
@@ -167,6 +167,12 @@ impl<R: Reader> RangeLists<R> {
         }
     }
 
+    /// Return the `.debug_ranges` section.
+    #[inline]
+    pub(crate) fn debug_ranges(&self) -> &DebugRanges<R> {
+        &self.debug_ranges
+    }
+
     /// Iterate over the `Range` list entries starting at the given offset.
     ///
     /// The `unit_version` and `address_size` must match the compilation unit that the
@@ -0,0 +1,619 @@
+use vec::Vec;
+
+use common::{DebugInfoOffset, UnitSectionOffset};
+use constants;
+use read::{
+    AttributeValue, ColumnType, CompleteLineProgram, DebuggingInformationEntry, Dwarf,
+    EntriesTreeNode, LineSequence, Reader, Result, Unit, UnitOffset, UnitRangeIndex,
+};
+
+/// The maximum number of `DW_AT_abstract_origin` and `DW_AT_specification`
+/// references that are followed when looking for the name of a function.
+const MAX_NAME_DEPTH: usize = 16;
+
+/// A location in the source code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation<R: Reader> {
+    /// The directory of the source file.
+    ///
+    /// This may be relative to the compilation directory of the unit.
+    pub directory: Option<R>,
+
+    /// The path name of the source file.
+    ///
+    /// This may be relative to `directory`.
+    pub file: Option<R>,
+
+    /// The line number, or `None` if unknown.
+    pub line: Option<u64>,
+
+    /// The column number, or `None` if unknown or the location is the
+    /// left edge of the line.
+    pub column: Option<u64>,
+}
+
+/// A frame in the stack of inlined functions at an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolFrame<R: Reader> {
+    /// The name of the function, or `None` if the address is not within a
+    /// function, or the function has no name.
+    ///
+    /// This is the linkage name if there is one, including one found through
+    /// `DW_AT_abstract_origin` or `DW_AT_specification`, and otherwise the
+    /// plain name.
+    pub function: Option<R>,
+
+    /// The source location within the function.
+    ///
+    /// For the innermost frame this is the location of the address, and for
+    /// the other frames this is the call site of the inlined function in the
+    /// next frame.
+    pub location: Option<SourceLocation<R>>,
+}
+
+/// Find the functions and source locations for addresses.
+///
+/// The units of the `Dwarf` are parsed when they are first needed, and
+/// are cached for subsequent lookups.
+#[derive(Debug)]
+pub struct Symbolizer<'dwarf, R: Reader + 'dwarf> {
+    dwarf: &'dwarf Dwarf<R>,
+    unit_ranges: UnitRangeIndex<R::Offset>,
+    /// The offsets of the headers of all units, in increasing order.
+    unit_offsets: Vec<DebugInfoOffset<R::Offset>>,
+    units: Vec<SymbolizerUnit<R>>,
+}
+
+impl<'dwarf, R: Reader> Symbolizer<'dwarf, R> {
+    /// Construct a new `Symbolizer` for the given DWARF sections.
+    ///
+    /// This builds a `UnitRangeIndex` for the units of `dwarf`.
+    pub fn new(dwarf: &'dwarf Dwarf<R>) -> Result<Self> {
+        let mut unit_offsets = Vec::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            unit_offsets.push(header.offset());
+        }
+        Ok(Symbolizer {
+            dwarf,
+            unit_ranges: UnitRangeIndex::new(dwarf)?,
+            unit_offsets,
+            units: Vec::new(),
+        })
+    }
+
+    /// Return the stack of frames for the given address.
+    ///
+    /// The innermost inlined function is first, and the function that
+    /// contains the address in the object code is last.
+    ///
+    /// Returns an empty vector if no unit contains the address.
+    pub fn find_frames(&mut self, address: u64) -> Result<Vec<SymbolFrame<R>>> {
+        let offset = match self.unit_ranges.find_unit_offset(address) {
+            Some(offset) => offset,
+            None => return Ok(Vec::new()),
+        };
+        let index = self.unit_index(offset)?;
+        let frames = self.units[index].find_frames(self.dwarf, address)?;
+        let mut result = Vec::with_capacity(frames.len());
+        for (name, location) in frames {
+            let function = match name {
+                Some(name) => self.resolve_name(name)?,
+                None => None,
+            };
+            result.push(SymbolFrame { function, location });
+        }
+        Ok(result)
+    }
+
+    /// Return the source location for the given address, ignoring inlined
+    /// functions.
+    pub fn find_location(&mut self, address: u64) -> Result<Option<SourceLocation<R>>> {
+        let offset = match self.unit_ranges.find_unit_offset(address) {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+        let index = self.unit_index(offset)?;
+        self.units[index].find_location(self.dwarf, address)
+    }
+
+    /// Finish searching for a name by following references to other units.
+    fn resolve_name(&mut self, mut name: EntryName<R>) -> Result<Option<R>> {
+        while let Some(offset) = name.next.take() {
+            let index = match self.unit_offsets.binary_search(&offset) {
+                Ok(index) => index,
+                Err(0) => break,
+                Err(index) => index - 1,
+            };
+            let index = self.unit_index(self.unit_offsets[index])?;
+            let unit = &self.units[index];
+            match UnitSectionOffset::DebugInfoOffset(offset).to_unit_offset(&unit.unit) {
+                Some(offset) => unit.entry_name_at(self.dwarf, offset, &mut name)?,
+                None => break,
+            }
+        }
+        Ok(name.linkage_name.or(name.name))
+    }
+
+    fn unit_index(&mut self, offset: DebugInfoOffset<R::Offset>) -> Result<usize> {
+        match self.units.binary_search_by_key(&offset, |unit| unit.offset) {
+            Ok(index) => Ok(index),
+            Err(index) => {
+                let header = self.dwarf.debug_info.header_from_offset(offset)?;
+                let mut unit = Unit::new(self.dwarf, header)?;
+                let (program, sequences) = match unit.line_program.take() {
+                    Some(program) => {
+                        let (program, mut sequences) = program.sequences()?;
+                        sequences.sort_by_key(|sequence| sequence.start);
+                        (Some(program), sequences)
+                    }
+                    None => (None, Vec::new()),
+                };
+                self.units.insert(
+                    index,
+                    SymbolizerUnit {
+                        offset,
+                        unit,
+                        program,
+                        sequences,
+                    },
+                );
+                Ok(index)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SymbolizerUnit<R: Reader> {
+    offset: DebugInfoOffset<R::Offset>,
+    unit: Unit<R>,
+    program: Option<CompleteLineProgram<R, R::Offset>>,
+    sequences: Vec<LineSequence<R>>,
+}
+
+/// A function entry that contains the address being symbolized.
+struct Function<R: Reader> {
+    name: EntryName<R>,
+    call_file: Option<u64>,
+    call_line: Option<u64>,
+    call_column: Option<u64>,
+}
+
+/// The state of the search for the name of a function entry.
+///
+/// A linkage name anywhere in the chain of `DW_AT_abstract_origin` and
+/// `DW_AT_specification` references is preferred over a `DW_AT_name`.
+struct EntryName<R: Reader> {
+    linkage_name: Option<R>,
+    /// The first `DW_AT_name` in the chain.
+    name: Option<R>,
+    /// A reference to an entry in another unit that is still to be searched.
+    next: Option<DebugInfoOffset<R::Offset>>,
+    /// The number of references that may still be followed.
+    depth: usize,
+}
+
+/// A frame with a function name that may still need to be resolved using
+/// other units.
+type UnitFrame<R> = (Option<EntryName<R>>, Option<SourceLocation<R>>);
+
+impl<R: Reader> SymbolizerUnit<R> {
+    fn find_frames(&self, dwarf: &Dwarf<R>, address: u64) -> Result<Vec<UnitFrame<R>>> {
+        let mut functions = Vec::new();
+        {
+            let mut tree = self.unit.entries_tree(None)?;
+            let root = tree.root()?;
+            self.find_functions(dwarf, root, address, &mut functions)?;
+        }
+
+        let mut location = self.find_location(dwarf, address)?;
+        if functions.is_empty() {
+            return Ok(vec![(None, location)]);
+        }
+
+        let mut frames = Vec::with_capacity(functions.len());
+        for function in functions.into_iter().rev() {
+            frames.push((Some(function.name), location));
+            location = if function.call_file.is_some() || function.call_line.is_some() {
+                Some(self.location(
+                    dwarf,
+                    function.call_file,
+                    function.call_line,
+                    function.call_column,
+                )?)
+            } else {
+                None
+            };
+        }
+        Ok(frames)
+    }
+
+    /// Append the chain of subprograms and inlined subroutines that contain
+    /// the address, starting with the children of `node`.
+    fn find_functions(
+        &self,
+        dwarf: &Dwarf<R>,
+        node: EntriesTreeNode<R>,
+        address: u64,
+        functions: &mut Vec<Function<R>>,
+    ) -> Result<()> {
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            let (has_ranges, contains) = {
+                let mut has_ranges = false;
+                let mut contains = false;
+                dwarf.die_ranges(&self.unit, child.entry(), |range| {
+                    has_ranges = true;
+                    if range.begin <= address && address < range.end {
+                        contains = true;
+                    }
+                })?;
+                (has_ranges, contains)
+            };
+            let tag = child.entry().tag();
+            let is_function =
+                tag == constants::DW_TAG_subprogram || tag == constants::DW_TAG_inlined_subroutine;
+            if contains {
+                if is_function {
+                    functions.push(self.function(dwarf, child.entry())?);
+                }
+                // Only one child at each level can contain the address.
+                return self.find_functions(dwarf, child, address, functions);
+            } else if !has_ranges && !is_function {
+                // Namespaces and types may contain functions.
+                self.find_functions(dwarf, child, address, functions)?;
+                if !functions.is_empty() {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn function(
+        &self,
+        dwarf: &Dwarf<R>,
+        entry: &DebuggingInformationEntry<R, R::Offset>,
+    ) -> Result<Function<R>> {
+        let mut name = EntryName {
+            linkage_name: None,
+            name: None,
+            next: None,
+            depth: MAX_NAME_DEPTH,
+        };
+        if let Some(offset) = self.entry_name(dwarf, entry, &mut name)? {
+            self.entry_name_at(dwarf, offset, &mut name)?;
+        }
+        let mut function = Function {
+            name,
+            call_file: None,
+            call_line: None,
+            call_column: None,
+        };
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_call_file => function.call_file = attr.udata_value(),
+                constants::DW_AT_call_line => {
+                    function.call_line = attr.udata_value().filter(|&line| line != 0)
+                }
+                constants::DW_AT_call_column => {
+                    function.call_column = attr.udata_value().filter(|&column| column != 0)
+                }
+                _ => {}
+            }
+        }
+        Ok(function)
+    }
+
+    /// Search for the name of the entry at the given offset, following
+    /// references within this unit.
+    fn entry_name_at(
+        &self,
+        dwarf: &Dwarf<R>,
+        offset: UnitOffset<R::Offset>,
+        name: &mut EntryName<R>,
+    ) -> Result<()> {
+        let mut next = Some(offset);
+        while let Some(offset) = next {
+            let mut entries = self.unit.entries_at_offset(offset)?;
+            next = match entries.next_entry()?.and_then(|_| entries.current()) {
+                Some(entry) => self.entry_name(dwarf, entry, name)?,
+                None => None,
+            };
+        }
+        Ok(())
+    }
+
+    /// Search the attributes of an entry for its name.
+    ///
+    /// Returns the offset of the next entry to search if there is a reference
+    /// to another entry in this unit. A reference to another unit is stored
+    /// in `name.next` instead.
+    fn entry_name(
+        &self,
+        dwarf: &Dwarf<R>,
+        entry: &DebuggingInformationEntry<R, R::Offset>,
+        name: &mut EntryName<R>,
+    ) -> Result<Option<UnitOffset<R::Offset>>> {
+        let mut next = None;
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_linkage_name | constants::DW_AT_MIPS_linkage_name => {
+                    name.linkage_name = Some(dwarf.attr_string(&self.unit, attr.value())?);
+                    return Ok(None);
+                }
+                constants::DW_AT_name if name.name.is_none() => {
+                    name.name = Some(dwarf.attr_string(&self.unit, attr.value())?);
+                }
+                constants::DW_AT_abstract_origin | constants::DW_AT_specification => {
+                    next = Some(attr.value())
+                }
+                _ => {}
+            }
+        }
+        if name.depth == 0 {
+            return Ok(None);
+        }
+        name.depth -= 1;
+
+        match next {
+            Some(AttributeValue::UnitRef(offset)) => Ok(Some(offset)),
+            Some(AttributeValue::DebugInfoRef(offset)) => {
+                let unit_offset =
+                    UnitSectionOffset::DebugInfoOffset(offset).to_unit_offset(&self.unit);
+                if unit_offset.is_none() {
+                    name.next = Some(offset);
+                }
+                Ok(unit_offset)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn find_location(&self, dwarf: &Dwarf<R>, address: u64) -> Result<Option<SourceLocation<R>>> {
+        let program = match self.program {
+            Some(ref program) => program,
+            None => return Ok(None),
+        };
+        let sequences = &self.sequences;
+        let index = match sequences.binary_search_by_key(&address, |sequence| sequence.start) {
+            Ok(index) => index,
+            Err(0) => return Ok(None),
+            Err(index) => index - 1,
+        };
+        let sequence = &sequences[index];
+        if address >= sequence.end {
+            return Ok(None);
+        }
+
+        let mut found = None;
+        let mut rows = program.resume_from(sequence);
+        while let Some((_, row)) = rows.next_row()? {
+            if row.end_sequence() || row.address() > address {
+                break;
+            }
+            let column = match row.column() {
+                ColumnType::LeftEdge => None,
+                ColumnType::Column(column) => Some(column),
+            };
+            found = Some((row.file_index(), row.line(), column));
+        }
+        match found {
+            Some((file, line, column)) => {
+                Ok(Some(self.location(dwarf, Some(file), line, column)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn location(
+        &self,
+        dwarf: &Dwarf<R>,
+        file: Option<u64>,
+        line: Option<u64>,
+        column: Option<u64>,
+    ) -> Result<SourceLocation<R>> {
+        let mut location = SourceLocation {
+            directory: None,
+            file: None,
+            line,
+            column,
+        };
+        let header = match self.program {
+            Some(ref program) => program.header(),
+            None => return Ok(location),
+        };
+        if let Some(file) = file.and_then(|file| header.file(file)) {
+            if let Some(directory) = file.directory(header) {
+                location.directory = Some(dwarf.attr_string(&self.unit, directory)?);
+            }
+            location.file = Some(dwarf.attr_string(&self.unit, file.path_name())?);
+        }
+        Ok(location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
+    use super::*;
+    use read::{DebugAbbrev, DebugInfo, DebugLine, EndianSlice};
+    use test_util::GimliSectionMethods;
+    use LittleEndian;
+
+    #[test]
+    fn test_symbolizer() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev = Section::with_endian(Endian::Little)
+            .D8(1).uleb(constants::DW_TAG_compile_unit.0.into()).D8(1)
+            .uleb(constants::DW_AT_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_low_pc.0.into()).uleb(constants::DW_FORM_addr.0.into())
+            .uleb(constants::DW_AT_high_pc.0.into()).uleb(constants::DW_FORM_data8.0.into())
+            .uleb(constants::DW_AT_stmt_list.0.into()).uleb(constants::DW_FORM_sec_offset.0.into())
+            .D8(0).D8(0)
+            .D8(2).uleb(constants::DW_TAG_subprogram.0.into()).D8(0)
+            .uleb(constants::DW_AT_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .D8(0).D8(0)
+            .D8(3).uleb(constants::DW_TAG_subprogram.0.into()).D8(1)
+            .uleb(constants::DW_AT_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_linkage_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_low_pc.0.into()).uleb(constants::DW_FORM_addr.0.into())
+            .uleb(constants::DW_AT_high_pc.0.into()).uleb(constants::DW_FORM_data8.0.into())
+            .D8(0).D8(0)
+            .D8(4).uleb(constants::DW_TAG_inlined_subroutine.0.into()).D8(0)
+            .uleb(constants::DW_AT_abstract_origin.0.into()).uleb(constants::DW_FORM_ref4.0.into())
+            .uleb(constants::DW_AT_low_pc.0.into()).uleb(constants::DW_FORM_addr.0.into())
+            .uleb(constants::DW_AT_high_pc.0.into()).uleb(constants::DW_FORM_data8.0.into())
+            .uleb(constants::DW_AT_call_file.0.into()).uleb(constants::DW_FORM_data1.0.into())
+            .uleb(constants::DW_AT_call_line.0.into()).uleb(constants::DW_FORM_data1.0.into())
+            .uleb(constants::DW_AT_call_column.0.into()).uleb(constants::DW_FORM_data1.0.into())
+            .D8(0).D8(0)
+            .D8(5).uleb(constants::DW_TAG_subprogram.0.into()).D8(0)
+            .uleb(constants::DW_AT_linkage_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .D8(0).D8(0)
+            .D8(6).uleb(constants::DW_TAG_subprogram.0.into()).D8(0)
+            .uleb(constants::DW_AT_name.0.into()).uleb(constants::DW_FORM_string.0.into())
+            .uleb(constants::DW_AT_specification.0.into()).uleb(constants::DW_FORM_ref_addr.0.into())
+            .uleb(constants::DW_AT_low_pc.0.into()).uleb(constants::DW_FORM_addr.0.into())
+            .uleb(constants::DW_AT_high_pc.0.into()).uleb(constants::DW_FORM_data8.0.into())
+            .D8(0).D8(0)
+            .D8(0);
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let length1 = Label::new();
+        let start1 = Label::new();
+        let end1 = Label::new();
+        let length2 = Label::new();
+        let start2 = Label::new();
+        let end2 = Label::new();
+        let origin = Label::new();
+        let declaration = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let info = Section::with_endian(Endian::Little)
+            .set_start_const(0)
+            .L32(&length1).mark(&start1).L16(4).L32(0).D8(8)
+            .D8(1).append_bytes(b"a.c\0").L64(0x1000).L64(0x40).L32(0)
+                .mark(&origin)
+                .D8(2).append_bytes(b"inline_fn\0")
+                .D8(3).append_bytes(b"main\0").append_bytes(b"_main\0").L64(0x1000).L64(0x40)
+                    .D8(4).L32(&origin).L64(0x1010).L64(0x10).D8(1).D8(11).D8(3)
+                .D8(0)
+                .mark(&declaration)
+                .D8(5).append_bytes(b"_ZN1a1fEv\0")
+            .D8(0)
+            .mark(&end1)
+            // A second unit with a function whose linkage name is in the
+            // declaration in the first unit.
+            .L32(&length2).mark(&start2).L16(4).L32(0).D8(8)
+            .D8(1).append_bytes(b"b.c\0").L64(0x2000).L64(0x10).L32(0)
+                .D8(6).append_bytes(b"f\0").L32(&declaration).L64(0x2000).L64(0x10)
+            .D8(0)
+            .mark(&end2);
+        length1.set_const((&end1 - &start1) as u64);
+        length2.set_const((&end2 - &start2) as u64);
+        let info = info.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let header_length = Label::new();
+        let header_start = Label::new();
+        let header_end = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let line = Section::with_endian(Endian::Little)
+            .L32(&length).mark(&start).L16(4).L32(&header_length).mark(&header_start)
+            // Minimum instruction length, maximum operations, default is_stmt.
+            .D8(1).D8(1).D8(1)
+            // Line base, line range, opcode base.
+            .D8(-5i8 as u8).D8(14).D8(13)
+            .append_bytes(&[0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1])
+            .append_bytes(b"dir\0").D8(0)
+            .append_bytes(b"a.c\0").uleb(1).uleb(0).uleb(0)
+            .append_bytes(b"b.h\0").uleb(1).uleb(0).uleb(0)
+            .D8(0)
+            .mark(&header_end)
+            .D8(0).uleb(9).D8(constants::DW_LNE_set_address.0).L64(0x1000)
+            .D8(constants::DW_LNS_advance_line.0).sleb(9)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_set_file.0).uleb(2)
+            .D8(constants::DW_LNS_advance_line.0).sleb(10)
+            .D8(constants::DW_LNS_set_column.0).uleb(7)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(0x10)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_set_file.0).uleb(1)
+            .D8(constants::DW_LNS_advance_line.0).sleb(-8)
+            .D8(constants::DW_LNS_set_column.0).uleb(0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(0x10)
+            .D8(constants::DW_LNS_copy.0)
+            .D8(constants::DW_LNS_advance_pc.0).uleb(0x20)
+            .D8(0).uleb(1).D8(constants::DW_LNE_end_sequence.0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        header_length.set_const((&header_end - &header_start) as u64);
+        let line = line.get_contents().unwrap();
+
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_info: DebugInfo::new(&info, LittleEndian),
+            debug_line: DebugLine::new(&line, LittleEndian),
+            ..Default::default()
+        };
+        let mut symbolizer = Symbolizer::new(&dwarf).unwrap();
+
+        let string = |s: &'static str| Some(EndianSlice::new(s.as_bytes(), LittleEndian));
+        let location = |directory, file, line, column| {
+            Some(SourceLocation {
+                directory: string(directory),
+                file: string(file),
+                line: Some(line),
+                column,
+            })
+        };
+
+        assert_eq!(
+            symbolizer.find_frames(0x1000),
+            Ok(vec![SymbolFrame {
+                function: string("_main"),
+                location: location("dir", "a.c", 10, None),
+            }])
+        );
+        assert_eq!(
+            symbolizer.find_frames(0x1015),
+            Ok(vec![
+                SymbolFrame {
+                    function: string("inline_fn"),
+                    location: location("dir", "b.h", 20, Some(7)),
+                },
+                SymbolFrame {
+                    function: string("_main"),
+                    location: location("dir", "a.c", 11, Some(3)),
+                },
+            ])
+        );
+        assert_eq!(
+            symbolizer.find_frames(0x103f),
+            Ok(vec![SymbolFrame {
+                function: string("_main"),
+                location: location("dir", "a.c", 12, None),
+            }])
+        );
+        assert_eq!(
+            symbolizer.find_location(0x1015),
+            Ok(location("dir", "b.h", 20, Some(7)))
+        );
+        assert_eq!(symbolizer.find_frames(0x1040), Ok(Vec::new()));
+        assert_eq!(
+            symbolizer.find_frames(0x2004),
+            Ok(vec![SymbolFrame {
+                function: string("_ZN1a1fEv"),
+                location: None,
+            }])
+        );
+        assert_eq!(symbolizer.find_location(0xfff), Ok(None));
+    }
+}
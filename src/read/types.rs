@@ -0,0 +1,1185 @@
+use collections::btree_map;
+use vec::Vec;
+
+use common::{DebugInfoOffset, DebugTypeSignature, DebugTypesOffset, Encoding, UnitSectionOffset};
+use constants;
+use endianity::Endianity;
+use read::{
+    AttributeValue, DebuggingInformationEntry, Dwarf, EntriesTreeNode, Error, Operation, Reader,
+    Result, Unit, UnitHeader, UnitOffset, UnitType, ValueType,
+};
+
+/// The maximum number of typedefs and qualifiers that are followed when
+/// looking for an unqualified type.
+const MAX_TYPE_DEPTH: usize = 16;
+
+/// A reference to a type DIE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeRef<T = usize> {
+    /// The section offset of a type DIE in this file, which can be parsed
+    /// with [`TypeTable::get`](struct.TypeTable.html#method.get).
+    Offset(UnitSectionOffset<T>),
+
+    /// The offset of a type DIE in the `.debug_info` section of the
+    /// supplementary object file, such as those created by `dwz`.
+    ///
+    /// These references are not followed by a `TypeTable`, but the type can
+    /// be parsed with a separate `TypeTable` for the supplementary file.
+    Sup(DebugInfoOffset<T>),
+}
+
+/// A type parsed from a type DIE.
+///
+/// References to other types are stored as a `TypeRef`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Type<R: Reader> {
+    /// The section offset of the type DIE.
+    pub offset: UnitSectionOffset<R::Offset>,
+
+    /// The tag of the type DIE.
+    pub tag: constants::DwTag,
+
+    /// The `DW_AT_name` attribute of the type.
+    pub name: Option<R>,
+
+    /// The `DW_AT_byte_size` attribute of the type.
+    pub byte_size: Option<u64>,
+
+    /// The information that is specific to the kind of type.
+    pub kind: TypeKind<R>,
+}
+
+/// The information that is specific to a kind of type.
+///
+/// A referenced type of `None` means that the DIE has no `DW_AT_type`
+/// attribute, such as for a pointer to `void`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeKind<R: Reader> {
+    /// A `DW_TAG_base_type`.
+    Base(BaseType),
+
+    /// A `DW_TAG_unspecified_type`, such as `decltype(nullptr)`.
+    Unspecified,
+
+    /// A `DW_TAG_pointer_type` to the given type.
+    Pointer(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_reference_type` to the given type.
+    Reference(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_rvalue_reference_type` to the given type.
+    RvalueReference(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_typedef` for the given type.
+    Typedef(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_const_type` qualifying the given type.
+    Const(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_volatile_type` qualifying the given type.
+    Volatile(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_restrict_type` qualifying the given type.
+    Restrict(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_atomic_type` qualifying the given type.
+    Atomic(Option<TypeRef<R::Offset>>),
+
+    /// A `DW_TAG_structure_type`.
+    Struct(CompositeType<R>),
+
+    /// A `DW_TAG_class_type`.
+    Class(CompositeType<R>),
+
+    /// A `DW_TAG_union_type`.
+    Union(CompositeType<R>),
+
+    /// A `DW_TAG_enumeration_type`.
+    Enumeration(EnumerationType<R>),
+
+    /// A `DW_TAG_array_type`.
+    Array(ArrayType<R>),
+
+    /// A `DW_TAG_subroutine_type`.
+    Subroutine(SubroutineType<R>),
+
+    /// A DIE with a tag that is not supported by this type model.
+    Unknown,
+}
+
+impl<R: Reader> TypeKind<R> {
+    /// Return the type referenced by a pointer, reference, typedef, or
+    /// qualifier.
+    ///
+    /// Returns `None` for other kinds of types, or if there is no
+    /// referenced type.
+    pub fn target(&self) -> Option<TypeRef<R::Offset>> {
+        match *self {
+            TypeKind::Pointer(target)
+            | TypeKind::Reference(target)
+            | TypeKind::RvalueReference(target)
+            | TypeKind::Typedef(target)
+            | TypeKind::Const(target)
+            | TypeKind::Volatile(target)
+            | TypeKind::Restrict(target)
+            | TypeKind::Atomic(target) => target,
+            _ => None,
+        }
+    }
+}
+
+/// A base type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BaseType {
+    /// The `DW_AT_encoding` attribute of the type.
+    pub encoding: Option<constants::DwAte>,
+
+    /// The `DW_AT_bit_size` attribute of the type.
+    pub bit_size: Option<u64>,
+
+    /// The type that is used for this base type when evaluating DWARF
+    /// expressions, or `None` if there is no such type.
+    pub value_type: Option<ValueType>,
+}
+
+/// A structure, class, or union type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositeType<R: Reader> {
+    /// True if this is only a declaration of the type.
+    pub declaration: bool,
+
+    /// The `DW_TAG_inheritance` children of the type.
+    pub inheritance: Vec<Member<R>>,
+
+    /// The `DW_TAG_member` children of the type, excluding static members.
+    pub members: Vec<Member<R>>,
+}
+
+/// A data member or inherited base of a composite type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member<R: Reader> {
+    /// The `DW_AT_name` attribute of the member.
+    pub name: Option<R>,
+
+    /// The type of the member.
+    pub ty: Option<TypeRef<R::Offset>>,
+
+    /// The offset in bits of the member from the start of the containing type.
+    ///
+    /// This is converted from `DW_AT_bit_offset` for bit fields in DWARF
+    /// versions before 4. It is `None` if the offset is not a constant, such
+    /// as for virtual base classes.
+    pub bit_offset: Option<u64>,
+
+    /// The `DW_AT_bit_size` attribute of the member, which is only present
+    /// for bit fields.
+    pub bit_size: Option<u64>,
+}
+
+impl<R: Reader> Member<R> {
+    /// Return the offset in bytes of the member from the start of the
+    /// containing type, or `None` if it is not a whole number of bytes.
+    pub fn byte_offset(&self) -> Option<u64> {
+        match self.bit_offset {
+            Some(bit_offset) if bit_offset % 8 == 0 => Some(bit_offset / 8),
+            _ => None,
+        }
+    }
+}
+
+/// An enumeration type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnumerationType<R: Reader> {
+    /// True if this is only a declaration of the type.
+    pub declaration: bool,
+
+    /// The underlying type of the enumeration.
+    pub ty: Option<TypeRef<R::Offset>>,
+
+    /// The `DW_TAG_enumerator` children of the type.
+    pub enumerators: Vec<Enumerator<R>>,
+}
+
+/// An enumerator of an enumeration type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Enumerator<R: Reader> {
+    /// The `DW_AT_name` attribute of the enumerator.
+    pub name: Option<R>,
+
+    /// The `DW_AT_const_value` attribute of the enumerator.
+    ///
+    /// Values using `DW_FORM_data*` or `DW_FORM_udata` are stored as their
+    /// two's complement bit pattern.
+    pub value: Option<i64>,
+}
+
+/// An array type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArrayType<R: Reader> {
+    /// The type of the array elements.
+    pub ty: Option<TypeRef<R::Offset>>,
+
+    /// The dimensions of the array, from the outermost to the innermost.
+    pub dimensions: Vec<Subrange<R>>,
+}
+
+/// A `DW_TAG_subrange_type` describing a dimension of an array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subrange<R: Reader> {
+    /// The type of the index.
+    pub ty: Option<TypeRef<R::Offset>>,
+
+    /// The `DW_AT_lower_bound` attribute, if it is a constant.
+    pub lower_bound: Option<i64>,
+
+    /// The `DW_AT_upper_bound` attribute, if it is a constant.
+    pub upper_bound: Option<i64>,
+
+    /// The `DW_AT_count` attribute, if it is a constant.
+    pub count: Option<u64>,
+}
+
+impl<R: Reader> Subrange<R> {
+    /// Return the number of elements in this dimension.
+    ///
+    /// This uses `DW_AT_count` if present, and otherwise the difference
+    /// between the bounds. A missing lower bound is assumed to be 0.
+    pub fn element_count(&self) -> Option<u64> {
+        if self.count.is_some() {
+            return self.count;
+        }
+        let lower = self.lower_bound.unwrap_or(0);
+        let upper = self.upper_bound?;
+        if upper < lower {
+            return Some(0);
+        }
+        Some((upper as u64).wrapping_sub(lower as u64).wrapping_add(1))
+    }
+}
+
+/// A subroutine type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubroutineType<R: Reader> {
+    /// The return type of the subroutine.
+    pub ty: Option<TypeRef<R::Offset>>,
+
+    /// The types of the `DW_TAG_formal_parameter` children.
+    pub parameters: Vec<Option<TypeRef<R::Offset>>>,
+
+    /// True if there is a `DW_TAG_unspecified_parameters` child.
+    pub variadic: bool,
+}
+
+/// Parse type DIEs into `Type`s.
+///
+/// This resolves `DW_AT_type` references within a unit, to other units in
+/// the `.debug_info` section, and to type units using their signature.
+/// References to the supplementary object file are returned as
+/// `TypeRef::Sup` without being resolved.
+///
+/// The unit headers of the `Dwarf` are read when they are first needed, and
+/// units are parsed and cached as their types are parsed.
+#[derive(Debug)]
+pub struct TypeTable<'dwarf, R: Reader + 'dwarf> {
+    dwarf: &'dwarf Dwarf<R>,
+    loaded: bool,
+    units: Vec<TypeTableUnit<R>>,
+    signatures: btree_map::BTreeMap<u64, UnitSectionOffset<R::Offset>>,
+}
+
+#[derive(Debug)]
+struct TypeTableUnit<R: Reader> {
+    offset: UnitSectionOffset<R::Offset>,
+    header: UnitHeader<R, R::Offset>,
+    unit: Option<Unit<R>>,
+}
+
+impl<'dwarf, R: Reader> TypeTable<'dwarf, R> {
+    /// Construct a new `TypeTable` for the given DWARF sections.
+    pub fn new(dwarf: &'dwarf Dwarf<R>) -> Self {
+        TypeTable {
+            dwarf,
+            loaded: false,
+            units: Vec::new(),
+            signatures: btree_map::BTreeMap::new(),
+        }
+    }
+
+    /// Return the offset of the type DIE for the type unit with the given
+    /// signature, or `None` if there is no such type unit.
+    pub fn signature(
+        &mut self,
+        signature: DebugTypeSignature,
+    ) -> Result<Option<UnitSectionOffset<R::Offset>>> {
+        self.load()?;
+        Ok(self.signatures.get(&signature.0).cloned())
+    }
+
+    /// Resolve a reference attribute value of an entry in the given unit to
+    /// a reference to the DIE.
+    pub fn resolve(
+        &mut self,
+        unit: &Unit<R>,
+        value: AttributeValue<R, R::Offset>,
+    ) -> Result<TypeRef<R::Offset>> {
+        self.load()?;
+        resolve_reference(unit, &self.signatures, value)
+    }
+
+    /// Return the `DW_AT_type` of an entry in the given unit, such as a
+    /// variable or a function.
+    pub fn entry_type(
+        &mut self,
+        unit: &Unit<R>,
+        entry: &DebuggingInformationEntry<R, R::Offset>,
+    ) -> Result<Option<TypeRef<R::Offset>>> {
+        match entry.attr_value(constants::DW_AT_type)? {
+            Some(value) => self.resolve(unit, value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Parse the type DIE at the given offset.
+    pub fn get(&mut self, offset: UnitSectionOffset<R::Offset>) -> Result<Type<R>> {
+        self.load()?;
+        let index = self.unit_index(offset)?;
+        let dwarf = self.dwarf;
+        let unit = &self.units[index];
+        let unit = match unit.unit {
+            Some(ref unit) => unit,
+            None => {
+                let header = unit.header.clone();
+                let unit = Unit::new_internal(dwarf, unit.offset, header)?;
+                self.units[index].unit = Some(unit);
+                self.units[index].unit.as_ref().unwrap()
+            }
+        };
+        let unit_offset = match offset.to_unit_offset(unit) {
+            Some(unit_offset) => unit_offset,
+            None => return Err(Error::NoEntryAtGivenOffset),
+        };
+        let big_endian = unit
+            .header
+            .range_from(unit_offset..)?
+            .endian()
+            .is_big_endian();
+        let mut tree = unit.entries_tree(Some(unit_offset))?;
+        let parser = TypeParser {
+            dwarf,
+            unit,
+            signatures: &self.signatures,
+            big_endian,
+        };
+        parser.parse(offset, tree.root()?)
+    }
+
+    /// Parse the type DIE at the given offset, following any typedefs and
+    /// qualifiers.
+    ///
+    /// Typedefs and qualifiers of types in a supplementary object file are
+    /// not followed.
+    pub fn unqualified(&mut self, offset: UnitSectionOffset<R::Offset>) -> Result<Type<R>> {
+        let mut ty = self.get(offset)?;
+        for _ in 0..MAX_TYPE_DEPTH {
+            let target = match ty.kind {
+                TypeKind::Typedef(Some(TypeRef::Offset(target)))
+                | TypeKind::Const(Some(TypeRef::Offset(target)))
+                | TypeKind::Volatile(Some(TypeRef::Offset(target)))
+                | TypeKind::Restrict(Some(TypeRef::Offset(target)))
+                | TypeKind::Atomic(Some(TypeRef::Offset(target))) => target,
+                _ => break,
+            };
+            ty = self.get(target)?;
+        }
+        Ok(ty)
+    }
+
+    fn load(&mut self) -> Result<()> {
+        if self.loaded {
+            return Ok(());
+        }
+        let mut units = Vec::new();
+        let mut headers = self.dwarf.units();
+        while let Some(header) = headers.next()? {
+            let offset = UnitSectionOffset::DebugInfoOffset(header.offset());
+            units.push((offset, header.header()));
+        }
+        let mut headers = self.dwarf.type_units();
+        while let Some(header) = headers.next()? {
+            let offset = UnitSectionOffset::DebugTypesOffset(header.offset());
+            units.push((offset, header.header()));
+        }
+        for &(offset, ref header) in &units {
+            match header.unit_type() {
+                UnitType::Type {
+                    type_signature,
+                    type_offset,
+                }
+                | UnitType::SplitType {
+                    type_signature,
+                    type_offset,
+                } => {
+                    let type_offset = match offset {
+                        UnitSectionOffset::DebugInfoOffset(o) => {
+                            UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(o.0 + type_offset.0))
+                        }
+                        UnitSectionOffset::DebugTypesOffset(o) => {
+                            UnitSectionOffset::DebugTypesOffset(DebugTypesOffset(
+                                o.0 + type_offset.0,
+                            ))
+                        }
+                    };
+                    self.signatures.insert(type_signature.0, type_offset);
+                }
+                _ => {}
+            }
+        }
+        units.sort_by_key(|&(offset, _)| offset);
+        self.units = units
+            .into_iter()
+            .map(|(offset, header)| TypeTableUnit {
+                offset,
+                header,
+                unit: None,
+            })
+            .collect();
+        self.loaded = true;
+        Ok(())
+    }
+
+    fn unit_index(&self, offset: UnitSectionOffset<R::Offset>) -> Result<usize> {
+        let index = match self.units.binary_search_by_key(&offset, |unit| unit.offset) {
+            Ok(index) => index,
+            Err(0) => return Err(Error::NoEntryAtGivenOffset),
+            Err(index) => index - 1,
+        };
+        let unit = &self.units[index];
+        let unit_offset = match (offset, unit.offset) {
+            (
+                UnitSectionOffset::DebugInfoOffset(offset),
+                UnitSectionOffset::DebugInfoOffset(unit_offset),
+            ) => offset.0 - unit_offset.0,
+            (
+                UnitSectionOffset::DebugTypesOffset(offset),
+                UnitSectionOffset::DebugTypesOffset(unit_offset),
+            ) => offset.0 - unit_offset.0,
+            _ => return Err(Error::NoEntryAtGivenOffset),
+        };
+        if !unit.header.is_valid_offset(UnitOffset(unit_offset)) {
+            return Err(Error::NoEntryAtGivenOffset);
+        }
+        Ok(index)
+    }
+}
+
+fn resolve_reference<R: Reader>(
+    unit: &Unit<R>,
+    signatures: &btree_map::BTreeMap<u64, UnitSectionOffset<R::Offset>>,
+    value: AttributeValue<R, R::Offset>,
+) -> Result<TypeRef<R::Offset>> {
+    match value {
+        AttributeValue::UnitRef(offset) => Ok(TypeRef::Offset(offset.to_unit_section_offset(unit))),
+        AttributeValue::DebugInfoRef(offset) => {
+            Ok(TypeRef::Offset(UnitSectionOffset::DebugInfoOffset(offset)))
+        }
+        AttributeValue::DebugInfoRefSup(offset) => Ok(TypeRef::Sup(offset)),
+        AttributeValue::DebugTypesRef(signature) => match signatures.get(&signature.0) {
+            Some(offset) => Ok(TypeRef::Offset(*offset)),
+            None => Err(Error::MissingTypeUnit),
+        },
+        _ => Err(Error::UnsupportedAttributeForm),
+    }
+}
+
+/// The attributes of a DIE that are used by the type model.
+struct EntryAttributes<R: Reader> {
+    name: Option<AttributeValue<R, R::Offset>>,
+    ty: Option<AttributeValue<R, R::Offset>>,
+    byte_size: Option<u64>,
+    bit_size: Option<u64>,
+    bit_offset: Option<u64>,
+    data_bit_offset: Option<u64>,
+    data_member_location: Option<AttributeValue<R, R::Offset>>,
+    encoding: Option<constants::DwAte>,
+    const_value: Option<i64>,
+    lower_bound: Option<i64>,
+    upper_bound: Option<i64>,
+    count: Option<u64>,
+    declaration: bool,
+}
+
+impl<R: Reader> EntryAttributes<R> {
+    fn parse(entry: &DebuggingInformationEntry<R, R::Offset>) -> Result<Self> {
+        let mut attributes = EntryAttributes {
+            name: None,
+            ty: None,
+            byte_size: None,
+            bit_size: None,
+            bit_offset: None,
+            data_bit_offset: None,
+            data_member_location: None,
+            encoding: None,
+            const_value: None,
+            lower_bound: None,
+            upper_bound: None,
+            count: None,
+            declaration: false,
+        };
+        let mut attrs = entry.attrs();
+        while let Some(attr) = attrs.next()? {
+            match attr.name() {
+                constants::DW_AT_name => attributes.name = Some(attr.value()),
+                constants::DW_AT_type => attributes.ty = Some(attr.value()),
+                constants::DW_AT_byte_size => attributes.byte_size = attr.udata_value(),
+                constants::DW_AT_bit_size => attributes.bit_size = attr.udata_value(),
+                constants::DW_AT_bit_offset => attributes.bit_offset = attr.udata_value(),
+                constants::DW_AT_data_bit_offset => attributes.data_bit_offset = attr.udata_value(),
+                constants::DW_AT_data_member_location => {
+                    attributes.data_member_location = Some(attr.value())
+                }
+                constants::DW_AT_encoding => {
+                    if let AttributeValue::Encoding(encoding) = attr.value() {
+                        attributes.encoding = Some(encoding);
+                    }
+                }
+                constants::DW_AT_const_value => {
+                    attributes.const_value = constant_value(&attr.value())
+                }
+                constants::DW_AT_lower_bound => {
+                    attributes.lower_bound = constant_value(&attr.value())
+                }
+                constants::DW_AT_upper_bound => {
+                    attributes.upper_bound = constant_value(&attr.value())
+                }
+                constants::DW_AT_count => attributes.count = attr.udata_value(),
+                constants::DW_AT_declaration => {
+                    if let AttributeValue::Flag(flag) = attr.value() {
+                        attributes.declaration = flag;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// Convert a constant attribute value to an `i64`.
+///
+/// Unlike `AttributeValue::sdata_value`, this does not sign extend values
+/// using `DW_FORM_data*`, because their signedness depends on their type.
+fn constant_value<R: Reader>(value: &AttributeValue<R, R::Offset>) -> Option<i64> {
+    match *value {
+        AttributeValue::Sdata(value) => Some(value),
+        ref value => value.udata_value().map(|value| value as i64),
+    }
+}
+
+struct TypeParser<'a, 'dwarf: 'a, R: Reader + 'dwarf> {
+    dwarf: &'dwarf Dwarf<R>,
+    unit: &'a Unit<R>,
+    signatures: &'a btree_map::BTreeMap<u64, UnitSectionOffset<R::Offset>>,
+    big_endian: bool,
+}
+
+impl<'a, 'dwarf, R: Reader> TypeParser<'a, 'dwarf, R> {
+    fn parse(
+        &self,
+        offset: UnitSectionOffset<R::Offset>,
+        node: EntriesTreeNode<R>,
+    ) -> Result<Type<R>> {
+        let tag = node.entry().tag();
+        let attributes = EntryAttributes::parse(node.entry())?;
+        let name = match attributes.name {
+            Some(ref name) => Some(self.dwarf.attr_string(self.unit, name.clone())?),
+            None => None,
+        };
+        let ty = self.reference(&attributes.ty)?;
+        let kind = match tag {
+            constants::DW_TAG_base_type => TypeKind::Base(BaseType {
+                encoding: attributes.encoding,
+                bit_size: attributes.bit_size,
+                value_type: ValueType::from_entry(node.entry())?,
+            }),
+            constants::DW_TAG_unspecified_type => TypeKind::Unspecified,
+            constants::DW_TAG_pointer_type => TypeKind::Pointer(ty),
+            constants::DW_TAG_reference_type => TypeKind::Reference(ty),
+            constants::DW_TAG_rvalue_reference_type => TypeKind::RvalueReference(ty),
+            constants::DW_TAG_typedef => TypeKind::Typedef(ty),
+            constants::DW_TAG_const_type => TypeKind::Const(ty),
+            constants::DW_TAG_volatile_type => TypeKind::Volatile(ty),
+            constants::DW_TAG_restrict_type => TypeKind::Restrict(ty),
+            constants::DW_TAG_atomic_type => TypeKind::Atomic(ty),
+            constants::DW_TAG_structure_type => {
+                TypeKind::Struct(self.composite(&attributes, node, false)?)
+            }
+            constants::DW_TAG_class_type => {
+                TypeKind::Class(self.composite(&attributes, node, false)?)
+            }
+            constants::DW_TAG_union_type => {
+                TypeKind::Union(self.composite(&attributes, node, true)?)
+            }
+            constants::DW_TAG_enumeration_type => {
+                let mut enumerators = Vec::new();
+                let mut children = node.children();
+                while let Some(child) = children.next()? {
+                    if child.entry().tag() != constants::DW_TAG_enumerator {
+                        continue;
+                    }
+                    let attributes = EntryAttributes::parse(child.entry())?;
+                    enumerators.push(Enumerator {
+                        name: self.string(&attributes.name)?,
+                        value: attributes.const_value,
+                    });
+                }
+                TypeKind::Enumeration(EnumerationType {
+                    declaration: attributes.declaration,
+                    ty,
+                    enumerators,
+                })
+            }
+            constants::DW_TAG_array_type => {
+                let mut dimensions = Vec::new();
+                let mut children = node.children();
+                while let Some(child) = children.next()? {
+                    if child.entry().tag() != constants::DW_TAG_subrange_type {
+                        continue;
+                    }
+                    let attributes = EntryAttributes::parse(child.entry())?;
+                    dimensions.push(Subrange {
+                        ty: self.reference(&attributes.ty)?,
+                        lower_bound: attributes.lower_bound,
+                        upper_bound: attributes.upper_bound,
+                        count: attributes.count,
+                    });
+                }
+                TypeKind::Array(ArrayType { ty, dimensions })
+            }
+            constants::DW_TAG_subroutine_type => {
+                let mut parameters = Vec::new();
+                let mut variadic = false;
+                let mut children = node.children();
+                while let Some(child) = children.next()? {
+                    match child.entry().tag() {
+                        constants::DW_TAG_formal_parameter => {
+                            let attributes = EntryAttributes::parse(child.entry())?;
+                            parameters.push(self.reference(&attributes.ty)?);
+                        }
+                        constants::DW_TAG_unspecified_parameters => variadic = true,
+                        _ => {}
+                    }
+                }
+                TypeKind::Subroutine(SubroutineType {
+                    ty,
+                    parameters,
+                    variadic,
+                })
+            }
+            _ => TypeKind::Unknown,
+        };
+        Ok(Type {
+            offset,
+            tag,
+            name,
+            byte_size: attributes.byte_size,
+            kind,
+        })
+    }
+
+    fn composite(
+        &self,
+        attributes: &EntryAttributes<R>,
+        node: EntriesTreeNode<R>,
+        union: bool,
+    ) -> Result<CompositeType<R>> {
+        let mut inheritance = Vec::new();
+        let mut members = Vec::new();
+        let mut children = node.children();
+        while let Some(child) = children.next()? {
+            let tag = child.entry().tag();
+            if tag != constants::DW_TAG_member && tag != constants::DW_TAG_inheritance {
+                continue;
+            }
+            let attributes = EntryAttributes::parse(child.entry())?;
+            if attributes.declaration {
+                // A static member in DWARF versions before 5.
+                continue;
+            }
+            let member = Member {
+                name: self.string(&attributes.name)?,
+                ty: self.reference(&attributes.ty)?,
+                bit_offset: self.member_bit_offset(&attributes, union)?,
+                bit_size: attributes.bit_size,
+            };
+            if tag == constants::DW_TAG_inheritance {
+                inheritance.push(member);
+            } else {
+                members.push(member);
+            }
+        }
+        Ok(CompositeType {
+            declaration: attributes.declaration,
+            inheritance,
+            members,
+        })
+    }
+
+    fn member_bit_offset(
+        &self,
+        attributes: &EntryAttributes<R>,
+        union: bool,
+    ) -> Result<Option<u64>> {
+        if attributes.data_bit_offset.is_some() {
+            return Ok(attributes.data_bit_offset);
+        }
+        let byte_offset = match attributes.data_member_location {
+            None => {
+                if !union && attributes.bit_offset.is_none() {
+                    return Ok(None);
+                }
+                0
+            }
+            Some(AttributeValue::Exprloc(ref expression)) => {
+                match location_offset(expression.0.clone(), self.unit.encoding())? {
+                    Some(offset) => offset,
+                    None => return Ok(None),
+                }
+            }
+            Some(AttributeValue::Block(ref data)) => {
+                match location_offset(data.clone(), self.unit.encoding())? {
+                    Some(offset) => offset,
+                    None => return Ok(None),
+                }
+            }
+            Some(ref value) => match value.udata_value() {
+                Some(offset) => offset,
+                None => return Ok(None),
+            },
+        };
+        let bit_offset = match byte_offset.checked_mul(8) {
+            Some(bit_offset) => bit_offset,
+            None => return Ok(None),
+        };
+        match (attributes.bit_offset, attributes.bit_size) {
+            (Some(storage_bit_offset), Some(bit_size)) => {
+                // DWARF 2 and 3 bit fields are relative to the most significant
+                // bit of the storage unit.
+                if self.big_endian {
+                    return Ok(bit_offset.checked_add(storage_bit_offset));
+                }
+                let storage_size = match attributes.byte_size {
+                    Some(byte_size) => byte_size * 8,
+                    None => return Ok(None),
+                };
+                Ok(bit_offset
+                    .checked_add(storage_size)
+                    .and_then(|x| x.checked_sub(storage_bit_offset))
+                    .and_then(|x| x.checked_sub(bit_size)))
+            }
+            _ => Ok(Some(bit_offset)),
+        }
+    }
+
+    fn reference(
+        &self,
+        value: &Option<AttributeValue<R, R::Offset>>,
+    ) -> Result<Option<TypeRef<R::Offset>>> {
+        match *value {
+            Some(ref value) => {
+                resolve_reference(self.unit, self.signatures, value.clone()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn string(&self, value: &Option<AttributeValue<R, R::Offset>>) -> Result<Option<R>> {
+        match *value {
+            Some(ref value) => self.dwarf.attr_string(self.unit, value.clone()).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Return the offset for a `DW_AT_data_member_location` expression that
+/// consists of a single `DW_OP_plus_uconst` or `DW_OP_constu`.
+fn location_offset<R: Reader>(mut bytes: R, encoding: Encoding) -> Result<Option<u64>> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    let bytecode = bytes.clone();
+    let operation = Operation::parse(&mut bytes, &bytecode, encoding)?;
+    if !bytes.is_empty() {
+        return Ok(None);
+    }
+    match operation {
+        Operation::PlusConstant { value } | Operation::Literal { value } => Ok(Some(value)),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
+    use super::*;
+    use read::abbrev::tests::AbbrevSectionMethods;
+    use read::{DebugAbbrev, DebugInfo, DebugTypes, EndianSlice};
+    use test_util::GimliSectionMethods;
+    use LittleEndian;
+
+    #[test]
+    fn test_type_table() {
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let abbrev = Section::with_endian(Endian::Little)
+            .abbrev(1, constants::DW_TAG_compile_unit, constants::DW_CHILDREN_yes)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr_null()
+            .abbrev(2, constants::DW_TAG_base_type, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_encoding, constants::DW_FORM_data1)
+                .abbrev_attr(constants::DW_AT_byte_size, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(3, constants::DW_TAG_pointer_type, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_byte_size, constants::DW_FORM_data1)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(4, constants::DW_TAG_structure_type, constants::DW_CHILDREN_yes)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_byte_size, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(5, constants::DW_TAG_member, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr(constants::DW_AT_data_member_location, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(6, constants::DW_TAG_member, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr(constants::DW_AT_bit_size, constants::DW_FORM_data1)
+                .abbrev_attr(constants::DW_AT_data_bit_offset, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(7, constants::DW_TAG_member, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr(constants::DW_AT_byte_size, constants::DW_FORM_data1)
+                .abbrev_attr(constants::DW_AT_bit_size, constants::DW_FORM_data1)
+                .abbrev_attr(constants::DW_AT_bit_offset, constants::DW_FORM_data1)
+                .abbrev_attr(constants::DW_AT_data_member_location, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(8, constants::DW_TAG_typedef, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref_addr)
+                .abbrev_attr_null()
+            .abbrev(9, constants::DW_TAG_const_type, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref_sig8)
+                .abbrev_attr_null()
+            .abbrev(10, constants::DW_TAG_enumeration_type, constants::DW_CHILDREN_yes)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr(constants::DW_AT_byte_size, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(11, constants::DW_TAG_enumerator, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_const_value, constants::DW_FORM_sdata)
+                .abbrev_attr_null()
+            .abbrev(12, constants::DW_TAG_array_type, constants::DW_CHILDREN_yes)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(13, constants::DW_TAG_subrange_type, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr(constants::DW_AT_upper_bound, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(14, constants::DW_TAG_subroutine_type, constants::DW_CHILDREN_yes)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(15, constants::DW_TAG_formal_parameter, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr_null()
+            .abbrev(16, constants::DW_TAG_unspecified_parameters, constants::DW_CHILDREN_no)
+                .abbrev_attr_null()
+            .abbrev(17, constants::DW_TAG_type_unit, constants::DW_CHILDREN_yes)
+                .abbrev_attr_null()
+            .abbrev(18, constants::DW_TAG_pointer_type, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_byte_size, constants::DW_FORM_data1)
+                .abbrev_attr_null()
+            .abbrev(19, constants::DW_TAG_member, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr(constants::DW_AT_data_member_location, constants::DW_FORM_exprloc)
+                .abbrev_attr_null()
+            .abbrev(20, constants::DW_TAG_member, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_ref4)
+                .abbrev_attr(constants::DW_AT_declaration, constants::DW_FORM_flag_present)
+                .abbrev_attr_null()
+            .abbrev(21, constants::DW_TAG_typedef, constants::DW_CHILDREN_no)
+                .abbrev_attr(constants::DW_AT_name, constants::DW_FORM_string)
+                .abbrev_attr(constants::DW_AT_type, constants::DW_FORM_GNU_ref_alt)
+                .abbrev_attr_null()
+            .abbrev_null();
+        let abbrev = abbrev.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let int = Label::new();
+        let uchar = Label::new();
+        let ptr = Label::new();
+        let s = Label::new();
+        let e = Label::new();
+        let arr = Label::new();
+        let func = Label::new();
+        let c = Label::new();
+        let void_ptr = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let info1 = Section::with_endian(Endian::Little)
+            .set_start_const(0)
+            .L32(&length).mark(&start).L16(4).L32(0).D8(8)
+            .D8(1).append_bytes(b"a.c\0")
+                .mark(&int)
+                .D8(2).append_bytes(b"int\0").D8(constants::DW_ATE_signed.0).D8(4)
+                .mark(&uchar)
+                .D8(2).append_bytes(b"unsigned char\0").D8(constants::DW_ATE_unsigned_char.0).D8(1)
+                .mark(&ptr)
+                .D8(3).D8(8).L32(&s)
+                .mark(&s)
+                .D8(4).append_bytes(b"s\0").D8(16)
+                    .D8(5).append_bytes(b"a\0").L32(&int).D8(0)
+                    .D8(6).append_bytes(b"b\0").L32(&int).D8(3).D8(33)
+                    .D8(7).append_bytes(b"c\0").L32(&int).D8(4).D8(5).D8(24).D8(4)
+                    .D8(19).append_bytes(b"d\0").L32(&ptr)
+                        .uleb(2).D8(constants::DW_OP_plus_uconst.0).uleb(8)
+                    .D8(20).append_bytes(b"e\0").L32(&int)
+                    .D8(0)
+                .mark(&e)
+                .D8(10).append_bytes(b"e\0").L32(&uchar).D8(1)
+                    .D8(11).append_bytes(b"x\0").sleb(-1)
+                    .D8(11).append_bytes(b"y\0").sleb(2)
+                    .D8(0)
+                .mark(&arr)
+                .D8(12).L32(&int)
+                    .D8(13).L32(&int).D8(9)
+                    .D8(13).L32(&int).D8(255)
+                    .D8(0)
+                .mark(&func)
+                .D8(14).L32(&int)
+                    .D8(15).L32(&ptr)
+                    .D8(16)
+                    .D8(0)
+                .mark(&c)
+                .D8(9).L64(0x1234)
+                .mark(&void_ptr)
+                .D8(18).D8(8)
+                .D8(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let info1 = info1.get_contents().unwrap();
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let typedef = Label::new();
+        let sup_typedef = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let info2 = Section::with_endian(Endian::Little)
+            .set_start_const(0)
+            .L32(&length).mark(&start).L16(4).L32(0).D8(8)
+            .D8(1).append_bytes(b"b.c\0")
+                .mark(&typedef)
+                .D8(8).append_bytes(b"t\0").L32(&s)
+                .mark(&sup_typedef)
+                .D8(21).append_bytes(b"u\0").L32(0x40)
+                .D8(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let info2 = info2.get_contents().unwrap();
+
+        let mut info = info1.clone();
+        info.extend_from_slice(&info2);
+
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let long = Label::new();
+        #[cfg_attr(rustfmt, rustfmt_skip)]
+        let types = Section::with_endian(Endian::Little)
+            .set_start_const(0)
+            .L32(&length).mark(&start).L16(4).L32(0).D8(8).L64(0x1234).L32(&long)
+            .D8(17)
+                .mark(&long)
+                .D8(2).append_bytes(b"long\0").D8(constants::DW_ATE_signed.0).D8(8)
+                .D8(0)
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        let types = types.get_contents().unwrap();
+
+        let dwarf = Dwarf {
+            debug_abbrev: DebugAbbrev::new(&abbrev, LittleEndian),
+            debug_info: DebugInfo::new(&info, LittleEndian),
+            debug_types: DebugTypes::new(&types, LittleEndian),
+            ..Default::default()
+        };
+        let mut table = TypeTable::new(&dwarf);
+
+        let string = |s: &'static str| Some(EndianSlice::new(s.as_bytes(), LittleEndian));
+        let info_offset = |label: &Label| {
+            UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(label.value().unwrap() as usize))
+        };
+        let types_offset = |label: &Label| {
+            UnitSectionOffset::DebugTypesOffset(DebugTypesOffset(label.value().unwrap() as usize))
+        };
+        let info_ref = |label: &Label| TypeRef::Offset(info_offset(label));
+        let types_ref = |label: &Label| TypeRef::Offset(types_offset(label));
+
+        assert_eq!(
+            table.get(info_offset(&int)),
+            Ok(Type {
+                offset: info_offset(&int),
+                tag: constants::DW_TAG_base_type,
+                name: string("int"),
+                byte_size: Some(4),
+                kind: TypeKind::Base(BaseType {
+                    encoding: Some(constants::DW_ATE_signed),
+                    bit_size: None,
+                    value_type: Some(ValueType::I32),
+                }),
+            })
+        );
+
+        let ty = table.get(info_offset(&ptr)).unwrap();
+        assert_eq!(ty.byte_size, Some(8));
+        assert_eq!(ty.kind, TypeKind::Pointer(Some(info_ref(&s))));
+        assert_eq!(ty.kind.target(), Some(info_ref(&s)));
+
+        let ty = table.get(info_offset(&void_ptr)).unwrap();
+        assert_eq!(ty.kind, TypeKind::Pointer(None));
+
+        let struct_type = Type {
+            offset: info_offset(&s),
+            tag: constants::DW_TAG_structure_type,
+            name: string("s"),
+            byte_size: Some(16),
+            kind: TypeKind::Struct(CompositeType {
+                declaration: false,
+                inheritance: Vec::new(),
+                members: vec![
+                    Member {
+                        name: string("a"),
+                        ty: Some(info_ref(&int)),
+                        bit_offset: Some(0),
+                        bit_size: None,
+                    },
+                    Member {
+                        name: string("b"),
+                        ty: Some(info_ref(&int)),
+                        bit_offset: Some(33),
+                        bit_size: Some(3),
+                    },
+                    Member {
+                        name: string("c"),
+                        ty: Some(info_ref(&int)),
+                        bit_offset: Some(35),
+                        bit_size: Some(5),
+                    },
+                    Member {
+                        name: string("d"),
+                        ty: Some(info_ref(&ptr)),
+                        bit_offset: Some(64),
+                        bit_size: None,
+                    },
+                ],
+            }),
+        };
+        assert_eq!(table.get(info_offset(&s)), Ok(struct_type.clone()));
+
+        // The typedef is in the second unit and uses `DW_FORM_ref_addr`.
+        let typedef_offset = UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(
+            info1.len() + typedef.value().unwrap() as usize,
+        ));
+        let ty = table.get(typedef_offset).unwrap();
+        assert_eq!(ty.name, string("t"));
+        assert_eq!(ty.kind, TypeKind::Typedef(Some(info_ref(&s))));
+        assert_eq!(table.unqualified(typedef_offset), Ok(struct_type));
+
+        // The typedef refers to the supplementary object file using
+        // `DW_FORM_GNU_ref_alt`, which is not followed.
+        let sup_typedef_offset = UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(
+            info1.len() + sup_typedef.value().unwrap() as usize,
+        ));
+        let ty = table.get(sup_typedef_offset).unwrap();
+        assert_eq!(
+            ty.kind,
+            TypeKind::Typedef(Some(TypeRef::Sup(DebugInfoOffset(0x40))))
+        );
+        assert_eq!(table.unqualified(sup_typedef_offset), Ok(ty));
+
+        match table.get(info_offset(&s)).unwrap().kind {
+            TypeKind::Struct(ref composite) => {
+                assert_eq!(composite.members[3].byte_offset(), Some(8));
+                assert_eq!(composite.members[1].byte_offset(), None);
+            }
+            _ => panic!("expected struct"),
+        }
+
+        assert_eq!(
+            table.get(info_offset(&e)).unwrap().kind,
+            TypeKind::Enumeration(EnumerationType {
+                declaration: false,
+                ty: Some(info_ref(&uchar)),
+                enumerators: vec![
+                    Enumerator {
+                        name: string("x"),
+                        value: Some(-1),
+                    },
+                    Enumerator {
+                        name: string("y"),
+                        value: Some(2),
+                    },
+                ],
+            })
+        );
+
+        match table.get(info_offset(&arr)).unwrap().kind {
+            TypeKind::Array(ref array) => {
+                assert_eq!(array.ty, Some(info_ref(&int)));
+                assert_eq!(array.dimensions.len(), 2);
+                assert_eq!(array.dimensions[0].element_count(), Some(10));
+                assert_eq!(array.dimensions[1].upper_bound, Some(255));
+                assert_eq!(array.dimensions[1].element_count(), Some(256));
+            }
+            _ => panic!("expected array"),
+        }
+
+        assert_eq!(
+            table.get(info_offset(&func)).unwrap().kind,
+            TypeKind::Subroutine(SubroutineType {
+                ty: Some(info_ref(&int)),
+                parameters: vec![Some(info_ref(&ptr))],
+                variadic: true,
+            })
+        );
+
+        // The const type refers to the type unit using `DW_FORM_ref_sig8`.
+        assert_eq!(
+            table.signature(DebugTypeSignature(0x1234)),
+            Ok(Some(types_offset(&long)))
+        );
+        assert_eq!(table.signature(DebugTypeSignature(0x5678)), Ok(None));
+        let ty = table.get(info_offset(&c)).unwrap();
+        assert_eq!(ty.kind, TypeKind::Const(Some(types_ref(&long))));
+        let ty = table.unqualified(info_offset(&c)).unwrap();
+        assert_eq!(ty.name, string("long"));
+        match ty.kind {
+            TypeKind::Base(base) => assert_eq!(base.value_type, Some(ValueType::I64)),
+            _ => panic!("expected base type"),
+        }
+
+        assert_eq!(
+            table.get(UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(
+                info.len() + 10
+            ))),
+            Err(Error::NoEntryAtGivenOffset)
+        );
+    }
+}
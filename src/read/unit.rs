@@ -8,8 +8,8 @@ use std::{u16, u8};
 use common::{
     DebugAbbrevOffset, DebugAddrBase, DebugAddrIndex, DebugInfoOffset, DebugLineOffset,
     DebugLineStrOffset, DebugLocListsBase, DebugLocListsIndex, DebugMacinfoOffset,
-    DebugRngListsBase, DebugRngListsIndex, DebugStrOffset, DebugStrOffsetsBase,
-    DebugStrOffsetsIndex, DebugTypeSignature, DebugTypesOffset, Encoding, Format,
+    DebugMacroOffset, DebugRngListsBase, DebugRngListsIndex, DebugStrOffset, DebugStrOffsetsBase,
+    DebugStrOffsetsIndex, DebugTypeSignature, DebugTypesOffset, DwoId, Encoding, Format,
     LocationListsOffset, RangeListsOffset,
 };
 use constants;
@@ -270,6 +270,11 @@ where
         self.header.version()
     }
 
+    /// Get the type of this unit, and any type specific data from its header.
+    pub fn unit_type(&self) -> UnitType<R::Offset> {
+        self.header.unit_type()
+    }
+
     /// The offset into the `.debug_abbrev` section for this compilation unit's
     /// debugging information entries' abbreviations.
     pub fn debug_abbrev_offset(&self) -> DebugAbbrevOffset<R::Offset> {
@@ -433,6 +438,59 @@ pub(crate) fn parse_debug_info_offset<R: Reader>(
     input.read_offset(format).map(DebugInfoOffset)
 }
 
+/// The type of a unit, and any type specific data carried in the unit header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitType<Offset = usize>
+where
+    Offset: ReaderOffset,
+{
+    /// In DWARF 5, a unit with type `DW_UT_compile`. In previous DWARF versions,
+    /// any unit appearing in the `.debug_info` section.
+    Compilation,
+    /// In DWARF 5, a unit with type `DW_UT_type`. In DWARF 4, any unit appearing
+    /// in the `.debug_types` section.
+    Type {
+        /// The unique type signature for this type unit.
+        type_signature: DebugTypeSignature,
+        /// The offset within this type unit where the type is defined.
+        type_offset: UnitOffset<Offset>,
+    },
+    /// A unit with type `DW_UT_partial`.
+    Partial,
+    /// A unit with type `DW_UT_skeleton`. The `DwoId` can be used to link this
+    /// with the corresponding `SplitCompilation` unit.
+    Skeleton(DwoId),
+    /// A unit with type `DW_UT_split_compile`. The `DwoId` can be used to link
+    /// this with the corresponding `Skeleton` unit.
+    SplitCompilation(DwoId),
+    /// A unit with type `DW_UT_split_type`.
+    SplitType {
+        /// The unique type signature for this type unit.
+        type_signature: DebugTypeSignature,
+        /// The offset within this type unit where the type is defined.
+        type_offset: UnitOffset<Offset>,
+    },
+}
+
+impl<Offset> UnitType<Offset>
+where
+    Offset: ReaderOffset,
+{
+    /// Return the `DW_UT_*` constant for this unit type.
+    ///
+    /// Units in versions before DWARF 5 are given the equivalent DWARF 5 unit type.
+    pub fn dw_ut(&self) -> constants::DwUt {
+        match *self {
+            UnitType::Compilation => constants::DW_UT_compile,
+            UnitType::Type { .. } => constants::DW_UT_type,
+            UnitType::Partial => constants::DW_UT_partial,
+            UnitType::Skeleton(_) => constants::DW_UT_skeleton,
+            UnitType::SplitCompilation(_) => constants::DW_UT_split_compile,
+            UnitType::SplitType { .. } => constants::DW_UT_split_type,
+        }
+    }
+}
+
 /// The common fields for the headers of compilation units and
 /// type units.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -443,6 +501,7 @@ where
 {
     encoding: Encoding,
     unit_length: Offset,
+    unit_type: UnitType<Offset>,
     debug_abbrev_offset: DebugAbbrevOffset<Offset>,
     entries_buf: R,
 }
@@ -457,12 +516,14 @@ where
     pub fn new(
         encoding: Encoding,
         unit_length: R::Offset,
+        unit_type: UnitType<R::Offset>,
         debug_abbrev_offset: DebugAbbrevOffset<R::Offset>,
         entries_buf: R,
     ) -> Self {
         UnitHeader {
             encoding,
             unit_length,
+            unit_type,
             debug_abbrev_offset,
             entries_buf,
         }
@@ -508,6 +569,11 @@ where
         self.encoding.version
     }
 
+    /// Get the type of this unit, and any type specific data from its header.
+    pub fn unit_type(&self) -> UnitType<R::Offset> {
+        self.unit_type
+    }
+
     /// The offset into the `.debug_abbrev` section for this compilation unit's
     /// debugging information entries' abbreviations.
     pub fn debug_abbrev_offset(&self) -> DebugAbbrevOffset<R::Offset> {
@@ -638,18 +704,42 @@ fn parse_unit_header<R: Reader>(input: &mut R) -> Result<UnitHeader<R, R::Offset
     let version = rest.read_u16()?;
     let offset;
     let address_size;
+    let unit_type;
     // DWARF 1 was very different, and is obsolete, so isn't supported by this
     // reader.
     if 2 <= version && version <= 4 {
         offset = parse_debug_abbrev_offset(&mut rest, format)?;
         address_size = rest.read_u8()?;
+        // Before DWARF 5, all units in `.debug_info` are compilation units.
+        // Type units in `.debug_types` are handled by `parse_type_unit_header`.
+        unit_type = UnitType::Compilation;
     } else if version == 5 {
-        let unit_type = parse_compilation_unit_type(&mut rest)?;
-        if unit_type != constants::DW_UT_compile {
-            return Err(Error::UnsupportedUnitType);
-        }
+        let dw_ut = parse_compilation_unit_type(&mut rest)?;
         address_size = rest.read_u8()?;
         offset = parse_debug_abbrev_offset(&mut rest, format)?;
+        unit_type = match dw_ut {
+            constants::DW_UT_compile => UnitType::Compilation,
+            constants::DW_UT_type => {
+                let type_signature = parse_type_signature(&mut rest)?;
+                let type_offset = parse_type_offset(&mut rest, format)?;
+                UnitType::Type {
+                    type_signature,
+                    type_offset,
+                }
+            }
+            constants::DW_UT_partial => UnitType::Partial,
+            constants::DW_UT_skeleton => UnitType::Skeleton(parse_dwo_id(&mut rest)?),
+            constants::DW_UT_split_compile => UnitType::SplitCompilation(parse_dwo_id(&mut rest)?),
+            constants::DW_UT_split_type => {
+                let type_signature = parse_type_signature(&mut rest)?;
+                let type_offset = parse_type_offset(&mut rest, format)?;
+                UnitType::SplitType {
+                    type_signature,
+                    type_offset,
+                }
+            }
+            _ => return Err(Error::UnsupportedUnitType),
+        };
     } else {
         return Err(Error::UnknownVersion(u64::from(version)));
     }
@@ -659,7 +749,18 @@ fn parse_unit_header<R: Reader>(input: &mut R) -> Result<UnitHeader<R, R::Offset
         address_size,
     };
 
-    Ok(UnitHeader::new(encoding, unit_length, offset, rest))
+    Ok(UnitHeader::new(
+        encoding,
+        unit_length,
+        unit_type,
+        offset,
+        rest,
+    ))
+}
+
+/// Parse the `dwo_id` in a skeleton or split compilation unit header.
+fn parse_dwo_id<R: Reader>(input: &mut R) -> Result<DwoId> {
+    input.read_u64().map(DwoId)
 }
 
 /// A Debugging Information Entry (DIE).
@@ -1054,6 +1155,9 @@ where
     /// An offset into the `.debug_macinfo` section.
     DebugMacinfoRef(DebugMacinfoOffset<Offset>),
 
+    /// An offset into the `.debug_macro` section.
+    DebugMacroRef(DebugMacroOffset<Offset>),
+
     /// An offset into the `.debug_ranges` section.
     RangeListsRef(RangeListsOffset<Offset>),
 
@@ -1236,6 +1340,13 @@ impl<R: Reader> Attribute<R> {
                 }
             };
         }
+        macro_rules! macroptr {
+            () => {
+                if let Some(offset) = self.offset_value() {
+                    return AttributeValue::DebugMacroRef(DebugMacroOffset(offset));
+                }
+            };
+        }
         macro_rules! reference {
             () => {};
         }
@@ -1434,6 +1545,9 @@ impl<R: Reader> Attribute<R> {
             constants::DW_AT_macro_info => {
                 macptr!();
             }
+            constants::DW_AT_macros | constants::DW_AT_GNU_macros => {
+                macroptr!();
+            }
             constants::DW_AT_namelist_item => {
                 reference!();
             }
@@ -1588,6 +1702,15 @@ impl<R: Reader> Attribute<R> {
             constants::DW_AT_loclists_base => {
                 loclistsptr!();
             }
+            constants::DW_AT_dwo_name | constants::DW_AT_GNU_dwo_name => {
+                string!();
+            }
+            constants::DW_AT_GNU_ranges_base => {
+                rnglistsptr!();
+            }
+            constants::DW_AT_GNU_addr_base => {
+                addrptr!();
+            }
             _ => {}
         }
         self.value.clone()
@@ -1828,6 +1951,8 @@ fn allow_section_offset(name: constants::DwAt, version: u16) -> bool {
         | constants::DW_AT_start_scope
         | constants::DW_AT_frame_base
         | constants::DW_AT_macro_info
+        | constants::DW_AT_macros
+        | constants::DW_AT_GNU_macros
         | constants::DW_AT_segment
         | constants::DW_AT_static_link
         | constants::DW_AT_use_location
@@ -3097,6 +3222,10 @@ fn parse_type_unit_header<R: Reader>(
     let format = header.format();
     let signature = parse_type_signature(&mut header.entries_buf)?;
     let type_offset = parse_type_offset(&mut header.entries_buf, format)?;
+    header.unit_type = UnitType::Type {
+        type_signature: signature,
+        type_offset,
+    };
     Ok(TypeUnitHeader::new(header, offset, signature, type_offset))
 }
 
@@ -3195,15 +3324,34 @@ mod tests {
                     .append_bytes(extra_header)
                     .append_bytes(unit.entries_buf.into())
                     .mark(&end),
-                5 => section
-                    .mark(&start)
-                    .L16(unit.version())
-                    .D8(constants::DW_UT_compile.0)
-                    .D8(unit.address_size())
-                    .offset(unit.debug_abbrev_offset.0, unit.format())
-                    .append_bytes(extra_header)
-                    .append_bytes(unit.entries_buf.into())
-                    .mark(&end),
+                5 => {
+                    let section = section
+                        .mark(&start)
+                        .L16(unit.version())
+                        .D8(unit.unit_type.dw_ut().0)
+                        .D8(unit.address_size())
+                        .offset(unit.debug_abbrev_offset.0, unit.format());
+                    let section = match unit.unit_type {
+                        UnitType::Compilation | UnitType::Partial => section,
+                        UnitType::Type {
+                            type_signature,
+                            type_offset,
+                        }
+                        | UnitType::SplitType {
+                            type_signature,
+                            type_offset,
+                        } => section
+                            .L64(type_signature.0)
+                            .offset(type_offset.0, unit.format()),
+                        UnitType::Skeleton(dwo_id) | UnitType::SplitCompilation(dwo_id) => {
+                            section.L64(dwo_id.0)
+                        }
+                    };
+                    section
+                        .append_bytes(extra_header)
+                        .append_bytes(unit.entries_buf.into())
+                        .mark(&end)
+                }
                 _ => unreachable!(),
             };
 
@@ -3347,6 +3495,7 @@ mod tests {
                     address_size: 8,
                 },
                 unit_length: 0,
+                unit_type: UnitType::Compilation,
                 debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
                 entries_buf: EndianSlice::new(expected_rest, LittleEndian),
             },
@@ -3360,6 +3509,7 @@ mod tests {
                     address_size: 4,
                 },
                 unit_length: 0,
+                unit_type: UnitType::Compilation,
                 debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
                 entries_buf: EndianSlice::new(expected_rest, LittleEndian),
             },
@@ -3419,6 +3569,7 @@ mod tests {
         let mut expected_unit = UnitHeader {
             encoding,
             unit_length: 0,
+            unit_type: UnitType::Compilation,
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
         };
@@ -3444,6 +3595,7 @@ mod tests {
         let mut expected_unit = UnitHeader {
             encoding,
             unit_length: 0,
+            unit_type: UnitType::Compilation,
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
         };
@@ -3468,6 +3620,7 @@ mod tests {
         let mut expected_unit = UnitHeader {
             encoding,
             unit_length: 0,
+            unit_type: UnitType::Compilation,
             debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
         };
@@ -3493,6 +3646,7 @@ mod tests {
         let mut expected_unit = UnitHeader {
             encoding,
             unit_length: 0,
+            unit_type: UnitType::Compilation,
             debug_abbrev_offset: DebugAbbrevOffset(0x0102_0304_0506_0708),
             entries_buf: EndianSlice::new(expected_rest, LittleEndian),
         };
@@ -3506,6 +3660,110 @@ mod tests {
         assert_eq!(*rest, EndianSlice::new(expected_rest, LittleEndian));
     }
 
+    #[test]
+    fn test_parse_v5_unit_header_unit_types() {
+        let expected_rest = &[1, 2, 3, 4, 5, 6, 7, 8, 9];
+        for &format in &[Format::Dwarf32, Format::Dwarf64] {
+            let encoding = Encoding {
+                format,
+                version: 5,
+                address_size: 8,
+            };
+            for &unit_type in &[
+                UnitType::Compilation,
+                UnitType::Type {
+                    type_signature: DebugTypeSignature(0xdead_beef_dead_beef),
+                    type_offset: UnitOffset(0x7856_3412),
+                },
+                UnitType::Partial,
+                UnitType::Skeleton(DwoId(0x0102_0304_0506_0708)),
+                UnitType::SplitCompilation(DwoId(0x0807_0605_0403_0201)),
+                UnitType::SplitType {
+                    type_signature: DebugTypeSignature(0xfeed_face_feed_face),
+                    type_offset: UnitOffset(0x1234_5678),
+                },
+            ] {
+                let mut expected_unit = UnitHeader {
+                    encoding,
+                    unit_length: 0,
+                    unit_type,
+                    debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
+                    entries_buf: EndianSlice::new(expected_rest, LittleEndian),
+                };
+                let section = Section::with_endian(Endian::Little)
+                    .unit(&mut expected_unit, &[])
+                    .append_bytes(expected_rest);
+                let buf = section.get_contents().unwrap();
+                let rest = &mut EndianSlice::new(&buf, LittleEndian);
+
+                let unit = parse_unit_header(rest).unwrap();
+                assert_eq!(unit, expected_unit);
+                assert_eq!(unit.unit_type().dw_ut(), unit_type.dw_ut());
+                assert_eq!(*rest, EndianSlice::new(expected_rest, LittleEndian));
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_v5_unit_header_unknown_unit_type() {
+        let section = Section::with_endian(Endian::Little)
+            .L32(8)
+            .L16(5)
+            .D8(constants::DW_UT_lo_user.0)
+            .D8(8)
+            .L32(0)
+            .append_bytes(&[0]);
+        let buf = section.get_contents().unwrap();
+        let rest = &mut EndianSlice::new(&buf, LittleEndian);
+
+        match parse_unit_header(rest) {
+            Err(Error::UnsupportedUnitType) => {}
+            otherwise => panic!("Unexpected result: {:?}", otherwise),
+        }
+    }
+
+    #[test]
+    fn test_v5_units_iter() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 4,
+        };
+        let mut units = Vec::new();
+        for &unit_type in &[
+            UnitType::Compilation,
+            UnitType::Type {
+                type_signature: DebugTypeSignature(0xdead_beef_dead_beef),
+                type_offset: UnitOffset(0x1c),
+            },
+            UnitType::Partial,
+            UnitType::Skeleton(DwoId(0x0102_0304_0506_0708)),
+        ] {
+            units.push(CompilationUnitHeader {
+                header: UnitHeader {
+                    encoding,
+                    unit_length: 0,
+                    unit_type,
+                    debug_abbrev_offset: DebugAbbrevOffset(0),
+                    entries_buf: EndianSlice::new(&[0], LittleEndian),
+                },
+                offset: DebugInfoOffset(0),
+            });
+        }
+        let mut section = Section::with_endian(Endian::Little);
+        for unit in &mut units {
+            section = section.comp_unit(unit);
+        }
+        let buf = section.get_contents().unwrap();
+
+        let debug_info = DebugInfo::new(&buf, LittleEndian);
+        let mut iter = debug_info.units();
+        for unit in &units {
+            assert_eq!(iter.next(), Ok(Some(*unit)));
+        }
+        assert_eq!(iter.next(), Ok(None));
+    }
+
     #[test]
     fn test_parse_type_offset_32_ok() {
         let buf = [0x12, 0x34, 0x56, 0x78, 0x00];
@@ -3559,6 +3817,10 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Type {
+                    type_signature: DebugTypeSignature(0xdead_beef_dead_beef),
+                    type_offset: UnitOffset(0x7856_3412),
+                },
                 debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
                 entries_buf: EndianSlice::new(expected_rest, LittleEndian),
             },
@@ -3592,6 +3854,10 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Type {
+                    type_signature: DebugTypeSignature(0xdead_beef_dead_beef),
+                    type_offset: UnitOffset(0x7856_3412_7856_3412),
+                },
                 debug_abbrev_offset: DebugAbbrevOffset(0x0807_0605),
                 entries_buf: EndianSlice::new(expected_rest, LittleEndian),
             },
@@ -3756,6 +4022,33 @@ mod tests {
                 AttributeValue::SecOffset(0x0102_0304),
                 AttributeValue::DebugLocListsBase(DebugLocListsBase(0x0102_0304)),
             ),
+            (
+                Format::Dwarf32,
+                4,
+                constants::DW_AT_GNU_addr_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x0102_0304),
+                AttributeValue::DebugAddrBase(DebugAddrBase(0x0102_0304)),
+            ),
+            (
+                Format::Dwarf32,
+                4,
+                constants::DW_AT_GNU_ranges_base,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x0102_0304),
+                AttributeValue::DebugRngListsBase(DebugRngListsBase(0x0102_0304)),
+            ),
+            (
+                Format::Dwarf32,
+                5,
+                constants::DW_AT_macros,
+                constants::DW_FORM_sec_offset,
+                data4,
+                AttributeValue::SecOffset(0x0102_0304),
+                AttributeValue::DebugMacroRef(DebugMacroOffset(0x0102_0304)),
+            ),
         ];
 
         for test in tests.iter() {
@@ -3835,6 +4128,7 @@ mod tests {
         UnitHeader::new(
             encoding,
             7,
+            UnitType::Compilation,
             DebugAbbrevOffset(0x0807_0605),
             EndianSlice::new(&[], endian),
         )
@@ -4422,6 +4716,7 @@ mod tests {
         let unit = UnitHeader::new(
             encoding,
             7,
+            UnitType::Compilation,
             DebugAbbrevOffset(0x0807_0605),
             EndianSlice::new(&[], LittleEndian),
         );
@@ -4533,6 +4828,7 @@ mod tests {
         let unit = UnitHeader::new(
             encoding,
             7,
+            UnitType::Compilation,
             DebugAbbrevOffset(0x0807_0605),
             EndianSlice::new(&[], LittleEndian),
         );
@@ -4739,6 +5035,7 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Compilation,
                 debug_abbrev_offset: DebugAbbrevOffset(0),
                 entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
             },
@@ -4766,6 +5063,7 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Compilation,
                 debug_abbrev_offset: DebugAbbrevOffset(0),
                 entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
             },
@@ -5097,6 +5395,7 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Compilation,
                 debug_abbrev_offset: DebugAbbrevOffset(0),
                 entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
             },
@@ -5138,6 +5437,10 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Type {
+                    type_signature: DebugTypeSignature(0),
+                    type_offset: UnitOffset(0),
+                },
                 debug_abbrev_offset: DebugAbbrevOffset(0),
                 entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
             },
@@ -5294,6 +5597,7 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Compilation,
                 debug_abbrev_offset: DebugAbbrevOffset(0),
                 entries_buf: EndianSlice::new(&entries_buf, LittleEndian),
             },
@@ -5390,6 +5694,7 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Compilation,
                 debug_abbrev_offset: DebugAbbrevOffset(0),
                 entries_buf: EndianSlice::new(entries, LittleEndian),
             },
@@ -5441,6 +5746,10 @@ mod tests {
             header: UnitHeader {
                 encoding,
                 unit_length: 0,
+                unit_type: UnitType::Type {
+                    type_signature: DebugTypeSignature(0),
+                    type_offset: UnitOffset(0),
+                },
                 debug_abbrev_offset: DebugAbbrevOffset(0),
                 entries_buf: EndianSlice::new(entries, LittleEndian),
             },
@@ -5494,6 +5803,7 @@ mod tests {
         let mut unit = UnitHeader {
             encoding,
             unit_length: 0,
+            unit_type: UnitType::Compilation,
             debug_abbrev_offset: DebugAbbrevOffset(0),
             entries_buf: EndianSlice::new(&[], LittleEndian),
         };
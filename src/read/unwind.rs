@@ -0,0 +1,812 @@
+use std::fmt;
+
+use common::{Encoding, Register};
+use read::{
+    BaseAddresses, CfaRule, Error, Evaluation, EvaluationContext, Expression, FdeIndex,
+    FrameDescriptionEntry, Location, Reader, ReaderOffset, RegisterRule, Result, StoreOnStack,
+    UninitializedUnwindContext, UnitOffset, UnwindContextStorage, UnwindSection, UnwindTableRow,
+    Value,
+};
+
+/// The register values of a single frame, as used by an
+/// [`Unwinder`](struct.Unwinder.html).
+///
+/// Implementations are specific to an architecture, and are free to choose
+/// which registers they track.
+pub trait UnwindRegisters: Clone {
+    /// The register that contains the stack pointer.
+    ///
+    /// When a frame is unwound, the stack pointer of the caller is set to the
+    /// CFA of the callee, unless the CFI gives an explicit rule for it.
+    fn stack_pointer(&self) -> Register;
+
+    /// Return the value of the given register, or `None` if its value is
+    /// unknown.
+    fn get(&self, register: Register) -> Option<u64>;
+
+    /// Set the value of the given register.
+    fn set(&mut self, register: Register, value: u64);
+
+    /// Mark the value of the given register as unknown.
+    fn undefine(&mut self, register: Register);
+
+    /// Remove the pointer authentication code from a signed return address.
+    ///
+    /// This is called for frames whose unwind table row says that the return
+    /// address is signed, which is only the case for sections using
+    /// `Vendor::AArch64`. Which bits hold the code depends on the target's
+    /// virtual address size, so the default implementation returns the
+    /// address unchanged.
+    fn strip_return_address(&self, address: u64) -> u64 {
+        address
+    }
+}
+
+/// A stack unwinder that walks frames by applying the `UnwindTableRow` for
+/// each frame's program counter to its registers.
+///
+/// Memory is read with a callback that is given an address and a size in
+/// bytes, and returns the value read in the target's byte order, or `None`
+/// if the memory cannot be read.
+///
+/// ```
+/// use gimli::{BaseAddresses, EhFrame, FdeIndex, NativeEndian, Register, Unwinder, UnwindRegisters};
+///
+/// #[derive(Clone)]
+/// struct Registers([Option<u64>; 17]);
+///
+/// impl UnwindRegisters for Registers {
+///     fn stack_pointer(&self) -> Register {
+///         gimli::X86_64::RSP
+///     }
+///     fn get(&self, register: Register) -> Option<u64> {
+///         self.0.get(register.0 as usize).cloned().unwrap_or(None)
+///     }
+///     fn set(&mut self, register: Register, value: u64) {
+///         if let Some(slot) = self.0.get_mut(register.0 as usize) {
+///             *slot = Some(value);
+///         }
+///     }
+///     fn undefine(&mut self, register: Register) {
+///         if let Some(slot) = self.0.get_mut(register.0 as usize) {
+///             *slot = None;
+///         }
+///     }
+/// }
+///
+/// # fn foo() -> gimli::Result<()> {
+/// # let read_eh_frame_section = || unimplemented!();
+/// # let read_memory = |_address: u64, _size: u8| -> Option<u64> { unimplemented!() };
+/// # let (pc, registers): (u64, Registers) = unimplemented!();
+/// let eh_frame = EhFrame::new(read_eh_frame_section(), NativeEndian);
+/// let bases = BaseAddresses::default();
+/// let index = FdeIndex::new(&eh_frame, &bases)?;
+/// let mut unwinder = Unwinder::new(&eh_frame, &bases, registers, pc, read_memory);
+/// unwinder.set_fde_index(&index);
+/// while let Some(pc) = unwinder.next()? {
+///     println!("{:#x}", pc);
+/// }
+/// # unreachable!()
+/// # }
+/// ```
+pub struct Unwinder<'a, Section, R, Registers, Memory, S = StoreOnStack>
+where
+    R: Reader,
+    Section: 'a + UnwindSection<R>,
+    S: UnwindContextStorage<R>,
+{
+    section: &'a Section,
+    bases: &'a BaseAddresses,
+    index: Option<&'a FdeIndex<Section, R>>,
+    ctx: UninitializedUnwindContext<Section, R, S>,
+    registers: Registers,
+    memory: Memory,
+    pc: u64,
+    cfa: Option<u64>,
+    // True if `pc` is the address of the next instruction to execute,
+    // rather than a return address.
+    exact_pc: bool,
+    done: bool,
+}
+
+impl<'a, Section, R, Registers, Memory, S> fmt::Debug
+    for Unwinder<'a, Section, R, Registers, Memory, S>
+where
+    R: Reader,
+    Section: 'a + UnwindSection<R>,
+    Registers: fmt::Debug,
+    S: UnwindContextStorage<R>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Unwinder")
+            .field("section", &self.section)
+            .field("bases", &self.bases)
+            .field("registers", &self.registers)
+            .field("pc", &self.pc)
+            .field("cfa", &self.cfa)
+            .field("exact_pc", &self.exact_pc)
+            .field("done", &self.done)
+            .finish()
+    }
+}
+
+impl<'a, Section, R, Registers, Memory> Unwinder<'a, Section, R, Registers, Memory>
+where
+    R: Reader,
+    Section: 'a + UnwindSection<R>,
+    Registers: UnwindRegisters,
+    Memory: FnMut(u64, u8) -> Option<u64>,
+{
+    /// Construct a new `Unwinder` for the innermost frame, given its program
+    /// counter and registers.
+    pub fn new(
+        section: &'a Section,
+        bases: &'a BaseAddresses,
+        registers: Registers,
+        pc: u64,
+        memory: Memory,
+    ) -> Self {
+        Unwinder::with_context(
+            section,
+            bases,
+            UninitializedUnwindContext::new(),
+            registers,
+            pc,
+            memory,
+        )
+    }
+}
+
+impl<'a, Section, R, Registers, Memory, S> Unwinder<'a, Section, R, Registers, Memory, S>
+where
+    R: Reader,
+    Section: 'a + UnwindSection<R>,
+    Registers: UnwindRegisters,
+    Memory: FnMut(u64, u8) -> Option<u64>,
+    S: UnwindContextStorage<R>,
+{
+    /// Construct a new `Unwinder` that uses the given context to evaluate
+    /// CFI programs.
+    ///
+    /// Use this to reuse a context, or to use an `UnwindContextStorage` other
+    /// than `StoreOnStack`.
+    pub fn with_context(
+        section: &'a Section,
+        bases: &'a BaseAddresses,
+        ctx: UninitializedUnwindContext<Section, R, S>,
+        registers: Registers,
+        pc: u64,
+        memory: Memory,
+    ) -> Self {
+        Unwinder {
+            section,
+            bases,
+            index: None,
+            ctx,
+            registers,
+            memory,
+            pc,
+            cfa: None,
+            exact_pc: true,
+            done: false,
+        }
+    }
+
+    /// Use the given index to find the FDE for each frame.
+    ///
+    /// Without an index, the entries of the section are searched in order
+    /// for every frame. `index` must have been built for the same section
+    /// and bases as this `Unwinder`.
+    #[inline]
+    pub fn set_fde_index(&mut self, index: &'a FdeIndex<Section, R>) {
+        self.index = Some(index);
+    }
+
+    /// The program counter of the current frame.
+    ///
+    /// For all frames except the innermost frame and frames interrupted by a
+    /// signal, this is a return address.
+    #[inline]
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    /// The registers of the current frame.
+    #[inline]
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// The CFA of the most recently unwound frame, which is the stack pointer
+    /// of the current frame.
+    ///
+    /// Returns `None` before the first call to `next`.
+    #[inline]
+    pub fn cfa(&self) -> Option<u64> {
+        self.cfa
+    }
+
+    /// Unwind the current frame, and return the program counter of its caller.
+    ///
+    /// Returns `Ok(None)` once the outermost frame has been reached, which is
+    /// indicated by the CFI giving the return address an undefined rule.
+    ///
+    /// Registers that have no rule in the `UnwindTableRow` keep their value,
+    /// since ABIs commonly omit rules for callee-saved registers that a
+    /// function does not modify.
+    ///
+    /// If an error is returned then the current frame is left unchanged.
+    pub fn next(&mut self) -> Result<Option<u64>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        // A return address is the address after the call instruction, which
+        // may be outside of the calling function, so look up the address of
+        // the call instruction instead.
+        let address = if self.exact_pc {
+            self.pc
+        } else {
+            self.pc.wrapping_sub(1)
+        };
+        let fde = match self.index {
+            Some(index) => index.fde_for_address(self.section, self.bases, address)?,
+            None => self.section.fde_for_address(self.bases, address)?,
+        };
+        let encoding = fde.cie().encoding();
+        match encoding.address_size {
+            1...8 => {}
+            size => return Err(Error::UnsupportedAddressSize(size)),
+        }
+        let row = self.row_for_address(&fde, address)?;
+        let return_address_register = fde.cie().return_address_register();
+
+        if let RegisterRule::Undefined = row.register(return_address_register) {
+            self.done = true;
+            return Ok(None);
+        }
+
+        let cfa = self.evaluate_cfa(row.cfa(), encoding)?;
+        let mut registers = self.registers.clone();
+        registers.set(self.registers.stack_pointer(), cfa);
+
+        for &(register, ref rule) in row.registers() {
+            match *rule {
+                RegisterRule::Undefined => registers.undefine(register),
+                RegisterRule::SameValue | RegisterRule::Architectural => {}
+                _ => {
+                    let value = self.evaluate_rule(register, rule, cfa, encoding)?;
+                    registers.set(register, value);
+                }
+            }
+        }
+
+        let mut pc = registers
+            .get(return_address_register)
+            .ok_or(Error::UnavailableRegister(return_address_register))?;
+        if row.is_return_address_signed() {
+            pc = registers.strip_return_address(pc);
+        }
+
+        self.registers = registers;
+        self.pc = pc;
+        self.cfa = Some(cfa);
+        // The caller of a signal trampoline was interrupted, so its program
+        // counter is not a return address.
+        self.exact_pc = fde.is_signal_trampoline();
+        Ok(Some(pc))
+    }
+
+    fn row_for_address(
+        &mut self,
+        fde: &FrameDescriptionEntry<Section, R, R::Offset>,
+        address: u64,
+    ) -> Result<UnwindTableRow<R, S>> {
+        self.ctx.row_for_address(fde, address)
+    }
+
+    fn evaluate_cfa(&mut self, rule: &CfaRule<R>, encoding: Encoding) -> Result<u64> {
+        match *rule {
+            CfaRule::RegisterAndOffset { register, offset } => {
+                let value = self
+                    .registers
+                    .get(register)
+                    .ok_or(Error::UnavailableRegister(register))?;
+                Ok(value.wrapping_add(offset as u64))
+            }
+            CfaRule::Expression(ref expression) => {
+                self.evaluate_expression(expression, encoding, None)
+            }
+        }
+    }
+
+    fn evaluate_rule(
+        &mut self,
+        register: Register,
+        rule: &RegisterRule<R>,
+        cfa: u64,
+        encoding: Encoding,
+    ) -> Result<u64> {
+        let size = encoding.address_size;
+        let address = match *rule {
+            RegisterRule::Offset(offset) => cfa.wrapping_add(offset as u64),
+            RegisterRule::ValOffset(offset) => return Ok(cfa.wrapping_add(offset as u64)),
+            RegisterRule::Register(other) => {
+                return self
+                    .registers
+                    .get(other)
+                    .ok_or(Error::UnavailableRegister(other));
+            }
+            RegisterRule::Expression(ref expression) => {
+                self.evaluate_expression(expression, encoding, Some(cfa))?
+            }
+            RegisterRule::ValExpression(ref expression) => {
+                return self.evaluate_expression(expression, encoding, Some(cfa));
+            }
+            RegisterRule::Undefined | RegisterRule::SameValue | RegisterRule::Architectural => {
+                return self
+                    .registers
+                    .get(register)
+                    .ok_or(Error::UnavailableRegister(register));
+            }
+        };
+        (self.memory)(address, size).ok_or(Error::UnavailableMemory(address))
+    }
+
+    /// Evaluate a CFI expression. For register rules, `cfa` is pushed onto the
+    /// stack before evaluation.
+    fn evaluate_expression(
+        &mut self,
+        expression: &Expression<R>,
+        encoding: Encoding,
+        cfa: Option<u64>,
+    ) -> Result<u64> {
+        let mut evaluation = Evaluation::new(expression.0.clone(), encoding);
+        if let Some(cfa) = cfa {
+            evaluation.set_initial_value(cfa);
+        }
+        evaluation.evaluate_with(&mut UnwindEvaluationContext {
+            registers: &self.registers,
+            memory: &mut self.memory,
+            cfa,
+        })?;
+
+        let mut pieces = evaluation.result();
+        if pieces.len() != 1 || pieces[0].size_in_bits.is_some() {
+            return Err(Error::UnsupportedUnwindExpression);
+        }
+        match pieces.pop().unwrap().location {
+            Location::Address { address } => Ok(address),
+            Location::Value { value } => {
+                // `next` has checked that the address size is 1 to 8.
+                value.to_u64(!0 >> (64 - u32::from(encoding.address_size) * 8))
+            }
+            _ => Err(Error::UnsupportedUnwindExpression),
+        }
+    }
+}
+
+struct UnwindEvaluationContext<'a, Registers: 'a, Memory: 'a> {
+    registers: &'a Registers,
+    memory: &'a mut Memory,
+    cfa: Option<u64>,
+}
+
+impl<'a, R, Registers, Memory> EvaluationContext<R>
+    for UnwindEvaluationContext<'a, Registers, Memory>
+where
+    R: Reader,
+    Registers: UnwindRegisters,
+    Memory: FnMut(u64, u8) -> Option<u64>,
+{
+    fn memory(
+        &mut self,
+        address: u64,
+        size: u8,
+        _space: Option<u64>,
+        base_type: UnitOffset<R::Offset>,
+    ) -> Option<Value> {
+        // CFI has no debugging information entries, so only the generic type
+        // is supported.
+        if base_type != UnitOffset(R::Offset::from_u8(0)) {
+            return None;
+        }
+        (self.memory)(address, size).map(Value::Generic)
+    }
+
+    fn register(&mut self, register: Register, base_type: UnitOffset<R::Offset>) -> Option<Value> {
+        if base_type != UnitOffset(R::Offset::from_u8(0)) {
+            return None;
+        }
+        self.registers.get(register).map(Value::Generic)
+    }
+
+    fn call_frame_cfa(&mut self) -> Option<u64> {
+        self.cfa
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate test_assembler;
+
+    use self::test_assembler::{Endian, Label, LabelMaker, Section};
+    use super::*;
+    use arch::{AArch64, X86_64};
+    use common::Vendor;
+    use constants;
+    use endianity::LittleEndian;
+    use read::{DebugFrame, EhFrame, EndianSlice};
+    use test_util::GimliSectionMethods;
+    use vec::Vec;
+
+    #[derive(Clone, Debug)]
+    struct Registers {
+        stack_pointer: Register,
+        values: Vec<(Register, u64)>,
+    }
+
+    impl Registers {
+        fn new(stack_pointer: Register) -> Self {
+            Registers {
+                stack_pointer,
+                values: Vec::new(),
+            }
+        }
+    }
+
+    impl UnwindRegisters for Registers {
+        fn stack_pointer(&self) -> Register {
+            self.stack_pointer
+        }
+
+        fn get(&self, register: Register) -> Option<u64> {
+            self.values
+                .iter()
+                .find(|entry| entry.0 == register)
+                .map(|entry| entry.1)
+        }
+
+        fn set(&mut self, register: Register, value: u64) {
+            self.undefine(register);
+            self.values.push((register, value));
+        }
+
+        fn undefine(&mut self, register: Register) {
+            self.values.retain(|entry| entry.0 != register);
+        }
+
+        fn strip_return_address(&self, address: u64) -> u64 {
+            address & 0x0000_ffff_ffff_ffff
+        }
+    }
+
+    fn cie(
+        section: Section,
+        label: &Label,
+        augmentation: &str,
+        return_address: Register,
+        instructions: Section,
+    ) -> Section {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let section = section
+            .mark(label)
+            .D32(&length)
+            .mark(&start)
+            .D32(0xffff_ffff)
+            .D8(1)
+            .append_bytes(augmentation.as_bytes())
+            .D8(0)
+            .uleb(1)
+            .sleb(-8)
+            .D8(return_address.0 as u8);
+        let section = if augmentation.is_empty() {
+            section
+        } else {
+            section.uleb(0)
+        };
+        let section = section
+            .append_bytes(&instructions.get_contents().unwrap())
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        section
+    }
+
+    fn fde(
+        section: Section,
+        cie: &Label,
+        augmentation: bool,
+        address: u64,
+        instructions: Section,
+    ) -> Section {
+        let length = Label::new();
+        let start = Label::new();
+        let end = Label::new();
+        let section = section
+            .D32(&length)
+            .mark(&start)
+            .D32(cie)
+            .D64(address)
+            .D64(0x100);
+        let section = if augmentation {
+            section.uleb(0)
+        } else {
+            section
+        };
+        let section = section
+            .append_bytes(&instructions.get_contents().unwrap())
+            .mark(&end);
+        length.set_const((&end - &start) as u64);
+        section
+    }
+
+    fn debug_frame() -> Vec<u8> {
+        let cie1 = Label::new();
+        let cie2 = Label::new();
+
+        let section = Section::with_endian(Endian::Little);
+        let section = cie(
+            section,
+            &cie1,
+            "",
+            X86_64::RA,
+            Section::with_endian(Endian::Little)
+                .D8(constants::DW_CFA_def_cfa.0)
+                .uleb(X86_64::RSP.0.into())
+                .uleb(8)
+                .D8(constants::DW_CFA_offset.0 | X86_64::RA.0 as u8)
+                .uleb(1),
+        );
+        let section = cie(
+            section,
+            &cie2,
+            "zS",
+            X86_64::RA,
+            Section::with_endian(Endian::Little),
+        );
+
+        // A normal function that has pushed RBP.
+        let section = fde(
+            section,
+            &cie1,
+            false,
+            0x1000,
+            Section::with_endian(Endian::Little)
+                .D8(constants::DW_CFA_advance_loc.0 | 1)
+                .D8(constants::DW_CFA_def_cfa_offset.0)
+                .uleb(16)
+                .D8(constants::DW_CFA_offset.0 | X86_64::RBP.0 as u8)
+                .uleb(2),
+        );
+
+        // A signal trampoline that describes everything with expressions.
+        let section = fde(
+            section,
+            &cie2,
+            true,
+            0x2000,
+            Section::with_endian(Endian::Little)
+                .D8(constants::DW_CFA_def_cfa_expression.0)
+                .uleb(2)
+                .D8(constants::DW_OP_breg7.0)
+                .sleb(0x20)
+                .D8(constants::DW_CFA_expression.0)
+                .uleb(X86_64::RA.0.into())
+                .uleb(2)
+                .D8(constants::DW_OP_breg7.0)
+                .sleb(8)
+                .D8(constants::DW_CFA_val_expression.0)
+                .uleb(X86_64::RBP.0.into())
+                .uleb(2)
+                .D8(constants::DW_OP_plus_uconst.0)
+                .uleb(4),
+        );
+
+        // The outermost function.
+        let section = fde(
+            section,
+            &cie1,
+            false,
+            0x3000,
+            Section::with_endian(Endian::Little)
+                .D8(constants::DW_CFA_undefined.0)
+                .uleb(X86_64::RA.0.into()),
+        );
+
+        section.start().set_const(0);
+        section.get_contents().unwrap()
+    }
+
+    fn read_memory(address: u64, size: u8) -> Option<u64> {
+        assert_eq!(size, 8);
+        match address {
+            0x8000 => Some(0x7777),
+            0x8008 => Some(0x2005),
+            0x8018 => Some(0x3000),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_unwinder() {
+        let contents = debug_frame();
+        let mut debug_frame = DebugFrame::new(&contents, LittleEndian);
+        debug_frame.set_address_size(8);
+        let bases = BaseAddresses::default();
+
+        let mut registers = Registers::new(X86_64::RSP);
+        registers.set(X86_64::RSP, 0x8000);
+        registers.set(X86_64::RBP, 0x9000);
+        let mut unwinder = Unwinder::new(&debug_frame, &bases, registers, 0x1010, read_memory);
+        assert_eq!(unwinder.cfa(), None);
+
+        assert_eq!(unwinder.next(), Ok(Some(0x2005)));
+        assert_eq!(unwinder.pc(), 0x2005);
+        assert_eq!(unwinder.cfa(), Some(0x8010));
+        assert_eq!(unwinder.registers().get(X86_64::RSP), Some(0x8010));
+        assert_eq!(unwinder.registers().get(X86_64::RBP), Some(0x7777));
+
+        assert_eq!(unwinder.next(), Ok(Some(0x3000)));
+        assert_eq!(unwinder.cfa(), Some(0x8030));
+        assert_eq!(unwinder.registers().get(X86_64::RSP), Some(0x8030));
+        assert_eq!(unwinder.registers().get(X86_64::RBP), Some(0x8034));
+
+        // The caller of the signal trampoline is looked up at its exact
+        // address, which is the start of the outermost function.
+        assert_eq!(unwinder.next(), Ok(None));
+        assert_eq!(unwinder.pc(), 0x3000);
+        assert_eq!(unwinder.next(), Ok(None));
+    }
+
+    #[test]
+    fn test_unwinder_fde_index() {
+        let contents = debug_frame();
+        let mut debug_frame = DebugFrame::new(&contents, LittleEndian);
+        debug_frame.set_address_size(8);
+        let bases = BaseAddresses::default();
+        let index = FdeIndex::new(&debug_frame, &bases).unwrap();
+
+        let mut registers = Registers::new(X86_64::RSP);
+        registers.set(X86_64::RSP, 0x8000);
+        registers.set(X86_64::RBP, 0x9000);
+        let mut unwinder = Unwinder::new(&debug_frame, &bases, registers, 0x1010, read_memory);
+        unwinder.set_fde_index(&index);
+        assert_eq!(unwinder.next(), Ok(Some(0x2005)));
+        assert_eq!(unwinder.registers().get(X86_64::RBP), Some(0x7777));
+        assert_eq!(unwinder.next(), Ok(Some(0x3000)));
+        assert_eq!(unwinder.registers().get(X86_64::RBP), Some(0x8034));
+        assert_eq!(unwinder.next(), Ok(None));
+
+        let registers = Registers::new(X86_64::RSP);
+        let mut unwinder = Unwinder::new(&debug_frame, &bases, registers, 0x5000, read_memory);
+        unwinder.set_fde_index(&index);
+        assert_eq!(unwinder.next(), Err(Error::NoUnwindInfoForAddress));
+    }
+
+    #[test]
+    fn test_unwinder_errors() {
+        let contents = debug_frame();
+        let mut debug_frame = DebugFrame::new(&contents, LittleEndian);
+        debug_frame.set_address_size(8);
+        let bases = BaseAddresses::default();
+
+        let mut registers = Registers::new(X86_64::RSP);
+        registers.set(X86_64::RSP, 0x8000);
+        let mut unwinder =
+            Unwinder::new(&debug_frame, &bases, registers.clone(), 0x5000, read_memory);
+        assert_eq!(unwinder.next(), Err(Error::NoUnwindInfoForAddress));
+
+        let mut unwinder =
+            Unwinder::new(&debug_frame, &bases, registers.clone(), 0x1000, |_, _| None);
+        assert_eq!(unwinder.next(), Err(Error::UnavailableMemory(0x8000)));
+        assert_eq!(unwinder.pc(), 0x1000);
+        assert_eq!(unwinder.registers().get(X86_64::RSP), Some(0x8000));
+
+        registers.undefine(X86_64::RSP);
+        let mut unwinder = Unwinder::new(&debug_frame, &bases, registers, 0x1000, read_memory);
+        assert_eq!(
+            unwinder.next(),
+            Err(Error::UnavailableRegister(X86_64::RSP))
+        );
+    }
+
+    #[test]
+    fn test_unwinder_signed_return_address() {
+        let cie1 = Label::new();
+        let section = Section::with_endian(Endian::Little);
+        let section = cie(
+            section,
+            &cie1,
+            "",
+            AArch64::X30,
+            Section::with_endian(Endian::Little)
+                .D8(constants::DW_CFA_def_cfa.0)
+                .uleb(AArch64::SP.0.into())
+                .uleb(8)
+                .D8(constants::DW_CFA_offset.0 | AArch64::X30.0 as u8)
+                .uleb(1),
+        );
+        let section = fde(
+            section,
+            &cie1,
+            false,
+            0x1000,
+            Section::with_endian(Endian::Little).D8(constants::DW_CFA_AARCH64_negate_ra_state.0),
+        );
+        section.start().set_const(0);
+        let contents = section.get_contents().unwrap();
+
+        let mut debug_frame = DebugFrame::new(&contents, LittleEndian);
+        debug_frame.set_address_size(8);
+        debug_frame.set_vendor(Vendor::AArch64);
+        let bases = BaseAddresses::default();
+
+        let mut registers = Registers::new(AArch64::SP);
+        registers.set(AArch64::SP, 0x8000);
+        let read_memory = |address, size| {
+            assert_eq!((address, size), (0x8000, 8));
+            Some(0x0012_0000_0000_2005)
+        };
+        let mut unwinder = Unwinder::new(&debug_frame, &bases, registers, 0x1010, read_memory);
+        assert_eq!(unwinder.next(), Ok(Some(0x2005)));
+        assert_eq!(unwinder.pc(), 0x2005);
+        assert_eq!(
+            unwinder.registers().get(AArch64::X30),
+            Some(0x0012_0000_0000_2005)
+        );
+    }
+
+    #[test]
+    fn test_unwinder_invalid_address_size() {
+        // The pointers in the FDE have an explicit encoding, so they can be
+        // parsed without a valid address size.
+        let cie_length = Label::new();
+        let cie_start = Label::new();
+        let cie_end = Label::new();
+        let fde_length = Label::new();
+        let fde_start = Label::new();
+        let fde_end = Label::new();
+        let section = Section::with_endian(Endian::Little)
+            .D32(&cie_length)
+            .mark(&cie_start)
+            .D32(0)
+            .D8(1)
+            .append_bytes(b"zR\0")
+            .uleb(1)
+            .sleb(-8)
+            .D8(X86_64::RA.0 as u8)
+            .uleb(1)
+            .D8(constants::DW_EH_PE_udata8.0)
+            .D8(constants::DW_CFA_def_cfa.0)
+            .uleb(X86_64::RSP.0.into())
+            .uleb(8)
+            .mark(&cie_end)
+            .D32(&fde_length)
+            .mark(&fde_start)
+            .D32(&fde_start)
+            .D64(0x1000)
+            .D64(0x100)
+            .uleb(0)
+            .mark(&fde_end);
+        cie_length.set_const((&cie_end - &cie_start) as u64);
+        fde_length.set_const((&fde_end - &fde_start) as u64);
+        section.start().set_const(0);
+        let contents = section.get_contents().unwrap();
+
+        let bases = BaseAddresses::default();
+        for &address_size in &[0, 16] {
+            let mut eh_frame = EhFrame::new(&contents, LittleEndian);
+            eh_frame.set_address_size(address_size);
+
+            let mut registers = Registers::new(X86_64::RSP);
+            registers.set(X86_64::RSP, 0x8000);
+            let mut unwinder = Unwinder::new(&eh_frame, &bases, registers, 0x1010, read_memory);
+            assert_eq!(
+                unwinder.next(),
+                Err(Error::UnsupportedAddressSize(address_size))
+            );
+        }
+    }
+}
@@ -910,7 +910,7 @@ mod tests {
     use endianity::LittleEndian;
     use read::{
         Abbreviation, AttributeSpecification, DebuggingInformationEntry, EndianSlice, UnitHeader,
-        UnitOffset,
+        UnitOffset, UnitType,
     };
 
     #[test]
@@ -924,6 +924,7 @@ mod tests {
         let unit = UnitHeader::new(
             encoding,
             7,
+            UnitType::Compilation,
             DebugAbbrevOffset(0),
             EndianSlice::new(&[], LittleEndian),
         );
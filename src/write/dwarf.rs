@@ -2,8 +2,8 @@ use vec::Vec;
 
 use common::Encoding;
 use write::{
-    AbbreviationTable, LineProgram, LineStringTable, Result, Sections, StringTable, Unit,
-    UnitTable, Writer,
+    AbbreviationTable, LineProgram, LineStringTable, NameTable, Result, Sections, StringTable,
+    Unit, UnitTable, Writer,
 };
 
 /// Writable DWARF information for more than one unit.
@@ -24,14 +24,32 @@ pub struct Dwarf {
 
     /// A table of strings that will be stored in the `.debug_str` section.
     pub strings: StringTable,
+
+    /// Whether to generate a name index for the units in the `.debug_names` section.
+    pub debug_names: bool,
 }
 
 impl Dwarf {
     /// Write the DWARF information to the given sections.
     pub fn write<W: Writer>(&mut self, sections: &mut Sections<W>) -> Result<()> {
+        // This may add strings, so it must be done before the strings are written.
+        let names = if self.debug_names {
+            Some(NameTable::from_units(&self.units, &mut self.strings))
+        } else {
+            None
+        };
+
         let line_strings = self.line_strings.write(&mut sections.debug_line_str)?;
         let strings = self.strings.write(&mut sections.debug_str)?;
-        self.units.write(sections, &line_strings, &strings)?;
+        let debug_info_offsets = self.units.write(sections, &line_strings, &strings)?;
+        if let Some(names) = names {
+            names.write(
+                &mut sections.debug_names,
+                &self.units,
+                &debug_info_offsets,
+                &strings,
+            )?;
+        }
         for line_program in &self.line_programs {
             line_program.write(
                 &mut sections.debug_line,
@@ -126,6 +144,7 @@ pub(crate) mod convert {
                 line_programs,
                 line_strings,
                 strings,
+                debug_names: false,
             })
         }
     }
@@ -0,0 +1,637 @@
+use indexmap::IndexSet;
+use std::ops::{Deref, DerefMut};
+use vec::Vec;
+
+use common::{DebugLineOffset, DebugMacinfoOffset, DebugMacroOffset, Encoding, Format};
+use constants;
+use write::{
+    BaseId, DebugStrOffsets, Error, FileId, Result, Section, SectionId, Sections, StringId, Writer,
+};
+
+define_section!(
+    DebugMacinfo,
+    DebugMacinfoOffset,
+    "A writable `.debug_macinfo` section."
+);
+define_section!(
+    DebugMacro,
+    DebugMacroOffset,
+    "A writable `.debug_macro` section."
+);
+
+/// The section offsets of a series of macro units within the `.debug_macinfo`
+/// or `.debug_macro` sections.
+#[derive(Debug)]
+pub struct MacroUnitOffsets {
+    base_id: BaseId,
+    section: SectionId,
+    // We know ids start at 0.
+    offsets: Vec<usize>,
+}
+
+impl MacroUnitOffsets {
+    /// Return an empty list of offsets.
+    #[inline]
+    pub fn none() -> Self {
+        MacroUnitOffsets {
+            base_id: BaseId::default(),
+            section: SectionId::DebugMacinfo,
+            offsets: Vec::new(),
+        }
+    }
+
+    /// Get the offset
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is invalid.
+    #[inline]
+    pub fn get(&self, id: MacroUnitId) -> usize {
+        debug_assert_eq!(self.base_id, id.base_id);
+        self.offsets[id.index]
+    }
+
+    /// Return the number of offsets.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Return the section that the macro units were written to.
+    ///
+    /// This is either `SectionId::DebugMacinfo` or `SectionId::DebugMacro`.
+    #[inline]
+    pub fn section(&self) -> SectionId {
+        self.section
+    }
+}
+
+define_id!(
+    MacroUnitId,
+    "An identifier for a macro unit in a `MacroTable`."
+);
+
+/// A table of macro units that will be stored in a `.debug_macinfo` or `.debug_macro` section.
+#[derive(Debug, Default)]
+pub struct MacroTable {
+    base_id: BaseId,
+    units: IndexSet<MacroUnit>,
+}
+
+impl MacroTable {
+    /// Add a macro unit to the table.
+    ///
+    /// A macro unit can only import macro units that were added before it.
+    pub fn add(&mut self, unit: MacroUnit) -> MacroUnitId {
+        let (index, _) = self.units.insert_full(unit);
+        MacroUnitId::new(self.base_id, index)
+    }
+
+    /// Return true if any of the macro units refer to a source file.
+    pub(crate) fn has_files(&self) -> bool {
+        self.units.iter().any(|unit| {
+            unit.0.iter().any(|entry| match *entry {
+                MacroEntry::StartFile { .. } => true,
+                _ => false,
+            })
+        })
+    }
+
+    /// Write the macro table to the given section.
+    ///
+    /// `section` must be either `SectionId::DebugMacinfo` or
+    /// `SectionId::DebugMacro`. The `.debug_macinfo` section can only be
+    /// used for DWARF versions 2 to 4. The `.debug_macro` section can be
+    /// used for DWARF version 5, or as a GNU extension for earlier versions.
+    ///
+    /// `line_program` is the offset of the line number program that the
+    /// source file ids refer to.
+    pub(crate) fn write<W: Writer>(
+        &self,
+        sections: &mut Sections<W>,
+        section: SectionId,
+        encoding: Encoding,
+        line_program: Option<DebugLineOffset>,
+        strings: &DebugStrOffsets,
+    ) -> Result<MacroUnitOffsets> {
+        match encoding.version {
+            2...5 => {}
+            _ => return Err(Error::UnsupportedVersion(encoding.version)),
+        }
+        if self.units.is_empty() {
+            return Ok(MacroUnitOffsets {
+                base_id: self.base_id,
+                section,
+                offsets: Vec::new(),
+            });
+        }
+
+        match section {
+            SectionId::DebugMacinfo if encoding.version <= 4 => {
+                self.write_macinfo(&mut sections.debug_macinfo)
+            }
+            SectionId::DebugMacinfo => Err(Error::InvalidAttributeValue),
+            SectionId::DebugMacro => {
+                self.write_macro(&mut sections.debug_macro, encoding, line_program, strings)
+            }
+            _ => Err(Error::InvalidAttributeValue),
+        }
+    }
+
+    /// Write the macro table to the `.debug_macinfo` section.
+    ///
+    /// `.debug_macinfo` does not support imports, so the entries of imported
+    /// macro units are written in place of the import.
+    fn write_macinfo<W: Writer>(&self, w: &mut DebugMacinfo<W>) -> Result<MacroUnitOffsets> {
+        let mut offsets = Vec::new();
+        for unit in self.units.iter() {
+            offsets.push(w.offset().0);
+            self.write_macinfo_entries(w, unit)?;
+            w.write_u8(0)?;
+        }
+        Ok(MacroUnitOffsets {
+            base_id: self.base_id,
+            section: SectionId::DebugMacinfo,
+            offsets,
+        })
+    }
+
+    fn write_macinfo_entries<W: Writer>(
+        &self,
+        w: &mut DebugMacinfo<W>,
+        unit: &MacroUnit,
+    ) -> Result<()> {
+        for entry in &unit.0 {
+            match *entry {
+                MacroEntry::Define { line, ref text } => {
+                    w.write_u8(constants::DW_MACINFO_define.0)?;
+                    w.write_uleb128(line)?;
+                    write_macinfo_string(w, text)?;
+                }
+                MacroEntry::Undef { line, ref name } => {
+                    w.write_u8(constants::DW_MACINFO_undef.0)?;
+                    w.write_uleb128(line)?;
+                    write_macinfo_string(w, name)?;
+                }
+                MacroEntry::StartFile { line, file } => {
+                    w.write_u8(constants::DW_MACINFO_start_file.0)?;
+                    w.write_uleb128(line)?;
+                    w.write_uleb128(file.raw())?;
+                }
+                MacroEntry::EndFile => {
+                    w.write_u8(constants::DW_MACINFO_end_file.0)?;
+                }
+                MacroEntry::Import(id) => {
+                    debug_assert_eq!(self.base_id, id.base_id);
+                    let unit = self
+                        .units
+                        .get_index(id.index)
+                        .ok_or(Error::InvalidAttributeValue)?;
+                    self.write_macinfo_entries(w, unit)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the macro table to the `.debug_macro` section.
+    ///
+    /// For DWARF versions before 5, this writes the GNU extension, which
+    /// uses version 4 in the header and the same entry encodings.
+    fn write_macro<W: Writer>(
+        &self,
+        w: &mut DebugMacro<W>,
+        encoding: Encoding,
+        line_program: Option<DebugLineOffset>,
+        strings: &DebugStrOffsets,
+    ) -> Result<MacroUnitOffsets> {
+        let version = if encoding.version >= 5 { 5 } else { 4 };
+        let offset_size = encoding.format.word_size();
+        let mut offsets = Vec::new();
+        for unit in self.units.iter() {
+            offsets.push(w.offset().0);
+
+            let mut flags = 0;
+            if encoding.format == Format::Dwarf64 {
+                flags |= 0x1; // offset_size_flag
+            }
+            if line_program.is_some() {
+                flags |= 0x2; // debug_line_offset_flag
+            }
+            w.write_u16(version)?;
+            w.write_u8(flags)?;
+            if let Some(line_program) = line_program {
+                w.write_offset(line_program.0, SectionId::DebugLine, offset_size)?;
+            }
+
+            for entry in &unit.0 {
+                match *entry {
+                    MacroEntry::Define { line, ref text } => match *text {
+                        MacroString::String(ref val) => {
+                            w.write_u8(constants::DW_MACRO_define.0)?;
+                            w.write_uleb128(line)?;
+                            w.write(val)?;
+                            w.write_u8(0)?;
+                        }
+                        MacroString::StringRef(val) => {
+                            w.write_u8(constants::DW_MACRO_define_strp.0)?;
+                            w.write_uleb128(line)?;
+                            w.write_offset(strings.get(val).0, SectionId::DebugStr, offset_size)?;
+                        }
+                    },
+                    MacroEntry::Undef { line, ref name } => match *name {
+                        MacroString::String(ref val) => {
+                            w.write_u8(constants::DW_MACRO_undef.0)?;
+                            w.write_uleb128(line)?;
+                            w.write(val)?;
+                            w.write_u8(0)?;
+                        }
+                        MacroString::StringRef(val) => {
+                            w.write_u8(constants::DW_MACRO_undef_strp.0)?;
+                            w.write_uleb128(line)?;
+                            w.write_offset(strings.get(val).0, SectionId::DebugStr, offset_size)?;
+                        }
+                    },
+                    MacroEntry::StartFile { line, file } => {
+                        w.write_u8(constants::DW_MACRO_start_file.0)?;
+                        w.write_uleb128(line)?;
+                        w.write_uleb128(file.raw())?;
+                    }
+                    MacroEntry::EndFile => {
+                        w.write_u8(constants::DW_MACRO_end_file.0)?;
+                    }
+                    MacroEntry::Import(id) => {
+                        debug_assert_eq!(self.base_id, id.base_id);
+                        // Imported units are always written before the units that import them.
+                        let offset = *offsets.get(id.index).ok_or(Error::InvalidAttributeValue)?;
+                        w.write_u8(constants::DW_MACRO_import.0)?;
+                        w.write_offset(offset, SectionId::DebugMacro, offset_size)?;
+                    }
+                }
+            }
+            w.write_u8(0)?;
+        }
+        Ok(MacroUnitOffsets {
+            base_id: self.base_id,
+            section: SectionId::DebugMacro,
+            offsets,
+        })
+    }
+}
+
+/// Write a string operand of a `.debug_macinfo` entry.
+///
+/// `.debug_macinfo` can only contain inline strings.
+fn write_macinfo_string<W: Writer>(w: &mut DebugMacinfo<W>, val: &MacroString) -> Result<()> {
+    match *val {
+        MacroString::String(ref val) => {
+            w.write(val)?;
+            w.write_u8(0)
+        }
+        MacroString::StringRef(_) => Err(Error::NeedVersion(5)),
+    }
+}
+
+/// A macro unit is a series of macro entries.
+///
+/// For DWARF version 5, this is stored in the `.debug_macro` section with a header.
+/// For earlier versions, this is stored in the `.debug_macinfo` section, or in
+/// the `.debug_macro` section if the unit uses `DW_AT_GNU_macros`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MacroUnit(pub Vec<MacroEntry>);
+
+/// A single macro entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MacroEntry {
+    /// A macro definition.
+    Define {
+        /// The line number of the definition.
+        line: u64,
+        /// The name of the macro, followed by its optional parameters and
+        /// definition.
+        text: MacroString,
+    },
+    /// The removal of a macro definition.
+    Undef {
+        /// The line number of the removal.
+        line: u64,
+        /// The name of the macro.
+        name: MacroString,
+    },
+    /// The start of a new source file inclusion.
+    StartFile {
+        /// The line number of the inclusion directive, or 0 for the
+        /// primary source file.
+        line: u64,
+        /// The file in the line number program of the unit.
+        file: FileId,
+    },
+    /// The end of the current source file inclusion.
+    EndFile,
+    /// The inclusion of the entries of another macro unit in the same table.
+    Import(MacroUnitId),
+}
+
+/// A string operand of a macro entry.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MacroString {
+    /// A string that is stored inline in the entry.
+    String(Vec<u8>),
+    /// A reference to a string in the `.debug_str` section.
+    ///
+    /// This is only supported for DWARF version 5.
+    StringRef(StringId),
+}
+
+#[cfg(feature = "read")]
+mod convert {
+    use super::*;
+
+    use read::{self, Reader};
+    use write::{ConvertError, ConvertResult, ConvertUnitContext};
+
+    impl MacroUnit {
+        /// Create a macro unit by reading the data from the given macro entry iter.
+        ///
+        /// Imported macro units are added to `context.macros`.
+        pub(crate) fn from<R: Reader<Offset = usize>>(
+            mut from: read::MacroIter<R>,
+            context: &mut ConvertUnitContext<R>,
+        ) -> ConvertResult<Self> {
+            let mut entries = Vec::new();
+            while let Some(from_entry) = from.next()? {
+                let entry = match from_entry {
+                    read::MacroEntry::Define { line, text } => MacroEntry::Define {
+                        line,
+                        text: MacroString::from(text, context)?,
+                    },
+                    read::MacroEntry::Undef { line, name } => MacroEntry::Undef {
+                        line,
+                        name: MacroString::from(name, context)?,
+                    },
+                    read::MacroEntry::StartFile { line, file } => {
+                        match context.line_program_files.get(file as usize) {
+                            Some(file) => MacroEntry::StartFile { line, file: *file },
+                            None => return Err(ConvertError::InvalidFileIndex),
+                        }
+                    }
+                    read::MacroEntry::EndFile => MacroEntry::EndFile,
+                    read::MacroEntry::Import { offset } => {
+                        let iter = context.dwarf.macros(offset)?;
+                        let unit = MacroUnit::from(iter, context)?;
+                        MacroEntry::Import(context.macros.add(unit))
+                    }
+                    read::MacroEntry::ImportSup { .. } | read::MacroEntry::VendorExt { .. } => {
+                        return Err(ConvertError::UnsupportedMacroEntry);
+                    }
+                };
+                entries.push(entry);
+            }
+            Ok(MacroUnit(entries))
+        }
+    }
+
+    impl MacroString {
+        /// Create a macro string by reading the data from the given sections.
+        ///
+        /// Strings are only converted to `.debug_str` references for DWARF version 5,
+        /// because earlier versions must store them inline.
+        fn from<R: Reader<Offset = usize>>(
+            from: read::MacroString<R>,
+            context: &mut ConvertUnitContext<R>,
+        ) -> ConvertResult<Self> {
+            let inline = match from {
+                read::MacroString::Direct(_) => true,
+                _ => context.unit.header.version() <= 4,
+            };
+            let r = context.dwarf.macro_string(context.unit, from)?;
+            let bytes = r.to_slice()?;
+            Ok(if inline {
+                MacroString::String(bytes.into())
+            } else {
+                MacroString::StringRef(context.strings.add(bytes))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "read")]
+mod tests {
+    use super::*;
+    use common::{
+        DebugAbbrevOffset, DebugAddrBase, DebugInfoOffset, DebugLocListsBase, DebugRngListsBase,
+        DebugStrOffsetsBase, UnitSectionOffset,
+    };
+    use read;
+    use write::{
+        Address, ConvertUnitContext, EndianVec, LineStringTable, LocationListTable, RangeListTable,
+        StringTable,
+    };
+    use Arc;
+    use LittleEndian;
+
+    #[test]
+    fn test_macro_table() {
+        let mut line_strings = LineStringTable::default();
+
+        for &(version, section) in &[
+            (2, SectionId::DebugMacinfo),
+            (3, SectionId::DebugMacinfo),
+            (4, SectionId::DebugMacinfo),
+            (4, SectionId::DebugMacro),
+            (5, SectionId::DebugMacro),
+        ] {
+            for &format in &[Format::Dwarf32, Format::Dwarf64] {
+                let encoding = Encoding {
+                    format,
+                    version,
+                    address_size: 8,
+                };
+
+                let mut strings = StringTable::default();
+                let string = |strings: &mut StringTable, s: &[u8]| {
+                    if section == SectionId::DebugMacro {
+                        MacroString::StringRef(strings.add(s))
+                    } else {
+                        MacroString::String(s.into())
+                    }
+                };
+                let file = FileId::new(0, version);
+
+                let mut macros = MacroTable::default();
+                let import_id = macros.add(MacroUnit(vec![
+                    MacroEntry::Define {
+                        line: 1,
+                        text: string(&mut strings, b"BAR 2"),
+                    },
+                    MacroEntry::Undef {
+                        line: 2,
+                        name: string(&mut strings, b"BAR"),
+                    },
+                ]));
+                let macro_unit = MacroUnit(vec![
+                    MacroEntry::StartFile { line: 0, file },
+                    MacroEntry::Define {
+                        line: 1,
+                        text: MacroString::String(b"FOO 1".to_vec()),
+                    },
+                    MacroEntry::Import(import_id),
+                    MacroEntry::Undef {
+                        line: 3,
+                        name: MacroString::String(b"FOO".to_vec()),
+                    },
+                    MacroEntry::EndFile,
+                ]);
+                let macro_id = macros.add(macro_unit.clone());
+
+                let mut sections = Sections::new(EndianVec::new(LittleEndian));
+                let debug_str_offsets = strings.write(&mut sections.debug_str).unwrap();
+                let offsets = macros
+                    .write(
+                        &mut sections,
+                        section,
+                        encoding,
+                        Some(DebugLineOffset(0x1234)),
+                        &debug_str_offsets,
+                    )
+                    .unwrap();
+
+                let dwarf = read::Dwarf {
+                    debug_macinfo: read::DebugMacinfo::new(
+                        sections.debug_macinfo.slice(),
+                        LittleEndian,
+                    ),
+                    debug_macro: read::DebugMacro::new(sections.debug_macro.slice(), LittleEndian),
+                    debug_str: read::DebugStr::new(sections.debug_str.slice(), LittleEndian),
+                    ..Default::default()
+                };
+                assert_eq!(offsets.section(), section);
+                let offset = offsets.get(macro_id);
+                let read_macros = if section == SectionId::DebugMacinfo {
+                    assert!(sections.debug_macro.slice().is_empty());
+                    dwarf.macinfo(DebugMacinfoOffset(offset)).unwrap()
+                } else {
+                    assert!(sections.debug_macinfo.slice().is_empty());
+                    let header = dwarf.debug_macro.header(DebugMacroOffset(offset)).unwrap();
+                    assert_eq!(header.version(), if version >= 5 { 5 } else { 4 });
+                    assert_eq!(header.format(), format);
+                    assert_eq!(header.debug_line_offset(), Some(DebugLineOffset(0x1234)));
+                    header.entries()
+                };
+
+                let unit = read::Unit {
+                    offset: UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0)),
+                    header: read::UnitHeader::new(
+                        encoding,
+                        0,
+                        read::UnitType::Compilation,
+                        DebugAbbrevOffset(0),
+                        read::EndianSlice::default(),
+                    ),
+                    abbreviations: Arc::new(read::Abbreviations::default()),
+                    name: None,
+                    comp_dir: None,
+                    low_pc: 0,
+                    str_offsets_base: DebugStrOffsetsBase(0),
+                    addr_base: DebugAddrBase(0),
+                    loclists_base: DebugLocListsBase(0),
+                    rnglists_base: DebugRngListsBase(0),
+                    dwo_id: None,
+                    dwo_name: None,
+                    line_program: None,
+                };
+                let mut convert_strings = StringTable::default();
+                let mut convert_macros = MacroTable::default();
+                let convert_macro_unit = {
+                    let mut context = ConvertUnitContext {
+                        dwarf: &dwarf,
+                        unit: &unit,
+                        line_strings: &mut line_strings,
+                        strings: &mut convert_strings,
+                        ranges: &mut RangeListTable::default(),
+                        locations: &mut LocationListTable::default(),
+                        macros: &mut convert_macros,
+                        convert_address: &|address| Some(Address::Absolute(address)),
+                        base_address: Address::Absolute(0),
+                        line_program_offset: None,
+                        line_program_files: vec![file; 2],
+                    };
+                    MacroUnit::from(read_macros, &mut context).unwrap()
+                };
+
+                if section == SectionId::DebugMacinfo {
+                    // The imported entries are written in place of the import.
+                    let mut expect = macro_unit.0.clone();
+                    let import = macros.units.get_index(import_id.index).unwrap();
+                    expect.splice(2..3, import.0.iter().cloned());
+                    assert_eq!(convert_macro_unit, MacroUnit(expect));
+                    assert_eq!(convert_macros.units.len(), 0);
+                } else {
+                    let convert_import_id = MacroUnitId::new(convert_macros.base_id, 0);
+                    let mut expect = macro_unit.0.clone();
+                    expect[2] = MacroEntry::Import(convert_import_id);
+                    assert_eq!(convert_macro_unit, MacroUnit(expect));
+                    assert_eq!(convert_macros.units.len(), 1);
+                    assert_eq!(
+                        convert_macros.units.get_index(0).unwrap().0.len(),
+                        macros.units.get_index(import_id.index).unwrap().0.len()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_macinfo_string_ref() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        let mut strings = StringTable::default();
+        let mut macros = MacroTable::default();
+        macros.add(MacroUnit(vec![MacroEntry::Define {
+            line: 1,
+            text: MacroString::StringRef(strings.add("FOO")),
+        }]));
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let debug_str_offsets = strings.write(&mut sections.debug_str).unwrap();
+        assert_eq!(
+            macros
+                .write(
+                    &mut sections,
+                    SectionId::DebugMacinfo,
+                    encoding,
+                    None,
+                    &debug_str_offsets
+                )
+                .map(|_| ()),
+            Err(Error::NeedVersion(5))
+        );
+    }
+
+    #[test]
+    fn test_macinfo_version_5() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 8,
+        };
+        let mut macros = MacroTable::default();
+        macros.add(MacroUnit(vec![MacroEntry::EndFile]));
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        assert_eq!(
+            macros
+                .write(
+                    &mut sections,
+                    SectionId::DebugMacinfo,
+                    encoding,
+                    None,
+                    &DebugStrOffsets::none()
+                )
+                .map(|_| ()),
+            Err(Error::InvalidAttributeValue)
+        );
+    }
+}
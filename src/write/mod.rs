@@ -4,6 +4,8 @@ use std::error;
 use std::fmt;
 use std::result;
 
+use constants;
+
 mod endian_vec;
 pub use self::endian_vec::*;
 
@@ -90,6 +92,21 @@ pub use self::unit::*;
 mod range;
 pub use self::range::*;
 
+mod loc;
+pub use self::loc::*;
+
+mod op;
+pub use self::op::*;
+
+mod macros;
+pub use self::macros::*;
+
+mod names;
+pub use self::names::*;
+
+mod cfi;
+pub use self::cfi::*;
+
 /// An error that occurred when writing.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
@@ -117,6 +134,20 @@ pub enum Error {
     InvalidRange,
     /// The line number program encoding is incompatible with the unit encoding.
     IncompatibleLineProgramEncoding,
+    /// Could not encode code offset for a frame instruction.
+    InvalidFrameCodeOffset(u32),
+    /// Could not encode data offset for a frame instruction.
+    InvalidFrameDataOffset(i32),
+    /// The LSDA of a FDE does not match the LSDA encoding of its CIE.
+    InvalidFrameLsda,
+    /// Unsupported eh_frame pointer encoding.
+    UnsupportedPointerEncoding(constants::DwEhPe),
+    /// A reference to an entry was used where it is not supported, such as
+    /// in a call frame instruction.
+    InvalidReference,
+    /// The target of a branch in an expression is not the index of an
+    /// operation in the expression.
+    InvalidBranchTarget(usize),
 }
 
 impl fmt::Display for Error {
@@ -150,6 +181,27 @@ impl fmt::Display for Error {
                 f,
                 "The line number program encoding is incompatible with the unit encoding."
             ),
+            Error::InvalidFrameCodeOffset(offset) => write!(
+                f,
+                "Could not encode code offset ({}) for a frame instruction.",
+                offset,
+            ),
+            Error::InvalidFrameDataOffset(offset) => write!(
+                f,
+                "Could not encode data offset ({}) for a frame instruction.",
+                offset,
+            ),
+            Error::InvalidFrameLsda => write!(
+                f,
+                "The LSDA of a FDE does not match the LSDA encoding of its CIE."
+            ),
+            Error::UnsupportedPointerEncoding(eh_pe) => {
+                write!(f, "Unsupported eh_frame pointer encoding ({}).", eh_pe)
+            }
+            Error::InvalidReference => write!(f, "A reference to an entry is not supported here."),
+            Error::InvalidBranchTarget(target) => {
+                write!(f, "Invalid branch target in expression: {}", target)
+            }
         }
     }
 }
@@ -233,6 +285,12 @@ mod convert {
         InvalidLineRef,
         /// Invalid relative address in a range list.
         InvalidRangeRelativeAddress,
+        /// Writing this CFI instruction is not implemented yet.
+        UnsupportedCfiInstruction,
+        /// A CFI alignment factor is too large to be written.
+        UnsupportedCfiAlignmentFactor,
+        /// Writing this macro entry is not implemented yet.
+        UnsupportedMacroEntry,
     }
 
     impl fmt::Display for ConvertError {
@@ -275,6 +333,15 @@ mod convert {
                 InvalidRangeRelativeAddress => {
                     write!(f, "Invalid relative address in a range list.")
                 }
+                UnsupportedCfiInstruction => {
+                    write!(f, "Writing this CFI instruction is not implemented yet.")
+                }
+                UnsupportedCfiAlignmentFactor => {
+                    write!(f, "A CFI alignment factor is too large to be written.")
+                }
+                UnsupportedMacroEntry => {
+                    write!(f, "Writing this macro entry is not implemented yet.")
+                }
             }
         }
     }
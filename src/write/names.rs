@@ -0,0 +1,431 @@
+use indexmap::{IndexMap, IndexSet};
+use std::ops::{Deref, DerefMut};
+use vec::Vec;
+
+use common::{debug_names_hash, Format};
+use constants;
+use write::{
+    AttributeValue, DebugInfoOffsets, DebugStrOffsets, EndianVec, Error, Result, Section,
+    SectionId, StringId, StringTable, Unit, UnitEntryId, UnitTable, Writer,
+};
+
+define_section!(DebugNames, "A writable `.debug_names` section.");
+
+/// A name index that will be stored in a `.debug_names` section.
+///
+/// The index contains the names of the subprograms, variables, types and
+/// namespaces in a `UnitTable`.
+#[derive(Debug, Default)]
+pub struct NameTable {
+    // The hash of each name, and the entries with that name.
+    names: IndexMap<StringId, (u32, Vec<NameEntry>)>,
+}
+
+#[derive(Debug)]
+struct NameEntry {
+    unit: usize,
+    entry: UnitEntryId,
+    tag: constants::DwTag,
+}
+
+impl NameTable {
+    /// Create a name index for the entries in the given units.
+    ///
+    /// The index uses the `DW_AT_name` and `DW_AT_linkage_name` attributes.
+    /// The index can only refer to strings in the `.debug_str` section, so
+    /// any names that are stored inline in the entries are also added to `strings`.
+    pub fn from_units(units: &UnitTable, strings: &mut StringTable) -> Self {
+        let mut table = NameTable::default();
+        for index in 0..units.count() {
+            let unit = units.get(units.id(index));
+            table.add_entry(unit, index, unit.root(), false, strings);
+        }
+        table
+    }
+
+    /// Return the number of names in the index.
+    #[inline]
+    pub fn count(&self) -> usize {
+        self.names.len()
+    }
+
+    fn add_entry(
+        &mut self,
+        unit: &Unit,
+        unit_index: usize,
+        id: UnitEntryId,
+        in_subprogram: bool,
+        strings: &mut StringTable,
+    ) {
+        let entry = unit.get(id);
+        let tag = entry.tag();
+        if is_indexed_tag(tag, in_subprogram)
+            && !is_declaration(entry.get(constants::DW_AT_declaration))
+        {
+            for name in &[
+                constants::DW_AT_name,
+                constants::DW_AT_linkage_name,
+                constants::DW_AT_MIPS_linkage_name,
+            ] {
+                let string = match entry.get(*name) {
+                    Some(value) => match *value {
+                        AttributeValue::StringRef(string) => string,
+                        AttributeValue::String(ref bytes) => strings.add(bytes.clone()),
+                        _ => continue,
+                    },
+                    None => continue,
+                };
+                let (_, entries) = self
+                    .names
+                    .entry(string)
+                    .or_insert_with(|| (debug_names_hash(strings.get(string)), Vec::new()));
+                // Avoid duplicates if the linkage name is the same as the name.
+                if entries.last().map(|last| last.entry) != Some(id) {
+                    entries.push(NameEntry {
+                        unit: unit_index,
+                        entry: id,
+                        tag,
+                    });
+                }
+            }
+        }
+
+        let in_subprogram = in_subprogram || tag == constants::DW_TAG_subprogram;
+        for child in entry.children() {
+            self.add_entry(unit, unit_index, *child, in_subprogram, strings);
+        }
+    }
+
+    /// Write the name index to the `.debug_names` section.
+    ///
+    /// `units` must be the `UnitTable` that the index was created from, and
+    /// `debug_info_offsets` must contain the offsets returned from writing it.
+    /// `strings` must contain the `.debug_str` offsets of the corresponding
+    /// `StringTable`.
+    ///
+    /// Nothing is written if the index is empty.
+    pub fn write<W: Writer>(
+        &self,
+        w: &mut DebugNames<W>,
+        units: &UnitTable,
+        debug_info_offsets: &DebugInfoOffsets,
+        strings: &DebugStrOffsets,
+    ) -> Result<()> {
+        if self.names.is_empty() {
+            return Ok(());
+        }
+
+        let unit_count = units.count();
+        let format = if (0..unit_count).all(|i| units.get(units.id(i)).format() == Format::Dwarf32)
+        {
+            Format::Dwarf32
+        } else {
+            Format::Dwarf64
+        };
+        let offset_size = format.word_size();
+
+        // The compilation unit index is omitted when there is only one unit.
+        let unit_index_form = if unit_count <= 1 {
+            None
+        } else if unit_count <= 0x100 {
+            Some((constants::DW_FORM_data1, 1))
+        } else if unit_count <= 0x1_0000 {
+            Some((constants::DW_FORM_data2, 2))
+        } else {
+            Some((constants::DW_FORM_data4, 4))
+        };
+
+        // Sort the names by bucket, preserving the insertion order within each bucket.
+        let bucket_count = self.names.len() as u32;
+        let mut names: Vec<_> = self
+            .names
+            .iter()
+            .map(|(string, &(hash, ref entries))| (hash, *string, entries))
+            .collect();
+        names.sort_by_key(|&(hash, _, _)| hash % bucket_count);
+
+        // Use one abbreviation for each tag.
+        let tags: IndexSet<constants::DwTag> = names
+            .iter()
+            .flat_map(|&(_, _, entries)| entries.iter().map(|entry| entry.tag))
+            .collect();
+        let mut abbrevs = EndianVec::new(w.endian());
+        for (index, tag) in tags.iter().enumerate() {
+            abbrevs.write_uleb128(index as u64 + 1)?;
+            abbrevs.write_uleb128(tag.0)?;
+            if let Some((form, _)) = unit_index_form {
+                abbrevs.write_uleb128(constants::DW_IDX_compile_unit.0.into())?;
+                abbrevs.write_uleb128(form.0)?;
+            }
+            abbrevs.write_uleb128(constants::DW_IDX_die_offset.0.into())?;
+            abbrevs.write_uleb128(constants::DW_FORM_ref4.0)?;
+            abbrevs.write_u8(0)?;
+            abbrevs.write_u8(0)?;
+        }
+        abbrevs.write_u8(0)?;
+
+        let mut entry_offsets = Vec::with_capacity(names.len());
+        let mut pool = EndianVec::new(w.endian());
+        for &(_, _, entries) in &names {
+            entry_offsets.push(pool.len());
+            for entry in entries {
+                let code = tags.get_full(&entry.tag).unwrap().0 + 1;
+                pool.write_uleb128(code as u64)?;
+                if let Some((_, size)) = unit_index_form {
+                    pool.write_word(entry.unit as u64, size)?;
+                }
+                let unit = units.id(entry.unit);
+                let unit_offset = debug_info_offsets.unit(unit).0;
+                let entry_offset = debug_info_offsets.entry(unit, entry.entry).0;
+                let die_offset = entry_offset - unit_offset;
+                if die_offset > u32::max_value() as usize {
+                    return Err(Error::ValueTooLarge);
+                }
+                pool.write_u32(die_offset as u32)?;
+            }
+            pool.write_u8(0)?;
+        }
+
+        let length_offset = w.write_initial_length(format)?;
+        let length_base = w.len();
+
+        w.write_u16(5)?; // version
+        w.write_u16(0)?; // padding
+        w.write_u32(unit_count as u32)?; // comp_unit_count
+        w.write_u32(0)?; // local_type_unit_count
+        w.write_u32(0)?; // foreign_type_unit_count
+        w.write_u32(bucket_count)?;
+        w.write_u32(names.len() as u32)?; // name_count
+        w.write_u32(abbrevs.len() as u32)?; // abbrev_table_size
+        w.write_u32(0)?; // augmentation_string_size
+
+        for index in 0..unit_count {
+            let offset = debug_info_offsets.unit(units.id(index));
+            w.write_offset(offset.0, SectionId::DebugInfo, offset_size)?;
+        }
+
+        // Indices in the bucket table start at 1, and 0 is an empty bucket.
+        let mut buckets = vec![0; bucket_count as usize];
+        for (index, &(hash, _, _)) in names.iter().enumerate().rev() {
+            buckets[(hash % bucket_count) as usize] = index as u32 + 1;
+        }
+        for bucket in buckets {
+            w.write_u32(bucket)?;
+        }
+        for &(hash, _, _) in &names {
+            w.write_u32(hash)?;
+        }
+        for &(_, string, _) in &names {
+            w.write_offset(strings.get(string).0, SectionId::DebugStr, offset_size)?;
+        }
+        for offset in entry_offsets {
+            w.write_word(offset as u64, offset_size)?;
+        }
+        w.write(abbrevs.slice())?;
+        w.write(pool.slice())?;
+
+        let length = (w.len() - length_base) as u64;
+        w.write_initial_length_at(length_offset, length, format)?;
+        Ok(())
+    }
+}
+
+fn is_indexed_tag(tag: constants::DwTag, in_subprogram: bool) -> bool {
+    match tag {
+        constants::DW_TAG_subprogram
+        | constants::DW_TAG_namespace
+        | constants::DW_TAG_base_type
+        | constants::DW_TAG_class_type
+        | constants::DW_TAG_enumeration_type
+        | constants::DW_TAG_interface_type
+        | constants::DW_TAG_structure_type
+        | constants::DW_TAG_typedef
+        | constants::DW_TAG_union_type
+        | constants::DW_TAG_unspecified_type => true,
+        // Local variables are not indexed.
+        constants::DW_TAG_variable => !in_subprogram,
+        _ => false,
+    }
+}
+
+fn is_declaration(value: Option<&AttributeValue>) -> bool {
+    match value {
+        Some(&AttributeValue::Flag(flag)) => flag,
+        Some(&AttributeValue::FlagPresent) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "read")]
+mod tests {
+    use super::*;
+    use common::{DebugInfoOffset, Encoding};
+    use read;
+    use write::{DebugLineStrOffsets, Dwarf, EndianVec, LineProgram, Sections};
+    use LittleEndian;
+
+    #[test]
+    fn test_name_table() {
+        for &format in &[Format::Dwarf32, Format::Dwarf64] {
+            for &unit_count in &[1, 2] {
+                test_name_table_format(format, unit_count);
+            }
+        }
+    }
+
+    fn test_name_table_format(format: Format, unit_count: usize) {
+        let encoding = Encoding {
+            format,
+            version: 5,
+            address_size: 8,
+        };
+        let mut strings = StringTable::default();
+        let mut units = UnitTable::default();
+        let mut ids = Vec::new();
+        for _ in 0..unit_count {
+            let unit_id = units.add(Unit::new(encoding, LineProgram::none()));
+            let unit = units.get_mut(unit_id);
+            let root = unit.root();
+
+            let subprogram = unit.add(root, constants::DW_TAG_subprogram);
+            unit.get_mut(subprogram).set(
+                constants::DW_AT_name,
+                AttributeValue::StringRef(strings.add("main")),
+            );
+            unit.get_mut(subprogram).set(
+                constants::DW_AT_linkage_name,
+                AttributeValue::String(b"_main".to_vec()),
+            );
+
+            // Local variables are not indexed.
+            let local = unit.add(subprogram, constants::DW_TAG_variable);
+            unit.get_mut(local).set(
+                constants::DW_AT_name,
+                AttributeValue::String(b"local".to_vec()),
+            );
+
+            let namespace = unit.add(root, constants::DW_TAG_namespace);
+            unit.get_mut(namespace).set(
+                constants::DW_AT_name,
+                AttributeValue::String(b"ns".to_vec()),
+            );
+
+            let variable = unit.add(namespace, constants::DW_TAG_variable);
+            unit.get_mut(variable).set(
+                constants::DW_AT_name,
+                AttributeValue::StringRef(strings.add("global")),
+            );
+
+            let structure = unit.add(namespace, constants::DW_TAG_structure_type);
+            unit.get_mut(structure).set(
+                constants::DW_AT_name,
+                AttributeValue::String(b"Main".to_vec()),
+            );
+
+            // Declarations are not indexed.
+            let declaration = unit.add(root, constants::DW_TAG_structure_type);
+            unit.get_mut(declaration).set(
+                constants::DW_AT_name,
+                AttributeValue::String(b"decl".to_vec()),
+            );
+            unit.get_mut(declaration)
+                .set(constants::DW_AT_declaration, AttributeValue::FlagPresent);
+
+            ids.push([subprogram, namespace, variable, structure]);
+        }
+
+        let names = NameTable::from_units(&units, &mut strings);
+        assert_eq!(names.count(), 5);
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let line_strings = DebugLineStrOffsets::none();
+        let debug_str_offsets = strings.write(&mut sections.debug_str).unwrap();
+        let debug_info_offsets = units
+            .write(&mut sections, &line_strings, &debug_str_offsets)
+            .unwrap();
+        names
+            .write(
+                &mut sections.debug_names,
+                &units,
+                &debug_info_offsets,
+                &debug_str_offsets,
+            )
+            .unwrap();
+
+        let debug_str = read::DebugStr::new(sections.debug_str.slice(), LittleEndian);
+        let debug_names = read::DebugNames::new(sections.debug_names.slice(), LittleEndian);
+        let mut indexes = debug_names.name_indexes();
+        let index = indexes.next().unwrap().unwrap();
+        assert!(indexes.next().unwrap().is_none());
+        assert_eq!(index.format(), format);
+        assert_eq!(index.version(), 5);
+        assert_eq!(index.comp_unit_count(), unit_count as u32);
+        assert_eq!(index.name_count(), 5);
+        for i in 0..unit_count {
+            assert_eq!(
+                index.compile_unit(i as u32).unwrap(),
+                debug_info_offsets.unit(units.id(i))
+            );
+        }
+
+        for &(name, tag, entry_index) in &[
+            (&b"main"[..], constants::DW_TAG_subprogram, 0),
+            (&b"_main"[..], constants::DW_TAG_subprogram, 0),
+            (&b"ns"[..], constants::DW_TAG_namespace, 1),
+            (&b"global"[..], constants::DW_TAG_variable, 2),
+            (&b"Main"[..], constants::DW_TAG_structure_type, 3),
+        ] {
+            let name = index.lookup(name, &debug_str).unwrap().unwrap();
+            let mut entries = name.entries().unwrap();
+            for (i, unit_ids) in ids.iter().enumerate() {
+                let unit_id = units.id(i);
+                let entry = entries.next().unwrap().unwrap();
+                assert_eq!(entry.tag(), tag);
+                let unit_offset = debug_info_offsets.unit(unit_id);
+                assert_eq!(index.entry_compile_unit(&entry).unwrap(), Some(unit_offset));
+                let DebugInfoOffset(offset) =
+                    debug_info_offsets.entry(unit_id, unit_ids[entry_index]);
+                assert_eq!(
+                    entry.die_offset(),
+                    Some(read::UnitOffset(offset - unit_offset.0))
+                );
+            }
+            assert!(entries.next().unwrap().is_none());
+        }
+
+        for name in &[&b"local"[..], &b"decl"[..], &b"missing"[..]] {
+            assert!(index.lookup(name, &debug_str).unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_dwarf_debug_names() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 8,
+        };
+        let mut dwarf = Dwarf::default();
+        let unit_id = dwarf.units.add(Unit::new(encoding, LineProgram::none()));
+        let unit = dwarf.units.get_mut(unit_id);
+        let root = unit.root();
+        let subprogram = unit.add(root, constants::DW_TAG_subprogram);
+        unit.get_mut(subprogram).set(
+            constants::DW_AT_name,
+            AttributeValue::String(b"main".to_vec()),
+        );
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        dwarf.write(&mut sections).unwrap();
+        assert!(sections.debug_names.slice().is_empty());
+
+        dwarf.debug_names = true;
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        dwarf.write(&mut sections).unwrap();
+        let debug_str = read::DebugStr::new(sections.debug_str.slice(), LittleEndian);
+        let debug_names = read::DebugNames::new(sections.debug_names.slice(), LittleEndian);
+        let index = debug_names.name_indexes().next().unwrap().unwrap();
+        assert!(index.lookup(b"main", &debug_str).unwrap().is_some());
+    }
+}
@@ -0,0 +1,787 @@
+use std::slice;
+use vec::Vec;
+
+use common::{Encoding, Format, Register};
+use constants::{self, DwOp};
+use write::{Address, EntryRef, Error, Result, UnitEntryId, UnitId, Writer};
+
+/// The bytecode for a DWARF expression or location description.
+///
+/// An expression is a sequence of operations. Operations that refer to
+/// addresses or to other entries are encoded when the expression is written,
+/// so that addresses can be relocated and entry references can be resolved
+/// once the offsets of the entries are known.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct Expression {
+    operations: Vec<Operation>,
+}
+
+impl Expression {
+    /// Create an empty expression.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an expression from raw bytecode.
+    ///
+    /// The bytecode is written unchanged, so it must not contain any
+    /// addresses that need relocation or references to other entries.
+    #[inline]
+    pub fn raw(bytecode: Vec<u8>) -> Self {
+        Expression {
+            operations: vec![Operation::Raw(bytecode)],
+        }
+    }
+
+    /// Add an operation to the expression.
+    ///
+    /// Returns the index of the operation, which may be used as the target
+    /// of an `Operation::Skip` or `Operation::Branch`.
+    #[inline]
+    pub fn push(&mut self, operation: Operation) -> usize {
+        self.operations.push(operation);
+        self.operations.len() - 1
+    }
+
+    /// Return the index that the next operation will have when it is added.
+    ///
+    /// This is the target to use for a branch to the end of the expression.
+    #[inline]
+    pub fn next_index(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Set the target of the `Operation::Skip` or `Operation::Branch` at the
+    /// given index.
+    ///
+    /// This is useful for forward branches, since the target is not known
+    /// when the branch is added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the operation at `index` is not a `Skip` or `Branch`.
+    pub fn set_target(&mut self, index: usize, target: usize) {
+        match self.operations[index] {
+            Operation::Skip(ref mut old) | Operation::Branch(ref mut old) => *old = target,
+            _ => panic!("operation is not a skip or branch"),
+        }
+    }
+
+    /// Return an iterator for the operations in the expression.
+    #[inline]
+    pub fn operations(&self) -> slice::Iter<Operation> {
+        self.operations.iter()
+    }
+
+    /// Return the size in bytes of the encoded expression.
+    pub fn size(&self, encoding: Encoding) -> usize {
+        self.operations
+            .iter()
+            .map(|operation| operation.size(encoding))
+            .sum()
+    }
+
+    /// Write the expression to the given writer.
+    ///
+    /// Placeholders are written for references to entries, and are added to
+    /// `refs` so that they can be patched later. Returns an error if the
+    /// expression contains references and `refs` is `None`.
+    pub(crate) fn write<W: Writer>(
+        &self,
+        w: &mut W,
+        encoding: Encoding,
+        mut refs: Option<&mut Vec<EntryRef>>,
+    ) -> Result<()> {
+        // Branch targets are byte offsets, so we need the offset of every operation.
+        let mut offsets = Vec::with_capacity(self.operations.len() + 1);
+        let mut offset = 0;
+        for operation in &self.operations {
+            offsets.push(offset);
+            offset += operation.size(encoding);
+        }
+        offsets.push(offset);
+
+        for (index, operation) in self.operations.iter().enumerate() {
+            let branch_offset = |target: usize| -> Result<i16> {
+                let target = *offsets
+                    .get(target)
+                    .ok_or(Error::InvalidBranchTarget(target))?;
+                let next = offsets[index + 1];
+                let offset = target as i64 - next as i64;
+                if !(-0x8000..=0x7fff).contains(&offset) {
+                    return Err(Error::ValueTooLarge);
+                }
+                Ok(offset as i16)
+            };
+            match *operation {
+                Operation::Skip(target) => {
+                    let offset = branch_offset(target)?;
+                    w.write_u8(constants::DW_OP_skip.0)?;
+                    w.write_u16(offset as u16)?;
+                }
+                Operation::Branch(target) => {
+                    let offset = branch_offset(target)?;
+                    w.write_u8(constants::DW_OP_bra.0)?;
+                    w.write_u16(offset as u16)?;
+                }
+                _ => operation.write(w, encoding, refs.as_deref_mut())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single operation in a DWARF expression.
+///
+/// Most variants correspond to one or more DWARF opcodes, and the most compact
+/// opcode for the operands and DWARF version is chosen when writing. For DWARF
+/// versions before 5, the GNU extensions are used for operations that were
+/// standardized in DWARF 5.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Raw bytecode.
+    ///
+    /// The bytecode is written unchanged, so it must not contain any
+    /// addresses that need relocation or references to other entries.
+    Raw(Vec<u8>),
+    /// An operation that has no explicit operands.
+    ///
+    /// This includes operations such as `DW_OP_deref`, `DW_OP_plus`,
+    /// `DW_OP_lit0` and `DW_OP_stack_value`.
+    Simple(DwOp),
+    /// Represents `DW_OP_addr`.
+    ///
+    /// The address is written using `Writer::write_address`, so it may be
+    /// relocated.
+    Address(Address),
+    /// Represents `DW_OP_constu`, or `DW_OP_lit*` for small values.
+    UnsignedConstant(u64),
+    /// Represents `DW_OP_consts`.
+    SignedConstant(i64),
+    /// Represents `DW_OP_const_type`.
+    ///
+    /// The entry is the base type of the constant value.
+    ConstantType(UnitEntryId, Vec<u8>),
+    /// Represents `DW_OP_fbreg`.
+    FrameOffset(i64),
+    /// Represents `DW_OP_reg*` or `DW_OP_regx`.
+    Register(Register),
+    /// Represents `DW_OP_breg*` or `DW_OP_bregx`.
+    RegisterOffset(Register, i64),
+    /// Represents `DW_OP_regval_type`.
+    ///
+    /// The entry is the base type of the register value.
+    RegisterType(Register, UnitEntryId),
+    /// Represents `DW_OP_pick`, or `DW_OP_dup` and `DW_OP_over` for indices 0 and 1.
+    Pick(u8),
+    /// Represents `DW_OP_deref_size`, or `DW_OP_xderef_size` if `space` is true.
+    DerefSize {
+        /// The size of the value in bytes.
+        size: u8,
+        /// True if the dereference operation takes an address space argument.
+        space: bool,
+    },
+    /// Represents `DW_OP_deref_type`, or `DW_OP_xderef_type` if `space` is true.
+    DerefType {
+        /// The size of the value in bytes.
+        size: u8,
+        /// The base type of the value.
+        base: UnitEntryId,
+        /// True if the dereference operation takes an address space argument.
+        space: bool,
+    },
+    /// Represents `DW_OP_plus_uconst`.
+    PlusConstant(u64),
+    /// Represents `DW_OP_skip`.
+    ///
+    /// The target is the index of an operation in the expression.
+    Skip(usize),
+    /// Represents `DW_OP_bra`.
+    ///
+    /// The target is the index of an operation in the expression.
+    Branch(usize),
+    /// Represents `DW_OP_call4`.
+    Call(UnitEntryId),
+    /// Represents `DW_OP_call_ref`.
+    CallRef((UnitId, UnitEntryId)),
+    /// Represents `DW_OP_convert`.
+    ///
+    /// `None` is the generic type.
+    Convert(Option<UnitEntryId>),
+    /// Represents `DW_OP_reinterpret`.
+    ///
+    /// `None` is the generic type.
+    Reinterpret(Option<UnitEntryId>),
+    /// Represents `DW_OP_entry_value`.
+    EntryValue(Expression),
+    /// Represents `DW_OP_implicit_value`.
+    ImplicitValue(Vec<u8>),
+    /// Represents `DW_OP_implicit_pointer`.
+    ImplicitPointer {
+        /// The entry of the variable that the pointer points to.
+        entry: (UnitId, UnitEntryId),
+        /// The offset of the pointer within the variable.
+        byte_offset: i64,
+    },
+    /// Represents `DW_OP_piece`.
+    Piece {
+        /// The size of the piece in bytes.
+        size_in_bytes: u64,
+    },
+    /// Represents `DW_OP_bit_piece`.
+    BitPiece {
+        /// The size of the piece in bits.
+        size_in_bits: u64,
+        /// The offset of the piece in bits.
+        bit_offset: u64,
+    },
+    /// Represents `DW_OP_GNU_parameter_ref`.
+    ParameterRef(UnitEntryId),
+}
+
+impl Operation {
+    /// Return the size in bytes of the encoded operation.
+    fn size(&self, encoding: Encoding) -> usize {
+        let base_size = |base: Option<UnitEntryId>| match base {
+            Some(_) => unit_ref_uleb128_size(encoding) as usize,
+            None => 1,
+        };
+        1 + match *self {
+            Operation::Raw(ref bytecode) => return bytecode.len(),
+            Operation::Simple(_) => 0,
+            Operation::Address(_) => encoding.address_size as usize,
+            Operation::UnsignedConstant(value) => {
+                if value < 32 {
+                    0
+                } else {
+                    uleb128_size(value)
+                }
+            }
+            Operation::SignedConstant(value) => sleb128_size(value),
+            Operation::ConstantType(base, ref value) => base_size(Some(base)) + 1 + value.len(),
+            Operation::FrameOffset(offset) => sleb128_size(offset),
+            Operation::Register(register) => {
+                if register.0 < 32 {
+                    0
+                } else {
+                    uleb128_size(register.0.into())
+                }
+            }
+            Operation::RegisterOffset(register, offset) => {
+                if register.0 < 32 {
+                    sleb128_size(offset)
+                } else {
+                    uleb128_size(register.0.into()) + sleb128_size(offset)
+                }
+            }
+            Operation::RegisterType(register, base) => {
+                uleb128_size(register.0.into()) + base_size(Some(base))
+            }
+            Operation::Pick(index) => {
+                if index > 1 {
+                    1
+                } else {
+                    0
+                }
+            }
+            Operation::DerefSize { .. } => 1,
+            Operation::DerefType { base, .. } => 1 + base_size(Some(base)),
+            Operation::PlusConstant(value) => uleb128_size(value),
+            Operation::Skip(_) | Operation::Branch(_) => 2,
+            Operation::Call(_) | Operation::ParameterRef(_) => 4,
+            Operation::CallRef(_) => encoding.format.word_size() as usize,
+            Operation::Convert(base) | Operation::Reinterpret(base) => base_size(base),
+            Operation::EntryValue(ref expression) => {
+                let size = expression.size(encoding);
+                uleb128_size(size as u64) + size
+            }
+            Operation::ImplicitValue(ref data) => uleb128_size(data.len() as u64) + data.len(),
+            Operation::ImplicitPointer { byte_offset, .. } => {
+                encoding.format.word_size() as usize + sleb128_size(byte_offset)
+            }
+            Operation::Piece { size_in_bytes } => uleb128_size(size_in_bytes),
+            Operation::BitPiece {
+                size_in_bits,
+                bit_offset,
+            } => uleb128_size(size_in_bits) + uleb128_size(bit_offset),
+        }
+    }
+
+    /// Write the operation to the given writer.
+    ///
+    /// `Skip` and `Branch` must be written by the containing `Expression`.
+    fn write<W: Writer>(
+        &self,
+        w: &mut W,
+        encoding: Encoding,
+        mut refs: Option<&mut Vec<EntryRef>>,
+    ) -> Result<()> {
+        // Select between the DWARF 5 opcode and its GNU extension.
+        let opcode = |dwarf5: DwOp, gnu: DwOp| {
+            if encoding.version >= 5 {
+                dwarf5
+            } else {
+                gnu
+            }
+        };
+        match *self {
+            Operation::Raw(ref bytecode) => {
+                w.write(bytecode)?;
+            }
+            Operation::Simple(opcode) => {
+                w.write_u8(opcode.0)?;
+            }
+            Operation::Address(address) => {
+                w.write_u8(constants::DW_OP_addr.0)?;
+                w.write_address(address, encoding.address_size)?;
+            }
+            Operation::UnsignedConstant(value) => {
+                if value < 32 {
+                    w.write_u8(constants::DW_OP_lit0.0 + value as u8)?;
+                } else {
+                    w.write_u8(constants::DW_OP_constu.0)?;
+                    w.write_uleb128(value)?;
+                }
+            }
+            Operation::SignedConstant(value) => {
+                w.write_u8(constants::DW_OP_consts.0)?;
+                w.write_sleb128(value)?;
+            }
+            Operation::ConstantType(base, ref value) => {
+                if value.len() > 0xff {
+                    return Err(Error::ValueTooLarge);
+                }
+                w.write_u8(opcode(constants::DW_OP_const_type, constants::DW_OP_GNU_const_type).0)?;
+                unit_ref(w, &mut refs, base, unit_ref_uleb128_size(encoding), true)?;
+                w.write_u8(value.len() as u8)?;
+                w.write(value)?;
+            }
+            Operation::FrameOffset(offset) => {
+                w.write_u8(constants::DW_OP_fbreg.0)?;
+                w.write_sleb128(offset)?;
+            }
+            Operation::Register(register) => {
+                if register.0 < 32 {
+                    w.write_u8(constants::DW_OP_reg0.0 + register.0 as u8)?;
+                } else {
+                    w.write_u8(constants::DW_OP_regx.0)?;
+                    w.write_uleb128(register.0.into())?;
+                }
+            }
+            Operation::RegisterOffset(register, offset) => {
+                if register.0 < 32 {
+                    w.write_u8(constants::DW_OP_breg0.0 + register.0 as u8)?;
+                } else {
+                    w.write_u8(constants::DW_OP_bregx.0)?;
+                    w.write_uleb128(register.0.into())?;
+                }
+                w.write_sleb128(offset)?;
+            }
+            Operation::RegisterType(register, base) => {
+                w.write_u8(
+                    opcode(
+                        constants::DW_OP_regval_type,
+                        constants::DW_OP_GNU_regval_type,
+                    )
+                    .0,
+                )?;
+                w.write_uleb128(register.0.into())?;
+                unit_ref(w, &mut refs, base, unit_ref_uleb128_size(encoding), true)?;
+            }
+            Operation::Pick(index) => match index {
+                0 => w.write_u8(constants::DW_OP_dup.0)?,
+                1 => w.write_u8(constants::DW_OP_over.0)?,
+                _ => {
+                    w.write_u8(constants::DW_OP_pick.0)?;
+                    w.write_u8(index)?;
+                }
+            },
+            Operation::DerefSize { size, space } => {
+                if space {
+                    w.write_u8(constants::DW_OP_xderef_size.0)?;
+                } else {
+                    w.write_u8(constants::DW_OP_deref_size.0)?;
+                }
+                w.write_u8(size)?;
+            }
+            Operation::DerefType { size, base, space } => {
+                if space {
+                    if encoding.version < 5 {
+                        return Err(Error::NeedVersion(5));
+                    }
+                    w.write_u8(constants::DW_OP_xderef_type.0)?;
+                } else {
+                    w.write_u8(
+                        opcode(constants::DW_OP_deref_type, constants::DW_OP_GNU_deref_type).0,
+                    )?;
+                }
+                w.write_u8(size)?;
+                unit_ref(w, &mut refs, base, unit_ref_uleb128_size(encoding), true)?;
+            }
+            Operation::PlusConstant(value) => {
+                w.write_u8(constants::DW_OP_plus_uconst.0)?;
+                w.write_uleb128(value)?;
+            }
+            Operation::Skip(_) | Operation::Branch(_) => {
+                // These need the offsets of the other operations.
+                unreachable!();
+            }
+            Operation::Call(entry) => {
+                w.write_u8(constants::DW_OP_call4.0)?;
+                unit_ref(w, &mut refs, entry, 4, false)?;
+            }
+            Operation::CallRef(entry) => {
+                w.write_u8(constants::DW_OP_call_ref.0)?;
+                debug_info_ref(w, &mut refs, entry, encoding.format.word_size())?;
+            }
+            Operation::Convert(base) | Operation::Reinterpret(base) => {
+                let opcode = match *self {
+                    Operation::Convert(_) => {
+                        opcode(constants::DW_OP_convert, constants::DW_OP_GNU_convert)
+                    }
+                    _ => opcode(
+                        constants::DW_OP_reinterpret,
+                        constants::DW_OP_GNU_reinterpret,
+                    ),
+                };
+                w.write_u8(opcode.0)?;
+                match base {
+                    Some(base) => {
+                        unit_ref(w, &mut refs, base, unit_ref_uleb128_size(encoding), true)?
+                    }
+                    None => w.write_uleb128(0)?,
+                }
+            }
+            Operation::EntryValue(ref expression) => {
+                w.write_u8(
+                    opcode(
+                        constants::DW_OP_entry_value,
+                        constants::DW_OP_GNU_entry_value,
+                    )
+                    .0,
+                )?;
+                w.write_uleb128(expression.size(encoding) as u64)?;
+                expression.write(w, encoding, refs)?;
+            }
+            Operation::ImplicitValue(ref data) => {
+                w.write_u8(constants::DW_OP_implicit_value.0)?;
+                w.write_uleb128(data.len() as u64)?;
+                w.write(data)?;
+            }
+            Operation::ImplicitPointer { entry, byte_offset } => {
+                w.write_u8(
+                    opcode(
+                        constants::DW_OP_implicit_pointer,
+                        constants::DW_OP_GNU_implicit_pointer,
+                    )
+                    .0,
+                )?;
+                debug_info_ref(w, &mut refs, entry, encoding.format.word_size())?;
+                w.write_sleb128(byte_offset)?;
+            }
+            Operation::Piece { size_in_bytes } => {
+                w.write_u8(constants::DW_OP_piece.0)?;
+                w.write_uleb128(size_in_bytes)?;
+            }
+            Operation::BitPiece {
+                size_in_bits,
+                bit_offset,
+            } => {
+                w.write_u8(constants::DW_OP_bit_piece.0)?;
+                w.write_uleb128(size_in_bits)?;
+                w.write_uleb128(bit_offset)?;
+            }
+            Operation::ParameterRef(entry) => {
+                w.write_u8(constants::DW_OP_GNU_parameter_ref.0)?;
+                unit_ref(w, &mut refs, entry, 4, false)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write a placeholder for the unit offset of an entry.
+fn unit_ref<W: Writer>(
+    w: &mut W,
+    refs: &mut Option<&mut Vec<EntryRef>>,
+    entry: UnitEntryId,
+    size: u8,
+    uleb128: bool,
+) -> Result<()> {
+    let refs = refs.as_mut().ok_or(Error::InvalidReference)?;
+    refs.push(EntryRef::Unit {
+        offset: w.len(),
+        entry,
+        size,
+        uleb128,
+    });
+    if uleb128 {
+        write_padded_uleb128(w, 0, size)
+    } else {
+        w.write_word(0, size)
+    }
+}
+
+/// Write a placeholder for the `.debug_info` offset of an entry.
+fn debug_info_ref<W: Writer>(
+    w: &mut W,
+    refs: &mut Option<&mut Vec<EntryRef>>,
+    entry: (UnitId, UnitEntryId),
+    size: u8,
+) -> Result<()> {
+    let refs = refs.as_mut().ok_or(Error::InvalidReference)?;
+    refs.push(EntryRef::DebugInfo {
+        offset: w.len(),
+        entry,
+        size,
+    });
+    w.write_word(0, size)
+}
+
+/// The size of the padded ULEB128 placeholder that is used for unit offsets
+/// of base types.
+///
+/// The offsets are not known until the unit has been written, so the size must
+/// be large enough for any offset that is possible for the format.
+fn unit_ref_uleb128_size(encoding: Encoding) -> u8 {
+    match encoding.format {
+        Format::Dwarf32 => 5,
+        Format::Dwarf64 => 10,
+    }
+}
+
+/// Write a ULEB128 value that is padded to `size` bytes.
+pub(crate) fn write_padded_uleb128<W: Writer>(w: &mut W, value: u64, size: u8) -> Result<()> {
+    let mut bytes = [0; 10];
+    let bytes = padded_uleb128(&mut bytes, value, size)?;
+    w.write(bytes)
+}
+
+/// Encode a ULEB128 value that is padded to `size` bytes.
+pub(crate) fn padded_uleb128(buf: &mut [u8; 10], value: u64, size: u8) -> Result<&[u8]> {
+    let size = size as usize;
+    if size == 0 || size > buf.len() || (size < buf.len() && value >> (7 * size) != 0) {
+        return Err(Error::ValueTooLarge);
+    }
+    for (i, byte) in buf[..size].iter_mut().enumerate() {
+        *byte = ((value >> (7 * i)) & 0x7f) as u8;
+        if i + 1 < size {
+            *byte |= 0x80;
+        }
+    }
+    Ok(&buf[..size])
+}
+
+fn uleb128_size(mut value: u64) -> usize {
+    let mut size = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        size += 1;
+    }
+    size
+}
+
+fn sleb128_size(mut value: i64) -> usize {
+    let mut size = 1;
+    while !(-0x40..0x40).contains(&value) {
+        value >>= 7;
+        size += 1;
+    }
+    size
+}
+
+#[cfg(test)]
+#[cfg(feature = "read")]
+mod tests {
+    use super::*;
+    use read;
+    use write::{EndianVec, LineProgram, Unit};
+    use LittleEndian;
+
+    #[test]
+    fn test_operation() {
+        for &version in &[2, 4, 5] {
+            for &format in &[Format::Dwarf32, Format::Dwarf64] {
+                let encoding = Encoding {
+                    format,
+                    version,
+                    address_size: 8,
+                };
+
+                let mut expression = Expression::new();
+                expression.push(Operation::Address(Address::Absolute(0x1234)));
+                expression.push(Operation::UnsignedConstant(3));
+                expression.push(Operation::UnsignedConstant(0x1000));
+                expression.push(Operation::SignedConstant(-2));
+                expression.push(Operation::Simple(constants::DW_OP_plus));
+                let branch = expression.push(Operation::Branch(0));
+                expression.push(Operation::Register(Register(5)));
+                expression.push(Operation::Register(Register(40)));
+                let skip = expression.push(Operation::Skip(0));
+                let target = expression.push(Operation::RegisterOffset(Register(7), 8));
+                expression.push(Operation::RegisterOffset(Register(33), -8));
+                expression.push(Operation::FrameOffset(-20));
+                expression.push(Operation::Pick(0));
+                expression.push(Operation::Pick(1));
+                expression.push(Operation::Pick(2));
+                expression.push(Operation::DerefSize {
+                    size: 4,
+                    space: false,
+                });
+                expression.push(Operation::PlusConstant(16));
+                expression.push(Operation::Piece { size_in_bytes: 4 });
+                expression.push(Operation::BitPiece {
+                    size_in_bits: 32,
+                    bit_offset: 4,
+                });
+                expression.push(Operation::ImplicitValue(vec![1, 2, 3]));
+                let mut entry_value = Expression::new();
+                entry_value.push(Operation::Register(Register(5)));
+                expression.push(Operation::EntryValue(entry_value));
+                expression.push(Operation::Convert(None));
+                expression.push(Operation::Raw(vec![constants::DW_OP_stack_value.0]));
+                expression.set_target(branch, target);
+                let end = expression.next_index();
+                expression.set_target(skip, end);
+
+                let mut w = EndianVec::new(LittleEndian);
+                expression.write(&mut w, encoding, None).unwrap();
+                assert_eq!(w.slice().len(), expression.size(encoding));
+
+                let bytecode = read::EndianSlice::new(w.slice(), LittleEndian);
+                let mut pc = bytecode;
+                let mut operations = Vec::new();
+                while !pc.is_empty() {
+                    operations.push(read::Operation::parse(&mut pc, &bytecode, encoding).unwrap());
+                }
+                let target_pc = |index: usize| {
+                    let offset = expression.operations[..index]
+                        .iter()
+                        .map(|op| op.size(encoding))
+                        .sum::<usize>();
+                    read::EndianSlice::new(&w.slice()[offset..], LittleEndian)
+                };
+                let generic = read::UnitOffset(0);
+                assert_eq!(
+                    operations,
+                    vec![
+                        read::Operation::Address { address: 0x1234 },
+                        read::Operation::Literal { value: 3 },
+                        read::Operation::Literal { value: 0x1000 },
+                        read::Operation::Literal { value: !1 },
+                        read::Operation::Plus,
+                        read::Operation::Bra {
+                            target: target_pc(target),
+                        },
+                        read::Operation::Register {
+                            register: Register(5),
+                        },
+                        read::Operation::Register {
+                            register: Register(40),
+                        },
+                        read::Operation::Skip {
+                            target: target_pc(end),
+                        },
+                        read::Operation::RegisterOffset {
+                            register: Register(7),
+                            offset: 8,
+                            base_type: generic,
+                        },
+                        read::Operation::RegisterOffset {
+                            register: Register(33),
+                            offset: -8,
+                            base_type: generic,
+                        },
+                        read::Operation::FrameOffset { offset: -20 },
+                        read::Operation::Pick { index: 0 },
+                        read::Operation::Pick { index: 1 },
+                        read::Operation::Pick { index: 2 },
+                        read::Operation::Deref {
+                            base_type: generic,
+                            size: 4,
+                            space: false,
+                        },
+                        read::Operation::PlusConstant { value: 16 },
+                        read::Operation::Piece {
+                            size_in_bits: 32,
+                            bit_offset: None,
+                        },
+                        read::Operation::Piece {
+                            size_in_bits: 32,
+                            bit_offset: Some(4),
+                        },
+                        read::Operation::ImplicitValue {
+                            data: read::EndianSlice::new(&[1, 2, 3], LittleEndian),
+                        },
+                        read::Operation::EntryValue {
+                            expression: read::EndianSlice::new(
+                                &[constants::DW_OP_reg5.0],
+                                LittleEndian
+                            ),
+                        },
+                        read::Operation::Convert { base_type: generic },
+                        read::Operation::StackValue,
+                    ]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_operation_refs() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 4,
+        };
+        let mut unit = Unit::new(encoding, LineProgram::none());
+        let root = unit.root();
+        let base = unit.add(root, constants::DW_TAG_base_type);
+        let mut expression = Expression::new();
+        expression.push(Operation::Convert(Some(base)));
+
+        // References need somewhere to record the placeholders.
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            expression.write(&mut w, encoding, None),
+            Err(Error::InvalidReference)
+        );
+
+        let mut w = EndianVec::new(LittleEndian);
+        let mut refs = Vec::new();
+        expression.write(&mut w, encoding, Some(&mut refs)).unwrap();
+        assert_eq!(
+            w.slice(),
+            &[constants::DW_OP_convert.0, 0x80, 0x80, 0x80, 0x80, 0x00]
+        );
+        assert_eq!(
+            refs,
+            vec![EntryRef::Unit {
+                offset: 1,
+                entry: base,
+                size: 5,
+                uleb128: true,
+            }]
+        );
+
+        let mut buf = [0; 10];
+        assert_eq!(
+            padded_uleb128(&mut buf, 0x1234, 5),
+            Ok(&[0xb4, 0xa4, 0x80, 0x80, 0x00][..])
+        );
+        assert_eq!(
+            padded_uleb128(&mut buf, 0x1234, 1),
+            Err(Error::ValueTooLarge)
+        );
+
+        // Branch targets must be within the expression.
+        let mut expression = Expression::new();
+        expression.push(Operation::Skip(2));
+        let mut w = EndianVec::new(LittleEndian);
+        assert_eq!(
+            expression.write(&mut w, encoding, None),
+            Err(Error::InvalidBranchTarget(2))
+        );
+    }
+}
@@ -302,8 +302,10 @@ mod tests {
     };
     use read;
     use write::{
-        ConvertUnitContext, EndianVec, LineStringTable, Range, RangeListTable, StringTable,
+        ConvertUnitContext, EndianVec, LineStringTable, LocationListTable, MacroTable, Range,
+        RangeListTable, StringTable,
     };
+    use Arc;
     use LittleEndian;
 
     #[test]
@@ -361,10 +363,11 @@ mod tests {
                         header: read::UnitHeader::new(
                             encoding,
                             0,
+                            read::UnitType::Compilation,
                             DebugAbbrevOffset(0),
                             read::EndianSlice::default(),
                         ),
-                        abbreviations: read::Abbreviations::default(),
+                        abbreviations: Arc::new(read::Abbreviations::default()),
                         name: None,
                         comp_dir: None,
                         low_pc: 0,
@@ -372,6 +375,8 @@ mod tests {
                         addr_base: DebugAddrBase(0),
                         loclists_base: DebugLocListsBase(0),
                         rnglists_base: DebugRngListsBase(0),
+                        dwo_id: None,
+                        dwo_name: None,
                         line_program: None,
                     };
                     let context = ConvertUnitContext {
@@ -380,6 +385,8 @@ mod tests {
                         line_strings: &mut line_strings,
                         strings: &mut strings,
                         ranges: &mut ranges,
+                        locations: &mut LocationListTable::default(),
+                        macros: &mut MacroTable::default(),
                         convert_address: &|address| Some(Address::Absolute(address)),
                         base_address: Address::Absolute(0),
                         line_program_offset: None,
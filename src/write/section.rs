@@ -2,14 +2,14 @@ use std::ops::DerefMut;
 use std::result;
 
 use write::{
-    DebugAbbrev, DebugInfo, DebugLine, DebugLineStr, DebugRanges, DebugRngLists, DebugStr, Writer,
+    DebugAbbrev, DebugFrame, DebugInfo, DebugLine, DebugLineStr, DebugLoc, DebugLocLists,
+    DebugMacinfo, DebugMacro, DebugNames, DebugRanges, DebugRngLists, DebugStr, EhFrame,
+    EhFrameHdr, Writer,
 };
 
 macro_rules! define_section {
     ($name:ident, $offset:ident, $docs:expr) => {
-        #[doc=$docs]
-        #[derive(Debug, Default)]
-        pub struct $name<W: Writer>(pub W);
+        define_section!($name, $docs);
 
         impl<W: Writer> $name<W> {
             /// Return the offset of the next write.
@@ -17,6 +17,11 @@ macro_rules! define_section {
                 $offset(self.len())
             }
         }
+    };
+    ($name:ident, $docs:expr) => {
+        #[doc=$docs]
+        #[derive(Debug, Default)]
+        pub struct $name<W: Writer>(pub W);
 
         impl<W: Writer> From<W> for $name<W> {
             #[inline]
@@ -55,6 +60,8 @@ macro_rules! define_section {
 pub enum SectionId {
     /// The `.debug_abbrev` section.
     DebugAbbrev,
+    /// The `.debug_frame` section.
+    DebugFrame,
     /// The `.debug_info` section.
     DebugInfo,
     /// The `.debug_line` section.
@@ -67,12 +74,20 @@ pub enum SectionId {
     DebugLocLists,
     /// The `.debug_macinfo` section.
     DebugMacinfo,
+    /// The `.debug_macro` section.
+    DebugMacro,
+    /// The `.debug_names` section.
+    DebugNames,
     /// The `.debug_ranges` section.
     DebugRanges,
     /// The `.debug_rnglists` section.
     DebugRngLists,
     /// The `.debug_str` section.
     DebugStr,
+    /// The `.eh_frame` section.
+    EhFrame,
+    /// The `.eh_frame_hdr` section.
+    EhFrameHdr,
 }
 
 impl SectionId {
@@ -80,15 +95,20 @@ impl SectionId {
     pub fn name(self) -> &'static str {
         match self {
             SectionId::DebugAbbrev => ".debug_abbrev",
+            SectionId::DebugFrame => ".debug_frame",
             SectionId::DebugInfo => ".debug_info",
             SectionId::DebugLine => ".debug_line",
             SectionId::DebugLineStr => ".debug_line_str",
             SectionId::DebugLoc => ".debug_loc",
             SectionId::DebugLocLists => ".debug_loclists",
             SectionId::DebugMacinfo => ".debug_macinfo",
+            SectionId::DebugMacro => ".debug_macro",
+            SectionId::DebugNames => ".debug_names",
             SectionId::DebugRanges => ".debug_ranges",
             SectionId::DebugRngLists => ".debug_rnglists",
             SectionId::DebugStr => ".debug_str",
+            SectionId::EhFrame => ".eh_frame",
+            SectionId::EhFrameHdr => ".eh_frame_hdr",
         }
     }
 }
@@ -109,18 +129,34 @@ pub trait Section<W: Writer>: DerefMut<Target = W> {
 pub struct Sections<W: Writer> {
     /// The `.debug_abbrev` section.
     pub debug_abbrev: DebugAbbrev<W>,
+    /// The `.debug_frame` section.
+    pub debug_frame: DebugFrame<W>,
     /// The `.debug_info` section.
     pub debug_info: DebugInfo<W>,
     /// The `.debug_line` section.
     pub debug_line: DebugLine<W>,
     /// The `.debug_line_str` section.
     pub debug_line_str: DebugLineStr<W>,
+    /// The `.debug_loc` section.
+    pub debug_loc: DebugLoc<W>,
+    /// The `.debug_loclists` section.
+    pub debug_loclists: DebugLocLists<W>,
+    /// The `.debug_macinfo` section.
+    pub debug_macinfo: DebugMacinfo<W>,
+    /// The `.debug_macro` section.
+    pub debug_macro: DebugMacro<W>,
+    /// The `.debug_names` section.
+    pub debug_names: DebugNames<W>,
     /// The `.debug_ranges` section.
     pub debug_ranges: DebugRanges<W>,
     /// The `.debug_rnglists` section.
     pub debug_rnglists: DebugRngLists<W>,
     /// The `.debug_str` section.
     pub debug_str: DebugStr<W>,
+    /// The `.eh_frame` section.
+    pub eh_frame: EhFrame<W>,
+    /// The `.eh_frame_hdr` section.
+    pub eh_frame_hdr: EhFrameHdr<W>,
 }
 
 impl<W: Writer + Clone> Sections<W> {
@@ -128,12 +164,20 @@ impl<W: Writer + Clone> Sections<W> {
     pub fn new(section: W) -> Self {
         Sections {
             debug_abbrev: DebugAbbrev(section.clone()),
+            debug_frame: DebugFrame(section.clone()),
             debug_info: DebugInfo(section.clone()),
             debug_line: DebugLine(section.clone()),
             debug_line_str: DebugLineStr(section.clone()),
+            debug_loc: DebugLoc(section.clone()),
+            debug_loclists: DebugLocLists(section.clone()),
+            debug_macinfo: DebugMacinfo(section.clone()),
+            debug_macro: DebugMacro(section.clone()),
+            debug_names: DebugNames(section.clone()),
             debug_ranges: DebugRanges(section.clone()),
             debug_rnglists: DebugRngLists(section.clone()),
             debug_str: DebugStr(section.clone()),
+            eh_frame: EhFrame(section.clone()),
+            eh_frame_hdr: EhFrameHdr(section.clone()),
         }
     }
 }
@@ -150,12 +194,20 @@ impl<W: Writer> Sections<W> {
             };
         }
         f!(self.debug_abbrev)?;
+        f!(self.debug_frame)?;
         f!(self.debug_info)?;
         f!(self.debug_line)?;
         f!(self.debug_line_str)?;
+        f!(self.debug_loc)?;
+        f!(self.debug_loclists)?;
+        f!(self.debug_macinfo)?;
+        f!(self.debug_macro)?;
+        f!(self.debug_names)?;
         f!(self.debug_ranges)?;
         f!(self.debug_rnglists)?;
         f!(self.debug_str)?;
+        f!(self.eh_frame)?;
+        f!(self.eh_frame_hdr)?;
         Ok(())
     }
 
@@ -170,12 +222,20 @@ impl<W: Writer> Sections<W> {
             };
         }
         f!(self.debug_abbrev)?;
+        f!(self.debug_frame)?;
         f!(self.debug_info)?;
         f!(self.debug_line)?;
         f!(self.debug_line_str)?;
+        f!(self.debug_loc)?;
+        f!(self.debug_loclists)?;
+        f!(self.debug_macinfo)?;
+        f!(self.debug_macro)?;
+        f!(self.debug_names)?;
         f!(self.debug_ranges)?;
         f!(self.debug_rnglists)?;
         f!(self.debug_str)?;
+        f!(self.eh_frame)?;
+        f!(self.eh_frame_hdr)?;
         Ok(())
     }
 }
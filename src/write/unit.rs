@@ -3,24 +3,47 @@ use std::{slice, usize};
 use vec::Vec;
 
 use common::{
-    DebugAbbrevOffset, DebugInfoOffset, DebugLineOffset, DebugMacinfoOffset, DebugStrOffset,
-    DebugTypeSignature, Encoding, Format, LocationListsOffset, UnitSectionOffset,
+    DebugAbbrevOffset, DebugInfoOffset, DebugLineOffset, DebugStrOffset, DebugTypeSignature,
+    Encoding, Format, UnitSectionOffset,
 };
 use constants;
 use write::{
-    Abbreviation, AbbreviationTable, Address, AttributeSpecification, BaseId, DebugLineStrOffsets,
-    DebugStrOffsets, Error, FileId, LineProgram, LineStringId, RangeList, RangeListId,
-    RangeListOffsets, RangeListTable, Result, Section, SectionId, Sections, StringId, Writer,
+    padded_uleb128, Abbreviation, AbbreviationTable, Address, AttributeSpecification, BaseId,
+    DebugLineStrOffsets, DebugStrOffsets, Error, Expression, FileId, LineProgram, LineStringId,
+    LocationList, LocationListId, LocationListOffsets, LocationListTable, MacroTable, MacroUnitId,
+    MacroUnitOffsets, RangeList, RangeListId, RangeListOffsets, RangeListTable, Result, Section,
+    SectionId, Sections, StringId, Writer,
 };
 
 define_id!(UnitId, "An identifier for a unit in a `UnitTable`.");
 
 define_id!(UnitEntryId, "An identifier for an entry in a `Unit`.");
 
-/// The bytecode for a DWARF expression or location description.
-// TODO: this needs to be a `Vec<Op>` so we can handle relocations
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Expression(pub Vec<u8>);
+/// A placeholder for a reference to an entry, which is patched once the
+/// offsets of the entries are known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EntryRef {
+    /// The offset of an entry relative to the start of its unit.
+    Unit {
+        /// The offset of the placeholder within its section.
+        offset: usize,
+        /// The referenced entry, which must be in the unit being written.
+        entry: UnitEntryId,
+        /// The size of the placeholder in bytes.
+        size: u8,
+        /// Whether the placeholder is a ULEB128 value that is padded to `size` bytes.
+        uleb128: bool,
+    },
+    /// The offset of an entry within the `.debug_info` section.
+    DebugInfo {
+        /// The offset of the placeholder within its section.
+        offset: usize,
+        /// The referenced entry, which may be in any unit.
+        entry: (UnitId, UnitEntryId),
+        /// The size of the placeholder in bytes.
+        size: u8,
+    },
+}
 
 /// A table of units that will be stored in the `.debug_info` section.
 #[derive(Debug, Default)]
@@ -111,15 +134,22 @@ impl UnitTable {
             )?);
         }
 
-        for (offset, (unit, entry), size) in debug_info_refs {
-            let entry_offset = offsets.entry(unit, entry).0;
-            debug_assert_ne!(entry_offset, 0);
-            sections.debug_info.write_offset_at(
-                offset.0,
-                entry_offset,
-                SectionId::DebugInfo,
+        for (section, reference) in debug_info_refs {
+            if let EntryRef::DebugInfo {
+                offset,
+                entry: (unit, entry),
                 size,
-            )?;
+            } = reference
+            {
+                let entry_offset = offsets.entry(unit, entry).0;
+                debug_assert_ne!(entry_offset, 0);
+                section_writer(sections, section).write_offset_at(
+                    offset,
+                    entry_offset,
+                    SectionId::DebugInfo,
+                    size,
+                )?;
+            }
         }
 
         abbrevs.write(&mut sections.debug_abbrev)?;
@@ -138,6 +168,10 @@ pub struct Unit {
     pub line_program: LineProgram,
     /// A table of range lists used by this unit.
     pub ranges: RangeListTable,
+    /// A table of location lists used by this unit.
+    pub locations: LocationListTable,
+    /// A table of macro units used by this unit.
+    pub macros: MacroTable,
     /// All entries in this unit. The order is unrelated to the tree order.
     // Requirements:
     // - entries form a tree
@@ -156,6 +190,8 @@ impl Unit {
     pub fn new(encoding: Encoding, line_program: LineProgram) -> Self {
         let base_id = BaseId::default();
         let ranges = RangeListTable::default();
+        let locations = LocationListTable::default();
+        let macros = MacroTable::default();
         let mut entries = Vec::new();
         let root = DebuggingInformationEntry::new(
             base_id,
@@ -168,6 +204,8 @@ impl Unit {
             encoding,
             line_program,
             ranges,
+            locations,
+            macros,
             entries,
             root,
         }
@@ -251,7 +289,7 @@ impl Unit {
         if self.line_program.is_none() {
             return false;
         }
-        if !self.line_program.is_empty() {
+        if !self.line_program.is_empty() || self.macros.has_files() {
             return true;
         }
 
@@ -266,6 +304,32 @@ impl Unit {
         false
     }
 
+    /// Return the section that `self.macros` should be written to.
+    ///
+    /// DWARF version 5 always uses `.debug_macro`. Earlier versions use
+    /// `.debug_macinfo`, unless a macro unit is referenced by a
+    /// `DW_AT_GNU_macros` attribute, in which case the GNU extension to
+    /// `.debug_macro` is used.
+    fn macro_section(&self) -> SectionId {
+        if self.version() >= 5 {
+            return SectionId::DebugMacro;
+        }
+
+        for entry in &self.entries {
+            for attr in &entry.attrs {
+                if let AttributeValue::MacroRef(_) = attr.value {
+                    if attr.name == constants::DW_AT_GNU_macros
+                        || attr.name == constants::DW_AT_macros
+                    {
+                        return SectionId::DebugMacro;
+                    }
+                }
+            }
+        }
+
+        SectionId::DebugMacinfo
+    }
+
     /// Write the unit to the given sections.
     pub(crate) fn write<W: Writer>(
         &mut self,
@@ -274,7 +338,7 @@ impl Unit {
         abbrevs: &mut AbbreviationTable,
         line_strings: &DebugLineStrOffsets,
         strings: &DebugStrOffsets,
-        debug_info_refs: &mut Vec<(DebugInfoOffset, (UnitId, UnitEntryId), u8)>,
+        debug_info_refs: &mut Vec<(SectionId, EntryRef)>,
     ) -> Result<UnitOffsets> {
         let line_program = if self.line_program_in_use() {
             self.entries[self.root.index]
@@ -290,6 +354,18 @@ impl Unit {
             None
         };
         let range_lists = self.ranges.write(sections, self.encoding)?;
+        let mut loc_refs = Vec::new();
+        let loc_lists = self
+            .locations
+            .write(sections, self.encoding, &mut loc_refs)?;
+        let macro_section = self.macro_section();
+        let macro_units = self.macros.write(
+            sections,
+            macro_section,
+            self.encoding,
+            line_program,
+            strings,
+        )?;
 
         // TODO: use .debug_types for type units in DWARF v4.
         let w = &mut sections.debug_info;
@@ -300,7 +376,7 @@ impl Unit {
             // Entries can be written in any order, so create the complete vec now.
             entries: vec![DebugInfoOffset(0); self.entries.len()],
         };
-        let mut unit_refs = Vec::new();
+        let mut refs = Vec::new();
 
         let length_offset = w.write_initial_length(self.format())?;
         let length_base = w.len();
@@ -334,23 +410,31 @@ impl Unit {
             line_strings,
             strings,
             &range_lists,
-            &mut unit_refs,
-            debug_info_refs,
+            &loc_lists,
+            &macro_units,
+            &mut refs,
         )?;
 
         let length = (w.len() - length_base) as u64;
         w.write_initial_length_at(length_offset, length, self.format())?;
 
-        for (offset, entry) in unit_refs {
-            let entry_offset = offsets.entry(entry).0;
-            debug_assert_ne!(entry_offset, 0);
-            // This does not need relocation.
-            w.write_word_at(
-                offset.0,
-                (entry_offset - offsets.unit.0) as u64,
-                self.format().word_size(),
+        for reference in refs {
+            patch_ref(
+                sections,
+                SectionId::DebugInfo,
+                reference,
+                &offsets,
+                debug_info_refs,
             )?;
         }
+        let loc_section = if self.version() <= 4 {
+            SectionId::DebugLoc
+        } else {
+            SectionId::DebugLocLists
+        };
+        for reference in loc_refs {
+            patch_ref(sections, loc_section, reference, &offsets, debug_info_refs)?;
+        }
 
         Ok(offsets)
     }
@@ -529,8 +613,9 @@ impl DebuggingInformationEntry {
         line_strings: &DebugLineStrOffsets,
         strings: &DebugStrOffsets,
         range_lists: &RangeListOffsets,
-        unit_refs: &mut Vec<(DebugInfoOffset, UnitEntryId)>,
-        debug_info_refs: &mut Vec<(DebugInfoOffset, (UnitId, UnitEntryId), u8)>,
+        loc_lists: &LocationListOffsets,
+        macro_units: &MacroUnitOffsets,
+        refs: &mut Vec<EntryRef>,
     ) -> Result<()> {
         offsets.entries[self.id.index] = w.offset();
         let code = abbrevs.add(self.abbreviation(unit.encoding())?);
@@ -552,8 +637,9 @@ impl DebuggingInformationEntry {
                 line_strings,
                 strings,
                 range_lists,
-                unit_refs,
-                debug_info_refs,
+                loc_lists,
+                macro_units,
+                refs,
             )?;
         }
 
@@ -568,8 +654,9 @@ impl DebuggingInformationEntry {
                     line_strings,
                     strings,
                     range_lists,
-                    unit_refs,
-                    debug_info_refs,
+                    loc_lists,
+                    macro_units,
+                    refs,
                 )?;
             }
             // Null child
@@ -630,9 +717,23 @@ impl Attribute {
         line_strings: &DebugLineStrOffsets,
         strings: &DebugStrOffsets,
         range_lists: &RangeListOffsets,
-        unit_refs: &mut Vec<(DebugInfoOffset, UnitEntryId)>,
-        debug_info_refs: &mut Vec<(DebugInfoOffset, (UnitId, UnitEntryId), u8)>,
+        loc_lists: &LocationListOffsets,
+        macro_units: &MacroUnitOffsets,
+        refs: &mut Vec<EntryRef>,
     ) -> Result<()> {
+        if let AttributeValue::MacroRef(_) = self.value {
+            // The attribute name determines which section the offset refers to.
+            let section = match self.name {
+                constants::DW_AT_macro_info => Some(SectionId::DebugMacinfo),
+                constants::DW_AT_macros | constants::DW_AT_GNU_macros => {
+                    Some(SectionId::DebugMacro)
+                }
+                _ => None,
+            };
+            if section.is_some() && section != Some(macro_units.section()) {
+                return Err(Error::InvalidAttributeValue);
+            }
+        }
         self.value.write(
             w,
             unit,
@@ -640,8 +741,9 @@ impl Attribute {
             line_strings,
             strings,
             range_lists,
-            unit_refs,
-            debug_info_refs,
+            loc_lists,
+            macro_units,
+            refs,
         )
     }
 }
@@ -729,19 +831,11 @@ pub enum AttributeValue {
     /// A reference to a line number program.
     LineProgramRef,
 
-    /// An offset into either the `.debug_loc` section or the `.debug_loclists` section.
-    ///
-    /// It is the user's responsibility to ensure the offset is valid.
-    /// This variant will be removed from the API once support for writing
-    /// `.debug_loc`/`.debug_loclists` sections is implemented.
-    LocationListsRef(LocationListsOffset),
+    /// A reference to a location list.
+    LocationListRef(LocationListId),
 
-    /// An offset into the `.debug_macinfo` section.
-    ///
-    /// It is the user's responsibility to ensure the offset is valid.
-    /// This variant will be removed from the API once support for writing
-    /// `.debug_macinfo` sections is implemented.
-    DebugMacinfoRef(DebugMacinfoOffset),
+    /// A reference to a macro unit.
+    MacroRef(MacroUnitId),
 
     /// A reference to a range list.
     RangeListRef(RangeListId),
@@ -851,8 +945,8 @@ impl AttributeValue {
                 }
             }
             AttributeValue::LineProgramRef
-            | AttributeValue::LocationListsRef(_)
-            | AttributeValue::DebugMacinfoRef(_)
+            | AttributeValue::LocationListRef(_)
+            | AttributeValue::MacroRef(_)
             | AttributeValue::RangeListRef(_) => {
                 if encoding.version == 2 || encoding.version == 3 {
                     match encoding.format {
@@ -900,8 +994,9 @@ impl AttributeValue {
         line_strings: &DebugLineStrOffsets,
         strings: &DebugStrOffsets,
         range_lists: &RangeListOffsets,
-        unit_refs: &mut Vec<(DebugInfoOffset, UnitEntryId)>,
-        debug_info_refs: &mut Vec<(DebugInfoOffset, (UnitId, UnitEntryId), u8)>,
+        loc_lists: &LocationListOffsets,
+        macro_units: &MacroUnitOffsets,
+        refs: &mut Vec<EntryRef>,
     ) -> Result<()> {
         macro_rules! debug_assert_form {
             ($form:expr) => {
@@ -944,8 +1039,8 @@ impl AttributeValue {
             }
             AttributeValue::Exprloc(ref val) => {
                 debug_assert_form!(constants::DW_FORM_exprloc);
-                w.write_uleb128(val.0.len() as u64)?;
-                w.write(&val.0)?;
+                w.write_uleb128(val.size(unit.encoding()) as u64)?;
+                val.write(&mut w.0, unit.encoding(), Some(refs))?;
             }
             AttributeValue::Flag(val) => {
                 debug_assert_form!(constants::DW_FORM_flag);
@@ -959,7 +1054,12 @@ impl AttributeValue {
                     Format::Dwarf32 => debug_assert_form!(constants::DW_FORM_ref4),
                     Format::Dwarf64 => debug_assert_form!(constants::DW_FORM_ref8),
                 }
-                unit_refs.push((w.offset(), id));
+                refs.push(EntryRef::Unit {
+                    offset: w.len(),
+                    entry: id,
+                    size: unit.format().word_size(),
+                    uleb128: false,
+                });
                 w.write_word(0, unit.format().word_size())?;
             }
             AttributeValue::AnyUnitEntryRef(id) => {
@@ -969,7 +1069,11 @@ impl AttributeValue {
                 } else {
                     unit.format().word_size()
                 };
-                debug_info_refs.push((w.offset(), id, size));
+                refs.push(EntryRef::DebugInfo {
+                    offset: w.len(),
+                    entry: id,
+                    size,
+                });
                 w.write_word(0, size)?;
             }
             AttributeValue::DebugInfoRefSup(val) => {
@@ -994,7 +1098,7 @@ impl AttributeValue {
                     None => return Err(Error::InvalidAttributeValue),
                 }
             }
-            AttributeValue::LocationListsRef(val) => {
+            AttributeValue::LocationListRef(val) => {
                 if unit.version() >= 4 {
                     debug_assert_form!(constants::DW_FORM_sec_offset);
                 }
@@ -1003,13 +1107,17 @@ impl AttributeValue {
                 } else {
                     SectionId::DebugLocLists
                 };
-                w.write_offset(val.0, section, unit.format().word_size())?;
+                w.write_offset(loc_lists.get(val).0, section, unit.format().word_size())?;
             }
-            AttributeValue::DebugMacinfoRef(val) => {
+            AttributeValue::MacroRef(val) => {
                 if unit.version() >= 4 {
                     debug_assert_form!(constants::DW_FORM_sec_offset);
                 }
-                w.write_offset(val.0, SectionId::DebugMacinfo, unit.format().word_size())?;
+                w.write_offset(
+                    macro_units.get(val),
+                    macro_units.section(),
+                    unit.format().word_size(),
+                )?;
             }
             AttributeValue::RangeListRef(val) => {
                 if unit.version() >= 4 {
@@ -1117,6 +1225,53 @@ define_section!(
     "A writable `.debug_info` section."
 );
 
+/// Patch a placeholder for an entry in the unit that was just written.
+///
+/// References to the `.debug_info` offset of an entry are deferred until all
+/// units have been written.
+fn patch_ref<W: Writer>(
+    sections: &mut Sections<W>,
+    section: SectionId,
+    reference: EntryRef,
+    offsets: &UnitOffsets,
+    debug_info_refs: &mut Vec<(SectionId, EntryRef)>,
+) -> Result<()> {
+    match reference {
+        EntryRef::Unit {
+            offset,
+            entry,
+            size,
+            uleb128,
+        } => {
+            let entry_offset = offsets.entry(entry).0;
+            debug_assert_ne!(entry_offset, 0);
+            let value = (entry_offset - offsets.unit.0) as u64;
+            let w = section_writer(sections, section);
+            // This does not need relocation.
+            if uleb128 {
+                let mut buf = [0; 10];
+                w.write_at(offset, padded_uleb128(&mut buf, value, size)?)
+            } else {
+                w.write_word_at(offset, value, size)
+            }
+        }
+        EntryRef::DebugInfo { .. } => {
+            debug_info_refs.push((section, reference));
+            Ok(())
+        }
+    }
+}
+
+/// Return the writer for a section that may contain references to entries.
+fn section_writer<W: Writer>(sections: &mut Sections<W>, section: SectionId) -> &mut W {
+    match section {
+        SectionId::DebugInfo => &mut sections.debug_info.0,
+        SectionId::DebugLoc => &mut sections.debug_loc.0,
+        SectionId::DebugLocLists => &mut sections.debug_loclists.0,
+        _ => unreachable!(),
+    }
+}
+
 /// The section offsets of all elements within a `.debug_info` section.
 #[derive(Debug, Default)]
 pub struct DebugInfoOffsets {
@@ -1161,7 +1316,7 @@ pub(crate) mod convert {
     use super::*;
     use collections::HashMap;
     use read::{self, Reader};
-    use write::{self, ConvertError, ConvertResult};
+    use write::{self, ConvertError, ConvertResult, MacroUnit};
 
     pub(crate) struct ConvertUnitContext<'a, R: Reader<Offset = usize> + 'a> {
         pub dwarf: &'a read::Dwarf<R>,
@@ -1169,6 +1324,8 @@ pub(crate) mod convert {
         pub line_strings: &'a mut write::LineStringTable,
         pub strings: &'a mut write::StringTable,
         pub ranges: &'a mut write::RangeListTable,
+        pub locations: &'a mut write::LocationListTable,
+        pub macros: &'a mut write::MacroTable,
         pub convert_address: &'a Fn(u64) -> Option<Address>,
         pub base_address: Address,
         pub line_program_offset: Option<DebugLineOffset>,
@@ -1276,6 +1433,8 @@ pub(crate) mod convert {
                 };
 
             let mut ranges = RangeListTable::default();
+            let mut locations = LocationListTable::default();
+            let mut macros = MacroTable::default();
             let mut entries = Vec::new();
             let root = {
                 let mut context = ConvertUnitContext {
@@ -1284,6 +1443,8 @@ pub(crate) mod convert {
                     line_strings,
                     strings,
                     ranges: &mut ranges,
+                    locations: &mut locations,
+                    macros: &mut macros,
                     convert_address,
                     base_address,
                     line_program_offset,
@@ -1307,6 +1468,8 @@ pub(crate) mod convert {
                 encoding,
                 line_program,
                 ranges,
+                locations,
+                macros,
                 entries,
                 root,
             })
@@ -1395,7 +1558,7 @@ pub(crate) mod convert {
                 read::AttributeValue::Udata(val) => AttributeValue::Udata(val),
                 // TODO: addresses and offsets in expressions need special handling.
                 read::AttributeValue::Exprloc(read::Expression(val)) => {
-                    AttributeValue::Exprloc(Expression(val.to_slice()?.into()))
+                    AttributeValue::Exprloc(Expression::raw(val.to_slice()?.into()))
                 }
                 // TODO: it would be nice to preserve the flag form.
                 read::AttributeValue::Flag(val) => AttributeValue::Flag(val),
@@ -1427,9 +1590,26 @@ pub(crate) mod convert {
                         return Err(ConvertError::InvalidLineRef);
                     }
                 }
-                read::AttributeValue::DebugMacinfoRef(val) => AttributeValue::DebugMacinfoRef(val),
+                read::AttributeValue::DebugMacinfoRef(val) => {
+                    let iter = context.dwarf.macinfo(val)?;
+                    let macro_unit = MacroUnit::from(iter, context)?;
+                    let macro_id = context.macros.add(macro_unit);
+                    AttributeValue::MacroRef(macro_id)
+                }
+                read::AttributeValue::DebugMacroRef(val) => {
+                    let iter = context.dwarf.macros(val)?;
+                    let macro_unit = MacroUnit::from(iter, context)?;
+                    let macro_id = context.macros.add(macro_unit);
+                    AttributeValue::MacroRef(macro_id)
+                }
                 read::AttributeValue::LocationListsRef(val) => {
-                    AttributeValue::LocationListsRef(val)
+                    let iter = context
+                        .dwarf
+                        .locations
+                        .raw_locations(val, context.unit.encoding())?;
+                    let loc_list = LocationList::from(iter, context)?;
+                    let loc_id = context.locations.add(loc_list);
+                    AttributeValue::LocationListRef(loc_id)
                 }
                 read::AttributeValue::DebugLocListsBase(_base) => {
                     // We convert all location list indices to offsets,
@@ -1438,7 +1618,13 @@ pub(crate) mod convert {
                 }
                 read::AttributeValue::DebugLocListsIndex(index) => {
                     let offset = context.dwarf.locations_offset(context.unit, index)?;
-                    AttributeValue::LocationListsRef(offset)
+                    let iter = context
+                        .dwarf
+                        .locations
+                        .raw_locations(offset, context.unit.encoding())?;
+                    let loc_list = LocationList::from(iter, context)?;
+                    let loc_id = context.locations.add(loc_list);
+                    AttributeValue::LocationListRef(loc_id)
                 }
                 read::AttributeValue::RangeListsRef(val) => {
                     let iter = context
@@ -1528,9 +1714,11 @@ mod tests {
     use read;
     use std::mem;
     use write::{
-        DebugLine, DebugLineStr, DebugStr, EndianVec, LineString, LineStringTable, Range,
-        RangeListOffsets, RangeListTable, StringTable,
+        DebugLine, DebugLineStr, DebugStr, EndianVec, LineString, LineStringTable, Location,
+        LocationListOffsets, LocationListTable, MacroEntry, MacroString, MacroUnit, Operation,
+        Range, RangeListOffsets, RangeListTable, StringTable,
     };
+    use Arc;
     use LittleEndian;
 
     #[test]
@@ -1848,6 +2036,15 @@ mod tests {
             begin: Address::Absolute(0x1234),
             end: Address::Absolute(0x2345),
         }]));
+        let mut locations = LocationListTable::default();
+        let loc_id = locations.add(LocationList(vec![Location::StartEnd {
+            begin: Address::Absolute(0x1234),
+            end: Address::Absolute(0x2345),
+            data: Expression::raw(vec![constants::DW_OP_lit0.0]),
+        }]));
+        let mut macros = MacroTable::default();
+        macros.add(MacroUnit(vec![MacroEntry::EndFile]));
+        let macro_id = macros.add(MacroUnit(Vec::new()));
 
         let mut debug_str = DebugStr::from(EndianVec::new(LittleEndian));
         let debug_str_offsets = strings.write(&mut debug_str).unwrap();
@@ -1875,10 +2072,40 @@ mod tests {
 
                     let mut sections = Sections::new(EndianVec::new(LittleEndian));
                     let range_list_offsets = ranges.write(&mut sections, encoding).unwrap();
+                    let mut refs = Vec::new();
+                    let loc_list_offsets =
+                        locations.write(&mut sections, encoding, &mut refs).unwrap();
+                    let macro_section = if version <= 4 {
+                        SectionId::DebugMacinfo
+                    } else {
+                        SectionId::DebugMacro
+                    };
+                    let macro_unit_offsets = macros
+                        .write(
+                            &mut sections,
+                            macro_section,
+                            encoding,
+                            None,
+                            &debug_str_offsets,
+                        )
+                        .unwrap();
+                    let macro_name = if version <= 4 {
+                        constants::DW_AT_macro_info
+                    } else {
+                        constants::DW_AT_macros
+                    };
                     let read_debug_ranges =
                         read::DebugRanges::new(sections.debug_ranges.slice(), LittleEndian);
                     let read_debug_rnglists =
                         read::DebugRngLists::new(sections.debug_rnglists.slice(), LittleEndian);
+                    let read_debug_loc =
+                        read::DebugLoc::new(sections.debug_loc.slice(), LittleEndian);
+                    let read_debug_loclists =
+                        read::DebugLocLists::new(sections.debug_loclists.slice(), LittleEndian);
+                    let read_debug_macinfo =
+                        read::DebugMacinfo::new(sections.debug_macinfo.slice(), LittleEndian);
+                    let read_debug_macro =
+                        read::DebugMacro::new(sections.debug_macro.slice(), LittleEndian);
 
                     let mut units = UnitTable::default();
                     let unit = units.add(Unit::new(encoding, LineProgram::none()));
@@ -1891,6 +2118,7 @@ mod tests {
                     let from_unit = read::UnitHeader::new(
                         encoding,
                         0,
+                        read::UnitType::Compilation,
                         DebugAbbrevOffset(0),
                         read::EndianSlice::new(&[], LittleEndian),
                     );
@@ -1938,7 +2166,7 @@ mod tests {
                         ),
                         (
                             constants::DW_AT_name,
-                            AttributeValue::Exprloc(Expression(data.clone())),
+                            AttributeValue::Exprloc(Expression::raw(data.clone())),
                             read::AttributeValue::Exprloc(read::Expression(read_data)),
                         ),
                         (
@@ -1960,13 +2188,13 @@ mod tests {
                         ),
                         (
                             constants::DW_AT_location,
-                            AttributeValue::LocationListsRef(LocationListsOffset(0x1234)),
-                            read::AttributeValue::SecOffset(0x1234),
+                            AttributeValue::LocationListRef(loc_id),
+                            read::AttributeValue::SecOffset(loc_list_offsets.get(loc_id).0),
                         ),
                         (
-                            constants::DW_AT_macro_info,
-                            AttributeValue::DebugMacinfoRef(DebugMacinfoOffset(0x1234)),
-                            read::AttributeValue::SecOffset(0x1234),
+                            macro_name,
+                            AttributeValue::MacroRef(macro_id),
+                            read::AttributeValue::SecOffset(macro_unit_offsets.get(macro_id)),
                         ),
                         (
                             constants::DW_AT_ranges,
@@ -2069,8 +2297,7 @@ mod tests {
                         };
 
                         let line_program_offset = None;
-                        let mut unit_refs = Vec::new();
-                        let mut debug_info_refs = Vec::new();
+                        let mut refs = Vec::new();
                         let mut debug_info = DebugInfo::from(EndianVec::new(LittleEndian));
                         attr.write(
                             &mut debug_info,
@@ -2079,8 +2306,9 @@ mod tests {
                             &debug_line_str_offsets,
                             &debug_str_offsets,
                             &range_list_offsets,
-                            &mut unit_refs,
-                            &mut debug_info_refs,
+                            &loc_list_offsets,
+                            &macro_unit_offsets,
+                            &mut refs,
                         )
                         .unwrap();
 
@@ -2103,13 +2331,19 @@ mod tests {
                             debug_str: read_debug_str.clone(),
                             debug_line_str: read_debug_line_str.clone(),
                             ranges: read::RangeLists::new(read_debug_ranges, read_debug_rnglists),
+                            locations: read::LocationLists::new(
+                                read_debug_loc,
+                                read_debug_loclists,
+                            ),
+                            debug_macinfo: read_debug_macinfo,
+                            debug_macro: read_debug_macro,
                             ..Default::default()
                         };
 
                         let unit = read::Unit {
                             offset: UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0)),
                             header: from_unit,
-                            abbreviations: read::Abbreviations::default(),
+                            abbreviations: Arc::new(read::Abbreviations::default()),
                             name: None,
                             comp_dir: None,
                             low_pc: 0,
@@ -2117,6 +2351,8 @@ mod tests {
                             addr_base: DebugAddrBase(0),
                             loclists_base: DebugLocListsBase(0),
                             rnglists_base: DebugRngListsBase(0),
+                            dwo_id: None,
+                            dwo_name: None,
                             line_program: None,
                         };
 
@@ -2126,6 +2362,8 @@ mod tests {
                             line_strings: &mut line_strings,
                             strings: &mut strings,
                             ranges: &mut ranges,
+                            locations: &mut locations,
+                            macros: &mut macros,
                             convert_address: &|address| Some(Address::Absolute(address)),
                             base_address: Address::Absolute(0),
                             line_program_offset: None,
@@ -2362,6 +2600,103 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_expression_refs() {
+        let encoding = Encoding {
+            version: 5,
+            address_size: 8,
+            format: Format::Dwarf32,
+        };
+        let mut units = UnitTable::default();
+        let unit_id1 = units.add(Unit::new(encoding, LineProgram::none()));
+        let unit_id2 = units.add(Unit::new(encoding, LineProgram::none()));
+        let unit2_child = {
+            let unit2 = units.get_mut(unit_id2);
+            let root = unit2.root();
+            unit2.add(root, constants::DW_TAG_variable)
+        };
+        let (unit1_base, unit1_child) = {
+            let unit1 = units.get_mut(unit_id1);
+            let root = unit1.root();
+            let base = unit1.add(root, constants::DW_TAG_base_type);
+            let child = unit1.add(root, constants::DW_TAG_variable);
+            let mut expression = Expression::new();
+            expression.push(Operation::Call(base));
+            expression.push(Operation::Convert(Some(base)));
+            expression.push(Operation::ImplicitPointer {
+                entry: (unit_id2, unit2_child),
+                byte_offset: 4,
+            });
+            unit1.get_mut(child).set(
+                constants::DW_AT_location,
+                AttributeValue::Exprloc(expression),
+            );
+            (base, child)
+        };
+
+        let debug_line_str_offsets = DebugLineStrOffsets::none();
+        let debug_str_offsets = DebugStrOffsets::none();
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let debug_info_offsets = units
+            .write(&mut sections, &debug_line_str_offsets, &debug_str_offsets)
+            .unwrap();
+
+        let dwarf = read::Dwarf {
+            debug_abbrev: read::DebugAbbrev::new(sections.debug_abbrev.slice(), LittleEndian),
+            debug_info: read::DebugInfo::new(sections.debug_info.slice(), LittleEndian),
+            ..Default::default()
+        };
+        let read_unit = dwarf.units().next().unwrap().unwrap();
+        let abbrevs = dwarf.abbreviations(&read_unit).unwrap();
+        let mut read_entries = read_unit.entries(&abbrevs);
+        let mut read_entry = None;
+        while let Some((_, entry)) = read_entries.next_dfs().unwrap() {
+            if entry.offset()
+                == debug_info_offsets
+                    .entry(unit_id1, unit1_child)
+                    .to_unit_offset(&read_unit)
+                    .unwrap()
+            {
+                read_entry = Some(entry.clone());
+            }
+        }
+        let read_expression = match read_entry
+            .unwrap()
+            .attr_value(constants::DW_AT_location)
+            .unwrap()
+        {
+            Some(read::AttributeValue::Exprloc(expression)) => expression,
+            otherwise => panic!("Unexpected location {:?}", otherwise),
+        };
+
+        let base_offset = debug_info_offsets
+            .entry(unit_id1, unit1_base)
+            .to_unit_offset(&read_unit)
+            .unwrap();
+        let bytecode = read_expression.0;
+        let mut bytes = bytecode;
+        assert_eq!(
+            read::Operation::parse(&mut bytes, &bytecode, encoding).unwrap(),
+            read::Operation::Call {
+                offset: read::DieReference::UnitRef(base_offset),
+            }
+        );
+        assert_eq!(
+            read::Operation::parse(&mut bytes, &bytecode, encoding).unwrap(),
+            read::Operation::Convert {
+                base_type: base_offset,
+            }
+        );
+        assert_eq!(
+            read::Operation::parse(&mut bytes, &bytecode, encoding).unwrap(),
+            read::Operation::ImplicitPointer {
+                value: debug_info_offsets.entry(unit_id2, unit2_child),
+                byte_offset: 4,
+            }
+        );
+        assert!(bytes.is_empty());
+    }
+
     #[test]
     fn test_sibling() {
         fn add_child(
@@ -2511,6 +2846,7 @@ mod tests {
                     let from_unit = read::UnitHeader::new(
                         encoding,
                         0,
+                        read::UnitType::Compilation,
                         DebugAbbrevOffset(0),
                         read::EndianSlice::new(&[], LittleEndian),
                     );
@@ -2534,6 +2870,8 @@ mod tests {
                     ][..]
                     {
                         let mut ranges = RangeListTable::default();
+                        let mut locations = LocationListTable::default();
+                        let mut macros = MacroTable::default();
                         let mut strings = StringTable::default();
                         let debug_str_offsets = DebugStrOffsets::none();
                         let mut line_strings = LineStringTable::default();
@@ -2545,10 +2883,11 @@ mod tests {
                             value: value.clone(),
                         };
 
-                        let mut unit_refs = Vec::new();
-                        let mut debug_info_refs = Vec::new();
+                        let mut refs = Vec::new();
                         let mut debug_info = DebugInfo::from(EndianVec::new(LittleEndian));
                         let range_list_offsets = RangeListOffsets::none();
+                        let loc_list_offsets = LocationListOffsets::none();
+                        let macro_unit_offsets = MacroUnitOffsets::none();
                         attr.write(
                             &mut debug_info,
                             &unit,
@@ -2556,8 +2895,9 @@ mod tests {
                             &debug_line_str_offsets,
                             &debug_str_offsets,
                             &range_list_offsets,
-                            &mut unit_refs,
-                            &mut debug_info_refs,
+                            &loc_list_offsets,
+                            &macro_unit_offsets,
+                            &mut refs,
                         )
                         .unwrap();
 
@@ -2579,7 +2919,7 @@ mod tests {
                         let unit = read::Unit {
                             offset: UnitSectionOffset::DebugInfoOffset(DebugInfoOffset(0)),
                             header: from_unit,
-                            abbreviations: read::Abbreviations::default(),
+                            abbreviations: Arc::new(read::Abbreviations::default()),
                             name: None,
                             comp_dir: None,
                             low_pc: 0,
@@ -2587,6 +2927,8 @@ mod tests {
                             addr_base: DebugAddrBase(0),
                             loclists_base: DebugLocListsBase(0),
                             rnglists_base: DebugRngListsBase(0),
+                            dwo_id: None,
+                            dwo_name: None,
                             line_program: None,
                         };
 
@@ -2596,6 +2938,8 @@ mod tests {
                             line_strings: &mut line_strings,
                             strings: &mut strings,
                             ranges: &mut ranges,
+                            locations: &mut locations,
+                            macros: &mut macros,
                             convert_address: &|address| Some(Address::Absolute(address)),
                             base_address: Address::Absolute(0),
                             line_program_offset: Some(line_program_offset),
@@ -2611,6 +2955,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gnu_macros() {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        let mut strings = StringTable::default();
+        let mut unit = Unit::new(encoding, LineProgram::none());
+        let macro_id = unit.macros.add(MacroUnit(vec![
+            MacroEntry::Define {
+                line: 1,
+                text: MacroString::String(b"FOO 1".to_vec()),
+            },
+            MacroEntry::Define {
+                line: 2,
+                text: MacroString::StringRef(strings.add("BAR 2")),
+            },
+        ]));
+        let root = unit.root();
+        unit.get_mut(root).set(
+            constants::DW_AT_GNU_macros,
+            AttributeValue::MacroRef(macro_id),
+        );
+        let mut units = UnitTable::default();
+        units.add(unit);
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let debug_line_str_offsets = DebugLineStrOffsets::none();
+        let debug_str_offsets = strings.write(&mut sections.debug_str).unwrap();
+        units
+            .write(&mut sections, &debug_line_str_offsets, &debug_str_offsets)
+            .unwrap();
+        assert!(sections.debug_macinfo.slice().is_empty());
+
+        let dwarf = read::Dwarf {
+            debug_abbrev: read::DebugAbbrev::new(sections.debug_abbrev.slice(), LittleEndian),
+            debug_info: read::DebugInfo::new(sections.debug_info.slice(), LittleEndian),
+            debug_macro: read::DebugMacro::new(sections.debug_macro.slice(), LittleEndian),
+            debug_str: read::DebugStr::new(sections.debug_str.slice(), LittleEndian),
+            ..Default::default()
+        };
+        let read_unit = dwarf.units().next().unwrap().unwrap();
+        let abbrevs = dwarf.abbreviations(&read_unit).unwrap();
+        let mut read_entries = read_unit.entries(&abbrevs);
+        let (_, read_root) = read_entries.next_dfs().unwrap().unwrap();
+        let offset = match read_root.attr_value(constants::DW_AT_GNU_macros).unwrap() {
+            Some(read::AttributeValue::DebugMacroRef(offset)) => offset,
+            otherwise => panic!("Unexpected attribute value: {:?}", otherwise),
+        };
+        assert_eq!(dwarf.debug_macro.header(offset).unwrap().version(), 4);
+
+        let mut convert_line_strings = LineStringTable::default();
+        let mut convert_strings = StringTable::default();
+        let mut convert_units = UnitTable::from(
+            &dwarf,
+            &mut convert_line_strings,
+            &mut convert_strings,
+            &|address| Some(Address::Absolute(address)),
+        )
+        .unwrap();
+        let convert_macro_id = {
+            let convert_unit = convert_units.get(UnitId::new(convert_units.base_id, 0));
+            let convert_root = convert_unit.get(convert_unit.root());
+            match *convert_root.get(constants::DW_AT_GNU_macros).unwrap() {
+                AttributeValue::MacroRef(id) => id,
+                ref otherwise => panic!("Unexpected attribute value: {:?}", otherwise),
+            }
+        };
+
+        // Writing the converted unit must use `.debug_macro` again.
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        let debug_str_offsets = convert_strings.write(&mut sections.debug_str).unwrap();
+        convert_units
+            .write(&mut sections, &debug_line_str_offsets, &debug_str_offsets)
+            .unwrap();
+        assert!(sections.debug_macinfo.slice().is_empty());
+        assert!(!sections.debug_macro.slice().is_empty());
+
+        // A unit can't refer to both `.debug_macinfo` and `.debug_macro`.
+        let convert_unit = convert_units.get_mut(UnitId::new(convert_units.base_id, 0));
+        let convert_root = convert_unit.root();
+        convert_unit.get_mut(convert_root).set(
+            constants::DW_AT_macro_info,
+            AttributeValue::MacroRef(convert_macro_id),
+        );
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        assert_eq!(
+            convert_units
+                .write(&mut sections, &debug_line_str_offsets, &debug_str_offsets)
+                .map(|_| ()),
+            Err(Error::InvalidAttributeValue)
+        );
+    }
+
     #[test]
     fn test_line_program_used() {
         for used in vec![false, true] {
@@ -1,3 +1,4 @@
+use constants;
 use endianity::Endianity;
 use leb128;
 use write::{Address, Error, Result, SectionId};
@@ -60,6 +61,60 @@ pub trait Writer {
         self.write_word_at(offset, val as u64, size)
     }
 
+    /// Write an address with a `.eh_frame` pointer encoding.
+    ///
+    /// The given size is only used for `DW_EH_PE_absptr` formats.
+    ///
+    /// This default implementation assumes that `DW_EH_PE_pcrel` addresses are
+    /// relative to the start of the section being written.
+    ///
+    /// If the writer supports relocations, then it must provide its own implementation
+    /// of this method.
+    fn write_eh_pointer(
+        &mut self,
+        address: Address,
+        eh_pe: constants::DwEhPe,
+        size: u8,
+    ) -> Result<()> {
+        match address {
+            Address::Absolute(val) => {
+                // Indirect doesn't matter here.
+                let val = match eh_pe.application() {
+                    constants::DW_EH_PE_absptr => val,
+                    constants::DW_EH_PE_pcrel => val.wrapping_sub(self.len() as u64),
+                    _ => return Err(Error::UnsupportedPointerEncoding(eh_pe)),
+                };
+                self.write_eh_pointer_data(val, eh_pe.format(), size)
+            }
+            Address::Relative { .. } => Err(Error::InvalidAddress),
+        }
+    }
+
+    /// Write a value with a `.eh_frame` pointer format.
+    ///
+    /// The given size is only used for `DW_EH_PE_absptr` formats.
+    ///
+    /// This must not be used directly for values that may require relocation.
+    fn write_eh_pointer_data(
+        &mut self,
+        val: u64,
+        format: constants::DwEhPe,
+        size: u8,
+    ) -> Result<()> {
+        match format {
+            constants::DW_EH_PE_absptr => self.write_word(val, size),
+            constants::DW_EH_PE_uleb128 => self.write_uleb128(val),
+            constants::DW_EH_PE_udata2 => self.write_word(val, 2),
+            constants::DW_EH_PE_udata4 => self.write_word(val, 4),
+            constants::DW_EH_PE_udata8 => self.write_word(val, 8),
+            constants::DW_EH_PE_sleb128 => self.write_sleb128(val as i64),
+            constants::DW_EH_PE_sdata2 => self.write_sdata(val as i64, 2),
+            constants::DW_EH_PE_sdata4 => self.write_sdata(val as i64, 4),
+            constants::DW_EH_PE_sdata8 => self.write_sdata(val as i64, 8),
+            _ => Err(Error::UnsupportedPointerEncoding(format)),
+        }
+    }
+
     /// Write a u8.
     fn write_u8(&mut self, val: u8) -> Result<()> {
         let bytes = [val];
@@ -146,6 +201,37 @@ pub trait Writer {
         }
     }
 
+    /// Write a signed value of the given size.
+    ///
+    /// Returns an error if the value is too large for the size.
+    fn write_sdata(&mut self, val: i64, size: u8) -> Result<()> {
+        match size {
+            1 => {
+                let write_val = val as i8;
+                if val != i64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u8(write_val as u8)
+            }
+            2 => {
+                let write_val = val as i16;
+                if val != i64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u16(write_val as u16)
+            }
+            4 => {
+                let write_val = val as i32;
+                if val != i64::from(write_val) {
+                    return Err(Error::ValueTooLarge);
+                }
+                self.write_u32(write_val as u32)
+            }
+            8 => self.write_u64(val as u64),
+            otherwise => Err(Error::UnsupportedWordSize(otherwise)),
+        }
+    }
+
     /// Write a word of the given size at the given offset.
     ///
     /// Returns an error if the value is too large for the size.
@@ -392,5 +478,29 @@ mod tests {
             w.slice(),
             &[0xff, 0xff, 0xff, 0xff, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11]
         );
+
+        let mut w = write::EndianVec::new(LittleEndian);
+        w.write_sdata(-1, 1).unwrap();
+        w.write_sdata(-2, 2).unwrap();
+        w.write_sdata(-3, 4).unwrap();
+        assert_eq!(w.slice(), &[0xff, 0xfe, 0xff, 0xfd, 0xff, 0xff, 0xff]);
+        assert_eq!(w.write_sdata(0x80, 1), Err(Error::ValueTooLarge));
+        assert_eq!(w.write_sdata(-0x8001, 2), Err(Error::ValueTooLarge));
+
+        let mut w = write::EndianVec::new(LittleEndian);
+        w.write_eh_pointer(Address::Absolute(0x1234), constants::DW_EH_PE_absptr, 4)
+            .unwrap();
+        w.write_eh_pointer(Address::Absolute(0x1234), constants::DW_EH_PE_udata2, 8)
+            .unwrap();
+        assert_eq!(w.slice(), &[0x34, 0x12, 0, 0, 0x34, 0x12]);
+        let eh_pe = constants::DwEhPe(constants::DW_EH_PE_pcrel.0 | constants::DW_EH_PE_sdata4.0);
+        w.write_eh_pointer(Address::Absolute(2), eh_pe, 8).unwrap();
+        assert_eq!(&w.slice()[6..], &[0xfc, 0xff, 0xff, 0xff]);
+        assert_eq!(
+            w.write_eh_pointer(Address::Absolute(0), constants::DW_EH_PE_textrel, 8),
+            Err(Error::UnsupportedPointerEncoding(
+                constants::DW_EH_PE_textrel
+            ))
+        );
     }
 }
@@ -41,6 +41,13 @@ fn test_convert_debug_info() {
     let debug_str = read_section("debug_str");
     let debug_str = read::DebugStr::new(&debug_str, LittleEndian);
 
+    let debug_loc = read_section("debug_loc");
+    let debug_loc = read::DebugLoc::new(&debug_loc, LittleEndian);
+
+    let debug_loclists = read::DebugLocLists::new(&[], LittleEndian);
+
+    let locations = gimli::LocationLists::new(debug_loc, debug_loclists);
+
     let debug_ranges = read_section("debug_ranges");
     let debug_ranges = read::DebugRanges::new(&debug_ranges, LittleEndian);
 
@@ -53,6 +60,7 @@ fn test_convert_debug_info() {
         debug_info,
         debug_line,
         debug_str,
+        locations,
         ranges,
         ..Default::default()
     };
@@ -76,11 +84,13 @@ fn test_convert_debug_info() {
     let debug_info_data = write_sections.debug_info.slice();
     let debug_abbrev_data = write_sections.debug_abbrev.slice();
     let debug_line_data = write_sections.debug_line.slice();
+    let debug_loc_data = write_sections.debug_loc.slice();
     let debug_ranges_data = write_sections.debug_ranges.slice();
     let debug_str_data = write_sections.debug_str.slice();
     assert_eq!(debug_info_data.len(), 394_930);
     assert_eq!(debug_abbrev_data.len(), 1282);
     assert_eq!(debug_line_data.len(), 105_797);
+    assert_eq!(debug_loc_data.len(), 245_768);
     assert_eq!(debug_ranges_data.len(), 155_712);
     assert_eq!(debug_str_data.len(), 144_731);
 
@@ -89,9 +99,12 @@ fn test_convert_debug_info() {
     let debug_info = read::DebugInfo::new(debug_info_data, LittleEndian);
     let debug_line = read::DebugLine::new(debug_line_data, LittleEndian);
     let debug_str = read::DebugStr::new(debug_str_data, LittleEndian);
+    let debug_loc = read::DebugLoc::new(debug_loc_data, LittleEndian);
+    let debug_loclists = read::DebugLocLists::new(&[], LittleEndian);
     let debug_ranges = read::DebugRanges::new(debug_ranges_data, LittleEndian);
     let debug_rnglists = read::DebugRngLists::new(&[], LittleEndian);
 
+    let locations = gimli::LocationLists::new(debug_loc, debug_loclists);
     let ranges = gimli::RangeLists::new(debug_ranges, debug_rnglists);
 
     let dwarf = read::Dwarf {
@@ -99,6 +112,7 @@ fn test_convert_debug_info() {
         debug_info,
         debug_line,
         debug_str,
+        locations,
         ranges,
         ..Default::default()
     };
@@ -113,3 +127,34 @@ fn test_convert_debug_info() {
     assert_eq!(entries, 29_560);
     assert_eq!(dwarf.strings.count(), 3921);
 }
+
+#[test]
+fn test_convert_eh_frame() {
+    // Convert existing section
+    let eh_frame = read_section("eh_frame");
+    let mut eh_frame = read::EhFrame::new(&eh_frame, LittleEndian);
+    // The `.eh_frame` fixture doesn't have any address size information.
+    eh_frame.set_address_size(8);
+
+    let frames = write::FrameTable::from(&eh_frame, &|address| Some(Address::Absolute(address)))
+        .expect("Should convert eh_frame information");
+    assert_eq!(frames.cie_count(), 2);
+    assert_eq!(frames.fde_count(), 3482);
+
+    // Write to new section
+    let mut write_eh_frame = write::EhFrame(EndianVec::new(LittleEndian));
+    frames
+        .write_eh_frame(&mut write_eh_frame)
+        .expect("Should write eh_frame information");
+    let eh_frame_data = write_eh_frame.slice();
+    assert_eq!(eh_frame_data.len(), 147_144);
+
+    // Convert new section
+    let mut eh_frame = read::EhFrame::new(eh_frame_data, LittleEndian);
+    eh_frame.set_address_size(8);
+
+    let frames = write::FrameTable::from(&eh_frame, &|address| Some(Address::Absolute(address)))
+        .expect("Should convert eh_frame information");
+    assert_eq!(frames.cie_count(), 2);
+    assert_eq!(frames.fde_count(), 3482);
+}